@@ -7,7 +7,9 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
+pub mod quantity;
 pub mod v1alpha1;
+pub mod v1beta1;
 
 /// GROUP is the kubernetes API group.
 pub static GROUP: &str = "clairproject.org";
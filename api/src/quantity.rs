@@ -0,0 +1,123 @@
+//! Quantity parses Kubernetes [`Quantity`](k8s_openapi::apimachinery::pkg::api::resource::Quantity)
+//! strings (e.g. `"2Gi"`, `"500m"`, `"1.5"`), so a CRD field that carries one (resource
+//! requests/limits, a PVC's requested size) can be rejected at admission time rather than only
+//! failing once the apiserver tries to parse it out of a rendered Pod/PVC spec.
+
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+
+/// Error indicates a string didn't parse as a Kubernetes `Quantity`.
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+#[error("invalid quantity {0:?}: does not match <number><suffix>, e.g. \"2Gi\" or \"500m\"")]
+pub struct Error(pub String);
+
+/// Binary_suffixes are the power-of-1024 suffixes `parse` recognizes, in ascending order, paired
+/// with the power of 1024 they scale by.
+const BINARY_SUFFIXES: &[(&str, i32)] = &[
+    ("Ki", 1),
+    ("Mi", 2),
+    ("Gi", 3),
+    ("Ti", 4),
+    ("Pi", 5),
+    ("Ei", 6),
+];
+
+/// Decimal_suffixes are the power-of-1000 suffixes `parse` recognizes, paired with the power of
+/// 1000 they scale by (negative for the sub-unit suffixes, e.g. `"m"` for milli).
+const DECIMAL_SUFFIXES: &[(&str, i32)] = &[
+    ("n", -3),
+    ("u", -2),
+    ("m", -1),
+    ("k", 1),
+    ("M", 2),
+    ("G", 3),
+    ("T", 4),
+    ("P", 5),
+    ("E", 6),
+];
+
+/// Parse validates `s` against the Kubernetes `Quantity` grammar (a decimal number, optionally
+/// followed by a binary SI suffix like `"Gi"`, a decimal SI suffix like `"k"`/`"m"`, or a decimal
+/// exponent like `"e3"`) and returns its value in base units (bytes, or whole cores for CPU).
+///
+/// This is deliberately permissive about what counts as "the number" --- it defers to
+/// [`str::parse<f64>`] --- and strict only about the suffix, since the suffix is what
+/// distinguishes a quantity a human meant to write from a typo that would otherwise fail only
+/// once the apiserver rejects the generated Pod/PVC spec.
+pub fn parse(s: &str) -> Result<f64, Error> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(Error(s.to_string()));
+    }
+
+    let (number, scale) = if let Some((number, scale)) = BINARY_SUFFIXES
+        .iter()
+        .find_map(|(suf, exp)| s.strip_suffix(suf).map(|n| (n, 1024f64.powi(*exp))))
+    {
+        (number, scale)
+    } else if let Some((number, scale)) = DECIMAL_SUFFIXES
+        .iter()
+        // Longest-suffix-first isn't needed: none of `DECIMAL_SUFFIXES` is a suffix of another.
+        .find_map(|(suf, exp)| s.strip_suffix(suf).map(|n| (n, 1000f64.powi(*exp))))
+    {
+        (number, scale)
+    } else {
+        (s, 1.0)
+    };
+
+    let number: f64 = number.parse().map_err(|_| Error(s.to_string()))?;
+    if !number.is_finite() {
+        return Err(Error(s.to_string()));
+    }
+    Ok(number * scale)
+}
+
+/// Validate is [`parse`] adapted for `#[validate(custom(...))]`: it checks that `s` parses,
+/// discarding the value, so a field like [`crate::v1alpha1::LayerCacheSpec::size`] can be
+/// rejected at admission with a message pointing at the offending string.
+pub fn validate(s: &str) -> Result<(), validator::ValidationError> {
+    parse(s).map(|_| ()).map_err(|err| {
+        validator::ValidationError::new("invalid_quantity").with_message(err.to_string().into())
+    })
+}
+
+/// Validate_resources checks every request/limit in `resources` parses as a [`Quantity`], for
+/// attaching to a `resources: Option<core::v1::ResourceRequirements>` field via
+/// `#[validate(custom(...))]` --- `ResourceRequirements` itself is a `k8s-openapi` type and can't
+/// derive `Validate`. Like the other `#[validate(custom(...))]` functions in [`crate::v1alpha1`],
+/// this only runs when the `Option` is `Some`, so there's nothing to unwrap here.
+pub fn validate_resources(
+    resources: &k8s_openapi::api::core::v1::ResourceRequirements,
+) -> Result<(), validator::ValidationError> {
+    let quantities = resources
+        .requests
+        .iter()
+        .flatten()
+        .chain(resources.limits.iter().flatten());
+    for (name, Quantity(q)) in quantities {
+        if let Err(err) = parse(q) {
+            return Err(validator::ValidationError::new("invalid_quantity")
+                .with_message(format!("resource {name:?}: {err}").into()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_binary_and_decimal_suffixes() {
+        assert_eq!(parse("10Gi").unwrap(), 10.0 * 1024f64.powi(3));
+        assert_eq!(parse("500m").unwrap(), 0.5);
+        assert_eq!(parse("2").unwrap(), 2.0);
+        assert_eq!(parse("1k").unwrap(), 1000.0);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert!(parse("").is_err());
+        assert!(parse("10Xi").is_err());
+        assert!(parse("not-a-number").is_err());
+    }
+}
@@ -1,5 +1,11 @@
 //! Module `v1alpha1` implements the v1alpha1 Clair CRD API.
-use k8s_openapi::{api::core, apimachinery::pkg::apis::meta, merge_strategies, DeepMerge};
+use std::collections::BTreeMap;
+
+use k8s_openapi::{
+    api::{autoscaling, core},
+    apimachinery::pkg::{api::resource::Quantity, apis::meta, util::intstr::IntOrString},
+    merge_strategies, DeepMerge,
+};
 use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -23,7 +29,7 @@ use validator::Validate;
 pub struct ClairSpec {
     /// .
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub image: Option<String>,
+    pub image: Option<ImageReference>,
     /// Databases indicates the Secret keys holding config drop-ins that services should connect
     /// to.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -44,6 +50,7 @@ pub struct ClairSpec {
     /// Dropins references additional config drop-in files.
     ///
     /// See the Clair documentation for how config drop-ins are handled.
+    #[validate(nested)]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub dropins: Vec<DropinSource>,
     /// ConfigDialect specifies the format to generate for the main config.
@@ -51,13 +58,77 @@ pub struct ClairSpec {
     /// This setting affects what format config drop-ins must be in.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub config_dialect: Option<ConfigDialect>,
+    /// Otlp configures exporting traces and metrics to an OpenTelemetry collector.
+    ///
+    /// If unset, the Prometheus-only metrics path is used and no traces are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp: Option<OtlpConfig>,
+    /// Resources overrides the default container resource requests/limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::quantity::validate_resources"))]
+    pub resources: Option<core::v1::ResourceRequirements>,
+    /// Scaling holds default replica counts and resource requirements for the subsystems. It is
+    /// stored and merged with the rest of the spec, but no controller reads it, so it does not
+    /// change any subsystem's spec or generated workload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scaling: Option<ScalingSpec>,
+    /// ConfigStorage requests a PersistentVolumeClaim-backed config volume instead of the default
+    /// ConfigMap projection, for a config (including drop-ins) too large for a ConfigMap's 1MiB
+    /// limit.
+    ///
+    /// If unset, `root-config`/`dropin-config` stay ConfigMap/Secret-projected volumes, as before
+    /// this field existed.
+    #[validate(nested)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_storage: Option<ConfigStorageSpec>,
+    /// Overlays are user-supplied patches applied to generated workloads, keyed by the target
+    /// kind (e.g. `"CronJob"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overlays: BTreeMap<String, Overlay>,
+    /// Templates are user-supplied Handlebars templates rendered over generated workloads,
+    /// keyed by the target kind (e.g. `"Deployment"`). Each value references the ConfigMap key
+    /// holding the template source.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, ConfigMapKeySelector>,
 }
 
 impl ClairSpec {
-    /// With_root creates the desired ConfigSource, using the provided name as the root config.
-    pub fn with_root<S: ToString>(&self, name: S) -> ConfigSource {
+    /// With_root validates the spec's config-related invariants and, if they hold, creates the
+    /// desired ConfigSource, using the provided name as the root config.
+    ///
+    /// This is a pre-flight check, not an I/O operation: it only inspects the spec itself, so a
+    /// [`ReconcileError`] returned here always indicates a misconfigured `Clair` rather than a
+    /// transient cluster failure.
+    pub fn with_root<S: ToString>(&self, name: S) -> Result<ConfigSource, ReconcileError> {
         let mut dropins = self.dropins.clone();
+        if self.notifier == Some(true) && self.databases.as_ref().is_none_or(|db| db.notifier.is_none()) {
+            return Err(ReconcileError {
+                code: Some("InvalidSpec".into()),
+                target: Some("/spec/databases/notifier".into()),
+                message: Some(
+                    "field \"/spec/notifier\" is set but \"/spec/databases/notifier\" is not"
+                        .into(),
+                ),
+                ..Default::default()
+            });
+        }
         if let Some(db) = &self.databases {
+            for (target, sel) in [
+                ("/spec/databases/indexer", &db.indexer),
+                ("/spec/databases/matcher", &db.matcher),
+            ]
+            .into_iter()
+            .chain(db.notifier.as_ref().map(|sel| ("/spec/databases/notifier", sel)))
+            {
+                if sel.name.is_empty() || sel.key.is_empty() {
+                    return Err(ReconcileError {
+                        code: Some("InvalidSpec".into()),
+                        target: Some(target.into()),
+                        message: Some(format!("{target} does not resolve to a name and key")),
+                        ..Default::default()
+                    });
+                }
+            }
             dropins.push(DropinSource {
                 secret_key_ref: Some(db.indexer.clone()),
                 config_map_key_ref: None,
@@ -77,13 +148,14 @@ impl ClairSpec {
         dropins.dedup();
         let name = name.to_string();
         let flavor = self.config_dialect.unwrap_or_default();
-        ConfigSource {
+        Ok(ConfigSource {
             root: ConfigMapKeySelector {
                 name,
                 key: format!("config.{flavor}"),
             },
             dropins,
-        }
+            persistent: self.config_storage.clone(),
+        })
     }
 }
 
@@ -95,6 +167,35 @@ impl DeepMerge for ClairSpec {
         self.notifier.merge_from(other.notifier);
         merge_strategies::list::set(self.dropins.as_mut(), other.dropins);
         self.config_dialect.merge_from(other.config_dialect);
+        self.otlp.merge_from(other.otlp);
+        self.resources.merge_from(other.resources);
+        self.scaling.merge_from(other.scaling);
+        self.config_storage.merge_from(other.config_storage);
+        self.overlays.extend(other.overlays);
+        self.templates.extend(other.templates);
+    }
+}
+
+/// ConfigStorageSpec requests a PersistentVolumeClaim-backed config volume (see
+/// [`ClairSpec::config_storage`]) rather than the default ConfigMap projection.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigStorageSpec {
+    /// Size is the requested storage size, e.g. "1Gi".
+    ///
+    /// If unset, "1Gi" is used.
+    #[validate(custom(function = "crate::quantity::validate"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// StorageClassName selects a non-default StorageClass for the volume.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_class_name: Option<String>,
+}
+
+impl DeepMerge for ConfigStorageSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.size.merge_from(other.size);
+        self.storage_class_name.merge_from(other.storage_class_name);
     }
 }
 
@@ -136,12 +237,50 @@ pub struct Endpoint {
     /// TLS inicates the `kubernetes.io/tls`-typed Secret that should be used.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tls: Option<core::v1::LocalObjectReference>,
+    /// Path is the path prefix routed to the backend Service. Defaults to "/" if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// IngressClassName selects the Ingress controller (or, when routed via the Gateway API, the
+    /// GatewayClass) that should serve this endpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ingress_class_name: Option<String>,
 }
 
 impl DeepMerge for Endpoint {
     fn merge_from(&mut self, other: Self) {
         self.hostname.merge_from(other.hostname);
         self.tls.merge_from(other.tls);
+        self.path.merge_from(other.path);
+        self.ingress_class_name.merge_from(other.ingress_class_name);
+    }
+}
+
+/// ScalingSpec holds default sizing for the component specs: a tier name in `size`, and explicit
+/// `replicas`/`resources`.
+///
+/// No controller reads this block: no named tiers are defined, and nothing here is copied onto a
+/// component spec or its generated workload.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScalingSpec {
+    /// Size names a tier of default replicas/resources; no tiers are defined.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// Replicas is a default replica count for the components; it is not applied to them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i32>,
+    /// Resources is a default set of resource requirements for the components; it is not applied
+    /// to them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::quantity::validate_resources"))]
+    pub resources: Option<core::v1::ResourceRequirements>,
+}
+
+impl DeepMerge for ScalingSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.size.merge_from(other.size);
+        self.replicas.merge_from(other.replicas);
+        self.resources.merge_from(other.resources);
     }
 }
 
@@ -188,8 +327,58 @@ pub struct ClairStatus {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub database: Option<core::v1::TypedLocalObjectReference>,
     */
+
+    /// Errors holds structured faults encountered while reconciling, e.g. an unresolvable config
+    /// drop-in. See [`ReconcileError`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ReconcileError>,
+}
+
+/// ReconcileError is a structured fault reported by the controller, meant to give `kubectl`
+/// users an actionable breakdown instead of a single flat error string.
+///
+/// Target is meant to identify the offending input, e.g. a [`DropinSource`] or
+/// [`SecretKeySelector`] key. Details and inner both let a top-level failure (say, "config merge
+/// failed") carry its per-cause breakdown underneath: details for a set of independent causes,
+/// inner for a single wrapped cause.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileError {
+    /// Code is a short, machine-readable identifier for the failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Target identifies the offending input, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Message is a human-readable description of the failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Details holds independent causes underneath this failure.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub details: Vec<ReconcileError>,
+    /// Inner holds a single wrapped cause underneath this failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inner: Option<Box<ReconcileError>>,
+}
+
+impl std::fmt::Display for ReconcileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(code) = &self.code {
+            write!(f, "{code}: ")?;
+        }
+        write!(f, "{}", self.message.as_deref().unwrap_or("reconcile error"))?;
+        if let Some(target) = &self.target {
+            write!(f, " (target: {target})")?;
+        }
+        if let Some(inner) = &self.inner {
+            write!(f, ": {inner}")?;
+        }
+        Ok(())
+    }
 }
 
+impl std::error::Error for ReconcileError {}
+
 /// ConfigSource specifies all the config files that will be arranged for Clair to load.
 ///
 /// All referenced configs need to be in the same dialect as specified on the parent ClairSpec to
@@ -202,14 +391,21 @@ pub struct ConfigSource {
     /// Root is a reference to the main config.
     pub root: ConfigMapKeySelector,
     /// Dropins is a list of references to drop-in configs.
+    #[validate(nested)]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub dropins: Vec<DropinSource>,
+    /// Persistent carries [`ClairSpec::config_storage`] through to the template layer, so
+    /// [`clair_templates`](../../clair_templates/index.html)'s volume builder knows to emit a
+    /// PersistentVolumeClaim for the config volume instead of the default ConfigMap projection.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistent: Option<ConfigStorageSpec>,
 }
 
 impl DeepMerge for ConfigSource {
     fn merge_from(&mut self, other: Self) {
         self.root.merge_from(other.root);
         merge_strategies::list::set(self.dropins.as_mut(), other.dropins);
+        self.persistent.merge_from(other.persistent);
     }
 }
 
@@ -227,6 +423,7 @@ impl DeepMerge for ConfigSource {
     Validate,
     JsonSchema,
 )]
+#[validate(custom(function = "validate_dropin_source"))]
 #[serde(rename_all = "camelCase")]
 pub struct DropinSource {
     /// Selects a key of a ConfigMap.
@@ -237,6 +434,16 @@ pub struct DropinSource {
     pub secret_key_ref: Option<SecretKeySelector>,
 }
 
+/// Validate_dropin_source rejects a [`DropinSource`] that references neither a ConfigMap nor a
+/// Secret key, since the config-building step has nothing to read in that case.
+fn validate_dropin_source(d: &DropinSource) -> Result<(), validator::ValidationError> {
+    if d.config_map_key_ref.is_none() && d.secret_key_ref.is_none() {
+        return Err(validator::ValidationError::new("missing_ref")
+            .with_message("neither \"configMapKeyRef\" nor \"secretKeyRef\" is set".into()));
+    }
+    Ok(())
+}
+
 /// SecretKeySelector selects a key from a Secret.
 #[derive(
     Clone,
@@ -303,6 +510,185 @@ impl DeepMerge for ConfigMapKeySelector {
     }
 }
 
+/// ImageReference is a parsed, validated container image reference, of the form
+/// `[registry/]repository[:tag|@digest]`.
+///
+/// It serializes and deserializes as its string form; [`Deserialize`] re-validates on the way in,
+/// so a malformed reference in a CRD manifest is rejected before it reaches the rest of the spec.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageReference {
+    /// Registry is the registry host (and optional port), if one was specified.
+    pub registry: Option<String>,
+    /// Repository is the image name, e.g. `quay/clair`.
+    pub repository: String,
+    /// Selector pins the reference to a tag or a digest; at most one may be set.
+    pub selector: Option<ImageSelector>,
+}
+
+/// ImageSelector pins an [`ImageReference`] to a mutable tag or an immutable content digest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImageSelector {
+    /// Tag names a mutable tag, e.g. `latest`.
+    Tag(String),
+    /// Digest pins an immutable content digest, e.g. `sha256:...`.
+    Digest(String),
+}
+
+/// ImageReferenceError reports why a string failed to parse as an [`ImageReference`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImageReferenceError(String);
+
+impl std::fmt::Display for ImageReferenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid image reference: {}", self.0)
+    }
+}
+
+impl std::error::Error for ImageReferenceError {}
+
+impl std::str::FromStr for ImageReference {
+    type Err = ImageReferenceError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ImageReferenceError("empty reference".into()));
+        }
+
+        let (rest, digest) = match s.split_once('@') {
+            Some((rest, digest)) => {
+                if !digest.starts_with("sha256:") || digest.len() != "sha256:".len() + 64 {
+                    return Err(ImageReferenceError(format!("malformed digest: {digest}")));
+                }
+                (rest, Some(digest.to_string()))
+            }
+            None => (s, None),
+        };
+
+        let (registry, path) = match rest.find('/') {
+            Some(i) if rest[..i].contains('.') || rest[..i].contains(':') || &rest[..i] == "localhost" => {
+                (Some(rest[..i].to_string()), &rest[i + 1..])
+            }
+            _ => (None, rest),
+        };
+
+        let (repository, tag) = match path.rfind(':') {
+            Some(i) if !path[i + 1..].contains('/') => (&path[..i], Some(path[i + 1..].to_string())),
+            _ => (path, None),
+        };
+
+        if repository.is_empty()
+            || !repository
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '_' | '-' | '.'))
+        {
+            return Err(ImageReferenceError(format!(
+                "malformed repository: {repository}"
+            )));
+        }
+
+        let selector = match (tag, digest) {
+            (Some(_), Some(_)) => {
+                return Err(ImageReferenceError(
+                    "cannot specify both a tag and a digest".into(),
+                ));
+            }
+            (Some(tag), None) => Some(ImageSelector::Tag(tag)),
+            (None, Some(digest)) => Some(ImageSelector::Digest(digest)),
+            (None, None) => None,
+        };
+
+        Ok(ImageReference {
+            registry,
+            repository: repository.to_string(),
+            selector,
+        })
+    }
+}
+
+impl TryFrom<&str> for ImageReference {
+    type Error = ImageReferenceError;
+
+    fn try_from(s: &str) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for ImageReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(registry) = &self.registry {
+            write!(f, "{registry}/")?;
+        }
+        write!(f, "{}", self.repository)?;
+        match &self.selector {
+            Some(ImageSelector::Tag(tag)) => write!(f, ":{tag}")?,
+            Some(ImageSelector::Digest(digest)) => write!(f, "@{digest}")?,
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for ImageReference {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ImageReference {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl JsonSchema for ImageReference {
+    fn schema_name() -> String {
+        "ImageReference".to_string()
+    }
+
+    fn json_schema(_gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            string: Some(Box::new(schemars::schema::StringValidation {
+                pattern: Some(
+                    r"^([a-zA-Z0-9.-]+(:[0-9]+)?/)?[a-zA-Z0-9/_.-]+(:[a-zA-Z0-9_.-]+|@sha256:[a-fA-F0-9]{64})?$"
+                        .to_string(),
+                ),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl DeepMerge for ImageReference {
+    fn merge_from(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+/// BoundImage declares an auxiliary image (an init container, migration Job, or sidecar) that
+/// should be pulled and version-tracked alongside a component's primary image.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundImage {
+    /// Name identifies this binding, e.g. `"migrate"` or `"auth-sidecar"`.
+    pub name: String,
+    /// Image is the auxiliary image reference.
+    pub image: ImageReference,
+    /// PullSecret is a registry credential to use when resolving this image, if different from
+    /// the component's own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_secret: Option<core::v1::LocalObjectReference>,
+}
+
+impl DeepMerge for BoundImage {
+    fn merge_from(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
 /// ConfigDialect selects between the dialects for a Clair config.
 ///
 /// The default for the operator to create is JSON.
@@ -314,6 +700,8 @@ pub enum ConfigDialect {
     JSON,
     /// YAML indicates a YAML config.
     YAML,
+    /// TOML indicates a TOML config.
+    TOML,
 }
 
 impl std::fmt::Display for ConfigDialect {
@@ -321,6 +709,7 @@ impl std::fmt::Display for ConfigDialect {
         match self {
             ConfigDialect::JSON => write!(f, "json"),
             ConfigDialect::YAML => write!(f, "yaml"),
+            ConfigDialect::TOML => write!(f, "toml"),
         }
     }
 }
@@ -331,6 +720,277 @@ impl DeepMerge for ConfigDialect {
     }
 }
 
+/// OtlpConfig configures exporting traces and metrics to an OpenTelemetry (OTLP) collector.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OtlpConfig {
+    /// Endpoint is the OTLP collector endpoint, e.g. `otel-collector.monitoring:4317`.
+    pub endpoint: String,
+    /// Protocol selects the OTLP wire protocol.
+    #[serde(default)]
+    pub protocol: OtlpProtocol,
+    /// HeadersSecretRef references a Secret key holding extra headers (e.g. an auth token) to
+    /// send with every export request, formatted as `key1=value1,key2=value2`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers_secret_ref: Option<SecretKeySelector>,
+    /// SamplingRatio is the fraction of traces to sample, in the range `[0.0, 1.0]`.
+    ///
+    /// If unset, the OTLP exporter's default sampler is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling_ratio: Option<f64>,
+}
+
+impl DeepMerge for OtlpConfig {
+    fn merge_from(&mut self, other: Self) {
+        if !other.endpoint.is_empty() {
+            self.endpoint = other.endpoint;
+        }
+        self.protocol.merge_from(other.protocol);
+        self.headers_secret_ref.merge_from(other.headers_secret_ref);
+        self.sampling_ratio.merge_from(other.sampling_ratio);
+    }
+}
+
+/// OtlpProtocol selects the wire protocol used to talk to an OTLP collector.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpProtocol {
+    /// Grpc indicates OTLP over gRPC.
+    #[default]
+    Grpc,
+    /// Http indicates OTLP over HTTP.
+    Http,
+}
+
+impl std::fmt::Display for OtlpProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OtlpProtocol::Grpc => write!(f, "grpc"),
+            OtlpProtocol::Http => write!(f, "http"),
+        }
+    }
+}
+
+impl DeepMerge for OtlpProtocol {
+    fn merge_from(&mut self, other: Self) {
+        *self = other;
+    }
+}
+
+/// AutoscalingSpec configures the HorizontalPodAutoscaler generated for a component.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoscalingSpec {
+    /// MinReplicas is the lower bound on replicas.
+    ///
+    /// If unset, the HorizontalPodAutoscaler default of 1 is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_replicas: Option<i32>,
+    /// MaxReplicas is the upper bound on replicas.
+    ///
+    /// If unset, defaults to 10.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_replicas: Option<i32>,
+    /// Metrics is the list of metric specs the HorizontalPodAutoscaler should scale on.
+    ///
+    /// If empty, a single CPU `Utilization` target of 80% is used. This is where `Pods` or
+    /// `External` metrics (e.g. notifier queue depth exposed at the introspection endpoint) can
+    /// be wired in to drive scaling on something other than CPU/memory.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<autoscaling::v2::MetricSpec>,
+    /// Behavior configures the scaling behavior, e.g. stabilization windows and scaling policies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behavior: Option<autoscaling::v2::HorizontalPodAutoscalerBehavior>,
+    /// RequestRateMetricName is the custom-metric identifier the frontend Deployment exposes for
+    /// request-per-second autoscaling (e.g. `http_requests_per_second`), used to populate a `Pods`
+    /// metric source when `metrics` is empty and the cluster serves the custom metrics API.
+    ///
+    /// If unset, defaults to `http_requests_per_second`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_rate_metric_name: Option<String>,
+    /// RequestRateTarget is the target `averageValue` for `requestRateMetricName`.
+    ///
+    /// If unset, defaults to `100`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_rate_target: Option<Quantity>,
+}
+
+impl DeepMerge for AutoscalingSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.min_replicas.merge_from(other.min_replicas);
+        self.max_replicas.merge_from(other.max_replicas);
+        merge_strategies::list::set(self.metrics.as_mut(), other.metrics);
+        self.behavior.merge_from(other.behavior);
+        self.request_rate_metric_name
+            .merge_from(other.request_rate_metric_name);
+        self.request_rate_target.merge_from(other.request_rate_target);
+    }
+}
+
+/// DisruptionSpec configures the PodDisruptionBudget generated for a component.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DisruptionSpec {
+    /// MinAvailable is the number or percentage of pods that must remain available.
+    ///
+    /// Mutually exclusive with `maxUnavailable`; if both are unset, `minAvailable: 1` is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_available: Option<IntOrString>,
+    /// MaxUnavailable is the number or percentage of pods that may be unavailable.
+    ///
+    /// Mutually exclusive with `minAvailable`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_unavailable: Option<IntOrString>,
+}
+
+impl DeepMerge for DisruptionSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.min_available.merge_from(other.min_available);
+        self.max_unavailable.merge_from(other.max_unavailable);
+    }
+}
+
+/// SchedulingSpec tunes how a component's replicas are spread across nodes and zones.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SchedulingSpec {
+    /// TopologySpreadMaxSkew overrides the default `maxSkew` (1) used for the generated
+    /// `topology.kubernetes.io/zone` spread constraint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topology_spread_max_skew: Option<i32>,
+    /// AntiAffinity disables the default soft pod anti-affinity (keyed on the component label)
+    /// when set to `false`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anti_affinity: Option<bool>,
+}
+
+impl DeepMerge for SchedulingSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.topology_spread_max_skew
+            .merge_from(other.topology_spread_max_skew);
+        self.anti_affinity.merge_from(other.anti_affinity);
+    }
+}
+
+/// ProbeTiming overrides a probe's `initialDelaySeconds`/`periodSeconds`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbeTiming {
+    /// InitialDelaySeconds overrides the probe's default initial delay.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_delay_seconds: Option<i32>,
+    /// PeriodSeconds overrides the probe's default period.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub period_seconds: Option<i32>,
+}
+
+impl DeepMerge for ProbeTiming {
+    fn merge_from(&mut self, other: Self) {
+        self.initial_delay_seconds
+            .merge_from(other.initial_delay_seconds);
+        self.period_seconds.merge_from(other.period_seconds);
+    }
+}
+
+/// ProbesSpec overrides the default startup/liveness/readiness probe timings.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProbesSpec {
+    /// Startup overrides the startup probe's timing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub startup: Option<ProbeTiming>,
+    /// Liveness overrides the liveness probe's timing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub liveness: Option<ProbeTiming>,
+    /// Readiness overrides the readiness probe's timing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readiness: Option<ProbeTiming>,
+}
+
+impl DeepMerge for ProbesSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.startup.merge_from(other.startup);
+        self.liveness.merge_from(other.liveness);
+        self.readiness.merge_from(other.readiness);
+    }
+}
+
+/// LayerCacheSpec configures the ephemeral volume the Indexer uses to cache unpacked layers.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerCacheSpec {
+    /// Size is the requested storage size, e.g. "10Gi".
+    ///
+    /// If unset, "10Gi" is used.
+    #[validate(custom(function = "crate::quantity::validate"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<String>,
+    /// StorageClassName selects a non-default StorageClass for the volume.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_class_name: Option<String>,
+    /// AccessMode overrides the default `ReadWriteOnce` access mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_mode: Option<String>,
+}
+
+impl DeepMerge for LayerCacheSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.size.merge_from(other.size);
+        self.storage_class_name.merge_from(other.storage_class_name);
+        self.access_mode.merge_from(other.access_mode);
+    }
+}
+
+/// RolloutSpec enables a canary/weighted rollout for a component, splitting traffic between the
+/// normal ("stable") Deployment and a second "canary" Deployment.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloutSpec {
+    /// CanaryWeight is the percentage (0-100) of traffic routed to the canary Deployment.
+    ///
+    /// If unset, 10 is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canary_weight: Option<i32>,
+    /// CanaryReplicas is the replica count for the canary Deployment.
+    ///
+    /// If unset, 1 is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canary_replicas: Option<i32>,
+    /// CanaryImage overrides `image` for the canary Deployment.
+    ///
+    /// If unset, the canary runs the same image as the stable Deployment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub canary_image: Option<String>,
+}
+
+impl DeepMerge for RolloutSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.canary_weight.merge_from(other.canary_weight);
+        self.canary_replicas.merge_from(other.canary_replicas);
+        self.canary_image.merge_from(other.canary_image);
+    }
+}
+
+/// Overlay is a user-supplied patch applied to a generated workload (e.g. the Deployment a
+/// component renders) before it's sent to the API server, so node selectors, tolerations, extra
+/// volumes, image pull secrets, and the like can be added without forking the operator.
+///
+/// Exactly one of the three patch flavors k8s-openapi's `Patch` type supports for a non-`Apply`
+/// patch is carried here; which one is in use is recorded by `type`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase", tag = "type", content = "patch")]
+pub enum Overlay {
+    /// Json is an RFC6902 JSON Patch, the same format `render_dropin` produces.
+    Json(serde_json::Value),
+    /// Merge is an RFC7386 JSON Merge Patch: a recursive object merge where a `null` value deletes
+    /// the corresponding key.
+    Merge(serde_json::Value),
+    /// Strategic is a Kubernetes strategic-merge-patch: like `Merge`, but known list fields (e.g.
+    /// `containers`, `volumes`) are merged element-by-element on their `patchMergeKey` instead of
+    /// being replaced wholesale.
+    Strategic(serde_json::Value),
+}
+
 // ImageRef exists to have some Object to hang pre/post Jobs off of.
 // I don't think this is actually needed -- The can/could be driven off of a Condition.
 /*
@@ -378,16 +1038,74 @@ pub struct ImageRefStatus {}
 pub struct IndexerSpec {
     /// Image is the image that should be used in the managed deployment.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub image: Option<String>,
+    pub image: Option<ImageReference>,
+    /// BoundImages declares auxiliary images (init containers, migration Jobs, sidecars) that
+    /// should be pulled and version-tracked alongside `image`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bound_images: Vec<BoundImage>,
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
     pub config: Option<ConfigSource>,
+    /// Otlp configures exporting traces and metrics to an OpenTelemetry collector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp: Option<OtlpConfig>,
+    /// Resources overrides the default container resource requests/limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::quantity::validate_resources"))]
+    pub resources: Option<core::v1::ResourceRequirements>,
+    /// Replicas is the explicit replica count for this component.
+    ///
+    /// It is stored on the spec only: the generated Deployment's replica count is not set from
+    /// it, and the parent ClairSpec's `scaling` does not fill it in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i32>,
+    /// Autoscaling configures the HorizontalPodAutoscaler generated for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autoscaling: Option<AutoscalingSpec>,
+    /// Disruption configures the PodDisruptionBudget generated for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disruption: Option<DisruptionSpec>,
+    /// Scheduling tunes the topology spread and anti-affinity applied to this component's pods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduling: Option<SchedulingSpec>,
+    /// Probes overrides the default startup/liveness/readiness probe timings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probes: Option<ProbesSpec>,
+    /// LayerCache configures the ephemeral volume used to cache unpacked layers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_cache: Option<LayerCacheSpec>,
+    /// Rollout enables a canary/weighted rollout, splitting traffic between a stable and canary
+    /// Deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<RolloutSpec>,
+    /// Overlays are user-supplied patches applied to generated workloads, keyed by the target
+    /// kind (e.g. `"Deployment"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overlays: BTreeMap<String, Overlay>,
+    /// Templates are user-supplied Handlebars templates rendered over generated workloads,
+    /// keyed by the target kind (e.g. `"Deployment"`). Each value references the ConfigMap key
+    /// holding the template source.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, ConfigMapKeySelector>,
 }
 
 impl DeepMerge for IndexerSpec {
     fn merge_from(&mut self, other: Self) {
         self.image.merge_from(other.image);
+        self.bound_images.extend(other.bound_images);
         self.config.merge_from(other.config);
+        self.otlp.merge_from(other.otlp);
+        self.resources.merge_from(other.resources);
+        self.replicas.merge_from(other.replicas);
+        self.autoscaling.merge_from(other.autoscaling);
+        self.disruption.merge_from(other.disruption);
+        self.scheduling.merge_from(other.scheduling);
+        self.probes.merge_from(other.probes);
+        self.layer_cache.merge_from(other.layer_cache);
+        self.rollout.merge_from(other.rollout);
+        self.overlays.extend(other.overlays);
+        self.templates.extend(other.templates);
     }
 }
 
@@ -406,6 +1124,26 @@ pub struct IndexerStatus {
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<ConfigSource>,
+    /// Resolved_image is the image actually deployed, with any floating tag pinned to the
+    /// content digest it resolved to on the last successful reconcile.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_image: Option<ImageReference>,
+    /// Errors holds structured faults encountered while reconciling. See [`ReconcileError`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ReconcileError>,
+    /// Failure_count is the number of consecutive reconcile failures, used to scale the
+    /// exponential backoff applied before the next retry. Reset to zero once a reconcile
+    /// succeeds.
+    #[serde(default)]
+    pub failure_count: u32,
+    /// Last_failure_time is when `failure_count` was last incremented.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_failure_time: Option<meta::v1::Time>,
+    /// Migrated_image is the image that the pre-deployment migration Job last ran successfully
+    /// against. Compared against `spec.image` to decide whether a new migration is needed before
+    /// the Deployment is rolled over.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub migrated_image: Option<ImageReference>,
 }
 
 /// MatcherSpec describes the desired state of an Matcher instance.
@@ -425,11 +1163,73 @@ pub struct IndexerStatus {
 #[serde(rename_all = "camelCase")]
 pub struct MatcherSpec {
     /// Image is the image that should be used in the managed deployment.
-    pub image: Option<String>,
+    pub image: Option<ImageReference>,
+    /// BoundImages declares auxiliary images (init containers, migration Jobs, sidecars) that
+    /// should be pulled and version-tracked alongside `image`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bound_images: Vec<BoundImage>,
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
     pub config: Option<ConfigSource>,
+    /// Otlp configures exporting traces and metrics to an OpenTelemetry collector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp: Option<OtlpConfig>,
+    /// Resources overrides the default container resource requests/limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::quantity::validate_resources"))]
+    pub resources: Option<core::v1::ResourceRequirements>,
+    /// Replicas is the explicit replica count for this component.
+    ///
+    /// It is stored on the spec only: the generated Deployment's replica count is not set from
+    /// it, and the parent ClairSpec's `scaling` does not fill it in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i32>,
+    /// Autoscaling configures the HorizontalPodAutoscaler generated for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autoscaling: Option<AutoscalingSpec>,
+    /// Disruption configures the PodDisruptionBudget generated for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disruption: Option<DisruptionSpec>,
+    /// Scheduling tunes the topology spread and anti-affinity applied to this component's pods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduling: Option<SchedulingSpec>,
+    /// Probes overrides the default startup/liveness/readiness probe timings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probes: Option<ProbesSpec>,
+    /// Rollout enables a canary/weighted rollout, splitting traffic between a stable and canary
+    /// Deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<RolloutSpec>,
+    /// Overlays are user-supplied patches applied to generated workloads, keyed by the target
+    /// kind (e.g. `"Deployment"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overlays: BTreeMap<String, Overlay>,
+    /// Templates are user-supplied Handlebars templates rendered over generated workloads,
+    /// keyed by the target kind (e.g. `"Deployment"`). Each value references the ConfigMap key
+    /// holding the template source.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, ConfigMapKeySelector>,
+}
+
+impl DeepMerge for MatcherSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.image.merge_from(other.image);
+        self.bound_images.extend(other.bound_images);
+        self.config.merge_from(other.config);
+        self.otlp.merge_from(other.otlp);
+        self.resources.merge_from(other.resources);
+        self.replicas.merge_from(other.replicas);
+        self.autoscaling.merge_from(other.autoscaling);
+        self.disruption.merge_from(other.disruption);
+        self.scheduling.merge_from(other.scheduling);
+        self.probes.merge_from(other.probes);
+        self.rollout.merge_from(other.rollout);
+        self.overlays.extend(other.overlays);
+        self.templates.extend(other.templates);
+    }
 }
+
 /// MatcherStatus describes the observed state of a Matcher instance.
 #[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -445,6 +1245,13 @@ pub struct MatcherStatus {
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<ConfigSource>,
+    /// Resolved_image is the image actually deployed, with any floating tag pinned to the
+    /// content digest it resolved to on the last successful reconcile.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved_image: Option<ImageReference>,
+    /// Errors holds structured faults encountered while reconciling. See [`ReconcileError`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ReconcileError>,
 }
 
 /// UpdaterSpec describes the desired state of an Updater instance.
@@ -462,7 +1269,9 @@ pub struct MatcherStatus {
     derive = "Default",
     printcolumn = r#"{"name":"Suspended","type":"boolean","jsonPath":".spec.suspend"}"#,
     printcolumn = r#"{"name":"Last Success","type":"date","format":"date-time","jsonPath":".status.cronJob.status.last_successful_time"}"#,
-    printcolumn = r#"{"name":"Last Schedule","type":"date","format":"date-time","jsonPath":".status.cronJob.status.last_schedule_time"}"#
+    printcolumn = r#"{"name":"Last Schedule","type":"date","format":"date-time","jsonPath":".status.cronJob.status.last_schedule_time"}"#,
+    printcolumn = r#"{"name":"Run Status","type":"string","jsonPath":".status.recentRuns[0].status"}"#,
+    printcolumn = r#"{"name":"Last Run","type":"date","format":"date-time","jsonPath":".status.recentRuns[0].finishedAt"}"#
 )]
 #[serde(rename_all = "camelCase")]
 pub struct UpdaterSpec {
@@ -471,15 +1280,79 @@ pub struct UpdaterSpec {
     /// If not provided, a sensible default will be used.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub schedule: Option<String>,
+    /// TimeZone is the IANA time zone the schedule is interpreted in, e.g. "America/New_York".
+    ///
+    /// If not provided, "Etc/UTC" is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
     /// Suspend subsequent runs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub suspend: Option<bool>,
+    /// StartingDeadlineSeconds is the deadline, in seconds, for starting a run if it misses its
+    /// scheduled time for any reason.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starting_deadline_seconds: Option<i64>,
+    /// ActiveDeadlineSeconds is the duration, in seconds, a run is allowed to actively run before
+    /// it's terminated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_deadline_seconds: Option<i64>,
+    /// SuccessfulJobsHistoryLimit is the number of successful finished runs to keep around.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub successful_jobs_history_limit: Option<i32>,
+    /// FailedJobsHistoryLimit is the number of failed finished runs to keep around.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_jobs_history_limit: Option<i32>,
 
     /// Image is the image that should be used in the managed deployment.
-    pub image: Option<String>,
+    pub image: Option<ImageReference>,
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<ConfigSource>,
+    /// Otlp configures exporting traces and metrics to an OpenTelemetry collector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp: Option<OtlpConfig>,
+    /// Resources overrides the default container resource requests/limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::quantity::validate_resources"))]
+    pub resources: Option<core::v1::ResourceRequirements>,
+    /// Replicas is the explicit parallelism for the generated CronJob's Job template.
+    ///
+    /// It is stored on the spec only: the generated Job template always uses a parallelism of 1,
+    /// and the parent ClairSpec's `scaling` is not read either.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i32>,
+    /// Overlays are user-supplied patches applied to generated workloads, keyed by the target
+    /// kind (e.g. `"CronJob"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overlays: BTreeMap<String, Overlay>,
+    /// Templates are user-supplied Handlebars templates rendered over generated workloads,
+    /// keyed by the target kind (e.g. `"CronJob"`). Each value references the ConfigMap key
+    /// holding the template source.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, ConfigMapKeySelector>,
+}
+
+impl DeepMerge for UpdaterSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.schedule.merge_from(other.schedule);
+        self.time_zone.merge_from(other.time_zone);
+        self.suspend.merge_from(other.suspend);
+        self.starting_deadline_seconds
+            .merge_from(other.starting_deadline_seconds);
+        self.active_deadline_seconds
+            .merge_from(other.active_deadline_seconds);
+        self.successful_jobs_history_limit
+            .merge_from(other.successful_jobs_history_limit);
+        self.failed_jobs_history_limit
+            .merge_from(other.failed_jobs_history_limit);
+        self.image.merge_from(other.image);
+        self.config.merge_from(other.config);
+        self.otlp.merge_from(other.otlp);
+        self.resources.merge_from(other.resources);
+        self.replicas.merge_from(other.replicas);
+        self.overlays.extend(other.overlays);
+        self.templates.extend(other.templates);
+    }
 }
 
 /// UpdaterStatus describes the observed state of a Updater instance.
@@ -500,6 +1373,67 @@ pub struct UpdaterStatus {
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<ConfigSource>,
+    /// Errors holds structured faults encountered while reconciling. See [`ReconcileError`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ReconcileError>,
+    /// RecentRuns holds a bounded history of recent updater runs, most recent first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_runs: Vec<UpdateRun>,
+    /// LastSuccessfulRun is the most recent run that completed successfully.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_successful_run: Option<UpdateRun>,
+}
+
+/// UpdateRunState is the outcome of a single vulnerability-feed update run.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "PascalCase")]
+pub enum UpdateRunState {
+    /// Pending indicates the run has been scheduled but not yet started.
+    #[default]
+    Pending,
+    /// InProgress indicates the run is currently executing.
+    InProgress,
+    /// Completed indicates the run finished successfully.
+    Completed,
+    /// Failed indicates the run finished unsuccessfully.
+    Failed,
+    /// RetryScheduled indicates the run failed but a retry has been scheduled.
+    RetryScheduled,
+}
+
+impl std::fmt::Display for UpdateRunState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateRunState::Pending => write!(f, "Pending"),
+            UpdateRunState::InProgress => write!(f, "InProgress"),
+            UpdateRunState::Completed => write!(f, "Completed"),
+            UpdateRunState::Failed => write!(f, "Failed"),
+            UpdateRunState::RetryScheduled => write!(f, "RetryScheduled"),
+        }
+    }
+}
+
+/// UpdateRun records the outcome of a single vulnerability-feed update run, modeled on the
+/// apply-update status pattern: a state enum paired with start/finish timestamps.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateRun {
+    /// Status is the outcome of this run.
+    #[serde(default)]
+    pub status: UpdateRunState,
+    /// StartedAt is when this run began.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<meta::v1::Time>,
+    /// FinishedAt is when this run ended, if it has.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<meta::v1::Time>,
+    /// Error holds the structured fault that caused this run to fail, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ReconcileError>,
+    /// Results holds per-updater result counts (e.g. items fetched or stored), keyed by updater
+    /// name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub results: BTreeMap<String, i64>,
 }
 
 /// NotifierSpec describes the desired state of an Notifier instance.
@@ -519,11 +1453,73 @@ pub struct UpdaterStatus {
 #[serde(rename_all = "camelCase")]
 pub struct NotifierSpec {
     /// Image is the image that should be used in the managed deployment.
-    pub image: Option<String>,
+    pub image: Option<ImageReference>,
+    /// BoundImages declares auxiliary images (init containers, migration Jobs, sidecars) that
+    /// should be pulled and version-tracked alongside `image`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bound_images: Vec<BoundImage>,
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(nested)]
     pub config: Option<ConfigSource>,
+    /// Otlp configures exporting traces and metrics to an OpenTelemetry collector.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp: Option<OtlpConfig>,
+    /// Resources overrides the default container resource requests/limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "crate::quantity::validate_resources"))]
+    pub resources: Option<core::v1::ResourceRequirements>,
+    /// Replicas is the explicit replica count for this component.
+    ///
+    /// It is stored on the spec only: the generated Deployment's replica count is not set from
+    /// it, and the parent ClairSpec's `scaling` does not fill it in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<i32>,
+    /// Autoscaling configures the HorizontalPodAutoscaler generated for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autoscaling: Option<AutoscalingSpec>,
+    /// Disruption configures the PodDisruptionBudget generated for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disruption: Option<DisruptionSpec>,
+    /// Scheduling tunes the topology spread and anti-affinity applied to this component's pods.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduling: Option<SchedulingSpec>,
+    /// Probes overrides the default startup/liveness/readiness probe timings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probes: Option<ProbesSpec>,
+    /// Rollout enables a canary/weighted rollout, splitting traffic between a stable and canary
+    /// Deployment.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout: Option<RolloutSpec>,
+    /// Overlays are user-supplied patches applied to generated workloads, keyed by the target
+    /// kind (e.g. `"Deployment"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overlays: BTreeMap<String, Overlay>,
+    /// Templates are user-supplied Handlebars templates rendered over generated workloads,
+    /// keyed by the target kind (e.g. `"Deployment"`). Each value references the ConfigMap key
+    /// holding the template source.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, ConfigMapKeySelector>,
+}
+
+impl DeepMerge for NotifierSpec {
+    fn merge_from(&mut self, other: Self) {
+        self.image.merge_from(other.image);
+        self.bound_images.extend(other.bound_images);
+        self.config.merge_from(other.config);
+        self.otlp.merge_from(other.otlp);
+        self.resources.merge_from(other.resources);
+        self.replicas.merge_from(other.replicas);
+        self.autoscaling.merge_from(other.autoscaling);
+        self.disruption.merge_from(other.disruption);
+        self.scheduling.merge_from(other.scheduling);
+        self.probes.merge_from(other.probes);
+        self.rollout.merge_from(other.rollout);
+        self.overlays.extend(other.overlays);
+        self.templates.extend(other.templates);
+    }
 }
+
 /// NotifierStatus describes the observed state of a Notifier instance.
 #[derive(Clone, Default, Debug, Deserialize, PartialEq, Serialize, Validate, JsonSchema)]
 #[serde(rename_all = "camelCase")]
@@ -539,6 +1535,9 @@ pub struct NotifierStatus {
     /// Config is configuration sources for the Clair instance.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<ConfigSource>,
+    /// Errors holds structured faults encountered while reconciling. See [`ReconcileError`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ReconcileError>,
 }
 
 /// Private holds traits that external modules can't name, and so can't implement.
@@ -557,14 +1556,37 @@ mod private {
         fn set_conditions(&mut self, cnd: Vec<meta::v1::Condition>);
         fn get_refs(&self) -> &Vec<core::v1::TypedLocalObjectReference>;
         fn set_refs(&mut self, refs: Vec<core::v1::TypedLocalObjectReference>);
+        fn get_errors(&self) -> &Vec<super::ReconcileError>;
+        fn set_errors(&mut self, errors: Vec<super::ReconcileError>);
     }
     pub trait SpecCommon {
-        fn get_image(&self) -> Option<&String>;
+        fn get_image(&self) -> Option<&super::ImageReference>;
         fn set_image<S: ToString>(&mut self, img: S);
+        fn get_overlay(&self, kind: &str) -> Option<&super::Overlay>;
+        /// Get_endpoint returns how this Spec's externally-reachable endpoint should be
+        /// configured, if this Spec kind exposes one. Only [`super::ClairSpec`] does today.
+        fn get_endpoint(&self) -> Option<&super::Endpoint> {
+            None
+        }
+        /// Get_template returns the user-supplied Handlebars template ConfigMap key for `kind`,
+        /// if any.
+        fn get_template(&self, kind: &str) -> Option<&super::ConfigMapKeySelector>;
     }
     pub trait SubSpecCommon: SpecCommon {
         fn get_config(&self) -> Option<&super::ConfigSource>;
         fn set_config(&mut self, cfg: Option<super::ConfigSource>);
+        fn get_autoscaling(&self) -> Option<&super::AutoscalingSpec>;
+        fn get_bound_images(&self) -> &Vec<super::BoundImage>;
+        fn set_bound_images(&mut self, images: Vec<super::BoundImage>);
+    }
+    pub trait SubStatusCommon {
+        fn get_config(&self) -> Option<&super::ConfigSource>;
+        fn set_config(&mut self, cfg: Option<super::ConfigSource>);
+    }
+    pub trait ScalableSpec {
+        fn get_replicas(&self) -> Option<i32>;
+        fn set_replicas(&mut self, n: Option<i32>);
+        fn get_resources(&self) -> Option<&core::v1::ResourceRequirements>;
     }
 }
 
@@ -645,6 +1667,45 @@ pub trait StatusCommon: private::StatusCommon {
         self.set_conditions(out);
     }
 
+    /// Reconcile_ready rolls `parts` (each a named health check, e.g. `("Indexer", true)`) into a
+    /// canonical `Ready` [`Condition`](meta::v1::Condition), following Kubernetes conventions:
+    /// `status` is `"True"` only when every part is ready, `reason` names the first failing part,
+    /// and `last_transition_time` only advances when `status` actually changes, so `kubectl wait
+    /// --for=condition=Ready` gets a stable signal.
+    fn reconcile_ready(&mut self, observed_generation: i64, parts: &[(&str, bool)]) {
+        use self::meta::v1::{Condition, Time};
+
+        let ready = parts.iter().all(|(_, ok)| *ok);
+        let status = if ready { "True" } else { "False" }.to_string();
+        let reason = match parts.iter().find(|(_, ok)| !ok) {
+            Some((name, _)) => name.to_string(),
+            None => "AllPartsReady".to_string(),
+        };
+        let message = if ready {
+            "all parts ready".to_string()
+        } else {
+            let waiting: Vec<&str> = parts
+                .iter()
+                .filter(|(_, ok)| !ok)
+                .map(|(name, _)| *name)
+                .collect();
+            format!("waiting on: {}", waiting.join(", "))
+        };
+        let last_transition_time = match self.get_conditions().iter().find(|c| c.type_ == "Ready") {
+            Some(existing) if existing.status == status => existing.last_transition_time.clone(),
+            _ => Time(chrono::Utc::now()),
+        };
+
+        self.add_condition(Condition {
+            type_: "Ready".to_string(),
+            status,
+            reason,
+            message,
+            observed_generation: Some(observed_generation),
+            last_transition_time,
+        });
+    }
+
     /// Add_ref adds a reference to `obj`, ensuring the list is deduplicated.
     fn add_ref<K>(&mut self, obj: &K)
     where
@@ -685,6 +1746,71 @@ pub trait StatusCommon: private::StatusCommon {
         let kind = K::kind(&());
         self.get_refs().iter().find(|r| r.kind == kind).cloned()
     }
+
+    /// Add_bound_image_ref records the resolved reference for the [`BoundImage`] named `name`,
+    /// reusing the same `refs` bookkeeping (and dedup-and-sort discipline) as [`add_ref`], keyed
+    /// by name rather than by kind since several bound images may coexist.
+    ///
+    /// [`add_ref`]: StatusCommon::add_ref
+    fn add_bound_image_ref(&mut self, name: &str, resolved: &ImageReference) {
+        use self::core::v1::TypedLocalObjectReference;
+        let r = TypedLocalObjectReference {
+            kind: "Image".to_string(),
+            api_group: Some(name.to_string()),
+            name: resolved.to_string(),
+        };
+        let mut found = false;
+        let mut out: Vec<TypedLocalObjectReference> = self
+            .get_refs()
+            .iter()
+            .map(|c| {
+                if c.kind == r.kind && c.api_group == r.api_group {
+                    found = true;
+                    &r
+                } else {
+                    c
+                }
+            })
+            .cloned()
+            .collect();
+        if !found {
+            out.push(r);
+        }
+        out.sort_unstable_by_key(|c| (c.kind.clone(), c.api_group.clone()));
+        self.set_refs(out);
+    }
+
+    /// Has_bound_image_ref returns the resolved reference recorded for the bound image named
+    /// `name`, if any.
+    fn has_bound_image_ref(&self, name: &str) -> Option<core::v1::TypedLocalObjectReference> {
+        self.get_refs()
+            .iter()
+            .find(|r| r.kind == "Image" && r.api_group.as_deref() == Some(name))
+            .cloned()
+    }
+
+    /// Add_error adds a ReconcileError, ensuring the list is deduplicated by `code` and `target`.
+    fn add_error(&mut self, err: ReconcileError) {
+        let mut found = false;
+        let mut out: Vec<ReconcileError> = self
+            .get_errors()
+            .iter()
+            .map(|e| {
+                if e.code == err.code && e.target == err.target {
+                    found = true;
+                    &err
+                } else {
+                    e
+                }
+            })
+            .cloned()
+            .collect();
+        if !found {
+            out.push(err);
+        }
+        out.sort_unstable_by_key(|e| (e.code.clone(), e.target.clone()));
+        self.set_errors(out);
+    }
 }
 
 macro_rules! impl_status {
@@ -703,6 +1829,12 @@ macro_rules! impl_status {
             fn set_refs(&mut self, refs: Vec<core::v1::TypedLocalObjectReference>) {
                 self.refs = refs;
             }
+            fn get_errors(&self) -> &Vec<ReconcileError> {
+                &self.errors
+            }
+            fn set_errors(&mut self, errors: Vec<ReconcileError>) {
+                self.errors = errors;
+            }
         }
         impl StatusCommon for $kind {}
         )+
@@ -718,9 +1850,42 @@ impl_status!(
 
 /// SpecCommon is helpers for working Spec objects.
 pub trait SpecCommon: private::SpecCommon {
+    /// Resolved_image composes this Spec's image override onto `default`, inheriting whichever
+    /// components (registry, repository, tag or digest) the override didn't specify.
+    fn resolved_image(&self, default: &ImageReference) -> ImageReference {
+        match self.get_image() {
+            Some(img) => ImageReference {
+                registry: img.registry.clone().or_else(|| default.registry.clone()),
+                repository: img.repository.clone(),
+                selector: img.selector.clone().or_else(|| default.selector.clone()),
+            },
+            None => default.clone(),
+        }
+    }
     /// Image_default reports the desired image, or "img" if unspecified.
+    ///
+    /// This is a thin wrapper around [`resolved_image`](SpecCommon::resolved_image) for callers
+    /// that only need the composed image as a string.
     fn image_default(&self, img: &String) -> String {
-        self.get_image().unwrap_or(img).clone()
+        let default: ImageReference = img
+            .parse()
+            .expect("default image must be a valid image reference");
+        self.resolved_image(&default).to_string()
+    }
+    /// Overlay returns the user-supplied patch for `kind` (e.g. `"Deployment"`), if any.
+    fn overlay(&self, kind: &str) -> Option<&Overlay> {
+        self.get_overlay(kind)
+    }
+    /// Endpoint returns how this instance's externally-reachable endpoint (an Ingress or Gateway
+    /// API HTTPRoute) should be configured, if this Spec kind supports one. Only [`ClairSpec`]
+    /// does today.
+    fn endpoint(&self) -> Option<&Endpoint> {
+        self.get_endpoint()
+    }
+    /// Template returns the user-supplied Handlebars template ConfigMap key for `kind` (e.g.
+    /// `"Deployment"`), if any.
+    fn template(&self, kind: &str) -> Option<&ConfigMapKeySelector> {
+        self.get_template(kind)
     }
 }
 
@@ -728,24 +1893,47 @@ macro_rules! impl_spec {
     ($($kind:ty),+ $(,)?) => {
         $(
         impl private::SpecCommon for $kind {
-            fn get_image(&self) -> Option<&String> {
+            fn get_image(&self) -> Option<&ImageReference> {
                 self.image.as_ref()
             }
-            fn set_image<S: ToString>(&mut self, img:S) {
-                self.image = Some(img.to_string());
+            fn set_image<S: ToString>(&mut self, img: S) {
+                if let Ok(img) = img.to_string().parse() {
+                    self.image = Some(img);
+                }
+            }
+            fn get_overlay(&self, kind: &str) -> Option<&Overlay> {
+                self.overlays.get(kind)
+            }
+            fn get_template(&self, kind: &str) -> Option<&ConfigMapKeySelector> {
+                self.templates.get(kind)
             }
         }
         impl SpecCommon for $kind {}
         )+
     };
 }
-impl_spec!(
-    ClairSpec,
-    IndexerSpec,
-    MatcherSpec,
-    NotifierSpec,
-    UpdaterSpec,
-);
+impl_spec!(IndexerSpec, MatcherSpec, NotifierSpec, UpdaterSpec);
+
+impl private::SpecCommon for ClairSpec {
+    fn get_image(&self) -> Option<&ImageReference> {
+        self.image.as_ref()
+    }
+    fn set_image<S: ToString>(&mut self, img: S) {
+        if let Ok(img) = img.to_string().parse() {
+            self.image = Some(img);
+        }
+    }
+    fn get_overlay(&self, kind: &str) -> Option<&Overlay> {
+        self.overlays.get(kind)
+    }
+    fn get_endpoint(&self) -> Option<&Endpoint> {
+        self.endpoint.as_ref()
+    }
+    fn get_template(&self, kind: &str) -> Option<&ConfigMapKeySelector> {
+        self.templates.get(kind)
+    }
+}
+impl SpecCommon for ClairSpec {}
 
 /// SubSpecCommon is helper for the common "subresource" types.
 pub trait SubSpecCommon: private::SubSpecCommon {
@@ -754,6 +1942,10 @@ pub trait SubSpecCommon: private::SubSpecCommon {
         self.set_image(img);
         self.set_config(cfg);
     }
+    /// Autoscaling returns the HorizontalPodAutoscaler tuning for this component, if set.
+    fn autoscaling(&self) -> Option<&AutoscalingSpec> {
+        self.get_autoscaling()
+    }
 }
 macro_rules! impl_subspec {
     ($($kind:ty),+ $(,)?) => {
@@ -765,9 +1957,75 @@ macro_rules! impl_subspec {
             fn set_config(&mut self, cfg: Option<ConfigSource>) {
                 self.config = cfg;
             }
+            fn get_autoscaling(&self) -> Option<&AutoscalingSpec> {
+                self.autoscaling.as_ref()
+            }
+            fn get_bound_images(&self) -> &Vec<BoundImage> {
+                &self.bound_images
+            }
+            fn set_bound_images(&mut self, images: Vec<BoundImage>) {
+                self.bound_images = images;
+            }
         }
         impl SubSpecCommon for $kind {}
         )+
     };
 }
 impl_subspec!(IndexerSpec, MatcherSpec, NotifierSpec);
+
+/// SubStatusCommon is the Status-side mirror of [`SubSpecCommon`], for the same "subresource"
+/// kinds: it lets generic reconcile code (the controller crate's `subresource` module) record the
+/// `ConfigSource` it last reconciled against without reaching into a kind-specific field.
+pub trait SubStatusCommon: private::SubStatusCommon {
+    /// Config returns the config source last recorded on this status, if any.
+    fn config(&self) -> Option<&ConfigSource> {
+        self.get_config()
+    }
+}
+macro_rules! impl_substatus {
+    ($($kind:ty),+ $(,)?) => {
+        $(
+        impl private::SubStatusCommon for $kind {
+            fn get_config(&self) -> Option<&ConfigSource> {
+                self.config.as_ref()
+            }
+            fn set_config(&mut self, cfg: Option<ConfigSource>) {
+                self.config = cfg;
+            }
+        }
+        impl SubStatusCommon for $kind {}
+        )+
+    };
+}
+impl_substatus!(IndexerStatus, MatcherStatus, NotifierStatus);
+
+/// ScalableSpec is helpers for component specs that can be explicitly sized.
+pub trait ScalableSpec: private::ScalableSpec {
+    /// Replicas returns the explicit replica count for this component, if set.
+    fn replicas(&self) -> Option<i32> {
+        self.get_replicas()
+    }
+    /// Resources returns the resource requirements tuning for this component, if set.
+    fn resources(&self) -> Option<&core::v1::ResourceRequirements> {
+        self.get_resources()
+    }
+}
+macro_rules! impl_scalable {
+    ($($kind:ty),+ $(,)?) => {
+        $(
+        impl private::ScalableSpec for $kind {
+            fn get_replicas(&self) -> Option<i32> {
+                self.replicas
+            }
+            fn set_replicas(&mut self, n: Option<i32>) {
+                self.replicas = n;
+            }
+            fn get_resources(&self) -> Option<&core::v1::ResourceRequirements> {
+                self.resources.as_ref()
+            }
+        }
+        impl ScalableSpec for $kind {}
+        )+
+    };
+}
+impl_scalable!(IndexerSpec, MatcherSpec, NotifierSpec, UpdaterSpec);
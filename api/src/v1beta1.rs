@@ -0,0 +1,166 @@
+//! Module `v1beta1` implements the v1beta1 Clair CRD API.
+//!
+//! Only `Clair` has been promoted to `v1beta1` so far, as the pilot for the multi-version +
+//! conversion-webhook machinery (see `controller::webhook`'s `convert` module); the other kinds
+//! stay on `v1alpha1` until they need it too. `ClairSpec` here is schema-identical to
+//! [`crate::v1alpha1::ClairSpec`] --- no fields have actually changed yet --- so every conversion
+//! below is a plain field-for-field copy. That'll stop being true the day `v1beta1` diverges, at
+//! which point the `From` impls (and likely the hub's `to_hub`/`from_hub` overrides) are where
+//! that mapping belongs.
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::core;
+use kube::CustomResource;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::v1alpha1::{ClairStatus, ConfigDialect, DropinSource, Endpoint, ImageReference, OtlpConfig, Overlay, ScalingSpec, ConfigMapKeySelector, ConfigStorageSpec, Databases};
+
+/// ClairSpec describes the desired state of a Clair instance.
+#[derive(
+    CustomResource, Clone, Debug, Default, Deserialize, PartialEq, Serialize, Validate, JsonSchema,
+)]
+#[kube(
+    group = "projectclair.io",
+    version = "v1beta1",
+    kind = "Clair",
+    namespaced,
+    status = "ClairStatus",
+    derive = "PartialEq",
+    shortname = "clair",
+    category = "apps"
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ClairSpec {
+    /// .
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<ImageReference>,
+    /// Databases indicates the Secret keys holding config drop-ins that services should connect
+    /// to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub databases: Option<Databases>,
+    /// Endpoint indicates how the Ingress should be created.
+    ///
+    /// If unspecified, the resulting endpoint will need to be read out of the status subresource.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<Endpoint>,
+    /// Notifier enables the notifier subsystem.
+    ///
+    /// The operator does not start the notifier by default. If it's configured via a drop-in, this
+    /// field should be set to start it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notifier: Option<bool>,
+    /// Dropins references additional config drop-in files.
+    ///
+    /// See the Clair documentation for how config drop-ins are handled.
+    #[validate(nested)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dropins: Vec<DropinSource>,
+    /// ConfigDialect specifies the format to generate for the main config.
+    ///
+    /// This setting affects what format config drop-ins must be in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_dialect: Option<ConfigDialect>,
+    /// Otlp configures exporting traces and metrics to an OpenTelemetry collector.
+    ///
+    /// If unset, the Prometheus-only metrics path is used and no traces are exported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otlp: Option<OtlpConfig>,
+    /// Resources overrides the default container resource requests/limits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<core::v1::ResourceRequirements>,
+    /// Scaling holds default replica counts and resource requirements for the subsystems. It is
+    /// stored and merged with the rest of the spec, but no controller reads it, so it does not
+    /// change any subsystem's spec or generated workload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scaling: Option<ScalingSpec>,
+    /// ConfigStorage requests a PersistentVolumeClaim-backed config volume instead of the default
+    /// ConfigMap projection.
+    #[validate(nested)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config_storage: Option<ConfigStorageSpec>,
+    /// Overlays are user-supplied patches applied to generated workloads, keyed by the target
+    /// kind (e.g. `"CronJob"`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overlays: BTreeMap<String, Overlay>,
+    /// Templates are user-supplied Handlebars templates rendered over generated workloads,
+    /// keyed by the target kind (e.g. `"Deployment"`). Each value references the ConfigMap key
+    /// holding the template source.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, ConfigMapKeySelector>,
+}
+
+impl From<crate::v1alpha1::Clair> for Clair {
+    fn from(c: crate::v1alpha1::Clair) -> Self {
+        let crate::v1alpha1::ClairSpec {
+            image,
+            databases,
+            endpoint,
+            notifier,
+            dropins,
+            config_dialect,
+            otlp,
+            resources,
+            scaling,
+            config_storage,
+            overlays,
+            templates,
+        } = c.spec;
+        Clair {
+            metadata: c.metadata,
+            spec: ClairSpec {
+                image,
+                databases,
+                endpoint,
+                notifier,
+                dropins,
+                config_dialect,
+                otlp,
+                resources,
+                scaling,
+                config_storage,
+                overlays,
+                templates,
+            },
+            status: c.status,
+        }
+    }
+}
+
+impl From<Clair> for crate::v1alpha1::Clair {
+    fn from(c: Clair) -> Self {
+        let ClairSpec {
+            image,
+            databases,
+            endpoint,
+            notifier,
+            dropins,
+            config_dialect,
+            otlp,
+            resources,
+            scaling,
+            config_storage,
+            overlays,
+            templates,
+        } = c.spec;
+        crate::v1alpha1::Clair {
+            metadata: c.metadata,
+            spec: crate::v1alpha1::ClairSpec {
+                image,
+                databases,
+                endpoint,
+                notifier,
+                dropins,
+                config_dialect,
+                otlp,
+                resources,
+                scaling,
+                config_storage,
+                overlays,
+                templates,
+            },
+            status: c.status,
+        }
+    }
+}
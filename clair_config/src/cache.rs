@@ -0,0 +1,68 @@
+//! Cache memoizes [`Parts::validate`](crate::Parts::validate) results keyed by the SHA-256 digest
+//! of the rendered config document, so retries, repeated `kubectl apply`s, and controller
+//! requeues submitting the same document don't pay for another four-mode FFI round trip.
+//!
+//! [`Warnings`](crate::Warnings) and [`Error`](crate::Error) aren't `Clone`, so what's cached is
+//! the plain strings backing them (`Ok(warning lines)` or `Err(message)`) rather than the types
+//! themselves; [`Parts::validate`](crate::Parts::validate) reconstructs the real types from the
+//! cache on a hit.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::LazyLock;
+
+use openssl::hash::{hash, MessageDigest};
+use tokio::sync::RwLock;
+
+/// Capacity bounds the cache so a long-running controller validating many distinct configs over
+/// its lifetime doesn't grow this map unboundedly. Eviction is plain FIFO, not true LRU — good
+/// enough given the cache only exists to absorb short bursts of identical resubmissions.
+const CAPACITY: usize = 256;
+
+/// Digest is the SHA-256 of a rendered config document, used as the cache key.
+pub(crate) type Digest = [u8; 32];
+
+/// Mode_outputs is one [`Parts::validate`](crate::Parts::validate) result, cached as plain
+/// strings for all four modes.
+#[derive(Clone)]
+pub(crate) struct ModeOutputs {
+    pub(crate) indexer: Result<String, String>,
+    pub(crate) matcher: Result<String, String>,
+    pub(crate) notifier: Result<String, String>,
+    pub(crate) updater: Result<String, String>,
+}
+
+#[derive(Default)]
+struct Inner {
+    map: HashMap<Digest, ModeOutputs>,
+    // Insertion order, oldest first, for FIFO eviction once `CAPACITY` is exceeded.
+    order: VecDeque<Digest>,
+}
+
+static CACHE: LazyLock<RwLock<Inner>> = LazyLock::new(|| RwLock::new(Inner::default()));
+
+/// Digest_of hashes a rendered config document into a cache key.
+pub(crate) fn digest_of(doc: &[u8]) -> Digest {
+    let d = hash(MessageDigest::sha256(), doc).expect("SHA-256 is always available");
+    let mut out = Digest::default();
+    out.copy_from_slice(&d);
+    out
+}
+
+/// Get returns the cached [`ModeOutputs`] for `digest`, if any.
+pub(crate) async fn get(digest: &Digest) -> Option<ModeOutputs> {
+    CACHE.read().await.map.get(digest).cloned()
+}
+
+/// Insert records `outputs` for `digest`, evicting the oldest entry first if the cache is full.
+pub(crate) async fn insert(digest: Digest, outputs: ModeOutputs) {
+    let mut inner = CACHE.write().await;
+    if !inner.map.contains_key(&digest) {
+        inner.order.push_back(digest);
+        if inner.order.len() > CAPACITY {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.map.remove(&oldest);
+            }
+        }
+    }
+    inner.map.insert(digest, outputs);
+}
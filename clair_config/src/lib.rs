@@ -8,14 +8,19 @@
 #![warn(missing_docs)]
 
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use k8s_openapi::api::core;
 use tracing::{debug, trace};
 
 use api::v1alpha1;
 
+mod cache;
 mod sys;
 
+#[cfg(feature = "subprocess-validator")]
+mod subprocess;
+
 /// Error enumerates the errors reported by this module.
 #[derive(Debug)]
 pub enum Error {
@@ -26,10 +31,14 @@ pub enum Error {
 
     /// YAML deserialization error.
     YAML(serde_yaml::Error),
+    /// TOML deserialization error.
+    TOML(toml::de::Error),
     /// JSON serialiization or deserialization error.
     JSON(serde_json::Error),
     /// JSON Patch error
     Patch(json_patch::PatchError),
+    /// Task inidicates a spawned validation task panicked or was cancelled.
+    Task(tokio::task::JoinError),
 
     /// Error for testing only.
     #[cfg(test)]
@@ -61,19 +70,31 @@ impl From<serde_yaml::Error> for Error {
         Self::YAML(err)
     }
 }
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Self::TOML(err)
+    }
+}
 impl From<json_patch::PatchError> for Error {
     fn from(err: json_patch::PatchError) -> Self {
         Self::Patch(err)
     }
 }
+impl From<tokio::task::JoinError> for Error {
+    fn from(err: tokio::task::JoinError) -> Self {
+        Self::Task(err)
+    }
+}
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Invalid(msg) => write!(f, "invalid ConfigSource: {msg}"),
             Error::Validation(msg) => write!(f, "validation failure: {msg}"),
             Error::YAML(err) => write!(f, "YAML error: {err}"),
+            Error::TOML(err) => write!(f, "TOML error: {err}"),
             Error::JSON(err) => write!(f, "JSON error: {err}"),
             Error::Patch(err) => write!(f, "json patch error: {err}"),
+            Error::Task(err) => write!(f, "task error: {err}"),
             #[cfg(test)]
             Error::Test(msg) => write!(f, "testing error: {msg}"),
         }
@@ -94,6 +115,10 @@ impl Parts {
     /// The changes for defaults made by the `Validate` function are not returned, so that the config
     /// package can change the defaults as needed.
     ///
+    /// Results are cached by the SHA-256 digest of the rendered document: repeated submissions of
+    /// the same config (retries, repeated `kubectl apply`, controller requeues) short-circuit the
+    /// FFI calls entirely instead of re-validating.
+    ///
     /// [`config.Validate`]: https://pkg.go.dev/github.com/quay/clair/config#Validate
     /// [`cmd.Load`]: https://pkg.go.dev/github.com/quay/clair/v4/cmd#Load
     pub async fn validate(&self) -> Result<Validate> {
@@ -116,15 +141,102 @@ impl Parts {
                 doc
             });
         trace!("config rendered");
-        let doc = serde_json::to_vec(&doc)?;
-        Ok(Validate {
-            indexer: validate_config(&doc, "indexer").await,
-            matcher: validate_config(&doc, "matcher").await,
-            notifier: validate_config(&doc, "notifier").await,
+        let doc = Arc::new(serde_json::to_vec(&doc)?);
+
+        let digest = cache::digest_of(&doc);
+        if let Some(outputs) = cache::get(&digest).await {
+            trace!("validation cache hit");
+            return Ok(Validate {
+                indexer: from_cached("indexer", outputs.indexer),
+                matcher: from_cached("matcher", outputs.matcher),
+                notifier: from_cached("notifier", outputs.notifier),
+                updater: from_cached("updater", outputs.updater),
+            });
+        }
 
-            updater: validate_config(&doc, "updater").await,
+        let (indexer, matcher, notifier, updater) = tokio::join!(
+            validate_config(doc.clone(), "indexer"),
+            validate_config(doc.clone(), "matcher"),
+            validate_config(doc.clone(), "notifier"),
+            validate_config(doc, "updater"),
+        );
+        cache::insert(
+            digest,
+            cache::ModeOutputs {
+                indexer: to_cached(&indexer),
+                matcher: to_cached(&matcher),
+                notifier: to_cached(&notifier),
+                updater: to_cached(&updater),
+            },
+        )
+        .await;
+        Ok(Validate {
+            indexer,
+            matcher,
+            notifier,
+            updater,
         })
     }
+
+    /// Defaults renders the config (as [`Parts::render`] does) and folds each mode's
+    /// Go-side-defaulted document back onto it, in the same (indexer, matcher, notifier, updater)
+    /// order `validate` checks them, so a later mode's defaults win any overlapping keys.
+    ///
+    /// This is the piece [`Parts::validate`]'s doc comment says is thrown away: the `config`
+    /// package fills in defaults while validating, but `Validate` only ever reported the
+    /// resulting warnings. A mutating webhook wants the defaulted document itself, so it can
+    /// patch a submitted object up to what the config package would have used anyway.
+    ///
+    /// A mode failing outright (e.g. "updater", which upstream doesn't implement yet, see
+    /// [`Validate::updater`]) just contributes nothing rather than failing the whole call — the
+    /// caller already has [`Parts::validate`] if it needs to know which modes are broken.
+    pub async fn defaults(&self) -> Result<serde_json::Value> {
+        let mut doc = self.render()?;
+        let raw = Arc::new(serde_json::to_vec(&doc)?);
+        let (indexer, matcher, notifier, updater) = tokio::join!(
+            default_config(raw.clone(), "indexer"),
+            default_config(raw.clone(), "matcher"),
+            default_config(raw.clone(), "notifier"),
+            default_config(raw, "updater"),
+        );
+        for defaulted in [indexer, matcher, notifier, updater] {
+            match defaulted {
+                Ok(buf) => json_patch::merge(&mut doc, &serde_json::from_slice(&buf)?),
+                Err(Error::Validation(err)) => trace!(err, "mode not applicable; no defaults to merge"),
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(doc)
+    }
+}
+
+impl Parts {
+    /// Render folds the dropins onto the root document, in order, and returns the resulting JSON
+    /// value.
+    ///
+    /// Unlike [`Parts::validate`], this never shells out to the (Go) semantic validator and never
+    /// panics on a malformed dropin — it's the cheap half of assembling a config, safe to run
+    /// before committing a rendered config anywhere. A failing JSON patch or an undeserializable
+    /// dropin is reported as an [`Error::Invalid`] naming the offending dropin key.
+    pub fn render(&self) -> Result<serde_json::Value> {
+        let mut doc: serde_json::Value = serde_json::from_slice(&self.root)?;
+        for (name, (buf, is_patch)) in &self.dropins {
+            if *is_patch {
+                let p: json_patch::Patch = serde_json::from_slice(buf)
+                    .map_err(|err| Error::invalid(format!("dropin {name:?}: invalid JSON patch: {err}")))?;
+                json_patch::patch(&mut doc, &p)
+                    .map_err(|err| Error::invalid(format!("dropin {name:?}: {err}")))?;
+            } else {
+                let m: serde_json::Value = serde_json::from_slice(buf)
+                    .map_err(|err| Error::invalid(format!("dropin {name:?}: invalid JSON: {err}")))?;
+                json_patch::merge(&mut doc, &m);
+            }
+        }
+        if !doc.is_object() {
+            return Err(Error::invalid("merged config is not a JSON object"));
+        }
+        Ok(doc)
+    }
 }
 
 impl From<Builder> for Parts {
@@ -138,9 +250,8 @@ impl From<Builder> for Parts {
 
 /// Builder constructs all the root and dropins for a configuration.
 pub struct Builder {
-    flavor: v1alpha1::ConfigDialect,
-
     root: Vec<u8>,
+    flavor: v1alpha1::ConfigDialect,
     dropins: BTreeMap<String, (Vec<u8>, bool)>,
 }
 
@@ -157,26 +268,24 @@ impl Builder {
         }
         .ok_or_else(|| Error::invalid(format!("missing key: {key}")))?;
         trace!(key, "loaded key");
-        let flavor = match key.rsplit_once('.') {
-            Some((_, ext)) => match ext {
-                "json" => v1alpha1::ConfigDialect::JSON,
-                "yaml" => v1alpha1::ConfigDialect::YAML,
-                ext => return Err(Error::invalid(format!("unknown file extension: {ext}"))),
-            },
-            None => return Err(Error::invalid("missing file extension")),
-        };
+        let flavor = guess_flavor(&key)?;
         trace!(%flavor, "guessed config flavor");
         let root = to_json(root, &flavor)?;
         trace!(key, "converted to JSON");
         debug!("created Builder");
         Ok(Builder {
-            flavor,
             root,
+            flavor,
             dropins: Default::default(),
         })
     }
 
     /// Add adds a dropin, converting to JSON if needed.
+    ///
+    /// The dropin's dialect is guessed from its own key extension, then checked against the
+    /// root's dialect: a mismatch (e.g. a YAML dropin alongside a JSON root) is a hard error,
+    /// since Clair expects an entire `ConfigSource` — root and dropins alike — to be authored in
+    /// one declared [`v1alpha1::ConfigDialect`].
     pub fn add<M, S>(mut self, map: M, key: S) -> Result<Self>
     where
         M: K8sMap,
@@ -188,7 +297,15 @@ impl Builder {
             .value(key.clone())
             .ok_or_else(|| Error::invalid(format!("missing key: {key}")))?;
         trace!(key, is_patch, "loaded key");
-        let buf = to_json(buf, &self.flavor)?;
+        let flavor = guess_flavor(&key)?;
+        trace!(key, %flavor, is_patch, "guessed dropin dialect");
+        if flavor != self.flavor {
+            return Err(Error::invalid(format!(
+                "dropin {key} is {flavor}, but the root config is {}",
+                self.flavor
+            )));
+        }
+        let buf = to_json(buf, &flavor)?;
         trace!(key, is_patch, "converted to JSON");
         self.dropins.insert(key, (buf, is_patch));
         debug!("added dropin");
@@ -196,6 +313,21 @@ impl Builder {
     }
 }
 
+/// Guess_flavor infers a [`v1alpha1::ConfigDialect`] from a key's file extension, ignoring any
+/// trailing `-patch` suffix (e.g. `00-components.json-patch` is JSON).
+fn guess_flavor(key: &str) -> Result<v1alpha1::ConfigDialect> {
+    let key = key.strip_suffix("-patch").unwrap_or(key);
+    match key.rsplit_once('.') {
+        Some((_, ext)) => match ext {
+            "json" => Ok(v1alpha1::ConfigDialect::JSON),
+            "yaml" => Ok(v1alpha1::ConfigDialect::YAML),
+            "toml" => Ok(v1alpha1::ConfigDialect::TOML),
+            ext => Err(Error::invalid(format!("unknown file extension: {ext}"))),
+        },
+        None => Err(Error::invalid("missing file extension")),
+    }
+}
+
 mod private {
     pub trait Sealed {}
 }
@@ -258,6 +390,13 @@ pub struct Warnings {
     out: String,
 }
 
+impl Warnings {
+    /// Lines returns the individual, non-empty warning lines reported by the validator.
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.out.lines().filter(|l| !l.is_empty())
+    }
+}
+
 impl std::fmt::Display for Warnings {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "warnings ({} mode):", self.mode)?;
@@ -287,6 +426,27 @@ impl std::fmt::Debug for Warnings {
     }
 }
 
+/// To_cached reduces a [`validate_config`] result to the plain string [`cache::ModeOutputs`]
+/// stores, since neither [`Warnings`] nor [`Error`] is `Clone`.
+fn to_cached(r: &Result<Warnings>) -> Result<String, String> {
+    match r {
+        Ok(w) => Ok(w.out.clone()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// From_cached rebuilds a single mode's [`Result<Warnings>`] from a cached string, the inverse of
+/// [`to_cached`].
+fn from_cached(mode: &str, cached: Result<String, String>) -> Result<Warnings> {
+    match cached {
+        Ok(out) => Ok(Warnings {
+            mode: mode.to_string(),
+            out,
+        }),
+        Err(msg) => Err(Error::validation(msg)),
+    }
+}
+
 /// To_json returns the bytes jsonified.
 fn to_json(buf: Vec<u8>, flavor: &v1alpha1::ConfigDialect) -> Result<Vec<u8>> {
     match flavor {
@@ -295,19 +455,36 @@ fn to_json(buf: Vec<u8>, flavor: &v1alpha1::ConfigDialect) -> Result<Vec<u8>> {
             let v = serde_yaml::from_slice::<serde_json::Value>(&buf)?;
             Ok(serde_json::to_vec(&v)?)
         }
+        v1alpha1::ConfigDialect::TOML => {
+            let s = std::str::from_utf8(&buf)
+                .map_err(|err| Error::invalid(format!("invalid UTF-8: {err}")))?;
+            let v = toml::from_str::<serde_json::Value>(s)?;
+            Ok(serde_json::to_vec(&v)?)
+        }
     }
 }
 
 /// Validate_config wraps a call to [config.Validate].
 ///
-/// The use of `block_in_place` here means we have a depenedency on tokio, but should make the ffi
-/// play nicer with the multi-threaded runtime (in theory).
+/// The call runs on [`tokio::task::spawn_blocking`] so that the four operating modes can be
+/// validated concurrently instead of serializing them onto a single `block_in_place` slot. The
+/// `buf` is reference-counted so each spawned task can own a cheap clone, satisfying the
+/// `'static` bound `spawn_blocking` requires.
 ///
 /// [config.Validate]: https://pkg.go.dev/github.com/quay/clair/config#Validate
-async fn validate_config<S: AsRef<str>>(buf: &[u8], mode: S) -> Result<Warnings> {
+#[cfg(not(feature = "subprocess-validator"))]
+async fn validate_config<S: AsRef<str>>(buf: Arc<Vec<u8>>, mode: S) -> Result<Warnings> {
+    let mode = mode.as_ref().to_string();
+    tokio::task::spawn_blocking(move || validate_config_blocking(&buf, mode)).await?
+}
+
+/// Validate_config_blocking makes the actual FFI call into [config.Validate].
+///
+/// [config.Validate]: https://pkg.go.dev/github.com/quay/clair/config#Validate
+#[cfg(not(feature = "subprocess-validator"))]
+fn validate_config_blocking(buf: &[u8], mode: String) -> Result<Warnings> {
     use libc::free;
     use std::ffi::{self, CStr};
-    use tokio::task;
     // Allocate a spot to hold the returning string data.
     let mut out: *mut ffi::c_char = std::ptr::null_mut();
     // Make the slice that go expects.
@@ -317,13 +494,12 @@ async fn validate_config<S: AsRef<str>>(buf: &[u8], mode: S) -> Result<Warnings>
         len: buf.len() as i64,
     };
     // Make the string that go expects.
-    let mode = mode.as_ref().to_string();
     let m = sys::GoString {
         p: mode.as_ptr() as *const i8,
         n: mode.len() as isize,
     };
     // This is a large-ish unsafe block, but the Validate, from_ptr, and free are all unsafe.
-    let res: Result<String, String> = task::block_in_place(|| unsafe {
+    let res: Result<String, String> = unsafe {
         let exit = sys::Validate(buf, &mut out, m);
         let res = match exit {
             0 => Ok(CStr::from_ptr(out)
@@ -337,11 +513,82 @@ async fn validate_config<S: AsRef<str>>(buf: &[u8], mode: S) -> Result<Warnings>
         };
         free(out as *mut ffi::c_void);
         res
-    });
+    };
     res.map_err(Error::validation)
         .map(|out| Warnings { mode, out })
 }
 
+/// Validator_path names the environment variable pointing at the prebuilt validator binary for
+/// the `subprocess-validator` backend, mirroring how the controller crate's `REPORTER`/etc. pull
+/// their configuration from the environment.
+#[cfg(feature = "subprocess-validator")]
+const VALIDATOR_PATH_VAR: &str = "CLAIR_CONFIG_VALIDATOR";
+
+#[cfg(feature = "subprocess-validator")]
+static VALIDATOR: tokio::sync::OnceCell<subprocess::Validator> = tokio::sync::OnceCell::const_new();
+
+/// Validate_config, under the `subprocess-validator` feature, hands `buf` to the long-lived
+/// validator subprocess (spawned lazily on first use) instead of calling into cgo.
+///
+/// [config.Validate]: https://pkg.go.dev/github.com/quay/clair/config#Validate
+#[cfg(feature = "subprocess-validator")]
+async fn validate_config<S: AsRef<str>>(buf: Arc<Vec<u8>>, mode: S) -> Result<Warnings> {
+    let validator = VALIDATOR
+        .get_or_try_init(|| async {
+            let path = std::env::var_os(VALIDATOR_PATH_VAR)
+                .ok_or_else(|| Error::invalid(format!("{VALIDATOR_PATH_VAR} not set")))?;
+            subprocess::Validator::spawn(path)
+        })
+        .await?;
+    validator.validate(&buf, mode).await
+}
+
+/// Default_config wraps a call to `config.Default`, the defaulting counterpart to
+/// [config.Validate] this module's Go side needs to grow alongside it: same `(buf, mode)` in,
+/// but on success it marshals the config *after* defaults are applied instead of a warnings
+/// string.
+///
+/// Runs on [`tokio::task::spawn_blocking`] for the same reason [`validate_config`] does.
+///
+/// [config.Validate]: https://pkg.go.dev/github.com/quay/clair/config#Validate
+async fn default_config<S: AsRef<str>>(buf: Arc<Vec<u8>>, mode: S) -> Result<Vec<u8>> {
+    let mode = mode.as_ref().to_string();
+    tokio::task::spawn_blocking(move || default_config_blocking(&buf, mode)).await?
+}
+
+/// Default_config_blocking makes the actual FFI call into `config.Default`.
+fn default_config_blocking(buf: &[u8], mode: String) -> Result<Vec<u8>> {
+    use libc::free;
+    use std::ffi::{self, CStr};
+    // Allocate a spot to hold the returning string data.
+    let mut out: *mut ffi::c_char = std::ptr::null_mut();
+    // Make the slice that go expects.
+    let buf = sys::GoSlice {
+        data: buf.as_ptr() as *mut ffi::c_void,
+        cap: buf.len() as i64,
+        len: buf.len() as i64,
+    };
+    // Make the string that go expects.
+    let m = sys::GoString {
+        p: mode.as_ptr() as *const i8,
+        n: mode.len() as isize,
+    };
+    // This is a large-ish unsafe block, but the Default, from_ptr, and free are all unsafe.
+    let res: Result<Vec<u8>, String> = unsafe {
+        let exit = sys::Default(buf, &mut out, m);
+        let res = match exit {
+            0 => Ok(CStr::from_ptr(out).to_bytes().to_vec()),
+            _ => Err(format!(
+                "{} (exit code {exit})",
+                CStr::from_ptr(out).to_string_lossy()
+            )),
+        };
+        free(out as *mut ffi::c_void);
+        res
+    };
+    res.map_err(Error::validation)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,23 +596,24 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn go_config_indexer() -> Result<()> {
-        let buf: Vec<u8> = Vec::from("{}");
-        let ws = validate_config(&buf, "indexer").await?;
+        let buf = Arc::new(Vec::from("{}"));
+        let ws = validate_config(buf, "indexer").await?;
         eprintln!("{ws}");
         Ok(())
     }
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn go_config_matcher() -> Result<()> {
-        let buf: Vec<u8> = Vec::from(r#"{"matcher":{"indexer_addr":"indexer"}}"#);
-        let ws = validate_config(&buf, "matcher").await?;
+        let buf = Arc::new(Vec::from(r#"{"matcher":{"indexer_addr":"indexer"}}"#));
+        let ws = validate_config(buf, "matcher").await?;
         eprintln!("{ws}");
         Ok(())
     }
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn go_config_notifier() -> Result<()> {
-        let buf: Vec<u8> =
-            Vec::from(r#"{"notifier":{"indexer_addr":"indexer","matcher_addr":"matcher"}}"#);
-        let ws = validate_config(&buf, "notifier").await?;
+        let buf = Arc::new(Vec::from(
+            r#"{"notifier":{"indexer_addr":"indexer","matcher_addr":"matcher"}}"#,
+        ));
+        let ws = validate_config(buf, "notifier").await?;
         eprintln!("{ws}");
         Ok(())
     }
@@ -373,8 +621,8 @@ mod tests {
     // TODO(hank) This test will need to be updated when the config go module is updated.
     #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
     async fn go_config_updater() -> Result<()> {
-        let buf: Vec<u8> = Vec::from("{}");
-        if validate_config(&buf, "updater").await.is_ok() {
+        let buf = Arc::new(Vec::from("{}"));
+        if validate_config(buf, "updater").await.is_ok() {
             Err(Error::test("expected error"))
         } else {
             Ok(())
@@ -0,0 +1,173 @@
+//! Subprocess is an alternate backend for calling into the config validator: instead of linking
+//! `libconfig.a` via cgo (see `build.rs`), it talks to a long-lived validator binary over its
+//! stdin/stdout using a tiny line-delimited JSON protocol. Selecting this backend (the
+//! `subprocess-validator` feature) lets a consumer that only has the prebuilt validator binary
+//! skip the Go/clang/bindgen toolchain the cgo backend otherwise requires.
+//!
+//! The wire format is one JSON object per line each direction: a request is
+//! `{"id": N, "config": "<base64>", "mode": "matcher"}`, a response is either
+//! `{"id": N, "warnings": "..."}` or `{"id": N, "error": "..."}`. Requests are pipelined — the
+//! `id` is how a response is matched back to the call awaiting it, so concurrent `validate` calls
+//! don't have to take turns waiting on a single in-flight round trip the way a single blocking
+//! FFI call would.
+#![cfg(feature = "subprocess-validator")]
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::{oneshot, Mutex};
+use tracing::{trace, warn};
+
+use crate::{Error, Result, Warnings};
+
+/// Request is one line written to the validator subprocess's stdin.
+#[derive(Serialize)]
+struct Request {
+    id: u64,
+    config: String,
+    mode: String,
+}
+
+/// Response is one line read from the validator subprocess's stdout.
+#[derive(Deserialize)]
+struct Response {
+    id: u64,
+    #[serde(default)]
+    warnings: String,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Validator manages one validator subprocess for the lifetime of the process, pipelining
+/// requests over its stdin/stdout by id.
+pub struct Validator {
+    next_id: AtomicU64,
+    stdin: Mutex<ChildStdin>,
+    pending: Arc<Mutex<HashMap<u64, oneshot::Sender<Response>>>>,
+    // Held only to keep the child alive and killed on drop; never read after spawn.
+    _child: Child,
+}
+
+impl Validator {
+    /// Spawn starts the validator binary at `path` and begins reading its responses in the
+    /// background.
+    pub fn spawn<S: AsRef<std::ffi::OsStr>>(path: S) -> Result<Self> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| Error::validation(format!("spawning validator subprocess: {err}")))?;
+        let stdin = child.stdin.take().expect("stdin piped above");
+        let stdout = child.stdout.take().expect("stdout piped above");
+
+        let pending: Arc<Mutex<HashMap<u64, oneshot::Sender<Response>>>> = Default::default();
+        tokio::spawn(Self::read_responses(stdout, pending.clone()));
+
+        Ok(Self {
+            next_id: AtomicU64::new(0),
+            stdin: Mutex::new(stdin),
+            pending,
+            _child: child,
+        })
+    }
+
+    /// Read_responses is the background task that demultiplexes the subprocess's stdout back to
+    /// whichever [`Validator::validate`] call is waiting on each response's id.
+    ///
+    /// Lines that fail to parse are dropped with a `warn!` rather than killing the task, since a
+    /// stray line on stdout shouldn't take down every in-flight and future request.
+    async fn read_responses(
+        stdout: ChildStdout,
+        pending: Arc<Mutex<HashMap<u64, oneshot::Sender<Response>>>>,
+    ) {
+        let mut lines = BufReader::new(stdout).lines();
+        loop {
+            let line = match lines.next_line().await {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    trace!("validator subprocess closed stdout");
+                    return;
+                }
+                Err(err) => {
+                    warn!(%err, "reading from validator subprocess");
+                    return;
+                }
+            };
+            let res: Response = match serde_json::from_str(&line) {
+                Ok(res) => res,
+                Err(err) => {
+                    warn!(%err, line, "unparseable line from validator subprocess");
+                    continue;
+                }
+            };
+            if let Some(tx) = pending.lock().await.remove(&res.id) {
+                let _ = tx.send(res);
+            }
+        }
+    }
+
+    /// Validate sends `buf` to the subprocess for validation under `mode` and awaits the
+    /// matching response.
+    pub async fn validate<S: AsRef<str>>(&self, buf: &[u8], mode: S) -> Result<Warnings> {
+        let mode = mode.as_ref().to_string();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        let req = Request {
+            id,
+            config: base64_encode(buf),
+            mode: mode.clone(),
+        };
+        let mut line = serde_json::to_vec(&req)?;
+        line.push(b'\n');
+        self.stdin
+            .lock()
+            .await
+            .write_all(&line)
+            .await
+            .map_err(|err| Error::validation(format!("writing to validator subprocess: {err}")))?;
+
+        let res = rx.await.map_err(|_| {
+            Error::validation("validator subprocess exited before responding".to_string())
+        })?;
+        match res.error {
+            Some(err) => Err(Error::validation(err)),
+            None => Ok(Warnings {
+                mode,
+                out: res.warnings,
+            }),
+        }
+    }
+}
+
+/// Base64_encode is a minimal standard (RFC 4648, padded) base64 encoder, avoiding a dedicated
+/// `base64` crate dependency for this one call site.
+fn base64_encode(buf: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(buf.len().div_ceil(3) * 4);
+    for chunk in buf.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        out.push(ALPHABET[(n >> 18 & 0x3f) as usize] as char);
+        out.push(ALPHABET[(n >> 12 & 0x3f) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6 & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            ALPHABET[(n & 0x3f) as usize] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
@@ -33,9 +33,9 @@
 
 use std::{collections::BTreeMap, sync::LazyLock};
 
-use gateway_networking_k8s_io::v1::httproutes::HTTPRoute;
+use gateway_networking_k8s_io::v1::{grpcroutes::GRPCRoute, httproutes::HTTPRoute};
 use k8s_openapi::{
-    api::{apps::v1::*, autoscaling::v2::*, batch::v1::*, core::v1::*},
+    api::{apps::v1::*, autoscaling::v2::*, batch::v1::*, core::v1::*, policy::v1::*},
     apimachinery::pkg::{
         api::resource::Quantity,
         apis::meta::v1::{LabelSelector, ObjectMeta, OwnerReference},
@@ -43,6 +43,7 @@ use k8s_openapi::{
     },
 };
 use kube::{Resource, ResourceExt};
+use monitoring_coreos_com::v1::{podmonitors::PodMonitor, servicemonitors::ServiceMonitor};
 use serde_json::json;
 
 use api::v1alpha1::*;
@@ -67,6 +68,8 @@ const CONFIG_ROOT_VOLUME_NAME: &str = "root-config";
 const CONFIG_DROPIN_VOLUME_NAME: &str = "dropin-config";
 const CONFIG_FILENAME: &str = "/etc/clair/config.json";
 const LAYER_VOLUME_NAME: &str = "layer-scratch";
+const CONFIG_STORE_VOLUME_NAME: &str = "config-store";
+const CONFIG_STORE_MOUNT_PATH: &str = "/var/lib/clair/config-store";
 
 /// Error is the error domain for creating templates.
 #[derive(thiserror::Error, Debug)]
@@ -85,7 +88,25 @@ pub enum Error {
     Other(&'static str),
 }
 
-pub fn render_dropin<O>(srv: &Service) -> Option<String>
+/// TaggedDropin is a drop-in's JSON-Patch operations, tagged with the component that produced
+/// them and the generation they were produced at, so a consumer can tell a stale replay from a
+/// genuine conflict with another component's write. See
+/// [`merge_dropins`](../controller/dropins/fn.merge_dropins.html) for how these are merged.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct TaggedDropin {
+    /// Source is the lower-cased Kind of the object that produced this drop-in, e.g. `"indexer"`.
+    pub source: String,
+    /// Generation is the source object's `metadata.generation`, used as a causal clock: a
+    /// re-render of the same source at the same or an older generation is a stale replay, not a
+    /// new write.
+    pub generation: i64,
+    /// Ops are the drop-in's JSON-Patch operations.
+    pub ops: Vec<serde_json::Value>,
+}
+
+/// Render_dropin renders `obj`'s config drop-in against the address `srv` resolves to, tagged
+/// with `obj`'s Kind and generation for [`merge_dropins`](../controller/dropins/fn.merge_dropins.html).
+pub fn render_dropin<O>(obj: &O, srv: &Service) -> Option<TaggedDropin>
 where
     O: Resource<DynamicType = ()>,
 {
@@ -93,18 +114,45 @@ where
     let ns = srv.namespace().unwrap();
     let addr = format!("{name}.{ns}.svc.cluster.local");
 
-    let v = match O::kind(&()).as_ref() {
-        "Indexer" => json!([
-          { "op": "add", "path": "/matcher/indexer_addr",  "value": addr },
-          { "op": "add", "path": "/notifier/indexer_addr", "value": addr },
-        ]),
-        "Matcher" => json!([
-          { "op": "add", "path": "/indexer/matcher_addr",  "value": addr },
-          { "op": "add", "path": "/notifier/matcher_addr", "value": addr },
-        ]),
+    let ops = match O::kind(&()).as_ref() {
+        "Indexer" => vec![
+            json!({ "op": "add", "path": "/matcher/indexer_addr",  "value": addr }),
+            json!({ "op": "add", "path": "/notifier/indexer_addr", "value": addr }),
+        ],
+        "Matcher" => vec![
+            json!({ "op": "add", "path": "/indexer/matcher_addr",  "value": addr }),
+            json!({ "op": "add", "path": "/notifier/matcher_addr", "value": addr }),
+        ],
         _ => return None,
     };
 
+    Some(TaggedDropin {
+        source: O::kind(&()).to_ascii_lowercase(),
+        generation: obj.meta().generation.unwrap_or(0),
+        ops,
+    })
+}
+
+/// Render_otlp_dropin renders a JSON patch that switches the Clair config's metrics and trace
+/// exporters over to the provided OTLP collector.
+pub fn render_otlp_dropin(otlp: &OtlpConfig) -> Option<String> {
+    let mut v = vec![
+        json!({ "op": "add", "path": "/metrics/name", "value": "otlp" }),
+        json!({
+            "op": "add",
+            "path": "/metrics/otlp",
+            "value": { "endpoint": otlp.endpoint, "protocol": otlp.protocol.to_string() },
+        }),
+        json!({
+            "op": "add",
+            "path": "/trace",
+            "value": { "name": "otlp", "otlp": { "endpoint": otlp.endpoint, "protocol": otlp.protocol.to_string() } },
+        }),
+    ];
+    if let Some(ratio) = otlp.sampling_ratio {
+        v.push(json!({ "op": "add", "path": "/trace/otlp/ratio", "value": ratio }));
+    }
+
     serde_json::to_string(&v).ok()
 }
 
@@ -119,7 +167,31 @@ fn standard_labels<S: ToString>(component: S) -> BTreeMap<String, String> {
     ])
 }
 
-fn make_volumes(cfgsrc: &ConfigSource) -> Vec<Volume> {
+/// ROLLOUT_LABEL marks which half of a [`RolloutSpec`]-driven canary rollout a
+/// Deployment/Service/Pod belongs to.
+const ROLLOUT_LABEL: &str = "projectclair.io/rollout";
+
+/// Rollout_labels is [`standard_labels`], plus [`ROLLOUT_LABEL`] set to "stable" or "canary" if
+/// `rollout` is configured, so the stable and canary halves of a rollout get disjoint selectors.
+fn rollout_labels<S: ToString>(
+    component: S,
+    rollout: Option<&RolloutSpec>,
+    canary: bool,
+) -> BTreeMap<String, String> {
+    let mut labels = standard_labels(component);
+    if rollout.is_some() {
+        let variant = if canary { "canary" } else { "stable" };
+        labels.insert(ROLLOUT_LABEL.to_string(), variant.to_string());
+    }
+    labels
+}
+
+/// Make_volumes builds the `root-config`/`dropins` projected volumes every component mounts its
+/// configuration from, plus, when `cfgsrc.persistent` is set, a claim template for a PVC-backed
+/// config store --- returned separately rather than appended to the `Vec<Volume>`, since only a
+/// long-lived workload (a Deployment, not a one-shot Job) can actually attach a
+/// `PersistentVolumeClaimTemplate`.
+fn make_volumes(cfgsrc: &ConfigSource) -> (Vec<Volume>, Option<PersistentVolumeClaimTemplate>) {
     enum Projection {
         ConfigMap(String, KeyToPath),
         Secret(String, KeyToPath),
@@ -173,7 +245,7 @@ fn make_volumes(cfgsrc: &ConfigSource) -> Vec<Volume> {
         })
         .collect::<Vec<_>>();
 
-    vec![
+    let volumes = vec![
         Volume {
             name: CONFIG_ROOT_VOLUME_NAME.into(),
             config_map: Some(ConfigMapVolumeSource {
@@ -195,7 +267,30 @@ fn make_volumes(cfgsrc: &ConfigSource) -> Vec<Volume> {
             }),
             ..Default::default()
         },
-    ]
+    ];
+
+    let claim = cfgsrc.persistent.clone().map(|storage| PersistentVolumeClaimTemplate {
+        metadata: ObjectMeta {
+            ..Default::default()
+        }
+        .into(),
+        spec: PersistentVolumeClaimSpec {
+            access_modes: vec!["ReadWriteOnce".to_string()].into(),
+            storage_class_name: storage.storage_class_name,
+            resources: VolumeResourceRequirements {
+                requests: BTreeMap::from([(
+                    "storage".into(),
+                    Quantity(storage.size.unwrap_or_else(|| "1Gi".to_string())),
+                )])
+                .into(),
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        },
+    });
+
+    (volumes, claim)
 }
 
 pub trait Build {
@@ -222,6 +317,7 @@ pub struct IndexerBuilder {
     image: String,
     cfgsrc: ConfigSource,
     gateway: Option<RouteParentRef>,
+    otlp: Option<OtlpConfig>,
 }
 
 impl TryFrom<&Clair> for IndexerBuilder {
@@ -230,7 +326,7 @@ impl TryFrom<&Clair> for IndexerBuilder {
     fn try_from(value: &Clair) -> Result<Self, Self::Error> {
         let name = value.name_unchecked();
         let namespace = value.namespace().ok_or(Error::Namespace)?;
-        let image = value.spec.image.clone().ok_or(Error::MissingImage)?;
+        let image = value.spec.image.clone().ok_or(Error::MissingImage)?.to_string();
         let cfgsrc = value
             .status
             .as_ref()
@@ -240,6 +336,7 @@ impl TryFrom<&Clair> for IndexerBuilder {
             .controller_owner_ref(&())
             .ok_or(Error::Other("unable to construct controller ref"))?;
         let gateway = value.spec.gateway.clone();
+        let otlp = value.spec.otlp.clone();
 
         Ok(Self {
             namespace,
@@ -248,6 +345,7 @@ impl TryFrom<&Clair> for IndexerBuilder {
             cfgsrc,
             ctl_ref,
             gateway,
+            otlp,
         })
     }
 }
@@ -267,9 +365,10 @@ impl Build for IndexerBuilder {
                 ..Default::default()
             },
             spec: IndexerSpec {
-                image: self.image.into(),
+                image: Some(self.image.parse().expect("image field was derived from an already-validated ImageReference")),
                 gateway: self.gateway,
                 config: self.cfgsrc.into(),
+                otlp: self.otlp,
             },
             ..Default::default()
         }
@@ -283,6 +382,7 @@ pub struct MatcherBuilder {
     image: String,
     cfgsrc: ConfigSource,
     gateway: Option<RouteParentRef>,
+    otlp: Option<OtlpConfig>,
 }
 
 impl TryFrom<&Clair> for MatcherBuilder {
@@ -290,7 +390,7 @@ impl TryFrom<&Clair> for MatcherBuilder {
     fn try_from(value: &Clair) -> Result<Self, Self::Error> {
         let name = value.name_unchecked();
         let namespace = value.namespace().ok_or(Error::Namespace)?;
-        let image = value.spec.image.clone().ok_or(Error::MissingImage)?;
+        let image = value.spec.image.clone().ok_or(Error::MissingImage)?.to_string();
         let cfgsrc = value
             .status
             .as_ref()
@@ -300,6 +400,7 @@ impl TryFrom<&Clair> for MatcherBuilder {
             .controller_owner_ref(&())
             .ok_or(Error::Other("unable to construct controller ref"))?;
         let gateway = value.spec.gateway.clone();
+        let otlp = value.spec.otlp.clone();
 
         Ok(Self {
             namespace,
@@ -308,6 +409,7 @@ impl TryFrom<&Clair> for MatcherBuilder {
             cfgsrc,
             ctl_ref,
             gateway,
+            otlp,
         })
     }
 }
@@ -326,9 +428,10 @@ impl Build for MatcherBuilder {
                 ..Default::default()
             },
             spec: MatcherSpec {
-                image: self.image.into(),
+                image: Some(self.image.parse().expect("image field was derived from an already-validated ImageReference")),
                 gateway: self.gateway,
                 config: self.cfgsrc.into(),
+                otlp: self.otlp,
             },
             ..Default::default()
         }
@@ -342,6 +445,7 @@ pub struct NotifierBuilder {
     image: String,
     cfgsrc: ConfigSource,
     gateway: Option<RouteParentRef>,
+    otlp: Option<OtlpConfig>,
 }
 
 impl TryFrom<&Clair> for NotifierBuilder {
@@ -349,7 +453,7 @@ impl TryFrom<&Clair> for NotifierBuilder {
     fn try_from(value: &Clair) -> Result<Self, Self::Error> {
         let name = value.name_unchecked();
         let namespace = value.namespace().ok_or(Error::Namespace)?;
-        let image = value.spec.image.clone().ok_or(Error::MissingImage)?;
+        let image = value.spec.image.clone().ok_or(Error::MissingImage)?.to_string();
         let cfgsrc = value
             .status
             .as_ref()
@@ -359,6 +463,7 @@ impl TryFrom<&Clair> for NotifierBuilder {
             .controller_owner_ref(&())
             .ok_or(Error::Other("unable to construct controller ref"))?;
         let gateway = value.spec.gateway.clone();
+        let otlp = value.spec.otlp.clone();
 
         Ok(Self {
             namespace,
@@ -367,6 +472,7 @@ impl TryFrom<&Clair> for NotifierBuilder {
             cfgsrc,
             ctl_ref,
             gateway,
+            otlp,
         })
     }
 }
@@ -386,14 +492,17 @@ impl Build for NotifierBuilder {
                 ..Default::default()
             },
             spec: NotifierSpec {
-                image: self.image.into(),
+                image: Some(self.image.parse().expect("image field was derived from an already-validated ImageReference")),
                 gateway: self.gateway,
                 config: self.cfgsrc.into(),
+                otlp: self.otlp,
             },
             ..Default::default()
         }
     }
 }
+const DEFAULT_SCHEDULE: &str = "0 */8 * * *";
+
 pub struct CronJobBuilder {
     namespace: String,
     name: String,
@@ -401,6 +510,15 @@ pub struct CronJobBuilder {
     image: String,
     cfgsrc: ConfigSource,
     owner_ref: OwnerReference,
+    otlp: Option<OtlpConfig>,
+    resources: Option<ResourceRequirements>,
+    schedule: String,
+    time_zone: Option<String>,
+    suspend: Option<bool>,
+    starting_deadline_seconds: Option<i64>,
+    active_deadline_seconds: Option<i64>,
+    successful_jobs_history_limit: Option<i32>,
+    failed_jobs_history_limit: Option<i32>,
 }
 
 impl TryFrom<&Updater> for CronJobBuilder {
@@ -413,13 +531,23 @@ impl TryFrom<&Updater> for CronJobBuilder {
             value.name_unchecked(),
             Updater::kind(&()).to_ascii_lowercase()
         );
-        let image = value.spec.image.clone().ok_or(Error::MissingImage)?;
+        let image = value.spec.image.clone().ok_or(Error::MissingImage)?.to_string();
         let cfgsrc = value
             .status
             .as_ref()
             .and_then(|status| status.config.clone())
             .ok_or(Error::MissingConfigSource)?;
         let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+        let otlp = value.spec.otlp.clone();
+        let resources = value.spec.resources.clone();
+        let schedule = value
+            .spec
+            .schedule
+            .clone()
+            .unwrap_or_else(|| DEFAULT_SCHEDULE.to_string());
+        schedule
+            .parse::<saffron::Cron>()
+            .map_err(|_| Error::Other("invalid cron schedule"))?;
 
         Ok(Self {
             namespace,
@@ -428,6 +556,15 @@ impl TryFrom<&Updater> for CronJobBuilder {
             cfgsrc,
             owner_ref,
             kind: ContainerKind::Updater,
+            otlp,
+            resources,
+            schedule,
+            time_zone: value.spec.time_zone.clone(),
+            suspend: value.spec.suspend,
+            starting_deadline_seconds: value.spec.starting_deadline_seconds,
+            active_deadline_seconds: value.spec.active_deadline_seconds,
+            successful_jobs_history_limit: value.spec.successful_jobs_history_limit,
+            failed_jobs_history_limit: value.spec.failed_jobs_history_limit,
         })
     }
 }
@@ -439,7 +576,7 @@ impl Build for CronJobBuilder {
         let kind = Updater::kind(&()).to_ascii_lowercase();
         let labels = standard_labels(kind);
         let container = ContainerBuilder::from(&self).build();
-        let volumes = make_volumes(&self.cfgsrc);
+        let (volumes, _claim) = make_volumes(&self.cfgsrc);
 
         CronJob {
             metadata: ObjectMeta {
@@ -451,9 +588,12 @@ impl Build for CronJobBuilder {
             },
             spec: CronJobSpec {
                 concurrency_policy: "Forbid".to_string().into(),
-                starting_deadline_seconds: 10.into(),
-                time_zone: "Etc/UTC".to_string().into(),
-                schedule: "0 */8 * * *".to_string(),
+                starting_deadline_seconds: self.starting_deadline_seconds.or(Some(10)),
+                time_zone: self.time_zone.or(Some("Etc/UTC".to_string())),
+                schedule: self.schedule,
+                suspend: self.suspend,
+                successful_jobs_history_limit: self.successful_jobs_history_limit,
+                failed_jobs_history_limit: self.failed_jobs_history_limit,
                 job_template: JobTemplateSpec {
                     metadata: ObjectMeta {
                         labels: labels.clone().into(),
@@ -461,7 +601,7 @@ impl Build for CronJobBuilder {
                     }
                     .into(),
                     spec: JobSpec {
-                        active_deadline_seconds: 3600.into(),
+                        active_deadline_seconds: self.active_deadline_seconds.or(Some(3600)),
                         completion_mode: "NonIndexed".to_string().into(),
                         completions: 1.into(),
                         parallelism: 1.into(),
@@ -505,6 +645,8 @@ pub struct JobBuilder {
     version: String,
     cfgsrc: ConfigSource,
     owner_ref: OwnerReference,
+    otlp: Option<OtlpConfig>,
+    resources: Option<ResourceRequirements>,
 }
 
 #[derive(Clone, Copy, strum::Display, strum::EnumString, strum::AsRefStr)]
@@ -512,6 +654,9 @@ pub struct JobBuilder {
 enum JobKind {
     AdminPre,
     AdminPost,
+    /// Migration runs the Indexer binary's migration mode ahead of rolling out a new image; see
+    /// [`JobBuilder::migration`].
+    Migration,
 }
 
 impl JobBuilder {
@@ -523,13 +668,51 @@ impl JobBuilder {
         Self::new(clair, JobKind::AdminPost)
     }
 
+    /// Migration builds a one-shot Job that runs `indexer`'s current image in migration mode,
+    /// ahead of rolling the Indexer Deployment over to it.
+    pub fn migration(indexer: &Indexer) -> Result<Self, Error> {
+        let cfgsrc = indexer
+            .status
+            .as_ref()
+            .and_then(|status| status.config.clone())
+            .ok_or(Error::MissingConfigSource)?;
+        let image = indexer
+            .spec
+            .image
+            .clone()
+            .ok_or(Error::MissingImage)?
+            .to_string();
+        let version = image
+            .rsplit_once(':')
+            .map(|(_, tag)| tag)
+            .ok_or(Error::Other("image ref missing tag"))?
+            .to_string();
+        let name = format!("{}-migration-{version}", indexer.name_unchecked());
+        let namespace = indexer.namespace().ok_or(Error::Namespace)?;
+        let owner_ref = indexer.owner_ref(&()).ok_or(Error::OwnerReference)?;
+        let otlp = indexer.spec.otlp.clone();
+        let resources = indexer.spec.resources.clone();
+
+        Ok(Self {
+            namespace,
+            name,
+            kind: JobKind::Migration,
+            image,
+            version,
+            cfgsrc,
+            owner_ref,
+            otlp,
+            resources,
+        })
+    }
+
     fn new(clair: &Clair, kind: JobKind) -> Result<Self, Error> {
         let cfgsrc = clair
             .status
             .as_ref()
             .and_then(|status| status.config.clone())
             .ok_or(Error::MissingConfigSource)?;
-        let image = clair.spec.image.clone().ok_or(Error::MissingImage)?;
+        let image = clair.spec.image.clone().ok_or(Error::MissingImage)?.to_string();
         let version = image
             .rsplit_once(':')
             .map(|(_, tag)| tag)
@@ -538,6 +721,8 @@ impl JobBuilder {
         let name = format!("{}-{kind}-{version}", clair.name_unchecked());
         let namespace = clair.namespace().ok_or(Error::Namespace)?;
         let owner_ref = clair.owner_ref(&()).ok_or(Error::OwnerReference)?;
+        let otlp = clair.spec.otlp.clone();
+        let resources = clair.spec.resources.clone();
 
         Ok(Self {
             namespace,
@@ -547,6 +732,8 @@ impl JobBuilder {
             version,
             cfgsrc,
             owner_ref,
+            otlp,
+            resources,
         })
     }
 }
@@ -556,8 +743,11 @@ impl Build for JobBuilder {
 
     fn build(self) -> Self::Output {
         let container = ContainerBuilder::from(&self).args([self.version]).build();
-        let volumes = make_volumes(&self.cfgsrc);
-        let labels = standard_labels(Clair::kind(&()).to_ascii_lowercase());
+        let (volumes, _claim) = make_volumes(&self.cfgsrc);
+        let labels = standard_labels(match self.kind {
+            JobKind::AdminPre | JobKind::AdminPost => Clair::kind(&()).to_ascii_lowercase(),
+            JobKind::Migration => Indexer::kind(&()).to_ascii_lowercase(),
+        });
 
         Job {
             metadata: ObjectMeta {
@@ -605,6 +795,10 @@ pub struct HorizontalPodAutoscalerBuilder {
     name: String,
     kind: HorizontalPodAutoscalerKind,
     owner_ref: OwnerReference,
+    min_replicas: Option<i32>,
+    max_replicas: Option<i32>,
+    metrics: Vec<MetricSpec>,
+    behavior: Option<HorizontalPodAutoscalerBehavior>,
 }
 
 macro_rules! tryfrom_impls_hpa {
@@ -619,12 +813,17 @@ macro_rules! tryfrom_impls_hpa {
                 let name = format!( "{}-{k}", value.name_unchecked());
                 let kind = HorizontalPodAutoscalerKind::try_from(k.as_str())?;
                 let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+                let autoscaling = value.spec.autoscaling.clone().unwrap_or_default();
 
                 Ok(Self {
                     namespace,
                     name,
                     kind,
                     owner_ref,
+                    min_replicas: autoscaling.min_replicas,
+                    max_replicas: autoscaling.max_replicas,
+                    metrics: autoscaling.metrics,
+                    behavior: autoscaling.behavior,
                 })
             }
         }
@@ -656,26 +855,32 @@ impl Build for HorizontalPodAutoscalerBuilder {
                 ..Default::default()
             },
             spec: HorizontalPodAutoscalerSpec {
-                max_replicas: 10,
+                min_replicas: self.min_replicas,
+                max_replicas: self.max_replicas.unwrap_or(10),
                 scale_target_ref: CrossVersionObjectReference {
                     api_version: "apps/v1".to_string().into(),
                     kind: "Deployment".into(),
                     name: self.name,
                 },
-                metrics: vec![MetricSpec {
-                    type_: "Resource".into(),
-                    resource: ResourceMetricSource {
-                        name: "cpu".into(),
-                        target: MetricTarget {
-                            type_: "Utilization".into(),
-                            average_utilization: 80.into(),
-                            ..Default::default()
-                        },
-                    }
-                    .into(),
-                    ..Default::default()
-                }]
+                metrics: if self.metrics.is_empty() {
+                    vec![MetricSpec {
+                        type_: "Resource".into(),
+                        resource: ResourceMetricSource {
+                            name: "cpu".into(),
+                            target: MetricTarget {
+                                type_: "Utilization".into(),
+                                average_utilization: 80.into(),
+                                ..Default::default()
+                            },
+                        }
+                        .into(),
+                        ..Default::default()
+                    }]
+                } else {
+                    self.metrics
+                }
                 .into(),
+                behavior: self.behavior,
                 ..Default::default()
             }
             .into(),
@@ -684,11 +889,16 @@ impl Build for HorizontalPodAutoscalerBuilder {
     }
 }
 
+#[derive(Clone)]
 pub struct ServiceBuilder {
     namespace: String,
     name: String,
     kind: ServiceKind,
     owner_ref: OwnerReference,
+    rollout: Option<RolloutSpec>,
+    /// Canary is set on the builder returned by [`ServiceBuilder::canary`], so `build` emits the
+    /// canary half of the rollout instead of the stable half.
+    canary: bool,
 }
 
 macro_rules! tryfrom_impls_service {
@@ -703,12 +913,15 @@ macro_rules! tryfrom_impls_service {
                 let name = format!( "{}-{k}", value.name_unchecked());
                 let kind = ServiceKind::try_from(k.as_str())?;
                 let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+                let rollout = value.spec.rollout.clone();
 
                 Ok(Self {
                     namespace,
                     name,
                     kind,
                     owner_ref,
+                    rollout,
+                    canary: false,
                 })
             }
         }
@@ -717,6 +930,17 @@ macro_rules! tryfrom_impls_service {
 }
 tryfrom_impls_service!(Indexer, Matcher, Notifier);
 
+impl ServiceBuilder {
+    /// Canary returns a builder for the paired canary Service, if `rollout` is configured on the
+    /// spec this builder came from.
+    pub fn canary(&self) -> Option<Self> {
+        self.rollout.is_some().then(|| Self {
+            canary: true,
+            ..self.clone()
+        })
+    }
+}
+
 #[derive(Clone, Copy, strum::Display, strum::EnumString, strum::AsRefStr)]
 #[strum(serialize_all = "lowercase")]
 enum ServiceKind {
@@ -732,15 +956,27 @@ static API_PORT: LazyLock<ServicePort> = LazyLock::new(|| ServicePort {
     ..Default::default()
 });
 
+static METRICS_PORT: LazyLock<ServicePort> = LazyLock::new(|| ServicePort {
+    name: "metrics".to_string().into(),
+    port: 8089,
+    target_port: IntOrString::String("introspection".into()).into(),
+    ..Default::default()
+});
+
 impl Build for ServiceBuilder {
     type Output = Service;
 
     fn build(self) -> Self::Output {
-        let labels = standard_labels(self.kind);
+        let labels = rollout_labels(self.kind, self.rollout.as_ref(), self.canary);
+        let name = if self.canary {
+            format!("{}-canary", self.name)
+        } else {
+            self.name
+        };
 
         Service {
             metadata: ObjectMeta {
-                name: self.name.into(),
+                name: name.into(),
                 namespace: self.namespace.into(),
                 labels: labels.clone().into(),
                 owner_references: vec![self.owner_ref].into(),
@@ -748,7 +984,81 @@ impl Build for ServiceBuilder {
             },
             spec: ServiceSpec {
                 selector: labels.into(),
-                ports: vec![API_PORT.clone()].into(),
+                ports: vec![API_PORT.clone(), METRICS_PORT.clone()].into(),
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// PodDisruptionBudgetBuilder constructs a `policy/v1` `PodDisruptionBudget` that protects a
+/// component's replicas from voluntary disruption (e.g. node drains).
+pub struct PodDisruptionBudgetBuilder {
+    namespace: String,
+    name: String,
+    kind: ServiceKind,
+    owner_ref: OwnerReference,
+    min_available: Option<IntOrString>,
+    max_unavailable: Option<IntOrString>,
+}
+
+macro_rules! tryfrom_impls_pdb {
+    ($($from:ty),+) => {
+        $(
+        impl TryFrom<&$from> for PodDisruptionBudgetBuilder {
+            type Error = Error;
+
+            fn try_from(value: &$from) -> Result<Self, Self::Error> {
+                let k = stringify!($from).to_ascii_lowercase();
+                let namespace = value.namespace().ok_or(Error::Namespace)?;
+                let name = format!( "{}-{k}", value.name_unchecked());
+                let kind = ServiceKind::try_from(k.as_str())?;
+                let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+                let disruption = value.spec.disruption.clone().unwrap_or_default();
+
+                Ok(Self {
+                    namespace,
+                    name,
+                    kind,
+                    owner_ref,
+                    min_available: disruption.min_available,
+                    max_unavailable: disruption.max_unavailable,
+                })
+            }
+        }
+        )+
+    };
+}
+tryfrom_impls_pdb!(Indexer, Matcher, Notifier);
+
+impl Build for PodDisruptionBudgetBuilder {
+    type Output = PodDisruptionBudget;
+
+    fn build(self) -> Self::Output {
+        let labels = standard_labels(self.kind);
+        let (min_available, max_unavailable) = match (self.min_available, self.max_unavailable) {
+            (None, None) => (Some(IntOrString::Int(1)), None),
+            (min, max) => (min, max),
+        };
+
+        PodDisruptionBudget {
+            metadata: ObjectMeta {
+                name: self.name.into(),
+                namespace: self.namespace.into(),
+                labels: labels.clone().into(),
+                owner_references: vec![self.owner_ref].into(),
+                ..Default::default()
+            },
+            spec: PodDisruptionBudgetSpec {
+                selector: LabelSelector {
+                    match_labels: labels.into(),
+                    ..Default::default()
+                }
+                .into(),
+                min_available,
+                max_unavailable,
                 ..Default::default()
             }
             .into(),
@@ -757,12 +1067,178 @@ impl Build for ServiceBuilder {
     }
 }
 
+/// ServiceMonitorBuilder constructs a Prometheus Operator `ServiceMonitor` targeting the
+/// `metrics` port published by the matching [`ServiceBuilder`] output.
+pub struct ServiceMonitorBuilder {
+    namespace: String,
+    name: String,
+    kind: ServiceKind,
+    owner_ref: OwnerReference,
+    interval: Option<String>,
+}
+
+macro_rules! tryfrom_impls_servicemonitor {
+    ($($from:ty),+) => {
+        $(
+        impl TryFrom<&$from> for ServiceMonitorBuilder {
+            type Error = Error;
+
+            fn try_from(value: &$from) -> Result<Self, Self::Error> {
+                let k = stringify!($from).to_ascii_lowercase();
+                let namespace = value.namespace().ok_or(Error::Namespace)?;
+                let name = format!( "{}-{k}", value.name_unchecked());
+                let kind = ServiceKind::try_from(k.as_str())?;
+                let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+
+                Ok(Self {
+                    namespace,
+                    name,
+                    kind,
+                    owner_ref,
+                    interval: None,
+                })
+            }
+        }
+        )+
+    };
+}
+tryfrom_impls_servicemonitor!(Indexer, Matcher, Notifier);
+
+impl ServiceMonitorBuilder {
+    /// Interval sets the scrape interval (e.g. `"30s"`), overriding the Prometheus Operator
+    /// default.
+    pub fn interval<S: ToString>(self, interval: S) -> Self {
+        Self {
+            interval: Some(interval.to_string()),
+            ..self
+        }
+    }
+}
+
+impl Build for ServiceMonitorBuilder {
+    type Output = ServiceMonitor;
+
+    fn build(self) -> Self::Output {
+        use monitoring_coreos_com::v1::servicemonitors::*;
+
+        let labels = standard_labels(self.kind);
+
+        ServiceMonitor {
+            metadata: ObjectMeta {
+                name: self.name.into(),
+                namespace: self.namespace.into(),
+                labels: labels.clone().into(),
+                owner_references: vec![self.owner_ref].into(),
+                ..Default::default()
+            },
+            spec: ServiceMonitorSpec {
+                selector: LabelSelector {
+                    match_labels: labels.into(),
+                    ..Default::default()
+                },
+                endpoints: vec![ServiceMonitorEndpoints {
+                    port: METRICS_PORT.name.clone(),
+                    path: "/metrics".to_string().into(),
+                    interval: self.interval,
+                    ..Default::default()
+                }]
+                .into(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// PodMonitorBuilder constructs a Prometheus Operator `PodMonitor` that scrapes the
+/// `introspection` container port directly, bypassing the `Service`.
+pub struct PodMonitorBuilder {
+    namespace: String,
+    name: String,
+    kind: ServiceKind,
+    owner_ref: OwnerReference,
+    interval: Option<String>,
+}
+
+macro_rules! tryfrom_impls_podmonitor {
+    ($($from:ty),+) => {
+        $(
+        impl TryFrom<&$from> for PodMonitorBuilder {
+            type Error = Error;
+
+            fn try_from(value: &$from) -> Result<Self, Self::Error> {
+                let k = stringify!($from).to_ascii_lowercase();
+                let namespace = value.namespace().ok_or(Error::Namespace)?;
+                let name = format!( "{}-{k}", value.name_unchecked());
+                let kind = ServiceKind::try_from(k.as_str())?;
+                let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+
+                Ok(Self {
+                    namespace,
+                    name,
+                    kind,
+                    owner_ref,
+                    interval: None,
+                })
+            }
+        }
+        )+
+    };
+}
+tryfrom_impls_podmonitor!(Indexer, Matcher, Notifier);
+
+impl PodMonitorBuilder {
+    /// Interval sets the scrape interval (e.g. `"30s"`), overriding the Prometheus Operator
+    /// default.
+    pub fn interval<S: ToString>(self, interval: S) -> Self {
+        Self {
+            interval: Some(interval.to_string()),
+            ..self
+        }
+    }
+}
+
+impl Build for PodMonitorBuilder {
+    type Output = PodMonitor;
+
+    fn build(self) -> Self::Output {
+        use monitoring_coreos_com::v1::podmonitors::*;
+
+        let labels = standard_labels(self.kind);
+
+        PodMonitor {
+            metadata: ObjectMeta {
+                name: self.name.into(),
+                namespace: self.namespace.into(),
+                labels: labels.clone().into(),
+                owner_references: vec![self.owner_ref].into(),
+                ..Default::default()
+            },
+            spec: PodMonitorSpec {
+                selector: LabelSelector {
+                    match_labels: labels.into(),
+                    ..Default::default()
+                },
+                pod_metrics_endpoints: vec![PodMonitorPodMetricsEndpoints {
+                    port: "introspection".to_string().into(),
+                    path: "/metrics".to_string().into(),
+                    interval: self.interval,
+                    ..Default::default()
+                }]
+                .into(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
 pub struct HTTPRouteBuilder {
     namespace: String,
     name: String,
     kind: RouteKind,
     owner_ref: OwnerReference,
     service: Service,
+    canary_service: Option<Service>,
+    rollout: Option<RolloutSpec>,
 
     gateway: Option<RouteParentRef>,
 }
@@ -779,7 +1255,10 @@ macro_rules! tryfrom_impls_httproute {
                 let kind = RouteKind::try_from(k.as_str())?;
                 let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
                 let gateway = value.spec.gateway.clone();
-                let service = ServiceBuilder::try_from(value)?.build();
+                let rollout = value.spec.rollout.clone();
+                let service_builder = ServiceBuilder::try_from(value)?;
+                let canary_service = service_builder.canary().map(Build::build);
+                let service = service_builder.build();
 
                 Ok(Self {
                     namespace,
@@ -787,6 +1266,8 @@ macro_rules! tryfrom_impls_httproute {
                     kind,
                     owner_ref,
                     service,
+                    canary_service,
+                    rollout,
                     gateway,
                 })
             }
@@ -828,16 +1309,31 @@ impl Build for HTTPRouteBuilder {
 
             ..Default::default()
         };
-        let rule = HTTPRouteRules {
-            matches: vec![HTTPRouteRulesMatches::from(&self.kind)].into(),
-            backend_refs: vec![HTTPRouteRulesBackendRefs {
+        let canary_weight = self
+            .canary_service
+            .is_some()
+            .then(|| self.rollout.as_ref().and_then(|r| r.canary_weight).unwrap_or(10));
+        let mut backend_refs = vec![HTTPRouteRulesBackendRefs {
+            namespace: self.namespace.clone().into(),
+            name: self.service.name_any(),
+            group: Service::group(&()).to_string().into(),
+            kind: Service::kind(&()).to_string().into(),
+            weight: canary_weight.map(|w| 100 - w),
+            ..Default::default()
+        }];
+        if let Some(canary_service) = self.canary_service {
+            backend_refs.push(HTTPRouteRulesBackendRefs {
                 namespace: self.namespace.clone().into(),
-                name: self.service.name_any(),
+                name: canary_service.name_any(),
                 group: Service::group(&()).to_string().into(),
                 kind: Service::kind(&()).to_string().into(),
+                weight: canary_weight,
                 ..Default::default()
-            }]
-            .into(),
+            });
+        }
+        let rule = HTTPRouteRules {
+            matches: vec![HTTPRouteRulesMatches::from(&self.kind)].into(),
+            backend_refs: backend_refs.into(),
             ..Default::default()
         };
 
@@ -887,13 +1383,128 @@ impl From<&RouteKind> for gateway_networking_k8s_io::v1::httproutes::HTTPRouteRu
 }
 
 impl From<&RouteKind> for Vec<gateway_networking_k8s_io::v1::grpcroutes::GRPCRouteRulesMatches> {
-    /// None, yet.
-    fn from(_value: &RouteKind) -> Self {
-        //use gateway_networking_k8s_io::v1::grpcroutes::*;
-        vec![]
+    fn from(value: &RouteKind) -> Self {
+        use gateway_networking_k8s_io::v1::grpcroutes::*;
+
+        let service = match value {
+            RouteKind::Indexer => "indexer.v1.IndexerService",
+            RouteKind::Matcher => "matcher.v1.MatcherService",
+            RouteKind::Notifier => "notifier.v1.NotifierService",
+        }
+        .to_string();
+
+        vec![GRPCRouteRulesMatches {
+            method: GRPCRouteRulesMatchesMethod {
+                r#type: GRPCRouteRulesMatchesMethodType::Exact.into(),
+                service: service.into(),
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        }]
+    }
+}
+
+pub struct GRPCRouteBuilder {
+    namespace: String,
+    name: String,
+    kind: RouteKind,
+    owner_ref: OwnerReference,
+    service: Service,
+
+    gateway: Option<RouteParentRef>,
+}
+macro_rules! tryfrom_impls_grpcroute {
+    ($($from:ty),+) => {
+        $(
+        impl TryFrom<&$from> for GRPCRouteBuilder {
+            type Error = Error;
+
+            fn try_from(value: &$from) -> Result<Self, Self::Error> {
+                let k = stringify!($from).to_ascii_lowercase();
+                let namespace = value.namespace().ok_or(Error::Namespace)?;
+                let name = format!( "{}-{k}-grpc", value.name_unchecked());
+                let kind = RouteKind::try_from(k.as_str())?;
+                let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+                let gateway = value.spec.gateway.clone();
+                let service = ServiceBuilder::try_from(value)?.build();
+
+                Ok(Self {
+                    namespace,
+                    name,
+                    kind,
+                    owner_ref,
+                    service,
+                    gateway,
+                })
+            }
+        }
+        )+
+    };
+}
+tryfrom_impls_grpcroute!(Indexer, Matcher, Notifier);
+
+impl Build for GRPCRouteBuilder {
+    type Output = GRPCRoute;
+
+    fn build(self) -> Self::Output {
+        use gateway_networking_k8s_io::v1::grpcroutes::*;
+
+        let r = GRPCRoute {
+            metadata: ObjectMeta {
+                name: self.name.clone().into(),
+                owner_references: vec![self.owner_ref].into(),
+                ..Default::default()
+            },
+            spec: GRPCRouteSpec {
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        if self.gateway.is_none() {
+            return r;
+        }
+        let gateway = self.gateway.unwrap();
+
+        let parent_ref = GRPCRouteParentRefs {
+            namespace: gateway.namespace.clone(),
+            name: gateway.name.clone().unwrap_or_else(|| self.name.clone()),
+
+            group: gateway.group.clone(),
+            kind: gateway.kind.clone(),
+            section_name: gateway.section_name.clone(),
+
+            ..Default::default()
+        };
+        let rule = GRPCRouteRules {
+            matches: Vec::<GRPCRouteRulesMatches>::from(&self.kind).into(),
+            backend_refs: vec![GRPCRouteRulesBackendRefs {
+                namespace: self.namespace.clone().into(),
+                name: self.service.name_any(),
+                group: Service::group(&()).to_string().into(),
+                kind: Service::kind(&()).to_string().into(),
+                ..Default::default()
+            }]
+            .into(),
+            ..Default::default()
+        };
+
+        GRPCRoute {
+            metadata: ObjectMeta {
+                name: self.name.clone().into(),
+                ..Default::default()
+            },
+            spec: GRPCRouteSpec {
+                parent_refs: vec![parent_ref].into(),
+                rules: vec![rule].into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
     }
 }
 
+#[derive(Clone)]
 pub struct DeploymentBuilder {
     namespace: String,
     name: String,
@@ -901,7 +1512,33 @@ pub struct DeploymentBuilder {
     image: String,
     cfgsrc: ConfigSource,
     owner_ref: OwnerReference,
+    otlp: Option<OtlpConfig>,
+    resources: Option<ResourceRequirements>,
+    scheduling: Option<SchedulingSpec>,
+    probes: Option<ProbesSpec>,
+    layer_cache: Option<LayerCacheSpec>,
+    annotations: BTreeMap<String, String>,
+    rollout: Option<RolloutSpec>,
+    /// Canary is set on the builder returned by [`DeploymentBuilder::canary`], so `build` emits
+    /// the canary half of the rollout instead of the stable half.
+    canary: bool,
+}
+
+/// LayerCacheSource lets [`tryfrom_impls_deployment!`] pull the Indexer-only `layerCache` spec
+/// field generically, defaulting to `None` for the other component kinds.
+trait LayerCacheSource {
+    fn layer_cache(&self) -> Option<LayerCacheSpec> {
+        None
+    }
+}
+impl LayerCacheSource for Indexer {
+    fn layer_cache(&self) -> Option<LayerCacheSpec> {
+        self.spec.layer_cache.clone()
+    }
 }
+impl LayerCacheSource for Matcher {}
+impl LayerCacheSource for Notifier {}
+
 macro_rules! tryfrom_impls_deployment {
     ($($from:ty),+) => {
         $(
@@ -913,13 +1550,19 @@ macro_rules! tryfrom_impls_deployment {
                 let namespace = value.namespace().ok_or(Error::Namespace)?;
                 let name = format!("{}-{k}", value.name_unchecked());
                 let kind = DeploymentKind::try_from(k.as_str())?;
-                let image = value.spec.image.clone().ok_or(Error::MissingImage)?;
+                let image = value.spec.image.clone().ok_or(Error::MissingImage)?.to_string();
                 let cfgsrc = value
                     .spec
                     .config
                     .clone()
                     .ok_or(Error::MissingConfigSource)?;
                 let owner_ref = value.owner_ref(&()).ok_or(Error::OwnerReference)?;
+                let otlp = value.spec.otlp.clone();
+                let resources = value.spec.resources.clone();
+                let scheduling = value.spec.scheduling.clone();
+                let probes = value.spec.probes.clone();
+                let layer_cache = value.layer_cache();
+                let rollout = value.spec.rollout.clone();
 
                 Ok(Self {
                     namespace,
@@ -928,6 +1571,14 @@ macro_rules! tryfrom_impls_deployment {
                     cfgsrc,
                     image,
                     owner_ref,
+                    otlp,
+                    resources,
+                    scheduling,
+                    probes,
+                    layer_cache,
+                    annotations: BTreeMap::new(),
+                    rollout,
+                    canary: false,
                 })
             }
         }
@@ -958,15 +1609,63 @@ impl DeploymentBuilder {
         let cfgsrc = cfgsrc.clone();
         Self { cfgsrc, ..self }
     }
+    /// Annotations merges the provided key/value pairs into the Deployment's annotations, e.g.
+    /// recording the tag a resolved image digest came from.
+    pub fn annotations<I, K, V>(mut self, annotations: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: ToString,
+        V: ToString,
+    {
+        self.annotations.extend(
+            annotations
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        self
+    }
+    /// Canary returns a builder for the paired canary Deployment, if `rollout` is configured on
+    /// the spec this builder came from.
+    pub fn canary(&self) -> Option<Self> {
+        self.rollout.is_some().then(|| Self {
+            canary: true,
+            ..self.clone()
+        })
+    }
 }
 
 impl Build for DeploymentBuilder {
     type Output = Deployment;
 
     fn build(self) -> Self::Output {
-        let labels = standard_labels(self.kind);
+        let labels = rollout_labels(self.kind, self.rollout.as_ref(), self.canary);
+        let layer_cache = self.layer_cache.clone().unwrap_or_default();
         let mut container = ContainerBuilder::from(&self).build();
-        let mut volumes = make_volumes(&self.cfgsrc);
+        if self.canary {
+            if let Some(image) = self.rollout.as_ref().and_then(|r| r.canary_image.clone()) {
+                container.image = image.into();
+            }
+        }
+        let (mut volumes, claim) = make_volumes(&self.cfgsrc);
+        if let Some(claim) = claim {
+            volumes.push(Volume {
+                name: CONFIG_STORE_VOLUME_NAME.to_string(),
+                ephemeral: EphemeralVolumeSource {
+                    volume_claim_template: claim.into(),
+                }
+                .into(),
+                ..Default::default()
+            });
+
+            container
+                .volume_mounts
+                .get_or_insert_default()
+                .push(VolumeMount {
+                    name: CONFIG_STORE_VOLUME_NAME.to_string(),
+                    mount_path: CONFIG_STORE_MOUNT_PATH.to_string(),
+                    ..Default::default()
+                });
+        }
         if self.kind == DeploymentKind::Indexer {
             volumes.push(Volume {
                 name: LAYER_VOLUME_NAME.to_string(),
@@ -977,11 +1676,15 @@ impl Build for DeploymentBuilder {
                         }
                         .into(),
                         spec: PersistentVolumeClaimSpec {
-                            access_modes: vec!["ReadWriteOnce".into()].into(),
+                            access_modes: vec![layer_cache
+                                .access_mode
+                                .unwrap_or_else(|| "ReadWriteOnce".to_string())]
+                            .into(),
+                            storage_class_name: layer_cache.storage_class_name,
                             resources: VolumeResourceRequirements {
                                 requests: BTreeMap::from([(
                                     "storage".into(),
-                                    Quantity("10Gi".into()),
+                                    Quantity(layer_cache.size.unwrap_or_else(|| "10Gi".to_string())),
                                 )])
                                 .into(),
                                 ..Default::default()
@@ -1006,19 +1709,74 @@ impl Build for DeploymentBuilder {
                 });
         }
 
+        let scheduling = self.scheduling.unwrap_or_default();
+        let topology_spread_constraints = vec![TopologySpreadConstraint {
+            max_skew: scheduling.topology_spread_max_skew.unwrap_or(1),
+            topology_key: "topology.kubernetes.io/zone".to_string(),
+            when_unsatisfiable: "ScheduleAnyway".to_string(),
+            label_selector: LabelSelector {
+                match_labels: labels.clone().into(),
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        }];
+        let affinity = scheduling.anti_affinity.unwrap_or(true).then(|| Affinity {
+            pod_anti_affinity: PodAntiAffinity {
+                preferred_during_scheduling_ignored_during_execution: vec![
+                    WeightedPodAffinityTerm {
+                        weight: 100,
+                        pod_affinity_term: PodAffinityTerm {
+                            label_selector: LabelSelector {
+                                match_labels: labels.clone().into(),
+                                ..Default::default()
+                            }
+                            .into(),
+                            topology_key: "kubernetes.io/hostname".to_string(),
+                            ..Default::default()
+                        },
+                    },
+                ]
+                .into(),
+                ..Default::default()
+            }
+            .into(),
+            ..Default::default()
+        });
+
+        let name = if self.canary {
+            format!("{}-canary", self.name)
+        } else {
+            self.name
+        };
+        let replicas = self.canary.then(|| {
+            self.rollout
+                .as_ref()
+                .and_then(|r| r.canary_replicas)
+                .unwrap_or(1)
+        });
+
         Deployment {
             metadata: ObjectMeta {
-                name: self.name.into(),
+                name: name.into(),
                 namespace: self.namespace.into(),
                 labels: labels.clone().into(),
+                annotations: (!self.annotations.is_empty()).then_some(self.annotations),
                 owner_references: vec![self.owner_ref].into(),
                 ..Default::default()
             },
             spec: DeploymentSpec {
+                replicas,
                 revision_history_limit: 3.into(),
                 progress_deadline_seconds: 60.into(),
                 strategy: DeploymentStrategy {
-                    type_: "Recreate".to_string().into(),
+                    type_: if self.rollout.is_some() {
+                        "RollingUpdate"
+                    } else {
+                        "Recreate"
+                    }
+                    .to_string()
+                    .into(),
                     ..Default::default()
                 }
                 .into(),
@@ -1042,6 +1800,8 @@ impl Build for DeploymentBuilder {
                         .into(),
                         containers: vec![container],
                         volumes: volumes.into(),
+                        topology_spread_constraints: topology_spread_constraints.into(),
+                        affinity: affinity.into(),
                         ..Default::default()
                     }
                     .into(),
@@ -1060,7 +1820,25 @@ struct ContainerBuilder {
     image: String,
     cfgsrc: ConfigSource,
     argv: Option<Vec<String>>,
+    otlp: Option<OtlpConfig>,
+    resources: Option<ResourceRequirements>,
+    probes: Option<ProbesSpec>,
+}
+
+/// ProbesSource lets [`from_impls_container!`] pull the `probes` override generically, defaulting
+/// to `None` for the builders that don't carry one.
+trait ProbesSource {
+    fn probes(&self) -> Option<ProbesSpec> {
+        None
+    }
 }
+impl ProbesSource for DeploymentBuilder {
+    fn probes(&self) -> Option<ProbesSpec> {
+        self.probes.clone()
+    }
+}
+impl ProbesSource for JobBuilder {}
+impl ProbesSource for CronJobBuilder {}
 
 macro_rules! from_impls_container{
     ($($from:ty),+) => {
@@ -1070,12 +1848,18 @@ macro_rules! from_impls_container{
                 let kind = value.kind.into();
                 let image = value.image.clone();
                 let cfgsrc = value.cfgsrc.clone();
+                let otlp = value.otlp.clone();
+                let resources = value.resources.clone();
+                let probes = value.probes();
 
                 Self {
                     kind,
                     image,
                     cfgsrc,
                     argv: None,
+                    otlp,
+                    resources,
+                    probes,
                 }
             }
         }
@@ -1144,11 +1928,17 @@ impl Build for ContainerBuilder {
                 ..Default::default()
             }]
             .into(),
-            resources: ResourceRequirements {
-                requests: BTreeMap::from([("cpu".into(), Quantity("1".into()))]).into(),
-                ..Default::default()
-            }
-            .into(),
+            resources: self
+                .resources
+                .unwrap_or_else(|| ResourceRequirements {
+                    requests: BTreeMap::from([
+                        ("cpu".into(), Quantity("1".into())),
+                        ("memory".into(), Quantity("512Mi".into())),
+                    ])
+                    .into(),
+                    ..Default::default()
+                })
+                .into(),
             ..Default::default()
         };
 
@@ -1164,9 +1954,69 @@ impl Build for ContainerBuilder {
                     ..Default::default()
                 });
             }
+            ContainerKind::IndexerMigration => {
+                let env = c.env.get_or_insert_default();
+                env.push(EnvVar {
+                    name: "CLAIR_MODE".into(),
+                    value: ContainerKind::Indexer.to_string().into(),
+                    ..Default::default()
+                });
+                env.push(EnvVar {
+                    name: "CLAIR_MIGRATIONS_ONLY".into(),
+                    value: "true".to_string().into(),
+                    ..Default::default()
+                });
+            }
             _ => {}
         };
 
+        // Wire up the OTLP exporter, if configured:
+        if let Some(otlp) = &self.otlp {
+            let env = c.env.get_or_insert_default();
+            env.push(EnvVar {
+                name: "OTEL_EXPORTER_OTLP_ENDPOINT".into(),
+                value: otlp.endpoint.clone().into(),
+                ..Default::default()
+            });
+            env.push(EnvVar {
+                name: "OTEL_EXPORTER_OTLP_PROTOCOL".into(),
+                value: match otlp.protocol {
+                    OtlpProtocol::Grpc => "grpc".to_string(),
+                    OtlpProtocol::Http => "http/protobuf".to_string(),
+                }
+                .into(),
+                ..Default::default()
+            });
+            if let Some(secref) = &otlp.headers_secret_ref {
+                env.push(EnvVar {
+                    name: "OTEL_EXPORTER_OTLP_HEADERS".into(),
+                    value_from: EnvVarSource {
+                        secret_key_ref: k8s_openapi::api::core::v1::SecretKeySelector {
+                            name: secref.name.clone(),
+                            key: secref.key.clone(),
+                            optional: false.into(),
+                        }
+                        .into(),
+                        ..Default::default()
+                    }
+                    .into(),
+                    ..Default::default()
+                });
+            }
+            if let Some(ratio) = otlp.sampling_ratio {
+                env.push(EnvVar {
+                    name: "OTEL_TRACES_SAMPLER".into(),
+                    value: "traceidratio".to_string().into(),
+                    ..Default::default()
+                });
+                env.push(EnvVar {
+                    name: "OTEL_TRACES_SAMPLER_ARG".into(),
+                    value: ratio.to_string().into(),
+                    ..Default::default()
+                });
+            }
+        }
+
         // Modify ports:
         match self.kind {
             ContainerKind::Indexer | ContainerKind::Matcher | ContainerKind::Notifier => {
@@ -1175,14 +2025,29 @@ impl Build for ContainerBuilder {
                     container_port: 6060,
                     ..Default::default()
                 });
+                let startup_timing = self
+                    .probes
+                    .as_ref()
+                    .and_then(|p| p.startup.clone())
+                    .unwrap_or_default();
+                let liveness_timing = self
+                    .probes
+                    .as_ref()
+                    .and_then(|p| p.liveness.clone())
+                    .unwrap_or_default();
+                let readiness_timing = self
+                    .probes
+                    .as_ref()
+                    .and_then(|p| p.readiness.clone())
+                    .unwrap_or_default();
                 c.startup_probe = Probe {
                     tcp_socket: TCPSocketAction {
                         port: IntOrString::String("api".into()),
                         ..Default::default()
                     }
                     .into(),
-                    initial_delay_seconds: 5.into(),
-                    period_seconds: 1.into(),
+                    initial_delay_seconds: startup_timing.initial_delay_seconds.or(Some(5)),
+                    period_seconds: startup_timing.period_seconds.or(Some(1)),
                     ..Default::default()
                 }
                 .into();
@@ -1193,8 +2058,8 @@ impl Build for ContainerBuilder {
                         ..Default::default()
                     }
                     .into(),
-                    initial_delay_seconds: 15.into(),
-                    period_seconds: 20.into(),
+                    initial_delay_seconds: liveness_timing.initial_delay_seconds.or(Some(15)),
+                    period_seconds: liveness_timing.period_seconds.or(Some(20)),
                     ..Default::default()
                 }
                 .into();
@@ -1205,13 +2070,13 @@ impl Build for ContainerBuilder {
                         ..Default::default()
                     }
                     .into(),
-                    initial_delay_seconds: 5.into(),
-                    period_seconds: 10.into(),
+                    initial_delay_seconds: readiness_timing.initial_delay_seconds.or(Some(5)),
+                    period_seconds: readiness_timing.period_seconds.or(Some(10)),
                     ..Default::default()
                 }
                 .into();
             }
-            ContainerKind::AdminPre | ContainerKind::AdminPost => {
+            ContainerKind::AdminPre | ContainerKind::AdminPost | ContainerKind::IndexerMigration => {
                 c.ports = None;
             }
             _ => {}
@@ -1249,6 +2114,9 @@ enum ContainerKind {
     Updater,
     AdminPre,
     AdminPost,
+    /// IndexerMigration runs the same image as [`ContainerKind::Indexer`], but in migration-only
+    /// mode; see [`JobBuilder::migration`].
+    IndexerMigration,
 }
 
 impl From<DeploymentKind> for ContainerKind {
@@ -1265,6 +2133,7 @@ impl From<JobKind> for ContainerKind {
         match value {
             JobKind::AdminPre => ContainerKind::AdminPre,
             JobKind::AdminPost => ContainerKind::AdminPost,
+            JobKind::Migration => ContainerKind::IndexerMigration,
         }
     }
 }
@@ -1391,18 +2260,63 @@ mod tests {
             Ok(())
         }
 
+        #[test]
+        fn deployment_overrides() -> Result {
+            let (indexer, want) = load_fixure::<Indexer>(module_path!(), "deployment_overrides");
+            let got = DeploymentBuilder::try_from(&indexer)?.build();
+            let got = to_value(got)?;
+
+            assert_json_eq!(got, want);
+            Ok(())
+        }
+
+        #[test]
+        fn deployment_rollout() -> Result {
+            let (indexer, want) = load_fixure::<Indexer>(module_path!(), "deployment_rollout");
+            let builder = DeploymentBuilder::try_from(&indexer)?;
+            let canary = builder.canary().expect("fixture has spec.rollout set");
+            let got = to_value(vec![builder.build(), canary.build()])?;
+
+            assert_json_eq!(got, want);
+            Ok(())
+        }
+
+        #[test]
+        fn service_rollout() -> Result {
+            let (indexer, want) = load_fixure::<Indexer>(module_path!(), "service_rollout");
+            let builder = ServiceBuilder::try_from(&indexer)?;
+            let canary = builder.canary().expect("fixture has spec.rollout set");
+            let got = to_value(vec![builder.build(), canary.build()])?;
+
+            assert_json_eq!(got, want);
+            Ok(())
+        }
+
+        #[test]
+        fn route_weighted() -> Result {
+            let (indexer, want) = load_fixure::<Indexer>(module_path!(), "route_weighted");
+            let got = HTTPRouteBuilder::try_from(&indexer)?.build();
+            let got = to_value(got)?;
+
+            assert_json_eq!(got, want);
+            Ok(())
+        }
+
         #[test]
         fn dropin() {
+            let mut indexer = Indexer::new("test-indexer", IndexerSpec::default());
+            indexer.meta_mut().generation = Some(3);
             let mut srv: Service = from_str(r#"{"metadata":{"name":"test-indexer"}}"#).unwrap();
             srv.metadata.namespace = Some("test".into());
-            let got = render_dropin::<Indexer>(&srv).unwrap();
-            let got: Value = from_str(&got).unwrap();
+            let got = render_dropin(&indexer, &srv).unwrap();
             let want = json!([
               { "op": "add", "path": "/matcher/indexer_addr",  "value": "test-indexer.test.svc.cluster.local" },
               { "op": "add", "path": "/notifier/indexer_addr", "value": "test-indexer.test.svc.cluster.local" },
             ]);
 
-            assert_json_eq!(got, want);
+            assert_eq!(got.source, "indexer");
+            assert_eq!(got.generation, 3);
+            assert_json_eq!(to_value(got.ops).unwrap(), want);
         }
     }
 }
@@ -7,7 +7,7 @@
 use std::{
     collections::BTreeMap,
     env,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{self, Command},
 };
 
@@ -23,6 +23,13 @@ fn main() {
         process::exit(1);
     }
 
+    // Mirror `etc/` into `$OUT_DIR/compressed-etc/` for the release build of `templates.rs` to
+    // embed instead of the raw tree.
+    if let Err(err) = compress_assets(&src_dir, &out_dir) {
+        eprintln!("{err}");
+        process::exit(1);
+    }
+
     for f in &["go.mod", "main.go"] {
         println!(
             "cargo:rerun-if-changed={}",
@@ -67,6 +74,41 @@ fn main() {
         .expect("Couldn't write bindings!");
 }
 
+/// Zstd-compresses every file under `src_dir/etc/` (excluding the same `tests/` and `README.md`
+/// paths that `controller::templates::Asset`'s `iftree` config excludes) into
+/// `out_dir/compressed-etc/`, preserving relative paths, so the release build embeds the
+/// compressed bytes instead of the raw ones. Debug builds read `etc/` directly and don't need the
+/// mirror, so this is skipped for them.
+fn compress_assets(src_dir: &Path, out_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if env::var("PROFILE")? == "debug" {
+        return Ok(());
+    }
+    let etc_dir = src_dir.join("etc");
+    println!("cargo:rerun-if-changed={}", etc_dir.to_string_lossy());
+    compress_dir(&etc_dir, &etc_dir, &out_dir.join("compressed-etc"))
+}
+
+fn compress_dir(root: &Path, dir: &Path, dest_root: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if path.file_name().and_then(|n| n.to_str()) == Some("tests") {
+                continue;
+            }
+            compress_dir(root, &path, dest_root)?;
+            continue;
+        }
+        if path.file_name().and_then(|n| n.to_str()) == Some("README.md") {
+            continue;
+        }
+        let dest = dest_root.join(path.strip_prefix(root)?);
+        std::fs::create_dir_all(dest.parent().unwrap())?;
+        let raw = std::fs::read(&path)?;
+        std::fs::write(&dest, zstd::stream::encode_all(raw.as_slice(), 19)?)?;
+    }
+    Ok(())
+}
+
 fn map_platform<S: AsRef<str>>(p: S) -> &'static str {
     match p.as_ref() {
         "aarch64" => "arm64",
@@ -0,0 +1,117 @@
+//! Admin exposes an HTTP control plane for poking individual `Clair` reconciles and reading back
+//! their status, mirroring Garage's admin `api_server.rs`/`router.rs` split between a mutating
+//! "do something now" endpoint and a read-only status endpoint.
+//!
+//! Unlike SIGUSR1 (wired up as [`crate::clairs::controller`]'s `reconcile_all_on`, which forces a
+//! full resync of every object), `POST /reconcile/:namespace/:name` targets exactly one object by
+//! pushing its [`ObjectRef`] onto [`Context::admin_trigger`].
+
+use std::sync::Arc;
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use tower_http::trace::TraceLayer;
+#[allow(unused_imports)]
+use tracing::{debug, error, info, instrument, trace};
+
+use crate::Context;
+
+/// State is the admin application server state.
+pub struct State {
+    client: kube::Client,
+    ctx: Arc<Context>,
+}
+
+impl State {
+    /// New creates a new State.
+    pub fn new(client: kube::Client, ctx: Arc<Context>) -> State {
+        State { client, ctx }
+    }
+}
+
+/// App returns an `axum::Router`.
+pub fn app(srv: State) -> Router {
+    let state = Arc::new(srv);
+    trace!("state constructed");
+    let app = Router::new()
+        .route("/reconcile/:namespace/:name", post(reconcile::handler))
+        .route("/status/:namespace/:name", get(status::handler))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state);
+    trace!("router constructed");
+    app
+}
+
+mod prelude {
+    pub use std::sync::Arc;
+
+    pub use axum::{extract, http::StatusCode};
+    pub use kube::api::Api;
+    pub use tracing::{debug, error, info, instrument, trace};
+
+    pub use super::State;
+}
+
+mod reconcile {
+    use api::v1alpha1::Clair;
+    use kube::runtime::reflector::ObjectRef;
+
+    use super::prelude::*;
+
+    /// Handler serves `POST /reconcile/:namespace/:name`: it enqueues the named [`Clair`] for an
+    /// immediate reconcile instead of waiting for the next resync, by pushing its [`ObjectRef`]
+    /// onto the controller's `admin_trigger` broadcast channel.
+    #[instrument(skip(srv))]
+    pub async fn handler(
+        extract::State(srv): extract::State<Arc<State>>,
+        extract::Path((namespace, name)): extract::Path<(String, String)>,
+    ) -> StatusCode {
+        let objref = ObjectRef::<Clair>::new(&name).within(&namespace);
+        match srv.ctx.admin_trigger.send(objref) {
+            Ok(_) => StatusCode::ACCEPTED,
+            Err(_) => {
+                debug!("no clair controller currently subscribed to admin_trigger");
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }
+    }
+}
+
+mod status {
+    use api::v1alpha1::Clair;
+    use k8s_openapi::api::core::v1::TypedLocalObjectReference;
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition;
+    use serde::Serialize;
+
+    use super::prelude::*;
+
+    /// ClairStatusView is the subset of `v1alpha1::ClairStatus` reported by the status endpoint:
+    /// the `clair_condition("SpecOK")`-style conditions and the refs tracked via `add_ref`.
+    #[derive(Serialize)]
+    struct ClairStatusView {
+        conditions: Vec<Condition>,
+        refs: Vec<TypedLocalObjectReference>,
+    }
+
+    /// Handler serves `GET /status/:namespace/:name`: it fetches the named [`Clair`] and reports
+    /// its recorded conditions and refs, giving a scriptable readiness probe tied to the actual
+    /// reconcile state instead of a `kubectl describe` scrape.
+    #[instrument(skip(srv))]
+    pub async fn handler(
+        extract::State(srv): extract::State<Arc<State>>,
+        extract::Path((namespace, name)): extract::Path<(String, String)>,
+    ) -> Result<axum::Json<ClairStatusView>, StatusCode> {
+        let api: Api<Clair> = Api::namespaced(srv.client.clone(), &namespace);
+        let obj = api.get(&name).await.map_err(|err| {
+            debug!(%err, "error fetching clair");
+            StatusCode::NOT_FOUND
+        })?;
+        let status = obj.status.ok_or(StatusCode::NOT_FOUND)?;
+        Ok(axum::Json(ClairStatusView {
+            conditions: status.conditions,
+            refs: status.refs,
+        }))
+    }
+}
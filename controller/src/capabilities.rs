@@ -0,0 +1,64 @@
+//! Capabilities resolves which `apiVersion` [`templates::render`](crate::templates::render)
+//! should emit for a Kind, by probing the cluster's discovery API instead of assuming every
+//! cluster serves the same group-version --- older distributions still only serve
+//! `autoscaling/v2beta2` or `batch/v1beta1`, and a future k8s-openapi bump could shift which
+//! version this operator prefers.
+
+use kube::api::GroupVersionKind;
+
+use crate::Context;
+
+/// Candidates pairs a Kind with its acceptable `(group, version)` pairs, most-preferred first.
+/// `group` is `""` for the core group.
+struct Candidates {
+    kind: &'static str,
+    versions: &'static [(&'static str, &'static str)],
+}
+
+static CANDIDATES: &[Candidates] = &[
+    Candidates {
+        kind: "HorizontalPodAutoscaler",
+        versions: &[("autoscaling", "v2"), ("autoscaling", "v2beta2")],
+    },
+    Candidates {
+        kind: "CronJob",
+        versions: &[("batch", "v1"), ("batch", "v1beta1")],
+    },
+    Candidates {
+        kind: "Deployment",
+        versions: &[("apps", "v1")],
+    },
+];
+
+/// Api_version returns the `group/version` string (or bare `version` for the core group) to use
+/// for `kind` in the cluster `ctx` is connected to: the first candidate [`Context::gvk_exists`]
+/// confirms is served, falling back to the most-preferred candidate if none of them resolve ---
+/// which keeps rendering usable against a disconnected or mocked client, e.g. in tests.
+///
+/// Panics if `kind` has no registered candidates; callers should only pass Kinds listed in
+/// [`CANDIDATES`].
+pub async fn api_version(ctx: &Context, kind: &str) -> String {
+    let candidates = CANDIDATES
+        .iter()
+        .find(|c| c.kind == kind)
+        .unwrap_or_else(|| panic!("programmer error: no apiVersion candidates for kind: {kind}"));
+
+    for &(group, version) in candidates.versions {
+        if ctx.gvk_exists(&GroupVersionKind::gvk(group, version, kind)).await {
+            return group_version(group, version);
+        }
+    }
+    let &(group, version) = candidates
+        .versions
+        .first()
+        .expect("programmer error: empty candidate list");
+    group_version(group, version)
+}
+
+fn group_version(group: &str, version: &str) -> String {
+    if group.is_empty() {
+        version.to_string()
+    } else {
+        format!("{group}/{version}")
+    }
+}
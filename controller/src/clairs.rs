@@ -7,21 +7,30 @@ use std::{
 
 use k8s_openapi::{api::core::v1::TypedLocalObjectReference, merge_strategies};
 use kube::{
-    api::{Api, Patch},
+    api::{Api, DeleteParams, Patch},
     client::Client,
     core::{GroupVersionKind, ObjectMeta},
     runtime::controller::Error as CtrlErr,
 };
+use serde_json::json;
 use tokio::{
     signal::unix::{signal, SignalKind},
     time::Duration,
 };
-use tokio_stream::wrappers::SignalStream;
+use tokio_stream::wrappers::{BroadcastStream, SignalStream};
 
-use crate::{clair_condition, prelude::*, COMPONENT_LABEL, DEFAULT_CONFIG_JSON};
-use clair_templates::{Build, IndexerBuilder, JobBuilder, MatcherBuilder, NotifierBuilder};
+use crate::dropins::{merge_dropins, CausalContext};
+use crate::{clair_condition, clair_label, prelude::*, COMPONENT_LABEL, DEFAULT_CONFIG_JSON};
+use clair_templates::{
+    render_otlp_dropin, Build, IndexerBuilder, JobBuilder, MatcherBuilder, NotifierBuilder,
+    TaggedDropin,
+};
 use v1alpha1::Clair;
 
+/// KIND labels this controller's metrics, matching [`crate::indexers`]/[`crate::matchers`]/
+/// [`crate::updaters`]'s `KIND` constants.
+const KIND: &str = "Clair";
+
 static COMPONENT: LazyLock<String> = LazyLock::new(|| Clair::kind(&()).to_ascii_lowercase());
 static SELF_GVK: LazyLock<GroupVersionKind> = LazyLock::new(|| GroupVersionKind {
     group: Clair::group(&()).to_string(),
@@ -29,6 +38,17 @@ static SELF_GVK: LazyLock<GroupVersionKind> = LazyLock::new(|| GroupVersionKind
     kind: Clair::kind(&()).to_string(),
 });
 
+/// ADMIN_UPGRADE_ATTEMPTS_ANNOTATION records the admin-upgrade Job's `status.failed` count as of
+/// the last reconcile, so [`error_policy`] can back off proportionally without an extra API call.
+static ADMIN_UPGRADE_ATTEMPTS_ANNOTATION: LazyLock<String> =
+    LazyLock::new(|| clair_label("admin-upgrade-attempts"));
+
+/// DROPIN_CONTEXT_ANNOTATION stores the serialized [`CausalContext`] used to causally merge the
+/// per-component drop-ins (see [`crate::dropins`]), so a stale or requeued reconcile of one
+/// component can't clobber a path another component already wrote at a newer generation.
+static DROPIN_CONTEXT_ANNOTATION: LazyLock<String> =
+    LazyLock::new(|| clair_label("dropin-context"));
+
 /// Controller is the Clair controller.
 ///
 /// An error is returned if any setup fails.
@@ -38,6 +58,8 @@ pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<Contro
     let ctlcfg = watcher::Config::default();
     let root: Api<v1alpha1::Clair> = Api::all(client.clone());
     let sig = SignalStream::new(signal(SignalKind::user_defined1())?);
+    let admin_reconciles = BroadcastStream::new(ctx.admin_trigger.subscribe())
+        .filter_map(|res| futures::future::ready(res.ok()));
 
     Ok(async move {
         let ctl = Controller::new(root, ctlcfg.clone())
@@ -60,6 +82,7 @@ pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<Contro
             )
             .owns(Api::<batch::v1::Job>::all(client.clone()), ctlcfg.clone())
             .reconcile_all_on(sig)
+            .reconcile_on(admin_reconciles)
             .graceful_shutdown_on(cancel.cancelled_owned());
         info!("starting clair controller");
 
@@ -89,12 +112,25 @@ pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<Contro
     .boxed())
 }
 
+/// Error_policy requeues with exponential backoff (`base * 2^attempts`, capped), where `attempts`
+/// is the failed-attempt count [`Reconciler::set_job_attempts`] last recorded on the object, so a
+/// crash-looping admin-upgrade Job doesn't get reconciled in a tight 5-second loop.
 fn error_policy(obj: Arc<v1alpha1::Clair>, err: &Error, _ctx: Arc<Context>) -> Action {
     error!(
         error = err.to_string(),
         obj.metadata.name, obj.metadata.uid, "reconcile error"
     );
-    Action::requeue(Duration::from_secs(5))
+    let base = Duration::from_secs(5);
+    let cap = Duration::from_secs(5 * 60);
+    let attempts: u32 = obj
+        .annotations()
+        .get(ADMIN_UPGRADE_ATTEMPTS_ANNOTATION.as_str())
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    let backoff = base
+        .saturating_mul(2u32.saturating_pow(attempts.min(10)))
+        .min(cap);
+    Action::requeue(backoff)
 }
 
 #[instrument(skip(ctx, clair),fields(
@@ -103,28 +139,42 @@ fn error_policy(obj: Arc<v1alpha1::Clair>, err: &Error, _ctx: Arc<Context>) -> A
     generation = clair.metadata.generation,
     resource_version = clair.metadata.resource_version
 ))]
+/// Reconcile drives one pass of the Clair state machine. The whole pass runs inside the span
+/// opened by [`reconcile_span`], so every event logged below --- including the condition
+/// transitions and Diagnostics recorder emissions further down the call chain --- carries this
+/// object's correlation `group` and can be traced end to end.
 async fn reconcile(clair: Arc<Clair>, ctx: Arc<Context>) -> Result<Action> {
-    info!("reconciling Clair");
-    let r = Reconciler::from((clair.clone(), ctx.clone()));
-
-    for (field, present) in [
-        ("$.spec.databases", clair.spec.databases.is_some()),
-        ("$.spec.image", clair.spec.image.is_some()),
-    ] {
-        if !present {
-            info!(field, "missing required field, skipping reconciliation");
-            return Ok(Action::await_change());
+    let mut timer = crate::metrics::ReconcileTimer::start(KIND);
+    let span = reconcile_span(clair.as_ref());
+    let ret = async move {
+        info!("reconciling Clair");
+        let r = Reconciler::from((clair.clone(), ctx.clone()));
+
+        for (field, present) in [
+            ("$.spec.databases", clair.spec.databases.is_some()),
+            ("$.spec.image", clair.spec.image.is_some()),
+        ] {
+            if !present {
+                info!(field, "missing required field, skipping reconciliation");
+                return Ok(Action::await_change());
+            }
         }
-    }
 
-    r.configuration().await?;
-    r.admin_pre().await?;
-    r.indexer().await?;
-    r.matcher().await?;
-    r.notifier().await?;
-    r.admin_post().await?;
+        let threshold = ctx.slow_step_threshold;
+        with_poll_timer("configuration", threshold, r.configuration()).await?;
+        with_poll_timer("admin_pre", threshold, r.admin_pre()).await?;
+        with_poll_timer("indexer", threshold, r.indexer()).await?;
+        with_poll_timer("live_config_check", threshold, r.live_config_check()).await?;
+        with_poll_timer("matcher", threshold, r.matcher()).await?;
+        with_poll_timer("notifier", threshold, r.notifier()).await?;
+        with_poll_timer("admin_post", threshold, r.admin_post()).await?;
 
-    Ok(DEFAULT_REQUEUE.clone())
+        Ok(DEFAULT_REQUEUE.clone())
+    }
+    .instrument(span)
+    .await;
+    timer.finish(&ret);
+    ret
 }
 
 #[derive(Debug)]
@@ -183,13 +233,31 @@ impl Reconciler {
         let mut created_dropins = Vec::new();
 
         contents.insert("config.json".to_string(), DEFAULT_CONFIG_JSON.into());
+
+        // The causal context from the last successful merge, so a requeued reconcile of one
+        // component doesn't look like a fresh write and clobber a sibling's newer one.
+        let mut causal_context: CausalContext = api
+            .get_opt(&cm.name_any())
+            .instrument(debug_span!("get_opt", kind = "ConfigMap"))
+            .await?
+            .and_then(|cm| {
+                cm.annotations()
+                    .get(DROPIN_CONTEXT_ANNOTATION.as_str())
+                    .and_then(|raw| serde_json::from_str(raw).ok())
+            })
+            .unwrap_or_default();
+
         if let Some(ref status) = self.clair.status {
             // For all of the resources owned by this Clair instance, see if they've published a
             // dropin snippet to their status resource.
             //
-            // If so, pull it into the ConfigMap managed by this Clair instance and put a reference
-            // in the main ConfigSource.
+            // If so, causally merge it into a single combined patch for the ConfigMap managed by
+            // this Clair instance and put a reference in the main ConfigSource. A genuine
+            // disagreement between two components on the same path fails the reconcile instead
+            // of silently picking one.
             let to_check = [status.indexer.as_ref(), status.matcher.as_ref()];
+            let mut ops = Vec::new();
+            let mut conflicts = Vec::new();
             for objref in to_check.into_iter().flatten() {
                 let kind = objref.kind.as_str().to_ascii_lowercase();
                 debug!(kind, "checking created object");
@@ -208,35 +276,57 @@ impl Reconciler {
                         .and_then(|obj| obj.status),
                     _ => unreachable!(),
                 }
-                .and_then(|s| s.dropin);
+                .and_then(|s| s.dropin)
+                .and_then(|raw| serde_json::from_str::<TaggedDropin>(&raw).ok());
 
                 debug!(kind, found = dropin.is_some(), "checking dropin");
-                if let Some(dropin) = dropin {
-                    let key = format!("00-{kind}.json-patch");
-                    contents.insert(key.clone(), dropin);
-                    created_dropins.push(v1alpha1::DropinSource {
-                        config_map_key_ref: Some(v1alpha1::ConfigMapKeySelector {
-                            name: cm.name_any(),
-                            key,
-                        }),
-                        ..Default::default()
-                    });
+                if let Some(tagged) = dropin {
+                    if let Err(cs) = merge_dropins(&mut causal_context, &mut ops, &tagged) {
+                        conflicts.extend(cs);
+                    }
                 }
             }
-        }
-        cm.data = Some(contents);
 
-        let cm = api
-            .patch(&cm.name_any(), &PATCH_PARAMS, &Patch::Apply(cm))
-            .instrument(debug_span!("patch", kind = "ConfigMap"))
-            .await?;
-        info!(
-            config_map.name = cm.metadata.name,
-            config_map.generation = cm.metadata.generation,
-            config_map.resource_version = cm.metadata.resource_version,
-            "patched ConfigMap"
-        );
+            if !conflicts.is_empty() {
+                warn!(?conflicts, "conflicting component drop-ins, not writing ConfigMap");
+                let first = &conflicts[0];
+                let err = ReconcileError::FailedPrecondition(format!(
+                    "{} conflicting drop-in write(s), e.g. {:?} written by both {:?} and {:?}",
+                    conflicts.len(),
+                    first.path,
+                    first.winner,
+                    first.loser
+                ));
+                return self
+                    .patch_reconcile_error(err, clair_condition("ConfigReady"))
+                    .await;
+            }
 
+            if !ops.is_empty() {
+                let key = "00-components.json-patch".to_string();
+                contents.insert(key.clone(), serde_json::to_string(&ops)?);
+                created_dropins.push(v1alpha1::DropinSource {
+                    config_map_key_ref: Some(v1alpha1::ConfigMapKeySelector {
+                        name: cm.name_any(),
+                        key,
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
+        if let Some(otlp) = &self.clair.spec.otlp {
+            if let Some(dropin) = render_otlp_dropin(otlp) {
+                let key = "05-otlp.json-patch".to_string();
+                contents.insert(key.clone(), dropin);
+                created_dropins.push(v1alpha1::DropinSource {
+                    config_map_key_ref: Some(v1alpha1::ConfigMapKeySelector {
+                        name: cm.name_any(),
+                        key,
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
         if let Some(dbs) = &self.clair.spec.databases {
             trace!("have databases");
             for &sec in &[&dbs.indexer, &dbs.matcher] {
@@ -255,6 +345,35 @@ impl Reconciler {
         }
         trace!(?created_dropins, "created dropins");
 
+        if let Err((key, err)) = self.validate_config(&contents, &created_dropins).await {
+            warn!(key, error = %err, "merged config failed validation, not writing ConfigMap");
+            let err = ReconcileError::FailedPrecondition(format!(
+                "dropin {key:?} failed validation: {err}"
+            ));
+            return self
+                .patch_reconcile_error(err, clair_condition("ConfigReady"))
+                .await;
+        }
+
+        cm.data = Some(contents);
+        cm.metadata
+            .annotations
+            .get_or_insert_with(BTreeMap::new)
+            .insert(
+                DROPIN_CONTEXT_ANNOTATION.to_string(),
+                serde_json::to_string(&causal_context)?,
+            );
+        let cm = api
+            .patch(&cm.name_any(), &PATCH_PARAMS, &Patch::Apply(cm))
+            .instrument(debug_span!("patch", kind = "ConfigMap"))
+            .await?;
+        info!(
+            config_map.name = cm.metadata.name,
+            config_map.generation = cm.metadata.generation,
+            config_map.resource_version = cm.metadata.resource_version,
+            "patched ConfigMap"
+        );
+
         let mut dropins = self.clair.spec.dropins.clone();
         merge_strategies::list::set(&mut dropins, created_dropins);
         let config = v1alpha1::ConfigSource {
@@ -263,6 +382,7 @@ impl Reconciler {
                 key: "config.json".into(),
             },
             dropins,
+            persistent: self.clair.spec.config_storage.clone(),
         };
         trace!(config_source=?config, "created ConfigSource");
         if self
@@ -319,132 +439,496 @@ impl Reconciler {
         Ok(())
     }
 
-    /// The admin_pre step is responsible for arranging for the admin pre-upgrade jobs to run and
-    /// for "promoting" the version.
+    /// Validate_config folds `contents` (the in-memory ConfigMap data about to be written) and
+    /// `dropins` (resolving any Secret-backed ones fresh) onto `DEFAULT_CONFIG_JSON`, the same way
+    /// Clair resolves its `ConfigSource` at runtime, and confirms the JSON patches apply cleanly
+    /// and the result deserializes as a JSON object. On failure, returns the offending dropin's
+    /// key alongside the error, so the caller can avoid shipping a broken ConfigMap.
+    async fn validate_config(
+        &self,
+        contents: &BTreeMap<String, String>,
+        dropins: &[v1alpha1::DropinSource],
+    ) -> std::result::Result<(), (String, clair_config::Error)> {
+        let root = core::v1::ConfigMap {
+            data: Some(contents.clone()),
+            ..Default::default()
+        };
+        let mut b = clair_config::Builder::from_root(&root, "config.json")
+            .map_err(|err| ("config.json".to_string(), err))?;
+        for d in dropins {
+            if let Some(r) = &d.config_map_key_ref {
+                b = b
+                    .add(root.clone(), r.key.clone())
+                    .map_err(|err| (r.key.clone(), err))?;
+            } else if let Some(r) = &d.secret_key_ref {
+                let api = Api::<core::v1::Secret>::namespaced(self.client(), self.ns());
+                let sec = api
+                    .get_opt(&r.name)
+                    .instrument(debug_span!("get_opt", kind = "Secret"))
+                    .await
+                    .map_err(|err| (r.key.clone(), clair_config::Error::Invalid(err.to_string())))?
+                    .ok_or_else(|| {
+                        (
+                            r.key.clone(),
+                            clair_config::Error::Invalid(format!("no such Secret: {}", r.name)),
+                        )
+                    })?;
+                b = b.add(sec, r.key.clone()).map_err(|err| (r.key.clone(), err))?;
+            }
+        }
+        let p: clair_config::Parts = b.into();
+        p.render().map_err(|err| ("<merged>".to_string(), err))?;
+        Ok(())
+    }
+
+    /// Live_config_check best-effort exec's into a running Indexer pod and runs the config check
+    /// baked into the Clair image (see [`Context::validate_config`]), catching a config this
+    /// operator's own parser accepts but the exact image version the cluster runs rejects.
+    ///
+    /// Before any Indexer pod has come up yet, this is a no-op rather than a failure --- the
+    /// static check in [`Self::validate_config`] already guards the ConfigMap write, so this is
+    /// strictly a belt-and-suspenders pass once there's something to run it against. A failed
+    /// check is reported as a Warning event rather than a reconcile error, since the image may
+    /// simply not have caught up to a config this operator already validated.
+    #[instrument(skip(self))]
+    async fn live_config_check(&self) -> Result<()> {
+        let pods = Api::<core::v1::Pod>::namespaced(self.client(), self.ns())
+            .list(&kube::api::ListParams::default().labels(
+                "app.kubernetes.io/name=clair,app.kubernetes.io/component=indexer",
+            ))
+            .await?;
+        let Some(pod) = pods
+            .items
+            .into_iter()
+            .find(|p| p.status.as_ref().and_then(|s| s.phase.as_deref()) == Some("Running"))
+        else {
+            debug!("no running indexer pod yet, skipping live config check");
+            return Ok(());
+        };
+
+        match self.ctx.validate_config(&pod, "/etc/clair/config.json").await {
+            Ok(_) => {
+                debug!(pod = pod.name_any(), "live config check passed");
+                Ok(())
+            }
+            Err(Error::Exec { code, message }) => {
+                warn!(code, message, "live config check failed");
+                let req = Request::new(&self.client());
+                let ev = Event {
+                    type_: EventType::Warning,
+                    reason: "ConfigValidationFailed".into(),
+                    note: Some(format!("live config check exited {code}: {message}")),
+                    action: "LiveConfigCheck".into(),
+                    secondary: None,
+                };
+                req.publish(&ev, &self.clair.object_ref(&())).await?;
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Upgrade_deferred returns a machine-readable reason the admin-upgrade state machine can't
+    /// launch a job right now, or `None` if it's safe to proceed.
+    fn upgrade_deferred(&self) -> Option<&'static str> {
+        if self.clair.spec.databases.is_none() {
+            return Some("DatabasesNotReady");
+        }
+        let cnds = self
+            .clair
+            .status
+            .as_ref()
+            .map(|s| s.conditions.as_slice())
+            .unwrap_or_default();
+        let ready = |type_: String| {
+            cnds.iter()
+                .find(|c| c.type_ == type_)
+                .is_some_and(|c| c.status == "True")
+        };
+        if !ready(clair_condition("IndexerCreated")) || !ready(clair_condition("MatcherCreated")) {
+            return Some("SubsystemNotReady");
+        }
+        None
+    }
+
+    /// Apply_condition merges `cnd` into `status.conditions` and, when `promote` is `Some`,
+    /// records it as the new `status.current_version`, moving the prior value to
+    /// `status.previous_version`. Returns the patched object along with whether the merge
+    /// actually changed this condition type's `status`.
+    async fn apply_condition(&self, cnd: Condition, promote: Option<String>) -> Result<(Clair, bool)> {
+        let type_ = cnd.type_.clone();
+        let (next, transitioned) = self
+            .api
+            .get_status(&self.name())
+            .instrument(debug_span!("get_status"))
+            .await
+            .map(|mut next| {
+                next.meta_mut().managed_fields = None;
+                let status = next.status.get_or_insert_default();
+                if let Some(image) = promote {
+                    status.previous_version = status.current_version.take();
+                    status.current_version = Some(image);
+                }
+                let cnds = status.conditions.get_or_insert_default();
+                let before = cnds.iter().find(|c| c.type_ == type_).map(|c| c.status.clone());
+                merge_strategies::list::map(cnds, vec![cnd], &[cmp_condition], merge_condition);
+                let after = cnds.iter().find(|c| c.type_ == type_).map(|c| c.status.clone());
+                (next, before != after)
+            })?;
+        trace!("patching status");
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&next))
+            .instrument(debug_span!("patch_status"))
+            .await?;
+        Ok((next, transitioned))
+    }
+
+    /// Find_condition looks up `type_` in `obj.status.conditions`.
+    fn find_condition<'a>(obj: &'a Clair, type_: &str) -> Option<&'a Condition> {
+        obj.status
+            .as_ref()?
+            .conditions
+            .as_ref()?
+            .iter()
+            .find(|c| c.type_ == type_)
+    }
+
+    /// Patch_condition merges `cnd` into `status.conditions` via [`Self::apply_condition`]. If
+    /// the merge actually changes the condition's `status`, this also records the transition (see
+    /// [`Context::record_transition`]).
+    async fn patch_condition(&self, cnd: Condition, promote: Option<String>) -> Result<()> {
+        let type_ = cnd.type_.clone();
+        let previous = Self::find_condition(&self.clair, &type_).map(|c| c.status.clone());
+        let (next, transitioned) = self.apply_condition(cnd, promote).await?;
+        if transitioned {
+            if let Some(cnd) = Self::find_condition(&next, &type_) {
+                self.ctx
+                    .record_transition(cnd, previous.as_deref(), &self.clair.object_ref(&()))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Patch_reconcile_error is like [`Self::patch_condition`], but builds the condition from a
+    /// [`ReconcileError`] (see [`ReconcileError::condition`]) and, on a transition, records it via
+    /// [`Context::record_reconcile_error`] so the emitted Event's Normal/Warning split comes from
+    /// the error's severity rather than from the condition's bare `status`.
+    async fn patch_reconcile_error(&self, err: ReconcileError, type_: String) -> Result<()> {
+        let previous = Self::find_condition(&self.clair, &type_).map(|c| c.status.clone());
+        let cnd = err.condition(type_.clone(), self.clair.metadata.generation);
+        let (next, transitioned) = self.apply_condition(cnd, None).await?;
+        if transitioned {
+            if let Some(cnd) = Self::find_condition(&next, &type_) {
+                self.ctx
+                    .record_reconcile_error(&err, cnd, previous.as_deref(), &self.clair.object_ref(&()))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set_job_attempts stamps [`ADMIN_UPGRADE_ATTEMPTS_ANNOTATION`] with the admin-upgrade Job's
+    /// current failed-attempt count, for [`error_policy`] to read back.
+    async fn set_job_attempts(&self, n: i32) -> Result<()> {
+        let patch = Patch::Merge(json!({
+            "metadata": { "annotations": { ADMIN_UPGRADE_ATTEMPTS_ANNOTATION.as_str(): n.to_string() } },
+        }));
+        self.api
+            .patch(&self.name(), &PATCH_PARAMS, &patch)
+            .instrument(debug_span!("patch", kind = "annotation"))
+            .await?;
+        Ok(())
+    }
+
+    /// The admin_pre step drives the admin-upgrade state machine's check/defer/apply/verify
+    /// phases: it notices `spec.image` changing, defers starting the "admin pre" job until
+    /// dependencies are ready, launches it, and commits the new image to
+    /// `status.current_version` once the job succeeds.
     #[instrument(skip(self), ret)]
     async fn admin_pre(&self) -> Result<()> {
         use batch::v1::Job;
-        let job_type = clair_condition("AdminPreJobDone");
-        let mut update = vec![];
-        let mut promote = false;
+        let type_ = clair_condition("AdminUpgrade");
+        let api = Api::<Job>::namespaced(self.client(), self.ns());
         let cnds = self
             .clair
             .status
             .as_ref()
             .and_then(|s| s.conditions.clone())
             .unwrap_or_default();
-        let api = Api::<Job>::namespaced(self.client(), self.ns());
 
-        // If there are no conditions, record the Job as done and continue.
-        //
-        // If there are conditions, check in order:
-        // - If the PreJob condition is not current to the spec:
-        //   - Check on the current image:
-        //     - If changed, start a the new job and set the condtion to False.
-        // - If the PreJob condition is current to the spec:
-        //   - If false, check on the job and update if need be.
-        //   - If true, swap the new image into the status.
-
-        if let Some(cnd) = cnds.iter().find(|&c| c.type_ == job_type) {
-            debug!("checking Condition");
-            if cnd.observed_generation != self.clair.metadata.generation {
+        let (update, promote) = match cnds.iter().find(|&c| c.type_ == type_) {
+            None => {
+                debug!("fresh instance, skipping \"admin pre\" job");
+                (
+                    Some(Condition {
+                        message: "pre/post jobs are not needed on a fresh system".into(),
+                        observed_generation: self.clair.metadata.generation,
+                        last_transition_time: meta::v1::Time(Utc::now()),
+                        reason: "Upgraded".into(),
+                        status: "True".into(),
+                        type_,
+                    }),
+                    self.clair.spec.image.clone().map(|img| img.to_string()),
+                )
+            }
+            Some(cnd) if cnd.observed_generation != self.clair.metadata.generation => {
                 debug!(
                     observed = cnd.observed_generation,
                     current = self.clair.metadata.generation,
                     "generation differs"
                 );
-                if self.clair.spec.image.as_ref()
-                    == self.clair.status.as_ref().and_then(|s| s.image.as_ref())
+                if self.clair.spec.image.as_ref().map(ToString::to_string).as_ref()
+                    == self.clair.status.as_ref().and_then(|s| s.current_version.as_ref())
                 {
                     debug!("\"spec.image\" not changed");
-                    update.push(Condition {
-                        message: "spec.image not changed".into(),
-                        observed_generation: self.clair.metadata.generation,
-                        last_transition_time: meta::v1::Time(Utc::now()),
-                        reason: "NoImageUpdate".into(),
-                        status: "True".into(),
-                        type_: job_type,
-                    });
+                    (
+                        Some(Condition {
+                            message: "spec.image not changed".into(),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: "Upgraded".into(),
+                            status: "True".into(),
+                            type_,
+                        }),
+                        None,
+                    )
+                } else if let Some(why) = self.upgrade_deferred() {
+                    debug!(why, "deferring \"admin pre\" job");
+                    (
+                        Some(Condition {
+                            message: format!("waiting on {why} before upgrading"),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: format!("Pre{why}"),
+                            status: "False".into(),
+                            type_,
+                        }),
+                        None,
+                    )
                 } else {
                     debug!("starting \"admin pre\" job");
-                    update.push(Condition {
-                        message: "spec.image changed, launching \"admin pre\" job".into(),
-                        observed_generation: self.clair.metadata.generation,
-                        last_transition_time: meta::v1::Time(Utc::now()),
-                        reason: "ImageUpdated".into(),
-                        status: "False".into(),
-                        type_: job_type,
-                    });
-                    info!(TODO = true, "launch job");
-
                     let j = JobBuilder::admin_pre(self.clair.as_ref())?.build();
-                    api.create(&CREATE_PARAMS, &j)
-                        .instrument(debug_span!("create"))
-                        .await?;
+                    launch_job(&api, j).await?;
+                    self.set_job_attempts(0).await?;
+                    (
+                        Some(Condition {
+                            message: "spec.image changed, launching \"admin pre\" job".into(),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: "PreUpgradeRunning".into(),
+                            status: "False".into(),
+                            type_,
+                        }),
+                        None,
+                    )
+                }
+            }
+            Some(cnd) if cnd.reason == "PreDatabasesNotReady" || cnd.reason == "PreSubsystemNotReady" => {
+                if let Some(why) = self.upgrade_deferred() {
+                    trace!(why, "still deferred");
+                    (None, None)
+                } else {
+                    debug!("dependencies ready, starting \"admin pre\" job");
+                    let j = JobBuilder::admin_pre(self.clair.as_ref())?.build();
+                    launch_job(&api, j).await?;
+                    self.set_job_attempts(0).await?;
+                    (
+                        Some(Condition {
+                            message: "dependencies ready, launching \"admin pre\" job".into(),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: "PreUpgradeRunning".into(),
+                            status: "False".into(),
+                            type_,
+                        }),
+                        None,
+                    )
                 }
-            } else {
-                debug!("checking ");
-                match cnd.status.as_str() {
-                    "False" => {
-                        info!(TODO = true, "check job");
+            }
+            Some(cnd) if cnd.reason == "PreUpgradeRunning" || cnd.reason == "JobRetrying" => {
+                let j = JobBuilder::admin_pre(self.clair.as_ref())?.build();
+                let progress = api
+                    .get_opt(&j.name_any())
+                    .instrument(debug_span!("get_opt", kind = "Job"))
+                    .await?
+                    .as_ref()
+                    .map(job_progress)
+                    .unwrap_or(JobProgress::Running);
+                match progress {
+                    JobProgress::Succeeded => {
+                        debug!("\"admin pre\" job succeeded, promoting image");
+                        self.set_job_attempts(0).await?;
+                        (
+                            Some(Condition {
+                                message: "\"admin pre\" job succeeded".into(),
+                                observed_generation: self.clair.metadata.generation,
+                                last_transition_time: meta::v1::Time(Utc::now()),
+                                reason: "Promoting".into(),
+                                status: "False".into(),
+                                type_,
+                            }),
+                            self.clair.spec.image.clone().map(|img| img.to_string()),
+                        )
                     }
-                    "True" => {
-                        if self.clair.spec.image.as_ref()
-                            != self.clair.status.as_ref().and_then(|s| s.image.as_ref())
-                        {
-                            debug!("promoting image");
-                            promote = true;
-                        }
+                    JobProgress::Retrying(failed, limit) => {
+                        debug!(failed, limit, "\"admin pre\" job retrying");
+                        self.set_job_attempts(failed).await?;
+                        (
+                            Some(Condition {
+                                message: format!(
+                                    "\"admin pre\" job retrying ({failed}/{limit} failed attempts)"
+                                ),
+                                observed_generation: self.clair.metadata.generation,
+                                last_transition_time: meta::v1::Time(Utc::now()),
+                                reason: "JobRetrying".into(),
+                                status: "False".into(),
+                                type_,
+                            }),
+                            None,
+                        )
                     }
-                    "Unknown" => {
-                        error!(condition = job_type, "job in unknown state???");
-                        return Ok(());
+                    JobProgress::Failed => {
+                        error!("\"admin pre\" job exhausted its retries");
+                        (
+                            Some(Condition {
+                                message: "\"admin pre\" job failed (retries exhausted)".into(),
+                                observed_generation: self.clair.metadata.generation,
+                                last_transition_time: meta::v1::Time(Utc::now()),
+                                reason: "Failed".into(),
+                                status: "False".into(),
+                                type_,
+                            }),
+                            None,
+                        )
+                    }
+                    JobProgress::Running => {
+                        trace!("\"admin pre\" job still running");
+                        (None, None)
                     }
-                    _ => unreachable!(),
                 }
             }
-        } else {
-            debug!("fresh instance, skipping \"admin pre\" job");
-            promote = true;
-            update.push(Condition {
-                message: "pre jobs are not needed on a fresh system".into(),
-                observed_generation: self.clair.metadata.generation,
-                last_transition_time: meta::v1::Time(Utc::now()),
-                reason: "NewClair".into(),
-                status: "True".into(),
-                type_: job_type,
-            });
-        }
+            Some(_) => (None, None),
+        };
 
-        if !update.is_empty() {
-            let next = self
-                .api
-                .get_status(&self.name())
-                .instrument(debug_span!("get_status"))
-                .await
-                .map(|mut next| {
-                    next.meta_mut().managed_fields = None;
-                    let status = next.status.get_or_insert_default();
-                    if promote {
-                        status.image = self.clair.spec.image.clone();
-                    }
-                    let cnds = status.conditions.get_or_insert_default();
-                    merge_strategies::list::map(cnds, update, &[cmp_condition], merge_condition);
-                    next
-                })?;
-            trace!("patching status");
-            self.api
-                .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&next))
-                .instrument(debug_span!("patch_status"))
-                .await?;
+        if let Some(cnd) = update {
+            self.patch_condition(cnd, promote).await?;
         }
 
         Ok(())
     }
 
+    /// The admin_post step picks up where [`Reconciler::admin_pre`] leaves off: once the new
+    /// image has been promoted, it defers starting the "admin post" job until the Indexer and
+    /// Matcher are back up, launches it, and marks the upgrade `Upgraded`/`Failed` once the job
+    /// finishes.
     #[instrument(skip(self), ret)]
     async fn admin_post(&self) -> Result<()> {
-        info!(TODO = true, "write admin post job");
+        use batch::v1::Job;
+        let type_ = clair_condition("AdminUpgrade");
+        let api = Api::<Job>::namespaced(self.client(), self.ns());
+        let cnds = self
+            .clair
+            .status
+            .as_ref()
+            .and_then(|s| s.conditions.clone())
+            .unwrap_or_default();
+        let Some(cnd) = cnds.iter().find(|&c| c.type_ == type_) else {
+            return Ok(());
+        };
+
+        let update = match cnd.reason.as_str() {
+            "Promoting" | "PostDatabasesNotReady" | "PostSubsystemNotReady" => {
+                if let Some(why) = self.upgrade_deferred() {
+                    if cnd.reason == "Promoting" {
+                        debug!(why, "deferring \"admin post\" job");
+                        Some(Condition {
+                            message: format!("waiting on {why} before upgrading"),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: format!("Post{why}"),
+                            status: "False".into(),
+                            type_,
+                        })
+                    } else {
+                        trace!(why, "still deferred");
+                        None
+                    }
+                } else {
+                    debug!("starting \"admin post\" job");
+                    let j = JobBuilder::admin_post(self.clair.as_ref())?.build();
+                    launch_job(&api, j).await?;
+                    self.set_job_attempts(0).await?;
+                    Some(Condition {
+                        message: "launching \"admin post\" job".into(),
+                        observed_generation: self.clair.metadata.generation,
+                        last_transition_time: meta::v1::Time(Utc::now()),
+                        reason: "PostUpgradeRunning".into(),
+                        status: "False".into(),
+                        type_,
+                    })
+                }
+            }
+            "PostUpgradeRunning" | "JobRetrying" => {
+                let j = JobBuilder::admin_post(self.clair.as_ref())?.build();
+                let progress = api
+                    .get_opt(&j.name_any())
+                    .instrument(debug_span!("get_opt", kind = "Job"))
+                    .await?
+                    .as_ref()
+                    .map(job_progress)
+                    .unwrap_or(JobProgress::Running);
+                match progress {
+                    JobProgress::Succeeded => {
+                        info!("upgrade complete");
+                        self.set_job_attempts(0).await?;
+                        Some(Condition {
+                            message: "\"admin post\" job succeeded".into(),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: "Upgraded".into(),
+                            status: "True".into(),
+                            type_,
+                        })
+                    }
+                    JobProgress::Retrying(failed, limit) => {
+                        debug!(failed, limit, "\"admin post\" job retrying");
+                        self.set_job_attempts(failed).await?;
+                        Some(Condition {
+                            message: format!(
+                                "\"admin post\" job retrying ({failed}/{limit} failed attempts)"
+                            ),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: "JobRetrying".into(),
+                            status: "False".into(),
+                            type_,
+                        })
+                    }
+                    JobProgress::Failed => {
+                        error!("\"admin post\" job exhausted its retries");
+                        Some(Condition {
+                            message: "\"admin post\" job failed (retries exhausted)".into(),
+                            observed_generation: self.clair.metadata.generation,
+                            last_transition_time: meta::v1::Time(Utc::now()),
+                            reason: "Failed".into(),
+                            status: "False".into(),
+                            type_,
+                        })
+                    }
+                    JobProgress::Running => {
+                        trace!("\"admin post\" job still running");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        if let Some(cnd) = update {
+            self.patch_condition(cnd, None).await?;
+        }
+
         Ok(())
     }
 
@@ -651,13 +1135,90 @@ impl Reconciler {
     }
 }
 
-fn cmp_condition(a: &Condition, b: &Condition) -> bool {
+pub(crate) fn cmp_condition(a: &Condition, b: &Condition) -> bool {
     a.type_.as_str() == b.type_.as_str()
 }
-fn merge_condition(to: &mut Condition, from: Condition) {
-    to.last_transition_time = from.last_transition_time;
-    if let Some(g) = from.observed_generation {
-        to.observed_generation = Some(g);
+
+/// JobProgress summarizes a monitored one-shot Job's state, read off `status.succeeded`/
+/// `status.failed` and compared against `spec.backoffLimit`. Shared by the admin-upgrade state
+/// machine below and the Indexer migration gate in [`crate::indexers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum JobProgress {
+    /// Still running, no failed attempts yet (or the Job hasn't been observed).
+    Running,
+    /// Failed `.0` of `.1` allowed attempts; below the limit, so kubernetes will retry it.
+    Retrying(i32, i32),
+    /// Succeeded.
+    Succeeded,
+    /// Failed `spec.backoffLimit` times; kubernetes has given up retrying it.
+    Failed,
+}
+
+/// Job_progress inspects a Job tracked by a Job-gated state machine, per its
+/// `status.succeeded`/`status.failed` counters rather than its `status.conditions`, so a
+/// mid-retry Job is distinguished from one that's exhausted `spec.backoffLimit`.
+pub(crate) fn job_progress(job: &batch::v1::Job) -> JobProgress {
+    let status = job.status.clone().unwrap_or_default();
+    if status.succeeded.unwrap_or(0) > 0 {
+        return JobProgress::Succeeded;
+    }
+    let failed = status.failed.unwrap_or(0);
+    let limit = job.spec.as_ref().and_then(|s| s.backoff_limit).unwrap_or(6);
+    if failed >= limit {
+        return JobProgress::Failed;
+    }
+    if failed > 0 {
+        return JobProgress::Retrying(failed, limit);
+    }
+    JobProgress::Running
+}
+
+/// Launch_job deletes any stale Job by the same name (ignoring a missing one) then creates `job`,
+/// so a previous attempt's `status.failed` count starts clean instead of counting against the new
+/// launch's `spec.backoffLimit`.
+pub(crate) async fn launch_job(api: &Api<batch::v1::Job>, job: batch::v1::Job) -> Result<()> {
+    let name = job.name_any();
+    if let Err(err) = api
+        .delete(&name, &DeleteParams::default())
+        .instrument(debug_span!("delete", kind = "Job"))
+        .await
+    {
+        debug!(%err, name, "no stale job to clear (or already gone)");
+    }
+    api.create(&CREATE_PARAMS, &job)
+        .instrument(debug_span!("create"))
+        .await?;
+    Ok(())
+}
+/// Merge_condition treats `observed_generation` as a monotonic Lamport clock so that racing or
+/// out-of-order patches to `.status.conditions` converge instead of clobbering one another: an
+/// `from` with a lower generation than `to` is dropped outright, a higher generation wins
+/// unconditionally, and an equal generation is broken deterministically by `last_transition_time`
+/// then lexicographic `status`, so the same pair of updates merges identically regardless of
+/// apply order. As before, only non-empty fields are accepted from the winning side, and
+/// `last_transition_time` is only bumped when `status` actually changes value, matching
+/// Kubernetes' Condition semantics.
+pub(crate) fn merge_condition(to: &mut Condition, from: Condition) {
+    use std::cmp::Ordering;
+
+    let from_wins = match from.observed_generation.cmp(&to.observed_generation) {
+        Ordering::Greater => true,
+        Ordering::Less => false,
+        Ordering::Equal => match from.last_transition_time.0.cmp(&to.last_transition_time.0) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => from.status.as_str() > to.status.as_str(),
+        },
+    };
+    if !from_wins {
+        return;
+    }
+
+    if from.observed_generation.is_some() {
+        to.observed_generation = from.observed_generation;
+    }
+    if !from.status.is_empty() && from.status != to.status {
+        to.last_transition_time = from.last_transition_time;
     }
     if !from.message.is_empty() {
         to.message = from.message;
@@ -670,26 +1231,73 @@ fn merge_condition(to: &mut Condition, from: Condition) {
     }
 }
 
-/*
-/// Diagnostics to be exposed by the web server
-#[derive(Clone, Serialize)]
-pub struct Diagnostics {
-    #[serde(deserialize_with = "from_ts")]
-    pub last_event: DateTime<Utc>,
-    #[serde(skip)]
-    pub reporter: Reporter,
-}
-impl Default for Diagnostics {
-    fn default() -> Self {
-        Self {
-            last_event: Utc::now(),
-            reporter: "doc-controller".into(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cnd(generation: Option<i64>, time: &str, status: &str) -> Condition {
+        Condition {
+            last_transition_time: meta::v1::Time(
+                chrono::DateTime::parse_from_rfc3339(time)
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ),
+            message: "msg".into(),
+            observed_generation: generation,
+            reason: "reason".into(),
+            status: status.into(),
+            type_: "test/condition".into(),
         }
     }
-}
-impl Diagnostics {
-    fn recorder(&self, client: Client) -> Recorder {
-        Recorder::new(client, self.reporter.clone())
+
+    #[test]
+    fn merge_drops_older_generation() {
+        let mut to = cnd(Some(5), "2024-01-01T00:00:00Z", "True");
+        let from = cnd(Some(4), "2024-01-02T00:00:00Z", "False");
+        let want = to.clone();
+        merge_condition(&mut to, from);
+        assert_eq!(to, want);
+    }
+
+    #[test]
+    fn merge_accepts_newer_generation() {
+        let mut to = cnd(Some(5), "2024-01-01T00:00:00Z", "True");
+        let from = cnd(Some(6), "2024-01-02T00:00:00Z", "False");
+        merge_condition(&mut to, from.clone());
+        assert_eq!(to.status, from.status);
+        assert_eq!(to.observed_generation, from.observed_generation);
+        assert_eq!(to.last_transition_time, from.last_transition_time);
+    }
+
+    #[test]
+    fn merge_equal_generation_breaks_tie_on_time() {
+        let mut to = cnd(Some(5), "2024-01-01T00:00:00Z", "True");
+        let from = cnd(Some(5), "2024-01-02T00:00:00Z", "False");
+        merge_condition(&mut to, from);
+        assert_eq!(to.status, "False");
+    }
+
+    #[test]
+    fn merge_equal_generation_and_time_breaks_tie_on_status() {
+        let mut to = cnd(Some(5), "2024-01-01T00:00:00Z", "Aaa");
+        let from = cnd(Some(5), "2024-01-01T00:00:00Z", "Bbb");
+        merge_condition(&mut to, from);
+        assert_eq!(to.status, "Bbb");
+
+        let mut to = cnd(Some(5), "2024-01-01T00:00:00Z", "Bbb");
+        let from = cnd(Some(5), "2024-01-01T00:00:00Z", "Aaa");
+        let want = to.clone();
+        merge_condition(&mut to, from);
+        assert_eq!(to, want);
+    }
+
+    #[test]
+    fn merge_same_status_does_not_bump_transition_time() {
+        let original_time = cnd(Some(5), "2024-01-01T00:00:00Z", "True").last_transition_time;
+        let mut to = cnd(Some(5), "2024-01-01T00:00:00Z", "True");
+        let from = cnd(Some(6), "2024-06-01T00:00:00Z", "True");
+        merge_condition(&mut to, from);
+        assert_eq!(to.last_transition_time, original_time);
     }
 }
-*/
+
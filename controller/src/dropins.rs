@@ -0,0 +1,81 @@
+//! Dropins implements causal merging of the per-component config drop-ins produced by
+//! [`clair_templates::render_dropin`]. Each drop-in is tagged with its source component and a
+//! generation counter (the source object's `metadata.generation`), borrowing the dotted-version-
+//! vector-set idea from causal key-value stores: a [`CausalContext`] remembers which source last
+//! wrote each JSON-Patch path, so a stale or requeued reconcile can't silently clobber a sibling
+//! component's write, and two components genuinely disagreeing on the same path surfaces as a
+//! [`Conflict`] instead of one being picked arbitrarily.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use clair_templates::TaggedDropin;
+
+/// CausalContext maps a JSON-Patch path to the `(source, generation)` that last wrote it.
+pub type CausalContext = BTreeMap<String, (String, i64)>;
+
+/// Conflict records two sources disagreeing on the same JSON-Patch path: `winner` is the source
+/// already recorded in the [`CausalContext`], `loser` is the incoming source whose write was
+/// rejected.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Conflict {
+    pub path: String,
+    pub winner: String,
+    pub loser: String,
+}
+
+/// Merge_dropins folds `incoming`'s operations into `ops`, updating `context` in place:
+///
+/// - a path not yet in `context` is accepted and recorded;
+/// - a path already recorded for the same source is re-applied only if `incoming.generation` is
+///   newer than the recorded one; an equal-or-older generation is a stale replay and is dropped;
+/// - a path already recorded for a *different* source is never applied, and is instead collected
+///   into the returned conflict list so the caller can surface it rather than pick a winner.
+///
+/// Ops whose `path` can't be read are ignored, since there's nothing to causally track.
+pub fn merge_dropins(
+    context: &mut CausalContext,
+    ops: &mut Vec<Value>,
+    incoming: &TaggedDropin,
+) -> Result<(), Vec<Conflict>> {
+    let mut conflicts = Vec::new();
+
+    for op in &incoming.ops {
+        let Some(path) = op.get("path").and_then(Value::as_str) else {
+            continue;
+        };
+        let path = path.to_string();
+
+        match context.get(&path) {
+            None => {
+                context.insert(path, (incoming.source.clone(), incoming.generation));
+                ops.push(op.clone());
+            }
+            Some((source, generation)) if *source == incoming.source => {
+                if incoming.generation > *generation {
+                    context.insert(path.clone(), (incoming.source.clone(), incoming.generation));
+                    match ops.iter_mut().find(|o| o.get("path").and_then(Value::as_str) == Some(path.as_str())) {
+                        Some(existing) => *existing = op.clone(),
+                        None => ops.push(op.clone()),
+                    }
+                }
+                // Same-or-older generation from the same source is a stale replay; drop it.
+            }
+            Some((source, _)) => {
+                conflicts.push(Conflict {
+                    path,
+                    winner: source.clone(),
+                    loser: incoming.source.clone(),
+                });
+            }
+        }
+    }
+
+    if conflicts.is_empty() {
+        Ok(())
+    } else {
+        Err(conflicts)
+    }
+}
@@ -3,14 +3,21 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use k8s_openapi::api::apps;
-use k8s_openapi::NamespaceResourceScope;
+use k8s_openapi::{DeepMerge, NamespaceResourceScope};
+use kube::api::entry::{CommitError, Entry};
+use kube::api::{Patch, PatchParams};
+use kube::core::object::HasStatus;
 use kube::core::PartialObjectMeta;
 use kube::runtime::controller::Action;
 use kube::{Resource, ResourceExt};
 use serde::de::DeserializeOwned;
-use tracing::trace;
+use serde::Serialize;
+use serde_json::json;
+use tracing::{debug, trace};
 
-use super::{Context, Result};
+use api::v1alpha1::{CrdCommon, StatusCommon};
+
+use super::{templates, Context, Error, Result, CONTROLLER_NAME};
 
 pub async fn create_if_needed<K, R>(
     obj: Arc<PartialObjectMeta<K>>,
@@ -20,11 +27,17 @@ where
     R: Resource<Scope = NamespaceResourceScope, DynamicType = ()>
         + Clone
         + DeserializeOwned
+        + Serialize
+        + DeepMerge
         + Debug,
     K: Resource<Scope = NamespaceResourceScope, DynamicType = ()>
+        + CrdCommon
+        + HasStatus<Status = <K as CrdCommon>::Status>
         + Clone
         + DeserializeOwned
+        + Serialize
         + Debug,
+    K::Status: Clone + Default + Serialize,
 {
     use kube::api::Api;
 
@@ -46,16 +59,70 @@ where
     let kind = K::kind(&()).to_string();
     let ns = obj.namespace().unwrap();
     let object_name = obj.name_any();
-    let api: Api<PartialObjectMeta<R>> = Api::namespaced(ctx.client, &ns);
+    let api: Api<R> = Api::namespaced(ctx.client.clone(), &ns);
+    trace!(kind, deployment, res_name, object_name, "resolved names");
+
+    let pp = PatchParams::apply(CONTROLLER_NAME).force();
+    let mut created: Option<R> = None;
 
     for n in 0..3 {
         trace!(n, "reconcile attempt");
-        let mut entry = api.entry(&res_name).await?;
-        match entry {
-            kube::api::entry::Entry::Occupied(_) => todo!(),
-            kube::api::entry::Entry::Vacant(_) => todo!(),
+        let rendered: R = templates::render(&*obj, &ctx).await;
+        let mut entry = match api.entry(&res_name).await? {
+            Entry::Occupied(e) => e.and_modify(|cur| {
+                let owned = cur.owner_references().iter().any(|r| {
+                    r.controller.unwrap_or(false) && r.uid == obj.uid().clone().unwrap_or_default()
+                });
+                if !owned {
+                    debug!(res_name, "adopting pre-existing object");
+                    if let Some(oref) = obj.controller_owner_ref(&()) {
+                        cur.meta_mut()
+                            .owner_references
+                            .get_or_insert_with(Vec::new)
+                            .push(oref);
+                    }
+                }
+                // Re-apply the desired fields so any out-of-band drift (an edited replica
+                // count, image, etc.) is corrected on every reconcile.
+                cur.merge_from(rendered.clone());
+            }),
+            Entry::Vacant(e) => {
+                debug!(res_name, "creating object");
+                e.insert(rendered)
+            }
+        };
+
+        match entry.commit(&pp).await {
+            Ok(()) => {
+                created = Some(entry.get().clone());
+                break;
+            }
+            Err(err) => match err {
+                CommitError::Validate(reason) => {
+                    debug!(reason = reason.to_string(), "commit conflict, retrying");
+                    continue;
+                }
+                CommitError::Save(_) => return Err(Error::Commit(err)),
+            },
         }
     }
 
+    if let Some(created) = created {
+        // Record the name we actually used so future reconciles read it back out of status
+        // instead of falling back to `guess_name`.
+        let owner_api: Api<K> = Api::namespaced(ctx.client.clone(), &ns);
+        let owner = owner_api.get_status(&object_name).await?;
+        let mut status = owner.status().cloned().unwrap_or_default();
+        status.add_ref(&created);
+        let patch = Patch::Apply(json!({
+            "apiVersion": K::api_version(&()),
+            "kind": K::kind(&()),
+            "status": status,
+        }));
+        owner_api
+            .patch_status(&object_name, &pp, &patch)
+            .await?;
+    }
+
     Ok(Action::requeue(Duration::from_secs(5 * 60)))
 }
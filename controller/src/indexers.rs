@@ -3,11 +3,14 @@
 //! ```mermaid
 //! ```
 
-use std::sync::{Arc, LazyLock};
+use std::{
+    hash::Hash,
+    sync::{Arc, LazyLock},
+};
 
 use k8s_openapi::merge_strategies;
 use kube::{
-    api::{Api, Patch},
+    api::{Api, DeleteParams, Patch},
     client::Client,
     core::GroupVersionKind,
     runtime::controller::Error as CtrlErr,
@@ -23,10 +26,70 @@ use tokio_stream::wrappers::SignalStream;
 use crate::{clair_condition, cmp_condition, merge_condition, prelude::*};
 use clair_templates::{
     render_dropin, Build, DeploymentBuilder, HorizontalPodAutoscalerBuilder, ServiceBuilder,
+    ServiceMonitorBuilder,
 };
-use v1alpha1::Indexer;
+use monitoring_coreos_com::v1::servicemonitors::ServiceMonitor;
+use v1alpha1::{Indexer, StatusCommon};
 
 //static COMPONENT: LazyLock<String> = LazyLock::new(|| Indexer::kind(&()).to_ascii_lowercase());
+
+/// Kind labels every metric this controller records, so `reconcile_duration_seconds` etc. can be
+/// broken out per-kind alongside the Clair, Matcher and Updater controllers' own `KIND`
+/// constants.
+const KIND: &str = "Indexer";
+
+/// ErrorClass categorizes an [`Error`] for [`handle_error`]'s retry policy, modeled on
+/// MeiliSearch's `Code` -> `ErrCode` mapping: the same failure always maps to the same requeue
+/// behavior, instead of it depending on which call site happened to return it.
+enum ErrorClass {
+    /// Transient errors (apiserver conflicts, connection resets, 5xxs) are expected to clear with
+    /// a retry; back off exponentially instead of hot-looping.
+    Transient,
+    /// Permanent errors (a disallowed name, a missing CRD) won't clear without a spec change;
+    /// retrying on a timer just wastes work, so wait for the next spec change instead.
+    Permanent,
+    /// RateLimited means the apiserver itself asked for backoff; requeue after the given hint
+    /// rather than computing our own delay.
+    RateLimited(Duration),
+}
+
+/// BACKOFF_BASE is the first retry delay for a Transient error.
+const BACKOFF_BASE: Duration = Duration::from_secs(5);
+/// BACKOFF_CAP bounds how far a string of consecutive Transient failures can push the retry
+/// delay out to.
+const BACKOFF_CAP: Duration = Duration::from_secs(10 * 60);
+/// RATE_LIMITED_RETRY is the requeue delay used for a RateLimited error, since the apiserver
+/// doesn't hand back a more specific retry-after hint through `kube::Error`.
+const RATE_LIMITED_RETRY: Duration = Duration::from_secs(30);
+
+/// Classify maps `err` to an [`ErrorClass`]: apiserver conflicts and 5xxs are Transient, a 429 is
+/// RateLimited, and anything that looks like a spec problem (a disallowed name) is Permanent.
+fn classify(err: &Error) -> ErrorClass {
+    match err {
+        Error::Kube(kube::Error::Api(resp)) => match resp.code {
+            409 | 500..=599 => ErrorClass::Transient,
+            429 => ErrorClass::RateLimited(RATE_LIMITED_RETRY),
+            _ => ErrorClass::Permanent,
+        },
+        Error::Kube(_) | Error::Commit(_) => ErrorClass::Transient,
+        Error::BadName(_) | Error::MissingName(_) => ErrorClass::Permanent,
+        _ => ErrorClass::Transient,
+    }
+}
+
+/// Backoff_delay computes a capped exponential backoff (`min(cap, base * 2^attempt)`), then
+/// samples the actual delay uniformly from `[0, that]` ("full jitter"), seeded by `key` and
+/// `attempt` so repeated calls for the same failure return a stable offset instead of a
+/// different one each time, and objects failing in lockstep don't all retry in lockstep too.
+fn backoff_delay(key: &str, attempt: u32) -> Duration {
+    let scale = 2f64.powi(attempt.min(16) as i32);
+    let capped = BACKOFF_BASE.mul_f64(scale).min(BACKOFF_CAP);
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    (key, attempt).hash(&mut hasher);
+    let frac = (hasher.finish() % 1000) as f64 / 1000.0;
+    capped.mul_f64(frac)
+}
+
 static SELF_GVK: LazyLock<GroupVersionKind> = LazyLock::new(|| GroupVersionKind {
     group: Indexer::group(&()).to_string(),
     version: Indexer::version(&()).to_string(),
@@ -59,6 +122,10 @@ pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<Contro
             .owns(
                 Api::<core::v1::Service>::all(client.clone()),
                 ctlcfg.clone(),
+            )
+            .owns(
+                Api::<batch::v1::Job>::all(client.clone()),
+                ctlcfg.clone(),
             );
         if ctx.gvk_exists(&crate::GATEWAY_NETWORKING_HTTPROUTE).await {
             ctl = ctl.owns(Api::<HTTPRoute>::all(client.clone()), ctlcfg.clone());
@@ -66,6 +133,9 @@ pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<Contro
         if ctx.gvk_exists(&crate::GATEWAY_NETWORKING_GRPCROUTE).await {
             ctl = ctl.owns(Api::<GRPCRoute>::all(client.clone()), ctlcfg.clone());
         }
+        if ctx.gvk_exists(&crate::MONITORING_SERVICEMONITOR).await {
+            ctl = ctl.owns(Api::<ServiceMonitor>::all(client.clone()), ctlcfg.clone());
+        }
         let ctl = ctl
             .reconcile_all_on(sig)
             .graceful_shutdown_on(cancel.cancelled_owned());
@@ -133,15 +203,61 @@ impl Reconciler {
 
     #[instrument(skip(self), ret)]
     async fn set_condition(&self, cnd: Condition) -> Result<()> {
+        let (type_, status) = (cnd.type_.clone(), cnd.status.clone());
         let mut next = self
             .api
             .get_status(&self.name())
             .instrument(debug_span!("get_status"))
             .await?;
         next.meta_mut().managed_fields = None;
-        let status = next.status.get_or_insert_default();
-        let cnds = status.conditions.get_or_insert_default();
+        let status_obj = next.status.get_or_insert_default();
+        let cnds = status_obj.conditions.get_or_insert_default();
         merge_strategies::list::map(cnds, vec![cnd], &[cmp_condition], merge_condition);
+        debug!(payload = ?next, "patching status");
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&next))
+            .instrument(debug_span!("patch_status"))
+            .await?;
+        crate::metrics::record_condition(KIND, &type_, &status);
+        Ok(())
+    }
+
+    #[instrument(skip(self), ret)]
+    async fn bound_images(&self) -> Result<()> {
+        if self.indexer.spec.bound_images.is_empty() {
+            return Ok(());
+        }
+
+        let mut next = self
+            .api
+            .get_status(&self.name())
+            .instrument(debug_span!("get_status"))
+            .await?;
+        next.meta_mut().managed_fields = None;
+        let status = next.status.get_or_insert_default();
+
+        for bound in &self.indexer.spec.bound_images {
+            match crate::registry::resolve_image(&self.ctx, &bound.image.to_string()).await {
+                Ok((resolved, _annotations)) => {
+                    let resolved: v1alpha1::ImageReference = resolved
+                        .parse()
+                        .expect("resolve_image returns a valid image reference");
+                    status.add_bound_image_ref(&bound.name, &resolved);
+                }
+                Err(error) => {
+                    error!(%error, bound = bound.name, "unable to resolve bound image");
+                    status.add_condition(Condition {
+                        message: format!("failed to resolve bound image {}: {error}", bound.name),
+                        observed_generation: self.indexer.metadata.generation,
+                        last_transition_time: meta::v1::Time(Utc::now()),
+                        reason: "BoundImageResolveFailed".into(),
+                        status: "False".into(),
+                        type_: clair_condition("BoundImagesResolved"),
+                    });
+                }
+            }
+        }
+
         debug!(payload = ?next, "patching status");
         self.api
             .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&next))
@@ -174,7 +290,8 @@ impl Reconciler {
             .await?;
 
         let status = v1alpha1::WorkerStatus {
-            dropin: render_dropin::<Indexer>(&srv),
+            dropin: render_dropin(self.indexer.as_ref(), &srv)
+                .and_then(|d| serde_json::to_string(&d).ok()),
             ..Default::default()
         };
         self.api
@@ -193,12 +310,50 @@ impl Reconciler {
         let api = Api::<Deployment>::namespaced(self.client(), self.ns());
         let status = self.indexer.status.clone().unwrap_or_default();
 
-        let d = DeploymentBuilder::try_from(self.indexer.as_ref())?.build();
+        let image = self
+            .indexer
+            .spec
+            .image
+            .as_ref()
+            .expect("DeploymentBuilder::try_from already checked spec.image is set")
+            .to_string();
+        let (image, annotations) = crate::registry::resolve_image(&self.ctx, &image).await?;
+        let resolved: v1alpha1::ImageReference = image
+            .parse()
+            .expect("resolve_image returns a valid image reference");
+        let builder = DeploymentBuilder::try_from(self.indexer.as_ref())?
+            .image(image)
+            .annotations(annotations);
+        let canary = builder.canary();
+        let d = builder.build();
         trace!(?d, "created Deployment");
         let _d = api
             .patch(&d.name_any(), &PATCH_PARAMS, &Patch::Apply(d))
             .instrument(debug_span!("patch", kind = "Deployment"))
             .await?;
+        crate::metrics::record_owned_patch(KIND, "Deployment");
+        if let Some(canary) = canary {
+            let cd = canary.build();
+            trace!(?cd, "created canary Deployment");
+            api.patch(&cd.name_any(), &PATCH_PARAMS, &Patch::Apply(cd))
+                .instrument(debug_span!(
+                    "patch",
+                    kind = "Deployment",
+                    variant = "canary"
+                ))
+                .await?;
+            crate::metrics::record_owned_patch(KIND, "Deployment");
+        }
+
+        let image_status = v1alpha1::IndexerStatus {
+            resolved_image: Some(resolved),
+            ..Default::default()
+        };
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&image_status))
+            .instrument(debug_span!("patch_status", field = "resolvedImage"))
+            .await
+            .inspect_err(|error| error!(%error, "unable to patch resolved image status"))?;
 
         let deployment_ref = status.refs.as_ref().and_then(|d| {
             d.iter().find(|&objref| {
@@ -232,11 +387,21 @@ impl Reconciler {
         let api = Api::<Service>::namespaced(self.client(), self.ns());
         let status = self.indexer.status.clone().unwrap_or_default();
 
-        let s = ServiceBuilder::try_from(self.indexer.as_ref())?.build();
+        let builder = ServiceBuilder::try_from(self.indexer.as_ref())?;
+        let canary = builder.canary();
+        let s = builder.build();
         let _s = api
             .patch(&s.name_any(), &PATCH_PARAMS, &Patch::Apply(s))
             .await
             .inspect_err(|error| error!(%error, "failed to patch Service"))?;
+        crate::metrics::record_owned_patch(KIND, "Service");
+        if let Some(canary) = canary {
+            let cs = canary.build();
+            api.patch(&cs.name_any(), &PATCH_PARAMS, &Patch::Apply(cs))
+                .await
+                .inspect_err(|error| error!(%error, "failed to patch canary Service"))?;
+            crate::metrics::record_owned_patch(KIND, "Service");
+        }
 
         let service_ref = status.refs.as_ref().and_then(|d| {
             d.iter().find(|&objref| {
@@ -263,6 +428,27 @@ impl Reconciler {
         Ok(())
     }
 
+    /// Service_monitor patches a `ServiceMonitor` selecting the Indexer's Service, so Prometheus
+    /// Operator scrapes the metrics port without any manual wiring.
+    ///
+    /// Only called once [`Context::gvk_exists`] confirms the `monitoring.coreos.com` CRD is
+    /// installed; skipped entirely otherwise, same as the Gateway API resources.
+    #[instrument(skip(self), ret)]
+    async fn service_monitor(&self) -> Result<()> {
+        if !self.ctx.gvk_exists(&crate::MONITORING_SERVICEMONITOR).await {
+            return Ok(());
+        }
+
+        let api = Api::<ServiceMonitor>::namespaced(self.client(), self.ns());
+        let sm = ServiceMonitorBuilder::try_from(self.indexer.as_ref())?.build();
+        api.patch(&sm.name_any(), &PATCH_PARAMS, &Patch::Apply(sm))
+            .await
+            .inspect_err(|error| error!(%error, "failed to patch ServiceMonitor"))?;
+        crate::metrics::record_owned_patch(KIND, "ServiceMonitor");
+
+        Ok(())
+    }
+
     #[instrument(skip(self), ret)]
     async fn horizontal_pod_autoscaler(&self) -> Result<()> {
         use self::autoscaling::v2::HorizontalPodAutoscaler;
@@ -275,6 +461,7 @@ impl Reconciler {
             .patch(&s.name_any(), &PATCH_PARAMS, &Patch::Apply(s))
             .await
             .inspect_err(|error| error!(%error, "failed to patch HorizontalPodAutoscaler"))?;
+        crate::metrics::record_owned_patch(KIND, "HPA");
 
         let service_ref = status.refs.as_ref().and_then(|d| {
             d.iter().find(|&objref| {
@@ -301,6 +488,138 @@ impl Reconciler {
         Ok(())
     }
 
+    /// Clear_failure_count resets `failureCount`/`lastFailureTime` once a reconcile succeeds, so
+    /// the next failure (if any) backs off from [`BACKOFF_BASE`] again instead of continuing to
+    /// escalate from wherever it left off.
+    #[instrument(skip(self), ret)]
+    async fn clear_failure_count(&self) -> Result<()> {
+        if self
+            .indexer
+            .status
+            .as_ref()
+            .is_none_or(|s| s.failure_count == 0)
+        {
+            return Ok(());
+        }
+        let status = json!({
+            "status": { "failureCount": 0, "lastFailureTime": Option::<meta::v1::Time>::None },
+        });
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Merge(&status))
+            .await?;
+        Ok(())
+    }
+
+    /// Migration gates the Deployment rollout on a one-shot Job that runs the new image's
+    /// migration mode first, so the Deployment never serves traffic against an un-migrated
+    /// backing store. It reuses the admin-upgrade Job machinery from [`crate::clairs`]
+    /// (`job_progress`/`launch_job`), scaled down to a single check/launch/poll/promote cycle ---
+    /// an Indexer only needs one migration pass per image, not a pre/post split.
+    ///
+    /// Returns `true` once `spec.image` is migrated and it's safe to call [`Reconciler::deployment`].
+    #[instrument(skip(self), ret)]
+    async fn migration(&self) -> Result<bool> {
+        use batch::v1::Job;
+
+        let type_ = clair_condition("MigrationComplete");
+        let api = Api::<Job>::namespaced(self.client(), self.ns());
+        let status = self.indexer.status.clone().unwrap_or_default();
+
+        if self.indexer.spec.image == status.migrated_image {
+            return Ok(true);
+        }
+
+        let running = status
+            .conditions
+            .iter()
+            .find(|&c| c.type_ == type_)
+            .is_some_and(|c| {
+                c.reason == "MigrationRunning" && c.observed_generation == self.indexer.metadata.generation
+            });
+
+        if !running {
+            debug!("spec.image changed, launching migration job");
+            let j = clair_templates::JobBuilder::migration(self.indexer.as_ref())?.build();
+            crate::clairs::launch_job(&api, j).await?;
+            self.set_condition(Condition {
+                message: "spec.image changed, launching migration job".into(),
+                observed_generation: self.indexer.metadata.generation,
+                last_transition_time: meta::v1::Time(Utc::now()),
+                reason: "MigrationRunning".into(),
+                status: "False".into(),
+                type_,
+            })
+            .await?;
+            return Ok(false);
+        }
+
+        let j = clair_templates::JobBuilder::migration(self.indexer.as_ref())?.build();
+        let progress = api
+            .get_opt(&j.name_any())
+            .instrument(debug_span!("get_opt", kind = "Job"))
+            .await?
+            .as_ref()
+            .map(crate::clairs::job_progress)
+            .unwrap_or(crate::clairs::JobProgress::Running);
+
+        match progress {
+            crate::clairs::JobProgress::Succeeded => {
+                debug!("migration job succeeded, promoting image");
+                let image_status = v1alpha1::IndexerStatus {
+                    migrated_image: self.indexer.spec.image.clone(),
+                    ..Default::default()
+                };
+                self.api
+                    .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&image_status))
+                    .instrument(debug_span!("patch_status", field = "migratedImage"))
+                    .await?;
+                self.set_condition(Condition {
+                    message: "migration job succeeded".into(),
+                    observed_generation: self.indexer.metadata.generation,
+                    last_transition_time: meta::v1::Time(Utc::now()),
+                    reason: "MigrationComplete".into(),
+                    status: "True".into(),
+                    type_,
+                })
+                .await?;
+                if let Err(error) = api.delete(&j.name_any(), &DeleteParams::default()).await {
+                    debug!(%error, "finished migration job already gone");
+                }
+                Ok(true)
+            }
+            crate::clairs::JobProgress::Retrying(failed, limit) => {
+                debug!(failed, limit, "migration job retrying");
+                self.set_condition(Condition {
+                    message: format!("migration job retrying ({failed}/{limit} failed attempts)"),
+                    observed_generation: self.indexer.metadata.generation,
+                    last_transition_time: meta::v1::Time(Utc::now()),
+                    reason: "MigrationRunning".into(),
+                    status: "False".into(),
+                    type_,
+                })
+                .await?;
+                Ok(false)
+            }
+            crate::clairs::JobProgress::Failed => {
+                error!("migration job exhausted its retries");
+                self.set_condition(Condition {
+                    message: "migration job failed (retries exhausted)".into(),
+                    observed_generation: self.indexer.metadata.generation,
+                    last_transition_time: meta::v1::Time(Utc::now()),
+                    reason: "MigrationFailed".into(),
+                    status: "False".into(),
+                    type_,
+                })
+                .await?;
+                Ok(false)
+            }
+            crate::clairs::JobProgress::Running => {
+                trace!("migration job still running");
+                Ok(false)
+            }
+        }
+    }
+
     #[instrument(skip(self), ret)]
     async fn check_spec(&self) -> Result<Option<Action>> {
         let mut cnd = Condition {
@@ -341,14 +660,36 @@ impl Reconciler {
 async fn reconcile(indexer: Arc<Indexer>, ctx: Arc<Context>) -> Result<Action> {
     assert!(indexer.meta().name.is_some());
     info!("reconciling Indexer");
+    let mut timer = crate::metrics::ReconcileTimer::start(KIND);
+
     let r = Reconciler::from((indexer.clone(), ctx.clone()));
+    let ret = reconcile_indexer(&r).await;
+
+    if ret.is_ok() {
+        if let Err(error) = r.clear_failure_count().await {
+            warn!(%error, "failed to clear failureCount after a successful reconcile");
+        }
+    }
+
+    timer.finish(&ret);
+    ret
+}
 
+/// Reconcile_indexer is [`reconcile`]'s body, split out so the duration/result metrics wrap every
+/// return path --- including the early returns out of [`Reconciler::check_spec`] --- instead of
+/// only the happy path that falls through to the end.
+async fn reconcile_indexer(r: &Reconciler) -> Result<Action> {
     if let Some(a) = r.check_spec().await? {
         return Ok(a);
     };
+    if !r.migration().await? {
+        return Ok(DEFAULT_REQUEUE.clone());
+    }
     r.deployment().await?;
     r.service().await?;
+    r.service_monitor().await?;
     r.horizontal_pod_autoscaler().await?;
+    r.bound_images().await?;
     r.publish_dropin().await?;
 
     Ok(DEFAULT_REQUEUE.clone())
@@ -422,7 +763,109 @@ async fn check_creation(
 }
 */
 
-#[instrument(skip_all)]
-fn handle_error(_obj: Arc<Indexer>, _err: &Error, _ctx: Arc<Context>) -> Action {
-    Action::await_change()
+/// Handle_error classifies `err` (see [`classify`]) and picks a requeue policy accordingly:
+/// Transient errors back off exponentially (tracked via `failureCount`/`lastFailureTime` on the
+/// status), Permanent errors wait for the next spec change and record why via a `Degraded`
+/// condition, and RateLimited errors requeue at the apiserver's hint.
+///
+/// The `kube::runtime` error policy callback isn't async, so the status patches themselves run on
+/// detached tasks rather than being awaited here; see [`patch_failure_count`]/[`patch_degraded`].
+#[instrument(skip(ctx))]
+fn handle_error(obj: Arc<Indexer>, err: &Error, ctx: Arc<Context>) -> Action {
+    let Some(ns) = obj.namespace() else {
+        return Action::await_change();
+    };
+    let name = obj.name_unchecked();
+
+    match classify(err) {
+        ErrorClass::RateLimited(retry) => Action::requeue(retry),
+        ErrorClass::Permanent => {
+            let cnd = Condition {
+                message: err.to_string(),
+                observed_generation: obj.metadata.generation,
+                last_transition_time: meta::v1::Time(Utc::now()),
+                reason: "ReconcileFailed".into(),
+                status: "True".into(),
+                type_: clair_condition("Degraded"),
+            };
+            tokio::spawn(patch_degraded(ctx.client.clone(), ns, name, cnd));
+            Action::await_change()
+        }
+        ErrorClass::Transient => {
+            let attempt = obj.status.as_ref().map_or(0, |s| s.failure_count) + 1;
+            let key = format!("{ns}/{name}");
+            tokio::spawn(patch_failure_count(ctx.client.clone(), ns, name, attempt));
+            Action::requeue(backoff_delay(&key, attempt - 1))
+        }
+    }
+}
+
+/// Patch_degraded patches a `Degraded=True` condition carrying `err`'s message onto `name`'s
+/// status, for [`handle_error`]'s Permanent case. Fire-and-forget (see [`handle_error`]'s doc
+/// comment): just logs if the patch itself fails.
+async fn patch_degraded(client: Client, ns: String, name: String, cnd: Condition) {
+    let api: Api<Indexer> = Api::namespaced(client, &ns);
+    let status = json!({ "status": { "conditions": [cnd] } });
+    if let Err(error) = api
+        .patch_status(&name, &PATCH_PARAMS, &Patch::Merge(&status))
+        .await
+    {
+        error!(%error, name, "failed to patch Degraded condition");
+    }
+}
+
+/// Patch_failure_count records `attempt` and the current time on `name`'s status, for
+/// [`handle_error`]'s Transient case; see [`patch_degraded`] for why this is fire-and-forget.
+async fn patch_failure_count(client: Client, ns: String, name: String, attempt: u32) {
+    let api: Api<Indexer> = Api::namespaced(client, &ns);
+    let status = json!({
+        "status": { "failureCount": attempt, "lastFailureTime": meta::v1::Time(Utc::now()) },
+    });
+    if let Err(error) = api
+        .patch_status(&name, &PATCH_PARAMS, &Patch::Merge(&status))
+        .await
+    {
+        error!(%error, name, "failed to patch failureCount");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock::ContextBuilder;
+
+    fn fixture() -> Indexer {
+        serde_json::from_value(json!({
+            "apiVersion": "projectclair.io/v1alpha1",
+            "kind": "Indexer",
+            "metadata": {"name": "test", "namespace": "default", "generation": 1},
+            "spec": {},
+        }))
+        .expect("fixture is a valid Indexer")
+    }
+
+    /// Check_spec, given a spec with no ConfigSource, should patch a "SpecOK: False" condition
+    /// onto the status and ask for a long requeue, rather than touch any owned resources.
+    #[tokio::test]
+    async fn check_spec_reports_missing_config_source() {
+        let indexer = Arc::new(fixture());
+        let status_path = "/apis/projectclair.io/v1alpha1/namespaces/default/indexers/test/status";
+        let body = serde_json::to_value(&*indexer).expect("fixture serializes");
+
+        let (ctx, verifier) = ContextBuilder::default()
+            .expect_get(status_path, &body)
+            .expect_patch(status_path, &body)
+            .build(crate::DEFAULT_IMAGE.as_str());
+
+        let r = Reconciler::from((indexer, ctx));
+        r.check_spec()
+            .await
+            .expect("check_spec should not error")
+            .expect("an incomplete spec should ask for a requeue");
+
+        drop(r);
+        verifier.await.expect("mock apiserver task panicked");
+    }
 }
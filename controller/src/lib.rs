@@ -4,14 +4,25 @@
 //! Controller implements common functionality for the controller binary and controller functions
 //! themselves.
 
-use std::{collections::HashMap, env, pin::Pin, sync::LazyLock};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env,
+    pin::Pin,
+    sync::{Arc, LazyLock, Mutex},
+};
 
-use chrono::Utc;
-use futures::Future;
+use chrono::{DateTime, Utc};
+use futures::{Future, FutureExt};
 use k8s_openapi::{api::core, apimachinery::pkg::apis::meta};
-use kube::{api::GroupVersionKind, runtime::events};
+use kube::{api::GroupVersionKind, discovery, runtime::events, Resource, ResourceExt};
+use rand::Rng;
 use regex::Regex;
-use tokio::sync::RwLock;
+use serde::Serialize;
+use tokio::{
+    sync::RwLock,
+    time::{self, Duration, Instant},
+};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, instrument, trace, warn};
 
 use api::v1alpha1;
@@ -47,18 +58,33 @@ pub(crate) mod prelude {
 
     pub use super::templates;
     pub use super::{make_volumes, new_templated};
-    pub use super::{Context, ControllerFuture, Error, Request, Result};
-    pub use super::{CONTROLLER_NAME, CREATE_PARAMS, DEFAULT_REQUEUE, PATCH_PARAMS};
+    pub use super::{Context, ControllerFuture, Error, ReconcileError, Request, Result, Severity};
+    pub use super::{
+        reconcile_span, with_poll_timer, CONTROLLER_NAME, CREATE_PARAMS, DEFAULT_REQUEUE,
+        PATCH_PARAMS,
+    };
+    pub use super::{WorkerState, WorkerStatus};
 }
 
+pub mod admin;
+pub mod capabilities;
 pub mod clairs;
+pub mod dropins;
 pub mod indexers;
-//pub mod matchers;
-//pub mod subresource;
+pub mod matchers;
+pub mod subresource;
 //mod worker;
 
+#[cfg(test)]
+pub(crate) mod mock;
+
+pub mod metrics;
+pub mod notify;
+pub mod registry;
+pub mod supervisor;
 pub mod templates;
 pub mod updaters;
+pub mod watcher;
 pub mod webhook;
 
 // NB The docs are unclear, but backtraces are unsupported on stable.
@@ -102,7 +128,10 @@ pub enum Error {
     Tokio(#[from] tokio::task::JoinError),
     /// TLS inidicates some TLS error.
     #[error("tls error: {0}")]
-    TLS(#[from] openssl::ssl::Error),
+    TLS(#[from] tokio_rustls::rustls::Error),
+    /// Hmac indicates signing an outbound webhook notification (see [`notify`]) failed.
+    #[error("hmac signing error: {0}")]
+    Hmac(#[from] openssl::error::ErrorStack),
     /// ...
     //#[error("webhook server error: {0}")]
     //Webhook(#[from] hyper::Error),
@@ -122,19 +151,251 @@ pub enum Error {
     /// Config means the Clair config validation process failed.
     #[error("clair config error: {0}")]
     Config(#[from] clair_config::Error),
+    /// WebhookBootstrap indicates the webhook TLS/registration bootstrap (Secret, Service,
+    /// webhook configurations, CRD conversion wiring) failed.
+    ///
+    /// Note the leading `::`: this crate also has a `webhook` module (above), so an unqualified
+    /// path here would resolve to that instead of the `webhook` crate.
+    #[error("webhook bootstrap error: {0}")]
+    WebhookBootstrap(#[from] ::webhook::bootstrap::Error),
+    /// Exec indicates a remote command run via [`Context::validate_config`] exited non-zero.
+    #[error("remote exec exited {code}: {message}")]
+    Exec {
+        /// Code is the remote process's exit code, if the apiserver reported one.
+        code: i32,
+        /// Message is whatever the remote process wrote to stderr, or else stdout.
+        message: String,
+    },
+    /// Http indicates a registry request failed.
+    #[error("http error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Registry indicates a registry responded, but not usefully, e.g. no digest reported.
+    #[error("registry error: {0}")]
+    Registry(String),
+}
+
+impl Error {
+    /// Variant_name returns this error's variant name, for labeling metrics (see
+    /// [`crate::metrics::ReconcileTimer`]) without stringifying the whole `Display` message --
+    /// a high-cardinality label would let an interpolated value (a name, a URL) blow up the
+    /// series count.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Self::TracingConfig(_) => "TracingConfig",
+            Self::Tracing(_) => "Tracing",
+            Self::Kube(_) => "Kube",
+            Self::KubeConfig(_) => "KubeConfig",
+            Self::Commit(_) => "Commit",
+            Self::Io(_) => "Io",
+            Self::JSON(_) => "JSON",
+            Self::YAML(_) => "YAML",
+            Self::JSONPatch(_) => "JSONPatch",
+            Self::AddrParse(_) => "AddrParse",
+            Self::Tokio(_) => "Tokio",
+            Self::TLS(_) => "TLS",
+            Self::Hmac(_) => "Hmac",
+            Self::MissingName(_) => "MissingName",
+            Self::BadName(_) => "BadName",
+            Self::Other(_) => "Other",
+            Self::Assets(_) => "Assets",
+            Self::Config(_) => "Config",
+            Self::WebhookBootstrap(_) => "WebhookBootstrap",
+            Self::Exec { .. } => "Exec",
+            Self::Http(_) => "Http",
+            Self::Registry(_) => "Registry",
+        }
+    }
 }
 
 /// Result typedef for controllers.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
+/// ReconcileError is a typed reconcile failure, modeled on the 16 error codes of the canonical 17
+/// gRPC status codes (omitting `OK`, which isn't a failure). Each variant's
+/// [`ReconcileError::code`] becomes the `reason` on the `status.conditions` entry
+/// [`ReconcileError::condition`] builds, so the same failure class always surfaces under the
+/// same, machine-parseable reason instead of ad hoc strings scattered across reconcilers.
+#[derive(thiserror::Error, Debug, Clone)]
+pub enum ReconcileError {
+    /// Cancelled means the operation was cancelled, typically by the caller.
+    #[error("cancelled: {0}")]
+    Cancelled(String),
+    /// Unknown means an error occurred that doesn't fit any other code.
+    #[error("unknown: {0}")]
+    Unknown(String),
+    /// InvalidArgument means the caller specified an invalid spec field.
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    /// DeadlineExceeded means a step didn't complete in the time allotted.
+    #[error("deadline exceeded: {0}")]
+    DeadlineExceeded(String),
+    /// NotFound means a referenced dependency (ConfigMap, Secret, owned object) doesn't exist.
+    #[error("not found: {0}")]
+    NotFound(String),
+    /// AlreadyExists means creation failed because the object is already present.
+    #[error("already exists: {0}")]
+    AlreadyExists(String),
+    /// PermissionDenied means the apiserver rejected the request as unauthorized.
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
+    /// ResourceExhausted means a quota or rate limit was hit.
+    #[error("resource exhausted: {0}")]
+    ResourceExhausted(String),
+    /// FailedPrecondition means the system isn't in a state this step can run in (e.g. a merged
+    /// config failed validation).
+    #[error("failed precondition: {0}")]
+    FailedPrecondition(String),
+    /// Aborted means the step was pre-empted by a concurrent modification, e.g. a resource
+    /// version conflict.
+    #[error("aborted: {0}")]
+    Aborted(String),
+    /// OutOfRange means an operation was attempted past a valid range.
+    #[error("out of range: {0}")]
+    OutOfRange(String),
+    /// Unimplemented means the step isn't supported, e.g. a `todo!()` controller.
+    #[error("unimplemented: {0}")]
+    Unimplemented(String),
+    /// Internal means an invariant this controller relies on was violated.
+    #[error("internal: {0}")]
+    Internal(String),
+    /// Unavailable means a dependency (the apiserver, the registry, the Go config validator) is
+    /// currently unreachable; retrying is expected to succeed.
+    #[error("unavailable: {0}")]
+    Unavailable(String),
+    /// DataLoss means unrecoverable data was lost or corrupted.
+    #[error("data loss: {0}")]
+    DataLoss(String),
+    /// Unauthenticated means the request lacks valid credentials.
+    #[error("unauthenticated: {0}")]
+    Unauthenticated(String),
+}
+
+impl ReconcileError {
+    /// Code returns the canonical, machine-parseable reason string for this error class.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Cancelled(_) => "Cancelled",
+            Self::Unknown(_) => "Unknown",
+            Self::InvalidArgument(_) => "InvalidArgument",
+            Self::DeadlineExceeded(_) => "DeadlineExceeded",
+            Self::NotFound(_) => "NotFound",
+            Self::AlreadyExists(_) => "AlreadyExists",
+            Self::PermissionDenied(_) => "PermissionDenied",
+            Self::ResourceExhausted(_) => "ResourceExhausted",
+            Self::FailedPrecondition(_) => "FailedPrecondition",
+            Self::Aborted(_) => "Aborted",
+            Self::OutOfRange(_) => "OutOfRange",
+            Self::Unimplemented(_) => "Unimplemented",
+            Self::Internal(_) => "Internal",
+            Self::Unavailable(_) => "Unavailable",
+            Self::DataLoss(_) => "DataLoss",
+            Self::Unauthenticated(_) => "Unauthenticated",
+        }
+    }
+
+    /// Severity classifies whether this failure is expected to clear on its own (no Warning
+    /// event) or is an actual regression that needs operator attention.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Cancelled(_)
+            | Self::Aborted(_)
+            | Self::DeadlineExceeded(_)
+            | Self::Unavailable(_)
+            | Self::NotFound(_)
+            | Self::AlreadyExists(_)
+            | Self::ResourceExhausted(_)
+            | Self::FailedPrecondition(_) => Severity::Pending,
+            Self::Unknown(_)
+            | Self::InvalidArgument(_)
+            | Self::PermissionDenied(_)
+            | Self::OutOfRange(_)
+            | Self::Unimplemented(_)
+            | Self::Internal(_)
+            | Self::DataLoss(_)
+            | Self::Unauthenticated(_) => Severity::Failing,
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Self::Cancelled(m)
+            | Self::Unknown(m)
+            | Self::InvalidArgument(m)
+            | Self::DeadlineExceeded(m)
+            | Self::NotFound(m)
+            | Self::AlreadyExists(m)
+            | Self::PermissionDenied(m)
+            | Self::ResourceExhausted(m)
+            | Self::FailedPrecondition(m)
+            | Self::Aborted(m)
+            | Self::OutOfRange(m)
+            | Self::Unimplemented(m)
+            | Self::Internal(m)
+            | Self::Unavailable(m)
+            | Self::DataLoss(m)
+            | Self::Unauthenticated(m) => m,
+        }
+    }
+
+    /// Condition builds the `status.conditions` entry for this error: `reason` is [`Self::code`]
+    /// and `status` is always `"False"`, since a [`ReconcileError`] only exists when a step
+    /// didn't succeed. [`Self::severity`], not `status`, is what decides whether
+    /// [`Context::record_reconcile_error`] raises a Warning event for it.
+    pub fn condition(&self, type_: String, observed_generation: Option<i64>) -> meta::v1::Condition {
+        meta::v1::Condition {
+            type_,
+            status: "False".into(),
+            reason: self.code().into(),
+            message: self.message().into(),
+            observed_generation,
+            last_transition_time: meta::v1::Time(Utc::now()),
+        }
+    }
+}
+
+/// Severity classifies how urgent a [`ReconcileError`] is: `Pending` failures are expected to
+/// clear on retry without operator action, `Failing` failures are not.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Pending: no Warning event is raised.
+    Pending,
+    /// Failing: a Warning event is raised.
+    Failing,
+}
+
 /// Context is common context for controllers.
 pub struct Context {
     /// Client is a k8s client. This should be only ever be `clone()`'d out of the Context.
     pub client: kube::Client,
     /// Image is the fallback container image to use.
     pub image: String,
+    /// Resolver looks up the content digest a `spec.image` tag currently points to.
+    pub resolver: Arc<dyn registry::DigestResolver>,
+    /// Notifier posts outbound webhook notifications for `status.conditions` transitions; see
+    /// [`Self::record_transition`].
+    pub notifier: notify::Notifier,
+    /// Slow_step_threshold is how long a single reconcile sub-step (as wrapped by
+    /// [`with_poll_timer`]) may run before it's logged as a `warn!`.
+    pub slow_step_threshold: Duration,
     /// ...
     kinds: RwLock<HashMap<GroupVersionKind, bool>>,
+    /// Diagnostics backs the `/diagnostics` introspection endpoint; see [`Context::record_transition`].
+    diagnostics: RwLock<Diagnostics>,
+    /// Admin_trigger carries `ObjectRef`s the [`admin`] server's `POST /reconcile/...` endpoint
+    /// wants reconciled immediately, instead of waiting for the next resync. A `broadcast` channel
+    /// (rather than `mpsc`) is used so [`clairs::controller`] can re-subscribe on every
+    /// [`supervisor::supervise`] restart, since a `Receiver`/`Stream` can only be drained once.
+    pub admin_trigger: tokio::sync::broadcast::Sender<kube::runtime::reflector::ObjectRef<v1alpha1::Clair>>,
+    /// Metrics is the handle onto the process-wide Prometheus recorder the reconcilers report
+    /// through (see [`metrics::record_reconcile_start`] and friends); kept here so a test can read
+    /// back what a reconcile just recorded via [`metrics::Metrics::counter_value`].
+    pub metrics: metrics::Metrics,
+    /// Backoff tracks consecutive reconcile failures per object, keyed by its GVK and
+    /// "namespace/name", for [`Self::backoff_action`]. A plain `std::sync::RwLock` (rather than
+    /// the `tokio` one used above) since `error_policy` callbacks are synchronous --- `kube`
+    /// doesn't give them an async context to await a `tokio::sync::RwLock` in --- and the lock is
+    /// only ever held for the handful of map operations below, never across an `.await`.
+    backoff: std::sync::RwLock<HashMap<(GroupVersionKind, String), (u32, Instant)>>,
 }
 
 impl std::fmt::Debug for Context {
@@ -153,16 +414,99 @@ impl Context {
         Self {
             client,
             image,
+            resolver: Arc::new(registry::RegistryResolver::default()),
+            notifier: notify::Notifier::default(),
+            slow_step_threshold: Duration::from_secs(5),
             kinds: RwLock::new(HashMap::new()),
+            diagnostics: RwLock::new(Diagnostics::default()),
+            admin_trigger: tokio::sync::broadcast::channel(16).0,
+            metrics: metrics::Metrics::handle(),
+            backoff: std::sync::RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// With_resolver swaps in a different [`DigestResolver`](registry::DigestResolver), e.g. a
+    /// fake one so tests don't make real registry calls.
+    pub fn with_resolver(mut self, resolver: Arc<dyn registry::DigestResolver>) -> Self {
+        self.resolver = resolver;
+        self
+    }
+
+    /// With_notifier swaps in a [`notify::Notifier`] configured with outbound webhook endpoints.
+    pub fn with_notifier(mut self, notifier: notify::Notifier) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// With_slow_step_threshold overrides the duration [`with_poll_timer`] treats as "slow".
+    pub fn with_slow_step_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_step_threshold = threshold;
+        self
+    }
+
+    /// Start_discovery spawns the background task that keeps [`Self::kinds`] fresh, so a CRD
+    /// installed after startup (e.g. a Gateway API kind) becomes visible to [`Self::gvk_exists`]
+    /// without restarting the controller. Every [`DISCOVERY_INTERVAL`] (plus an immediate first
+    /// tick) it re-runs API discovery and folds the result into the cache via
+    /// [`Self::refresh_discovery`].
+    pub fn start_discovery(self: Arc<Self>, cancel: CancellationToken) -> ControllerFuture {
+        async move {
+            let mut ticker = time::interval_at(Instant::now(), DISCOVERY_INTERVAL);
+            loop {
+                tokio::select! {
+                    () = cancel.cancelled() => return Ok(()),
+                    _ = ticker.tick() => {}
+                }
+                self.refresh_discovery().await;
+            }
+        }
+        .boxed()
+    }
+
+    /// Refresh_discovery re-runs API discovery for the groups [`Self::gvk_exists`] cares about and
+    /// folds the result into [`Self::kinds`].
+    ///
+    /// Only a negative-to-positive flip is ever applied here: once a kind is recorded present, a
+    /// later run can't un-cache it, so an in-flight reconcile never sees a kind disappear out from
+    /// under it mid-operation. If the discovery run itself fails (a transient API error), any
+    /// *negative* entries for the kinds watched here are dropped rather than left in place, so the
+    /// next [`Self::gvk_exists`] call retries via the oneshot lookup instead of being stuck behind
+    /// the failure until the next tick; positive entries are left alone.
+    async fn refresh_discovery(&self) {
+        match discovery::Discovery::new(self.client.clone())
+            .filter(&["gateway.networking.k8s.io", "networking.k8s.io", "batch"])
+            .run()
+            .await
+        {
+            Ok(discovery) => {
+                let mut kinds = self.kinds.write().await;
+                for gvk in watched_gvks() {
+                    if discovery.resolve_gvk(gvk).is_some() {
+                        kinds.insert(gvk.clone(), true);
+                    } else {
+                        kinds.entry(gvk.clone()).or_insert(false);
+                    }
+                }
+            }
+            Err(error) => {
+                warn!(%error, "periodic API discovery failed, will retry next tick");
+                let mut kinds = self.kinds.write().await;
+                for gvk in watched_gvks() {
+                    if kinds.get(gvk) == Some(&false) {
+                        kinds.remove(gvk);
+                    }
+                }
+            }
         }
     }
 
     /// Gvk_exists reports if the supplied GroupVersionKind is known to exist in this cluster.
     ///
-    /// This method may need to make requests to the API server.
-    /// This method assumes that a successful response never changes. If a resource is added or
-    /// removed from the cluster after this has returned, the process will need to be restarted to
-    /// see it.
+    /// This method may need to make requests to the API server. The positive/negative cache this
+    /// consults is periodically refreshed by [`Self::start_discovery`] for the kinds in
+    /// [`watched_gvks`]: one of those installed after startup becomes visible once that background
+    /// task's next tick resolves it, rather than requiring a restart. Any other kind's cached
+    /// answer is kept for the life of the process.
     pub async fn gvk_exists(&self, gvk: &GroupVersionKind) -> bool {
         use kube::discovery::oneshot;
         {
@@ -197,12 +541,333 @@ impl Context {
         }
         exists
     }
+
+    /// Backoff_action records a failed reconcile of `gvk`/`key` (`"namespace/name"`) and returns
+    /// an [`Action`] requeuing after `min(BACKOFF_CAP, BACKOFF_BASE * 2^n)`, where `n` is the
+    /// number of consecutive failures before this one, with full jitter --- the delay is sampled
+    /// uniformly from `[0, that]` --- so a batch of objects failing together (a dependency
+    /// outage) don't all retry in lockstep and re-hammer the API server the moment it recovers.
+    /// Call sites are `error_policy`/`handle_error` callbacks in place of a flat `DEFAULT_REQUEUE`;
+    /// pair with [`Self::backoff_reset`] on the first successful reconcile after a failure run.
+    pub fn backoff_action(&self, gvk: &GroupVersionKind, key: &str) -> kube::runtime::controller::Action {
+        let n = {
+            let mut backoff = self.backoff.write().expect("backoff lock poisoned");
+            let entry = backoff
+                .entry((gvk.clone(), key.to_string()))
+                .or_insert((0, Instant::now()));
+            entry.0 += 1;
+            entry.1 = Instant::now();
+            entry.0
+        };
+
+        let capped = BACKOFF_BASE
+            .mul_f64(2f64.powi((n - 1).min(16) as i32))
+            .min(*BACKOFF_CAP);
+        let jittered = capped.mul_f64(rand::thread_rng().gen_range(0.0..=1.0));
+        kube::runtime::controller::Action::requeue(jittered)
+    }
+
+    /// Backoff_reset clears `gvk`/`key`'s consecutive-failure count, so the next failure (if any)
+    /// starts back over at [`BACKOFF_BASE`] rather than wherever a previous, unrelated run of
+    /// failures left off.
+    pub fn backoff_reset(&self, gvk: &GroupVersionKind, key: &str) {
+        self.backoff
+            .write()
+            .expect("backoff lock poisoned")
+            .remove(&(gvk.clone(), key.to_string()));
+    }
+
+    /// Diagnostics returns a snapshot of the current [`Diagnostics`], for the `/diagnostics`
+    /// introspection endpoint.
+    pub async fn diagnostics(&self) -> Diagnostics {
+        self.diagnostics.read().await.clone()
+    }
+
+    /// Record_transition publishes a Kubernetes `Event` for a status-condition transition ---
+    /// `Normal` for a healthy (`"True"`) status, `Warning` otherwise --- and rolls it into
+    /// [`Diagnostics`] so `/diagnostics` reflects it without digging through `kubectl describe`.
+    ///
+    /// Callers are expected to only call this once a condition merge has actually changed a
+    /// `status` value; this does no such check itself. `previous` is the condition's `status`
+    /// before the merge, if it was already present, and is forwarded to [`notify::Notifier`]
+    /// alongside `cnd`'s new status.
+    pub async fn record_transition(
+        &self,
+        cnd: &meta::v1::Condition,
+        previous: Option<&str>,
+        reference: &core::v1::ObjectReference,
+    ) -> Result<()> {
+        let event_type = if cnd.status == "True" {
+            events::EventType::Normal
+        } else {
+            events::EventType::Warning
+        };
+        self.publish_transition(event_type, cnd, previous, reference)
+            .await
+    }
+
+    /// Record_reconcile_error is like [`Self::record_transition`], but for a condition built from
+    /// a [`ReconcileError`] (see [`ReconcileError::condition`]): it uses
+    /// [`ReconcileError::severity`], not the condition's bare `status`, to decide `Normal` vs.
+    /// `Warning`, since every such condition reads `status: "False"` but not every error class is
+    /// alarming enough to page an operator.
+    pub async fn record_reconcile_error(
+        &self,
+        err: &ReconcileError,
+        cnd: &meta::v1::Condition,
+        previous: Option<&str>,
+        reference: &core::v1::ObjectReference,
+    ) -> Result<()> {
+        let event_type = match err.severity() {
+            Severity::Pending => events::EventType::Normal,
+            Severity::Failing => events::EventType::Warning,
+        };
+        self.publish_transition(event_type, cnd, previous, reference)
+            .await
+    }
+
+    async fn publish_transition(
+        &self,
+        event_type: events::EventType,
+        cnd: &meta::v1::Condition,
+        previous: Option<&str>,
+        reference: &core::v1::ObjectReference,
+    ) -> Result<()> {
+        let recorder = {
+            let mut diag = self.diagnostics.write().await;
+            diag.record_transition(&cnd.type_);
+            diag.recorder(self.client.clone())
+        };
+        let ev = events::Event {
+            type_: event_type,
+            reason: if cnd.reason.is_empty() {
+                cnd.type_.clone()
+            } else {
+                cnd.reason.clone()
+            },
+            note: Some(cnd.message.clone()).filter(|m| !m.is_empty()),
+            action: cnd.type_.clone(),
+            secondary: None,
+        };
+        recorder.publish(&ev, reference).await?;
+        self.notifier
+            .notify(&notify::Transition {
+                object: reference.clone(),
+                type_: cnd.type_.clone(),
+                old_status: previous.map(String::from),
+                new_status: cnd.status.clone(),
+                reason: cnd.reason.clone(),
+                message: cnd.message.clone(),
+                timestamp: cnd.last_transition_time.clone(),
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Self_reference is an `ObjectReference` for the Pod this controller process is running in,
+    /// for events/conditions that describe the operator itself rather than a reconciled object.
+    fn self_reference(&self) -> core::v1::ObjectReference {
+        core::v1::ObjectReference {
+            kind: Some("Pod".to_string()),
+            namespace: Some(self.client.default_namespace().to_string()),
+            name: REPORTER.instance.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Record_worker_running marks `name` as healthy in [`Diagnostics`], e.g. right after
+    /// [`supervisor::supervise`] (re)starts its controller future.
+    pub async fn record_worker_running(&self, name: &str) {
+        let cnd = meta::v1::Condition {
+            type_: clair_condition("ControllerHealthy"),
+            status: "True".to_string(),
+            reason: "WorkerRunning".to_string(),
+            message: format!("worker {name:?} running"),
+            observed_generation: None,
+            last_transition_time: meta::v1::Time(Utc::now()),
+        };
+        let mut diag = self.diagnostics.write().await;
+        diag.workers.insert(
+            name.to_string(),
+            WorkerState {
+                status: WorkerStatus::Running,
+                restart_count: diag
+                    .workers
+                    .get(name)
+                    .map(|w| w.restart_count)
+                    .unwrap_or_default(),
+                last_error: None,
+                condition: cnd,
+            },
+        );
+    }
+
+    /// Record_worker_restart marks `name` as restarting in [`Diagnostics`] and publishes a
+    /// `Warning` event and a `ControllerHealthy` condition against [`Self::self_reference`], so
+    /// the operator's own degraded state is visible alongside the objects it reconciles.
+    pub async fn record_worker_restart(
+        &self,
+        name: &str,
+        restart_count: u32,
+        error: &str,
+    ) -> Result<()> {
+        let cnd = meta::v1::Condition {
+            type_: clair_condition("ControllerHealthy"),
+            status: "False".to_string(),
+            reason: "WorkerRestarted".to_string(),
+            message: format!("worker {name:?} restarted ({restart_count} so far): {error}"),
+            observed_generation: None,
+            last_transition_time: meta::v1::Time(Utc::now()),
+        };
+        let previous = {
+            let mut diag = self.diagnostics.write().await;
+            let previous = diag.workers.get(name).map(|w| w.condition.status.clone());
+            diag.workers.insert(
+                name.to_string(),
+                WorkerState {
+                    status: WorkerStatus::Restarting,
+                    restart_count,
+                    last_error: Some(error.to_string()),
+                    condition: cnd.clone(),
+                },
+            );
+            previous
+        };
+        let reference = self.self_reference();
+        self.publish_transition(events::EventType::Warning, &cnd, previous.as_deref(), &reference)
+            .await
+    }
+
+    /// Record_worker_failed marks `name` as unable to start at all in [`Diagnostics`] and
+    /// publishes a `Warning` event, for when [`supervisor::supervise`]'s constructor closure
+    /// itself errors, which no amount of restarting will fix.
+    pub async fn record_worker_failed(&self, name: &str, error: &str) -> Result<()> {
+        let cnd = meta::v1::Condition {
+            type_: clair_condition("ControllerHealthy"),
+            status: "False".to_string(),
+            reason: "WorkerFailed".to_string(),
+            message: format!("worker {name:?} failed to start: {error}"),
+            observed_generation: None,
+            last_transition_time: meta::v1::Time(Utc::now()),
+        };
+        let previous = {
+            let mut diag = self.diagnostics.write().await;
+            let previous = diag.workers.get(name).map(|w| w.condition.status.clone());
+            diag.workers.insert(
+                name.to_string(),
+                WorkerState {
+                    status: WorkerStatus::Failed,
+                    restart_count: diag
+                        .workers
+                        .get(name)
+                        .map(|w| w.restart_count)
+                        .unwrap_or_default(),
+                    last_error: Some(error.to_string()),
+                    condition: cnd.clone(),
+                },
+            );
+            previous
+        };
+        let reference = self.self_reference();
+        self.publish_transition(events::EventType::Warning, &cnd, previous.as_deref(), &reference)
+            .await
+    }
+
+    /// Validate_config attaches to `pod` and runs the Go config wrapper against the config file
+    /// at `config_path` inside the running container, returning what it wrote to stdout.
+    ///
+    /// This exercises the real parser baked into the Clair image (rather than re-implementing
+    /// validation here), so a reconcile can surface parse errors as a `status` condition before
+    /// the Pod ever crash-loops on a bad config.
+    #[instrument(skip(self, pod), fields(pod = pod.name_any()))]
+    pub async fn validate_config<S: AsRef<str>>(
+        &self,
+        pod: &core::v1::Pod,
+        config_path: S,
+    ) -> Result<String> {
+        use futures::TryStreamExt;
+        use kube::{api::AttachParams, Api};
+        use tokio_util::codec::{BytesCodec, FramedRead};
+
+        let ns = pod
+            .namespace()
+            .ok_or(Error::MissingName("pod has no namespace"))?;
+        let container = pod
+            .spec
+            .as_ref()
+            .and_then(|s| s.containers.first())
+            .map(|c| c.name.clone())
+            .ok_or(Error::MissingName("pod has no containers"))?;
+        let api: Api<core::v1::Pod> = Api::namespaced(self.client.clone(), &ns);
+
+        let ap = AttachParams::default()
+            .container(container)
+            .stdin(false)
+            .stdout(true)
+            .stderr(true);
+        let mut proc = api
+            .exec(
+                &pod.name_any(),
+                [
+                    "go",
+                    "run",
+                    "./cmd/config",
+                    "validate",
+                    "-f",
+                    config_path.as_ref(),
+                ],
+                &ap,
+            )
+            .await?;
+
+        let mut stdout = String::new();
+        if let Some(out) = proc.stdout() {
+            let mut frames = FramedRead::new(out, BytesCodec::new());
+            while let Some(chunk) = frames.try_next().await? {
+                stdout.push_str(&String::from_utf8_lossy(&chunk));
+            }
+        }
+        let mut stderr = String::new();
+        if let Some(err) = proc.stderr() {
+            let mut frames = FramedRead::new(err, BytesCodec::new());
+            while let Some(chunk) = frames.try_next().await? {
+                stderr.push_str(&String::from_utf8_lossy(&chunk));
+            }
+        }
+
+        let status = match proc.take_status() {
+            Some(fut) => fut.await,
+            None => None,
+        };
+        proc.join().await.map_err(|err| Error::Other(err.into()))?;
+
+        match status {
+            Some(status) if status.status.as_deref() == Some("Success") => Ok(stdout),
+            Some(status) => {
+                let code = status
+                    .details
+                    .as_ref()
+                    .and_then(|d| d.causes.first())
+                    .and_then(|c| c.message.as_ref())
+                    .and_then(|m| m.parse().ok())
+                    .unwrap_or(-1);
+                Err(Error::Exec {
+                    code,
+                    message: if stderr.is_empty() { stdout } else { stderr },
+                })
+            }
+            None => Ok(stdout),
+        }
+    }
 }
 
 /// Request is common per-request data for controllers.
 pub struct Request {
     now: meta::v1::Time,
     recorder: events::Recorder,
+    /// Requeue carries the soonest [`Self::request_requeue`] call made so far this request, if
+    /// any. A plain `Mutex` (rather than a `RwLock`) since writes and reads are both infrequent.
+    requeue: Mutex<Option<Duration>>,
 }
 
 impl Request {
@@ -211,12 +876,26 @@ impl Request {
         Request {
             now: meta::v1::Time(Utc::now()),
             recorder: events::Recorder::new(c.clone(), REPORTER.clone()),
+            requeue: Mutex::new(None),
         }
     }
     /// Now reports the "now" of this request.
     pub fn now(&self) -> meta::v1::Time {
         self.now.clone()
     }
+    /// Request_requeue asks the controller to come back to this object after `after`, instead of
+    /// reporting the current step as succeeded or failed --- e.g. a
+    /// [`subresource::HookResult::Requeue`] hook waiting on a dependency that isn't ready yet. If
+    /// more than one step asks during the same reconcile, the soonest request wins.
+    pub fn request_requeue(&self, after: Duration) {
+        let mut requeue = self.requeue.lock().expect("poisoned");
+        *requeue = Some(requeue.map_or(after, |existing| existing.min(after)));
+    }
+    /// Requested_requeue returns the soonest [`Self::request_requeue`] call made so far this
+    /// request, if any.
+    pub fn requested_requeue(&self) -> Option<Duration> {
+        *self.requeue.lock().expect("poisoned")
+    }
     /// Publish publishes a kubernetes Event.
     pub async fn publish(
         &self,
@@ -230,6 +909,75 @@ impl Request {
 /// ControllerFuture is the type the controller constructors should return.
 pub type ControllerFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
 
+/// Diagnostics is a point-in-time snapshot of controller activity, exposed read-only at
+/// `/diagnostics` so operators can check reconciler liveness without digging through `kubectl
+/// describe`.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diagnostics {
+    /// Last_event is when the most recent condition transition was recorded.
+    pub last_event: DateTime<Utc>,
+    /// Reporter identifies this controller instance to the Kubernetes events API.
+    #[serde(skip)]
+    pub reporter: events::Reporter,
+    /// Recent_transitions counts, per condition `type_`, how many transitions have been recorded
+    /// since this process started.
+    pub recent_transitions: BTreeMap<String, u64>,
+    /// Workers reports the live state of each [`supervisor`]-supervised controller task, keyed by
+    /// the name it was started with (e.g. `"clair"`).
+    pub workers: BTreeMap<String, WorkerState>,
+}
+
+impl Default for Diagnostics {
+    fn default() -> Self {
+        Self {
+            last_event: Utc::now(),
+            reporter: REPORTER.clone(),
+            recent_transitions: BTreeMap::new(),
+            workers: BTreeMap::new(),
+        }
+    }
+}
+
+/// WorkerStatus is the lifecycle state of a [`supervisor`]-supervised controller task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum WorkerStatus {
+    /// Running means the worker's controller future is currently polling normally.
+    Running,
+    /// Restarting means the worker's controller future ended (panic or early return) and the
+    /// supervisor is backing off before trying again.
+    Restarting,
+    /// Failed means the supervisor could not even start the worker (its constructor returned an
+    /// error), so no restart will be attempted.
+    Failed,
+}
+
+/// WorkerState is a point-in-time snapshot of one supervised worker, exposed through
+/// [`Diagnostics::workers`].
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerState {
+    /// Status is the worker's current lifecycle state.
+    pub status: WorkerStatus,
+    /// Restart_count is how many times this worker has been restarted since the process started.
+    pub restart_count: u32,
+    /// Last_error is the error (or panic message) that caused the most recent restart, if any.
+    pub last_error: Option<String>,
+    /// Condition mirrors the `ControllerHealthy` condition most recently recorded for this
+    /// worker.
+    pub condition: meta::v1::Condition,
+}
+
+impl Diagnostics {
+    fn recorder(&self, client: kube::Client) -> events::Recorder {
+        events::Recorder::new(client, self.reporter.clone())
+    }
+
+    fn record_transition(&mut self, type_: &str) {
+        *self.recent_transitions.entry(type_.to_string()).or_default() += 1;
+        self.last_event = Utc::now();
+    }
+}
+
 static REPORTER: LazyLock<events::Reporter> = LazyLock::new(|| events::Reporter {
     controller: CONTROLLER_NAME.to_string(),
     instance: Some(
@@ -265,6 +1013,48 @@ fn keyify<S: ToString, K: AsRef<str>>(space: S, key: K) -> String {
     out
 }
 
+/// With_poll_timer awaits `fut`, recording its wall-clock duration against the
+/// `reconcile_phase_seconds` metric (labeled by `phase`) and logging a `warn!` if it ran longer
+/// than `threshold`. Wrap a reconcile sub-step with this (alongside its own `#[instrument]` span)
+/// to catch a slow/blocking `get_status` or `patch` against the API server instead of an opaque
+/// hang.
+pub async fn with_poll_timer<F: Future>(
+    phase: &'static str,
+    threshold: Duration,
+    fut: F,
+) -> F::Output {
+    let start = Instant::now();
+    let out = fut.await;
+    let elapsed = start.elapsed();
+    ::metrics::histogram!("reconcile_phase_seconds", "phase" => phase).record(elapsed.as_secs_f64());
+    if elapsed > threshold {
+        warn!(phase, ?elapsed, ?threshold, "reconcile phase took longer than the slow-step threshold");
+    }
+    out
+}
+
+static NEXT_RECONCILE_GROUP: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Reconcile_span opens a tracing span for one reconcile pass, carrying a monotonically
+/// increasing correlation ID (`group`) plus the object's namespace/name/generation, so every
+/// event logged while reconciling a single object --- including `merge_condition` transitions and
+/// the [`Diagnostics`] recorder's emissions --- can be traced end to end, e.g. filtered on
+/// `group` in `tokio-console` or a structured log query.
+pub fn reconcile_span<K>(obj: &K) -> tracing::Span
+where
+    K: Resource<DynamicType = ()>,
+{
+    let group = NEXT_RECONCILE_GROUP.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    tracing::info_span!(
+        "reconcile",
+        group,
+        kind = K::kind(&()).as_ref(),
+        namespace = obj.namespace(),
+        name = obj.name_any(),
+        generation = obj.meta().generation,
+    )
+}
+
 /// Clair_condition returns the provided argument as a name in the clair-controller's space,
 /// sutable for use as a condition type.
 pub fn clair_condition<S: AsRef<str>>(s: S) -> String {
@@ -300,12 +1090,12 @@ pub fn image_version(img: &str) -> Option<&str> {
 
 /// New_templated returns a `K` with patches for `S` applied and the owner set to `obj`.
 #[instrument(skip_all)]
-pub async fn new_templated<S, K>(obj: &S, _ctx: &Context) -> Result<K>
+pub async fn new_templated<S, K>(obj: &S, ctx: &Context) -> Result<K>
 where
-    S: kube::Resource<DynamicType = ()>,
+    S: kube::Resource<DynamicType = ()> + v1alpha1::CrdCommon,
     K: kube::Resource<DynamicType = ()> + serde::de::DeserializeOwned,
 {
-    Ok(templates::render(obj))
+    Ok(templates::render(obj, ctx).await)
     /*
     use kube::ResourceExt;
     let oref = obj
@@ -480,6 +1270,28 @@ pub static DEFAULT_REQUEUE: LazyLock<kube::runtime::controller::Action> = LazyLo
     kube::runtime::controller::Action::requeue(tokio::time::Duration::from_secs(60 * 60))
 });
 
+/// BACKOFF_BASE is the first retry delay [`Context::backoff_action`] computes for a failing
+/// object, before jitter; overridable via `CLAIR_BACKOFF_BASE_SECS` for tests and unusually
+/// twitchy clusters.
+static BACKOFF_BASE: LazyLock<Duration> = LazyLock::new(|| {
+    env::var("CLAIR_BACKOFF_BASE_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(5))
+});
+
+/// BACKOFF_CAP bounds how far [`Context::backoff_action`] can push a failing object's retry
+/// delay out to, no matter how long its failure streak runs; overridable via
+/// `CLAIR_BACKOFF_CAP_SECS`.
+static BACKOFF_CAP: LazyLock<Duration> = LazyLock::new(|| {
+    env::var("CLAIR_BACKOFF_CAP_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(Duration::from_secs(60 * 60))
+});
+
 /// CONTROLLER_NAME is the name the controller uses whenever it needs a human-readable name.
 pub const CONTROLLER_NAME: &str = "clair-controller";
 
@@ -492,51 +1304,22 @@ pub static GATEWAY_NETWORKING_HTTPROUTE: LazyLock<GroupVersionKind> =
 /// GVK for `gateway.networking.k8s.io/v1/GRPCRoute`.
 pub static GATEWAY_NETWORKING_GRPCROUTE: LazyLock<GroupVersionKind> =
     LazyLock::new(|| GroupVersionKind::gvk("gateway.networking.k8s.io", "v1", "GRPCRoute"));
+/// GVK for `monitoring.coreos.com/v1/ServiceMonitor`.
+pub static MONITORING_SERVICEMONITOR: LazyLock<GroupVersionKind> =
+    LazyLock::new(|| GroupVersionKind::gvk("monitoring.coreos.com", "v1", "ServiceMonitor"));
 
-/*
-use futures::future;
-use kube::discovery;
-use tokio::time::{self, Duration, Instant, Interval};
+/// DISCOVERY_INTERVAL is how often [`Context::start_discovery`] re-runs API discovery.
+static DISCOVERY_INTERVAL: Duration = Duration::from_secs(60 * 60 * 2);
 
-pub struct Discovery {
-    client: Client,
-    d: discovery::Discovery,
-    t: Interval,
+/// Watched_gvks lists the [`GroupVersionKind`]s [`Context::refresh_discovery`] resolves on every
+/// tick; kept separate from [`Context::gvk_exists`]'s general-purpose cache entries (which may
+/// hold arbitrary GVKs populated via the oneshot fallback) since only these are ever backed by the
+/// periodic background refresh.
+fn watched_gvks() -> [&'static GroupVersionKind; 3] {
+    [
+        &GATEWAY_NETWORKING_GATEWAY,
+        &GATEWAY_NETWORKING_HTTPROUTE,
+        &GATEWAY_NETWORKING_GRPCROUTE,
+    ]
 }
 
-impl Discovery {
-    pub fn new(client: Client) -> Discovery {
-        let t = time::interval_at(Instant::now(), Duration::from_secs(60 * 60 * 2));
-        Discovery { client, d, t }
-    }
-
-    async fn client(&mut self) -> Result<(), kube::Error> {
-        tokio::select! {
-                    _ = self.t.tick() => {
-                        let d = discovery::Discovery::new(self.client.clone()).filter(&[
-                    "networking.k8s.io",
-                    "batch",
-                    "gateway.networking.k8s.io",
-                ])
-        .run().await?;
-                self.d = d;
-                    }
-                    _= future::ready(()) => {}
-                }
-        Ok(())
-    }
-
-    pub fn get(&self, group: &str) -> Option<&discovery::ApiGroup> {
-        None
-    }
-    pub fn has_group(&self, group: &str) -> bool {
-        false
-    }
-    pub fn resolve_gvk(
-        &self,
-        gvk: &GroupVersionKind,
-    ) -> Option<(discovery::ApiResource, discovery::ApiCapabilities)> {
-        unimplemented!()
-    }
-}
-*/
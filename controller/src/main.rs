@@ -7,14 +7,33 @@ use std::{
 use futures::prelude::*;
 use is_terminal::IsTerminal;
 use tokio::net::TcpListener;
-use tokio_native_tls::{native_tls, TlsAcceptor};
 use tokio_stream::wrappers::TcpListenerStream;
-use tokio_util::sync::CancellationToken;
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
 use tracing::{error, info, warn};
 
 use controller::*;
 
+mod tls;
+
+/// DEFAULT_ACME_DIRECTORY is the CA directory `--acme` orders against when `--acme-directory-url`
+/// isn't given; Let's Encrypt's production endpoint, since that's what most operators reaching
+/// for `--acme` actually want. Point it at a staging directory (or a step-ca instance) for
+/// testing, to avoid burning through Let's Encrypt's production rate limits.
+const DEFAULT_ACME_DIRECTORY: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+/// Dhat-heap's global allocator records every allocation made for the lifetime of the process, so
+/// it has to be installed before anything else runs. Opt-in via Cargo feature only --- no runtime
+/// flag --- since it adds per-allocation overhead no production build wants paying for.
+#[cfg(feature = "dhat-heap")]
+#[global_allocator]
+static ALLOC: dhat::Alloc = dhat::Alloc;
+
 fn main() {
+    // Held for the lifetime of main(): its Drop impl writes out the heap profile, so it has to
+    // outlive startup() returning rather than being dropped inside it.
+    #[cfg(feature = "dhat-heap")]
+    let _profiler = dhat::Profiler::new_heap();
+
     use clap::{
         crate_authors, crate_description, crate_name, crate_version, Arg, ArgAction, Command,
         ValueHint,
@@ -30,6 +49,16 @@ fn main() {
                 .long("introspection-bind-address")
                 .help("address to bind for the HTTP introspection server")
                 .default_value("[::]:8089"),
+            Arg::new("admin_address")
+                .long("admin-bind-address")
+                .help("address to bind for the HTTP admin server")
+                .long_help(concat!(
+                    "Address to bind for the HTTP admin server.\n",
+                    "Exposes `POST /reconcile/:namespace/:name` to trigger an immediate ",
+                    "reconcile of a single Clair, and `GET /status/:namespace/:name` to read ",
+                    "back its recorded conditions."
+                ))
+                .default_value("[::]:8090"),
             Arg::new("image")
                 .long("image-clair")
                 .env("RELATED_IMAGE_CLAIR")
@@ -66,6 +95,48 @@ fn main() {
                 .long("key-name")
                 .help("file inside `cert-dir` containing the TLS certificate key")
                 .default_value("tls.key"),
+            Arg::new("acme")
+                .long("acme")
+                .help("provision the webhook's serving certificate via ACME instead of `cert-dir`")
+                .long_help(concat!(
+                    "Provision the webhook's serving certificate via ACME instead of reading ",
+                    "`cert-dir`. Requires this binary to be built with the `acme` feature, and ",
+                    "at least one `--acme-dns-name`."
+                ))
+                .action(ArgAction::SetTrue),
+            Arg::new("acme_directory_url")
+                .long("acme-directory-url")
+                .help("ACME directory URL to order certificates from")
+                .default_value(DEFAULT_ACME_DIRECTORY),
+            Arg::new("acme_dns_name")
+                .long("acme-dns-name")
+                .help("DNS name to request the ACME certificate for; may be given multiple times")
+                .action(ArgAction::Append),
+            Arg::new("acme_renewal_window_days")
+                .long("acme-renewal-window-days")
+                .help("renew the ACME certificate once it's within this many days of expiring")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("30"),
+            Arg::new("acme_secret_name")
+                .long("acme-secret-name")
+                .help("Secret the issued ACME certificate is persisted to, for reuse across restarts")
+                .default_value("clair-operator-webhook-acme-tls"),
+            Arg::new("cert_host")
+                .long("cert-host")
+                .help("additional `<host>=<dir>` cert/key mapping, resolved by SNI; may be given multiple times")
+                .long_help(concat!(
+                    "Register an additional hostname to serve over the webhook listener, resolved ",
+                    "by the TLS handshake's SNI server name. `<dir>` is a directory holding a ",
+                    "`cert-name`/`key-name` pair the same way `cert-dir` does. Connections with no ",
+                    "SNI, or SNI matching none of these hosts, fall back to the `cert-dir` pair."
+                ))
+                .value_name("host>=<dir")
+                .action(ArgAction::Append),
+            Arg::new("shutdown_timeout_secs")
+                .long("shutdown-timeout-secs")
+                .help("seconds to wait for in-flight webhook requests and controller reconciles to drain on shutdown")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("30"),
             Arg::new("controllers")
                 .action(ArgAction::Append)
                 .default_values(["clair", "indexer", "matcher"]),
@@ -85,17 +156,40 @@ fn main() {
 
 struct Args {
     _leader_elect: bool,
+    acme: AcmeArgs,
+    admin_address: std::net::SocketAddr,
     cert_dir: PathBuf,
+    cert_hosts: Vec<(String, PathBuf)>,
     cert_name: String,
     controllers: Vec<String>,
     image: String,
     introspection_address: std::net::SocketAddr,
     key_name: String,
+    shutdown_timeout: std::time::Duration,
     webhook_address: std::net::SocketAddr,
 }
 
+/// AcmeArgs groups the `--acme*` flags, split out of [`Args`] since [`webhooks`] only cares about
+/// this subset.
+#[derive(Clone)]
+struct AcmeArgs {
+    enabled: bool,
+    directory_url: String,
+    dns_names: Vec<String>,
+    renewal_window_days: u64,
+    secret_name: String,
+}
+
+/// Parse_host_cert parses one `--cert-host` value of the form `<host>=<dir>`.
+fn parse_host_cert(s: &str) -> std::result::Result<(String, PathBuf), Error> {
+    let (host, dir) = s.split_once('=').ok_or_else(|| {
+        Error::BadName(format!("`--cert-host` value {s:?} is not of the form <host>=<dir>"))
+    })?;
+    Ok((host.to_string(), dir.into()))
+}
+
 impl TryFrom<&clap::ArgMatches> for Args {
-    type Error = std::net::AddrParseError;
+    type Error = Error;
 
     fn try_from(m: &clap::ArgMatches) -> std::result::Result<Self, Self::Error> {
         Ok(Self {
@@ -105,6 +199,7 @@ impl TryFrom<&clap::ArgMatches> for Args {
                 .get_one::<String>("introspection_address")
                 .unwrap()
                 .parse()?,
+            admin_address: m.get_one::<String>("admin_address").unwrap().parse()?,
             _leader_elect: m.get_flag("leader_elect"),
             controllers: m
                 .get_many::<String>("controllers")
@@ -112,18 +207,33 @@ impl TryFrom<&clap::ArgMatches> for Args {
                 .map(Clone::clone)
                 .collect(),
             cert_dir: m.get_one::<String>("cert_dir").unwrap().into(),
+            cert_hosts: m
+                .get_many::<String>("cert_host")
+                .map(|i| i.map(|s| parse_host_cert(s)).collect::<std::result::Result<_, _>>())
+                .transpose()?
+                .unwrap_or_default(),
             cert_name: m.get_one::<String>("cert_name").unwrap().into(),
             key_name: m.get_one::<String>("key_name").unwrap().into(),
+            shutdown_timeout: std::time::Duration::from_secs(
+                *m.get_one::<u64>("shutdown_timeout_secs").unwrap(),
+            ),
+            acme: AcmeArgs {
+                enabled: m.get_flag("acme"),
+                directory_url: m.get_one::<String>("acme_directory_url").unwrap().clone(),
+                dns_names: m
+                    .get_many::<String>("acme_dns_name")
+                    .map(|i| i.cloned().collect())
+                    .unwrap_or_default(),
+                renewal_window_days: *m.get_one::<u64>("acme_renewal_window_days").unwrap(),
+                secret_name: m.get_one::<String>("acme_secret_name").unwrap().clone(),
+            },
         })
     }
 }
 
 impl Args {
     fn context(&self, client: kube::Client) -> Arc<Context> {
-        Arc::new(Context {
-            client,
-            image: self.image.clone(),
-        })
+        Arc::new(Context::new(client, &self.image))
     }
 }
 
@@ -133,8 +243,25 @@ fn startup(args: Args) -> controller::Result<()> {
     use tracing_subscriber::{filter::EnvFilter, prelude::*};
 
     let env_filter = EnvFilter::try_from_default_env().or_else(|_| EnvFilter::try_new("info"))?;
+    // The tokio-console layer is opt-in twice over: the binary must be built with the
+    // "tokio-console" feature *and* TOKIO_CONSOLE set at runtime, so normal production builds
+    // pay zero cost for it.
+    let console_layer = if std::env::var_os("TOKIO_CONSOLE").is_some() {
+        #[cfg(feature = "tokio-console")]
+        {
+            Some(console_subscriber::spawn())
+        }
+        #[cfg(not(feature = "tokio-console"))]
+        {
+            warn!("TOKIO_CONSOLE set, but this binary was not built with the tokio-console feature");
+            None
+        }
+    } else {
+        None
+    };
     let collector = tracing_subscriber::Registry::default()
         .with(env_filter)
+        .with(console_layer)
         .with(if std::io::stdout().is_terminal() {
             Some(tracing_subscriber::fmt::layer())
         } else {
@@ -155,41 +282,99 @@ fn startup(args: Args) -> controller::Result<()> {
             error!("error setting up prometheus endpoint: {e}");
         }
     });
+
+    let client = rt.block_on(async {
+        let config = kube::Config::infer().await?;
+        controller::Result::Ok(kube::client::ClientBuilder::try_from(config)?.build())
+    })?;
+    let ctx = args.context(client);
+    let challenges = tls::ChallengeState::new();
+
+    let extra_hosts = args
+        .cert_hosts
+        .iter()
+        .map(|(host, dir)| tls::HostCert {
+            host: host.clone(),
+            certfile: dir.join(&args.cert_name),
+            keyfile: dir.join(&args.key_name),
+        })
+        .collect();
+
+    // Tasks tracks the webhook and admin servers, so startup() can wait (up to
+    // `shutdown_timeout`) for their own graceful shutdowns to finish instead of returning --- and
+    // the process exiting --- the instant the controller JoinSet in run() drains.
+    let tasks = TaskTracker::new();
+    let shutdown_timeout = args.shutdown_timeout;
+
     let ctlstop = token.clone();
-    rt.handle().spawn(webhooks(
-        args.webhook_address,
-        args.cert_dir.join(&args.cert_name),
-        args.cert_dir.join(&args.key_name),
-        token.clone(),
-    ));
+    tasks.spawn_on(
+        webhooks(
+            args.webhook_address,
+            args.cert_dir.join(&args.cert_name),
+            args.cert_dir.join(&args.key_name),
+            extra_hosts,
+            args.acme.clone(),
+            challenges.clone(),
+            token.clone(),
+            ctx.clone(),
+        ),
+        rt.handle(),
+    );
+    tasks.spawn_on(
+        admin(
+            args.admin_address,
+            challenges.clone(),
+            token.clone(),
+            ctx.clone(),
+        ),
+        rt.handle(),
+    );
     rt.handle().spawn(async move {
         if let Err(err) = signal::ctrl_c().await {
             error!("error reading SIGTERM: {err}");
         }
         token.cancel();
     });
-    rt.block_on(run(args, ctlstop))
+    tasks.close();
+
+    rt.block_on(run(args, ctlstop, ctx))?;
+    if rt
+        .block_on(tokio::time::timeout(shutdown_timeout, tasks.wait()))
+        .is_err()
+    {
+        warn!("webhook/admin servers did not shut down within the shutdown timeout, exiting anyway");
+    }
+    Ok(())
 }
 
-async fn run(args: Args, token: CancellationToken) -> controller::Result<()> {
+async fn run(args: Args, token: CancellationToken, ctx: Arc<Context>) -> controller::Result<()> {
     use tokio::task;
 
-    let config = kube::Config::infer().await?;
-    let client = kube::client::ClientBuilder::try_from(config.clone())?.build();
     // TODO(hank) Will eventually need to use the more manual construction of controllers to make
     // sure the caches are used optimally.
 
     info!(image = args.image, "default image set");
     info!("setup done, starting controllers");
-    let ctx = args.context(client);
     let mut ctrls = task::JoinSet::new();
+    ctrls.spawn(watcher::controller(token.clone(), ctx.clone())?);
+    ctrls.spawn(ctx.clone().start_discovery(token.clone()));
     for name in &args.controllers {
-        let fut = match name.to_lowercase().as_str() {
-            "clair" | "clairs" => clairs::controller(token.clone(), ctx.clone())?,
-            "indexer" | "indexers" => indexers::controller(token.clone(), ctx.clone())?,
-            "matcher" | "matchers" => matchers::controller(token.clone(), ctx.clone())?,
+        let name = name.to_lowercase();
+        let (t, c) = (token.clone(), ctx.clone());
+        let fut = match name.as_str() {
+            "clair" | "clairs" => supervisor::supervise(name.clone(), t.clone(), c.clone(), {
+                move || clairs::controller(t.clone(), c.clone())
+            }),
+            "indexer" | "indexers" => supervisor::supervise(name.clone(), t.clone(), c.clone(), {
+                move || indexers::controller(t.clone(), c.clone())
+            }),
+            "matcher" | "matchers" => supervisor::supervise(name.clone(), t.clone(), c.clone(), {
+                move || matchers::controller(t.clone(), c.clone())
+            }),
             "notifier" | "notifiers" => todo!(),
-            "updater" | "updaters" => todo!(),
+            "updater" | "updaters" => supervisor::supervise(name.clone(), t.clone(), c.clone(), {
+                move || updaters::controller(t.clone(), c.clone())
+            }),
             other => {
                 warn!(name = other, "unrecognized controller name, skipping");
                 continue;
@@ -197,16 +382,21 @@ async fn run(args: Args, token: CancellationToken) -> controller::Result<()> {
         };
         ctrls.spawn(fut);
     }
-    while let Some(res) = ctrls.join_next().await {
-        match res {
-            Err(e) => error!("error starting controller: {e}"),
-            Ok(res) => {
-                if let Err(e) = res {
-                    error!("error from controller: {e}");
-                    token.cancel();
+    let drain = async {
+        while let Some(res) = ctrls.join_next().await {
+            match res {
+                Err(e) => error!("error starting controller: {e}"),
+                Ok(res) => {
+                    if let Err(e) = res {
+                        error!("error from controller: {e}");
+                        token.cancel();
+                    }
                 }
-            }
-        };
+            };
+        }
+    };
+    if tokio::time::timeout(args.shutdown_timeout, drain).await.is_err() {
+        warn!("controllers did not shut down within the shutdown timeout, exiting anyway");
     }
     Ok(())
 }
@@ -215,44 +405,245 @@ async fn webhooks<A, Pa, Pb>(
     addr: A,
     certfile: Pa,
     keyfile: Pb,
+    extra_hosts: Vec<tls::HostCert>,
+    acme: AcmeArgs,
+    challenges: Arc<tls::ChallengeState>,
     cancel: CancellationToken,
+    ctx: Arc<Context>,
 ) -> controller::Result<()>
 where
     A: Into<SocketAddr>,
     Pa: AsRef<Path>,
     Pb: AsRef<Path>,
 {
-    use axum::Server;
-    use hyper::server::accept;
-
     use webhook::State;
 
-    let certfile = certfile.as_ref();
-    let keyfile = keyfile.as_ref();
+    let certfile = certfile.as_ref().to_path_buf();
+    let keyfile = keyfile.as_ref().to_path_buf();
     let addr = addr.into();
 
-    let client = kube::Client::try_default().await?;
-    let app = webhook::app(State::new(client));
+    let client = ctx.client.clone();
+    // ACME provisioning replaces the self-signed bootstrap path entirely: the issued certificate
+    // comes from a public CA, so there's no local CA cert for `bootstrap::reconcile` to fold into
+    // the webhook configurations' `caBundle`. Wiring the Service/WebhookConfiguration/conversion
+    // registration up in ACME mode is left to the operator for now.
+    if !acme.enabled {
+        let bootstrap_cfg = webhook::bootstrap::Config {
+            namespace: client.default_namespace().to_string(),
+            service_name: "clair-operator-webhook".to_string(),
+            secret_name: "clair-operator-webhook-tls".to_string(),
+            webhook_port: i32::from(addr.port()),
+        };
+        webhook::bootstrap::reconcile(&client, &bootstrap_cfg, &certfile, &keyfile).await?;
+    }
+    let app = webhook::app(State::new(client.clone(), ctx));
     let l = TcpListenerStream::new(TcpListener::bind(addr).await?).map_err(Error::from);
     info!(%addr, "started webhook server");
     // I can't figure out how to name the listener type such that it's either
     // TryStream<TcpStream> or TryStream<TlsStream<TcpStream>>.
-    if certfile.exists() && keyfile.exists() {
-        let (cert, key) = tokio::join!(tokio::fs::read(certfile), tokio::fs::read(keyfile));
-        let id = native_tls::Identity::from_pkcs8(&cert?, &key?)?;
-        let acceptor = TlsAcceptor::from(native_tls::TlsAcceptor::new(id)?);
-        let l = l
-            .map_ok(|s| (s, acceptor.clone()))
-            .and_then(|(s, a)| async move { a.accept(s).await.map_err(Error::from) });
-        Server::builder(accept::from_stream(l))
-            .serve(app.into_make_service())
-            .with_graceful_shutdown(cancel.cancelled_owned())
-            .await
+    if acme.enabled || has_tls_pair(&certfile, &keyfile) {
+        serve_tls(
+            l,
+            &certfile,
+            &keyfile,
+            &extra_hosts,
+            &acme,
+            challenges,
+            client,
+            app,
+            cancel,
+        )
+        .await
+    } else {
+        serve(l, app, cancel).await
+    }
+}
+
+/// Serve drives `app` over every connection accepted from `incoming`, via hyper 1.0 +
+/// `hyper-util`'s "auto" builder so both HTTP/1.1 and HTTP/2 (negotiated over TLS's ALPN) are
+/// served, instead of the hyper-0.14-only `axum::Server`/`hyper::server::accept` this replaces.
+/// Each connection is spawned onto a [`TaskTracker`] and given `cancel` directly, so that once
+/// `cancel` fires, already-accepted connections get a chance to finish their in-flight request
+/// before `serve` returns, rather than being dropped mid-response.
+async fn serve<S>(
+    mut incoming: impl futures::TryStream<Ok = S, Error = Error> + Unpin,
+    app: axum::Router,
+    cancel: CancellationToken,
+) -> controller::Result<()>
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin + 'static,
+{
+    use hyper_util::{
+        rt::{TokioExecutor, TokioIo},
+        server::conn::auto,
+    };
+    use tower::Service;
+
+    let conns = TaskTracker::new();
+    loop {
+        let stream = tokio::select! {
+            () = cancel.cancelled() => break,
+            next = incoming.try_next() => match next {
+                Ok(Some(stream)) => stream,
+                Ok(None) => break,
+                Err(error) => {
+                    warn!(%error, "error accepting connection");
+                    continue;
+                }
+            },
+        };
+        let tower_service = app.clone();
+        let cancel = cancel.clone();
+        conns.spawn(async move {
+            let io = TokioIo::new(stream);
+            let hyper_service =
+                hyper::service::service_fn(move |req| tower_service.clone().call(req));
+            let conn = auto::Builder::new(TokioExecutor::new()).serve_connection_with_upgrades(io, hyper_service);
+            tokio::pin!(conn);
+            tokio::select! {
+                res = conn.as_mut() => {
+                    if let Err(error) = res {
+                        warn!(%error, "error serving webhook connection");
+                    }
+                }
+                () = cancel.cancelled() => {
+                    conn.as_mut().graceful_shutdown();
+                    if let Err(error) = conn.await {
+                        warn!(%error, "error during graceful shutdown of webhook connection");
+                    }
+                }
+            }
+        });
+    }
+    conns.close();
+    conns.wait().await;
+    Ok(())
+}
+
+/// Has_tls_pair reports whether `certfile`/`keyfile` both exist, i.e. whether [`webhooks`]
+/// should serve TLS at all. Always `false` when neither TLS feature is enabled, so the plain-HTTP
+/// path is the only one a no-TLS-feature build ever takes.
+#[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))]
+fn has_tls_pair(certfile: &Path, keyfile: &Path) -> bool {
+    certfile.exists() && keyfile.exists()
+}
+
+#[cfg(not(any(feature = "rustls-tls", feature = "openssl-tls")))]
+fn has_tls_pair(_certfile: &Path, _keyfile: &Path) -> bool {
+    false
+}
+
+/// Serve_tls builds a [`tls::Acceptor`] (from `certfile`/`keyfile`, or via an ACME order if
+/// `acme.enabled`) and serves `app` over it.
+///
+/// Only reachable once [`webhooks`] has confirmed TLS is wanted (either `acme.enabled` or
+/// [`has_tls_pair`]), which in turn can't happen unless a TLS feature is enabled --- the
+/// `not(any(...))` twin below exists purely so this crate still compiles without either feature
+/// selected.
+#[cfg(any(feature = "rustls-tls", feature = "openssl-tls"))]
+async fn serve_tls(
+    l: impl futures::TryStream<Ok = tokio::net::TcpStream, Error = Error> + Send + 'static,
+    certfile: &Path,
+    keyfile: &Path,
+    extra_hosts: &[tls::HostCert],
+    acme: &AcmeArgs,
+    challenges: Arc<tls::ChallengeState>,
+    client: kube::Client,
+    app: axum::Router,
+    cancel: CancellationToken,
+) -> controller::Result<()> {
+    let acceptor = if acme.enabled {
+        acme_acceptor(acme, client, challenges, cancel.clone()).await?
     } else {
-        Server::builder(accept::from_stream(l))
-            .serve(app.into_make_service())
-            .with_graceful_shutdown(cancel.cancelled_owned())
-            .await
+        tls::Acceptor::new(certfile, keyfile, extra_hosts, cancel.clone())?
+    };
+    let l = l
+        .map_ok(move |s| (s, acceptor.clone()))
+        .and_then(|(s, a)| async move { a.accept(s).await });
+    serve(l, app, cancel).await
+}
+
+#[cfg(not(any(feature = "rustls-tls", feature = "openssl-tls")))]
+async fn serve_tls(
+    _l: impl futures::TryStream<Ok = tokio::net::TcpStream, Error = Error> + Send + 'static,
+    _certfile: &Path,
+    _keyfile: &Path,
+    _extra_hosts: &[tls::HostCert],
+    _acme: &AcmeArgs,
+    _challenges: Arc<tls::ChallengeState>,
+    _client: kube::Client,
+    _app: axum::Router,
+    _cancel: CancellationToken,
+) -> controller::Result<()> {
+    unreachable!("webhooks() only calls serve_tls once a TLS feature has confirmed TLS is wanted")
+}
+
+/// Acme_acceptor drives [`tls::acme::run`] to completion, turning `acme`'s CLI flags into a
+/// [`tls::Acceptor`]. Requires the `acme` feature on top of a `rustls-tls`-capable build; the
+/// `not(feature = "acme")` twin below lets `--acme` fail with a clear error at runtime instead of
+/// this crate refusing to build every time someone enables `rustls-tls` alone.
+#[cfg(feature = "acme")]
+async fn acme_acceptor(
+    acme: &AcmeArgs,
+    client: kube::Client,
+    challenges: Arc<tls::ChallengeState>,
+    cancel: CancellationToken,
+) -> controller::Result<tls::Acceptor> {
+    if acme.dns_names.is_empty() {
+        return Err(Error::BadName("--acme requires at least one --acme-dns-name".into()));
+    }
+    let cfg = tls::acme::Config {
+        directory_url: acme.directory_url.clone(),
+        dns_names: acme.dns_names.clone(),
+        renewal_window: std::time::Duration::from_secs(acme.renewal_window_days * 60 * 60 * 24),
+        secret_namespace: client.default_namespace().to_string(),
+        secret_name: acme.secret_name.clone(),
+    };
+    tls::acme::run(cfg, client, challenges, cancel).await
+}
+
+#[cfg(all(not(feature = "acme"), any(feature = "rustls-tls", feature = "openssl-tls")))]
+async fn acme_acceptor(
+    _acme: &AcmeArgs,
+    _client: kube::Client,
+    _challenges: Arc<tls::ChallengeState>,
+    _cancel: CancellationToken,
+) -> controller::Result<tls::Acceptor> {
+    Err(Error::BadName(
+        "--acme was given but this binary was not built with the acme feature".into(),
+    ))
+}
+
+async fn admin<A>(
+    addr: A,
+    challenges: Arc<tls::ChallengeState>,
+    cancel: CancellationToken,
+    ctx: Arc<Context>,
+) -> controller::Result<()>
+where
+    A: Into<SocketAddr>,
+{
+    use axum::{extract, http::StatusCode, routing::get, Extension};
+
+    use admin::State;
+
+    /// Acme_challenge serves `GET /.well-known/acme-challenge/:token` on the (plain HTTP) admin
+    /// server, independent of whatever port/protocol the webhook server itself is using --- this
+    /// is what lets `tls::acme::run`'s renewal loop satisfy an http-01 challenge even after the
+    /// webhook listener has long since switched over to TLS.
+    async fn acme_challenge(
+        extract::Path(token): extract::Path<String>,
+        Extension(challenges): Extension<Arc<tls::ChallengeState>>,
+    ) -> Result<String, StatusCode> {
+        challenges.get(&token).ok_or(StatusCode::NOT_FOUND)
     }
-    .map_err(Error::from)
+
+    let addr = addr.into();
+    let client = ctx.client.clone();
+    let app = admin::app(State::new(client, ctx))
+        .route("/.well-known/acme-challenge/:token", get(acme_challenge))
+        .layer(Extension(challenges));
+    let l = TcpListenerStream::new(TcpListener::bind(addr).await?).map_err(Error::from);
+    info!(%addr, "started admin server");
+    serve(l, app, cancel).await
 }
@@ -17,12 +17,17 @@ use tokio_stream::wrappers::SignalStream;
 
 use crate::{clair_condition, cmp_condition, merge_condition, prelude::*};
 use clair_templates::{
-    render_dropin, Build, DeploymentBuilder, HTTPRouteBuilder, HorizontalPodAutoscalerBuilder,
-    ServiceBuilder,
+    render_dropin, Build, DeploymentBuilder, GRPCRouteBuilder, HTTPRouteBuilder,
+    HorizontalPodAutoscalerBuilder, ServiceBuilder,
 };
-use v1alpha1::Matcher;
+use v1alpha1::{Matcher, StatusCommon};
 
 //static COMPONENT: LazyLock<String> = LazyLock::new(|| Matcher::kind(&()).to_ascii_lowercase());
+
+/// Kind labels every metric this controller records; see the identical constant in
+/// `indexers.rs`.
+const KIND: &str = "Matcher";
+
 static SELF_GVK: LazyLock<GroupVersionKind> = LazyLock::new(|| GroupVersionKind {
     group: Matcher::group(&()).to_string(),
     version: Matcher::version(&()).to_string(),
@@ -75,14 +80,23 @@ pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<Contro
             .for_each(|ret| {
                 match ret {
                     Ok(_) => (),
-                    Err(err) => match err {
-                        CtrlErr::ObjectNotFound(objref) => error!(%objref, "object not found"),
-                        CtrlErr::ReconcilerFailed(error, objref) => {
-                            error!(%objref, %error, "reconcile error")
+                    Err(err) => {
+                        let variant = match &err {
+                            CtrlErr::ObjectNotFound(_) => "object_not_found",
+                            CtrlErr::ReconcilerFailed(_, _) => "reconciler_failed",
+                            CtrlErr::QueueError(_) => "queue_error",
+                            CtrlErr::RunnerError(_) => "runner_error",
+                        };
+                        crate::metrics::record_handle_error(KIND, variant);
+                        match err {
+                            CtrlErr::ObjectNotFound(objref) => error!(%objref, "object not found"),
+                            CtrlErr::ReconcilerFailed(error, objref) => {
+                                error!(%objref, %error, "reconcile error")
+                            }
+                            CtrlErr::QueueError(error) => error!(%error, "queue error"),
+                            CtrlErr::RunnerError(error) => error!(%error, "runner error"),
                         }
-                        CtrlErr::QueueError(error) => error!(%error, "queue error"),
-                        CtrlErr::RunnerError(error) => error!(%error, "runner error"),
-                    },
+                    }
                 };
                 futures::future::ready(())
             })
@@ -146,6 +160,50 @@ impl Reconciler {
         Ok(())
     }
 
+    #[instrument(skip(self), ret)]
+    async fn bound_images(&self) -> Result<()> {
+        if self.matcher.spec.bound_images.is_empty() {
+            return Ok(());
+        }
+
+        let mut next = self
+            .api
+            .get_status(&self.name())
+            .instrument(debug_span!("get_status"))
+            .await?;
+        next.meta_mut().managed_fields = None;
+        let status = next.status.get_or_insert_default();
+
+        for bound in &self.matcher.spec.bound_images {
+            match crate::registry::resolve_image(&self.ctx, &bound.image.to_string()).await {
+                Ok((resolved, _annotations)) => {
+                    let resolved: v1alpha1::ImageReference = resolved
+                        .parse()
+                        .expect("resolve_image returns a valid image reference");
+                    status.add_bound_image_ref(&bound.name, &resolved);
+                }
+                Err(error) => {
+                    error!(%error, bound = bound.name, "unable to resolve bound image");
+                    status.add_condition(Condition {
+                        message: format!("failed to resolve bound image {}: {error}", bound.name),
+                        observed_generation: self.matcher.metadata.generation,
+                        last_transition_time: meta::v1::Time(Utc::now()),
+                        reason: "BoundImageResolveFailed".into(),
+                        status: "False".into(),
+                        type_: clair_condition("BoundImagesResolved"),
+                    });
+                }
+            }
+        }
+
+        debug!(payload = ?next, "patching status");
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&next))
+            .instrument(debug_span!("patch_status"))
+            .await?;
+        Ok(())
+    }
+
     #[instrument(skip(self), ret)]
     async fn publish_dropin(&self) -> Result<()> {
         use self::core::v1::Service;
@@ -170,7 +228,8 @@ impl Reconciler {
             .await?;
 
         let status = v1alpha1::WorkerStatus {
-            dropin: render_dropin::<Matcher>(&srv),
+            dropin: render_dropin(self.matcher.as_ref(), &srv)
+                .and_then(|d| serde_json::to_string(&d).ok()),
             ..Default::default()
         };
         self.api
@@ -189,12 +248,48 @@ impl Reconciler {
         let api = Api::<Deployment>::namespaced(self.client(), self.ns());
         let status = self.matcher.status.clone().unwrap_or_default();
 
-        let d = DeploymentBuilder::try_from(self.matcher.as_ref())?.build();
+        let image = self
+            .matcher
+            .spec
+            .image
+            .as_ref()
+            .expect("DeploymentBuilder::try_from already checked spec.image is set")
+            .to_string();
+        let (image, annotations) = crate::registry::resolve_image(&self.ctx, &image).await?;
+        let resolved: v1alpha1::ImageReference = image
+            .parse()
+            .expect("resolve_image returns a valid image reference");
+        let builder = DeploymentBuilder::try_from(self.matcher.as_ref())?
+            .image(image)
+            .annotations(annotations);
+        let canary = builder.canary();
+        let d = builder.build();
         trace!(?d, "created Deployment");
         let _d = api
             .patch(&d.name_any(), &PATCH_PARAMS, &Patch::Apply(d))
             .instrument(debug_span!("patch", kind = "Deployment"))
             .await?;
+        if let Some(canary) = canary {
+            let cd = canary.build();
+            trace!(?cd, "created canary Deployment");
+            api.patch(&cd.name_any(), &PATCH_PARAMS, &Patch::Apply(cd))
+                .instrument(debug_span!(
+                    "patch",
+                    kind = "Deployment",
+                    variant = "canary"
+                ))
+                .await?;
+        }
+
+        let image_status = v1alpha1::MatcherStatus {
+            resolved_image: Some(resolved),
+            ..Default::default()
+        };
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&image_status))
+            .instrument(debug_span!("patch_status", field = "resolvedImage"))
+            .await
+            .inspect_err(|error| error!(%error, "unable to patch resolved image status"))?;
 
         let deployment_ref = status.refs.as_ref().and_then(|d| {
             d.iter().find(|&objref| {
@@ -221,6 +316,59 @@ impl Reconciler {
         Ok(())
     }
 
+    /// Readiness reads back the Deployment [`Reconciler::deployment`] applied and projects its
+    /// rollout state onto a single `clair_condition("DeploymentAvailable")`, the same
+    /// `Available`/`Progressing` signal [`crate::watcher::watch_deployments`] reflects
+    /// out-of-band --- this just lets the in-band reconcile loop itself back off while a rollout
+    /// is progressing, instead of reporting `DEFAULT_REQUEUE` while pods are still coming up.
+    #[instrument(skip(self), ret)]
+    async fn readiness(&self) -> Result<Option<Action>> {
+        use apps::v1::Deployment;
+
+        let name = DeploymentBuilder::try_from(self.matcher.as_ref())?.build().name_any();
+        let api = Api::<Deployment>::namespaced(self.client(), self.ns());
+        let dep = api.get(&name).await?;
+        let status = dep.status.clone().unwrap_or_default();
+
+        let stalled = status.conditions.as_ref().is_some_and(|cs| {
+            cs.iter()
+                .any(|c| c.type_ == "Progressing" && c.reason.as_deref() == Some("ProgressDeadlineExceeded"))
+        });
+        let desired = dep.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+        let available = status.available_replicas.unwrap_or(0);
+        let ready = available >= desired && !stalled;
+
+        let cnd = Condition {
+            message: if stalled {
+                "rollout exceeded its progress deadline".into()
+            } else {
+                format!("{available}/{desired} replicas available")
+            },
+            observed_generation: self.matcher.metadata.generation,
+            last_transition_time: meta::v1::Time(Utc::now()),
+            reason: if stalled {
+                "RolloutStalled".into()
+            } else if ready {
+                "RolloutComplete".into()
+            } else {
+                "RolloutProgressing".into()
+            },
+            status: if ready { "True".into() } else { "False".into() },
+            type_: clair_condition("DeploymentAvailable"),
+        };
+        self.set_condition(cnd).await?;
+
+        if stalled {
+            warn!("matcher deployment rollout stalled");
+            return Ok(Action::requeue(Duration::from_secs(300)).into());
+        }
+        if !ready {
+            debug!("matcher deployment rollout still in progress");
+            return Ok(Action::requeue(Duration::from_secs(15)).into());
+        }
+        Ok(None)
+    }
+
     #[instrument(skip(self), ret)]
     async fn service(&self) -> Result<()> {
         use self::core::v1::Service;
@@ -228,11 +376,19 @@ impl Reconciler {
         let api = Api::<Service>::namespaced(self.client(), self.ns());
         let status = self.matcher.status.clone().unwrap_or_default();
 
-        let s = ServiceBuilder::try_from(self.matcher.as_ref())?.build();
+        let builder = ServiceBuilder::try_from(self.matcher.as_ref())?;
+        let canary = builder.canary();
+        let s = builder.build();
         let _s = api
             .patch(&s.name_any(), &PATCH_PARAMS, &Patch::Apply(s))
             .await
             .inspect_err(|error| error!(%error, "failed to patch Service"))?;
+        if let Some(canary) = canary {
+            let cs = canary.build();
+            api.patch(&cs.name_any(), &PATCH_PARAMS, &Patch::Apply(cs))
+                .await
+                .inspect_err(|error| error!(%error, "failed to patch canary Service"))?;
+        }
 
         let service_ref = status.refs.as_ref().and_then(|d| {
             d.iter().find(|&objref| {
@@ -335,6 +491,44 @@ impl Reconciler {
         Ok(())
     }
 
+    #[instrument(skip(self), ret)]
+    async fn grpc_route(&self) -> Result<()> {
+        use gateway_networking_k8s_io::v1::grpcroutes::*;
+
+        let api = Api::<GRPCRoute>::namespaced(self.client(), self.ns());
+        let status = self.matcher.status.clone().unwrap_or_default();
+
+        let r = GRPCRouteBuilder::try_from(self.matcher.as_ref())?.build();
+        let _r = api
+            .patch(&r.name_any(), &PATCH_PARAMS, &Patch::Apply(r))
+            .await
+            .inspect_err(|error| error!(%error, "failed to patch GRPCRoute"))?;
+
+        let route_ref = status.refs.as_ref().and_then(|d| {
+            d.iter().find(|&objref| {
+                objref.kind == GRPCRoute::kind(&())
+                    && objref.api_group == GRPCRoute::group(&()).to_string().into()
+            })
+        });
+        if route_ref.is_some() {
+            debug!("no need to update status");
+            return Ok(());
+        }
+        debug!("updating status");
+
+        let cnd = Condition {
+            message: "created GRPCRoute".into(),
+            observed_generation: self.matcher.metadata.generation,
+            last_transition_time: meta::v1::Time(Utc::now()),
+            reason: "GRPCRouteCreated".into(),
+            status: "True".into(),
+            type_: clair_condition("GRPCRouteCreated"),
+        };
+        self.set_condition(cnd).await?;
+
+        Ok(())
+    }
+
     #[instrument(skip(self), ret)]
     async fn check_spec(&self) -> Result<Option<Action>> {
         let mut cnd = Condition {
@@ -369,21 +563,44 @@ impl Reconciler {
 async fn reconcile(matcher: Arc<Matcher>, ctx: Arc<Context>) -> Result<Action> {
     assert!(matcher.meta().name.is_some());
     info!("reconciling Matcher");
+    let mut timer = crate::metrics::ReconcileTimer::start(KIND);
+
+    let ret = reconcile_matcher(matcher.clone(), ctx.clone()).await;
+    if ret.is_ok() {
+        let key = format!("{}/{}", matcher.namespace().unwrap(), matcher.name_any());
+        ctx.backoff_reset(&SELF_GVK, &key);
+    }
+
+    timer.finish(&ret);
+    ret
+}
+
+/// Reconcile_matcher is [`reconcile`]'s body, split out so the duration/result metrics wrap every
+/// return path --- including the early return out of [`Reconciler::check_spec`] --- instead of
+/// only the happy path that falls through to the end.
+async fn reconcile_matcher(matcher: Arc<Matcher>, ctx: Arc<Context>) -> Result<Action> {
     let r = Reconciler::from((matcher.clone(), ctx.clone()));
 
     if let Some(a) = r.check_spec().await? {
         return Ok(a);
     };
     r.deployment().await?;
+    if let Some(a) = r.readiness().await? {
+        return Ok(a);
+    };
     r.service().await?;
     r.horizontal_pod_autoscaler().await?;
     r.route().await?;
+    r.grpc_route().await?;
+    r.bound_images().await?;
     r.publish_dropin().await?;
 
     Ok(DEFAULT_REQUEUE.clone())
 }
 
 #[instrument(skip_all)]
-fn handle_error(_obj: Arc<Matcher>, _err: &Error, _ctx: Arc<Context>) -> Action {
-    Action::await_change()
+fn handle_error(obj: Arc<Matcher>, err: &Error, ctx: Arc<Context>) -> Action {
+    error!(%err, "reconcile error");
+    let key = format!("{}/{}", obj.namespace().unwrap(), obj.name_any());
+    ctx.backoff_action(&SELF_GVK, &key)
 }
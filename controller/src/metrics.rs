@@ -1,42 +1,249 @@
-//! Metrics contains the metrics setup for the controller.
+//! Metrics instruments the subresource controllers with Prometheus counters and histograms,
+//! modeled on Garage's `admin/metrics.rs`, so operators get reconcile throughput/latency and
+//! per-hook-step failure rates as SLO signals instead of having to parse logs.
+//!
+//! Recording goes through the `metrics` crate's global recorder --- the same one
+//! [`crate::with_poll_timer`] already reports `reconcile_phase_seconds` through --- so these show
+//! up on whichever exporter the process installed. [`serve`] installs a dedicated Prometheus
+//! exporter and binds its `/metrics` HTTP endpoint, for embedders that spawn a subresource
+//! [`crate::subresource::controller`] future without a surrounding `main` that already installs
+//! one (compare `main`'s own `PrometheusBuilder::with_http_listener`).
 
-use opentelemetry::{
-    global,
-    metrics::{Counter, Meter},
-};
+use std::net::SocketAddr;
+use std::sync::LazyLock;
 
-/// Common metrics for the reconcilers.
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+
+use crate::{Error, Result};
+
+/// Bucket boundaries for `reconcile_duration_seconds`, doubling from 1ms to ~8s: most reconciles
+/// finish in milliseconds, but one waiting on a Deployment rollout or a slow apiserver call can
+/// take several seconds, and exponential buckets keep resolution at both ends.
+static RECONCILE_DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.002, 0.004, 0.008, 0.016, 0.032, 0.064, 0.128, 0.256, 0.512, 1.024, 2.048, 4.096,
+    8.192,
+];
+
+/// Metrics is a handle onto the `metrics` crate's process-wide Prometheus recorder, so callers
+/// can read back what a reconcile just recorded instead of only being able to increment the
+/// series blindly through the free functions below.
 #[derive(Clone)]
 pub struct Metrics {
-    /// Metrics collected from reconcilers.
-    pub reconcile: ReconcileMetrics,
+    handle: PrometheusHandle,
 }
 
-impl Default for Metrics {
-    fn default() -> Self {
-        let meter = global::meter("clair_ctrl_reconcile");
-        let reconcile = ReconcileMetrics::from(meter);
-        Self { reconcile }
+impl Metrics {
+    /// Handle returns the process-wide `Metrics`, installing the global Prometheus recorder the
+    /// first time it's called.
+    ///
+    /// The `metrics` crate only allows installing one global recorder per process, so --- like
+    /// [`crate::REPORTER`] --- this is memoized: whichever caller (`main`'s `startup`, [`serve`],
+    /// or a test's `Context::clair_tests`) asks first wins, and everyone else shares that handle.
+    pub fn handle() -> Self {
+        static HANDLE: LazyLock<PrometheusHandle> = LazyLock::new(|| {
+            PrometheusBuilder::new()
+                .set_buckets_for_metric(
+                    Matcher::Full("reconcile_duration_seconds".into()),
+                    RECONCILE_DURATION_BUCKETS,
+                )
+                .expect("valid bucket matcher")
+                .install_recorder()
+                .expect("installing the global Prometheus recorder")
+        });
+        Self {
+            handle: HANDLE.clone(),
+        }
+    }
+
+    /// Render returns the current Prometheus text-exposition snapshot of every series this
+    /// recorder has seen, e.g. for the `/metrics` endpoint, or for a test comparing against a
+    /// snapshot taken before a scenario ran.
+    pub fn render(&self) -> String {
+        self.handle.render()
+    }
+
+    /// Counter_value reads the current value of the counter or histogram sample count named
+    /// `metric` with the given `labels` out of [`Self::render`]'s snapshot, or `0.0` if that
+    /// series hasn't recorded a sample yet (Prometheus counters are omitted from the exposition
+    /// until their first increment).
+    ///
+    /// Intended for tests: compare a value captured before a scenario runs against one captured
+    /// after to assert how many series were recorded by that scenario alone, since the recorder
+    /// itself is shared process-wide across every test in the binary.
+    pub fn counter_value(&self, metric: &str, labels: &[(&str, &str)]) -> f64 {
+        let mut label_str = labels
+            .iter()
+            .map(|(k, v)| format!("{k}=\"{v}\""))
+            .collect::<Vec<_>>();
+        label_str.sort();
+        let prefix = format!("{metric}{{{}}} ", label_str.join(","));
+        self.render()
+            .lines()
+            .find_map(|line| line.strip_prefix(&prefix))
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(0.0)
     }
 }
 
-/// Metrics collected from reconcilers.
-#[derive(Clone)]
-pub struct ReconcileMetrics {
-    /// Runs records the total number of calls to a reconciler.
-    pub runs: Counter<u64>,
-    /// Failures records the total number of reconciler calls that resulted in a failure.
-    pub failures: Counter<u64>,
+/// Record_reconcile_start increments `reconciles_started_total`, labeled by `kind`.
+pub fn record_reconcile_start(kind: &str) {
+    metrics::counter!("reconciles_started_total", "kind" => kind.to_string()).increment(1);
 }
 
-impl From<Meter> for ReconcileMetrics {
-    fn from(meter: Meter) -> Self {
+/// Record_reconcile_result increments `reconciles_succeeded_total` or `reconciles_failed_total`,
+/// labeled by `kind`, depending on `ok`.
+pub fn record_reconcile_result(kind: &str, ok: bool) {
+    let name = if ok {
+        "reconciles_succeeded_total"
+    } else {
+        "reconciles_failed_total"
+    };
+    metrics::counter!(name, "kind" => kind.to_string()).increment(1);
+}
+
+/// Record_reconcile_inflight adjusts the `reconciles_in_flight` gauge, labeled by `kind`, by
+/// `delta` (`1.0` when a reconcile starts, `-1.0` when it ends), so queue saturation --- how many
+/// reconciles are running concurrently, not just how many have started or finished --- is
+/// observable alongside the throughput/latency series above.
+pub fn record_reconcile_inflight(kind: &str, delta: f64) {
+    metrics::gauge!("reconciles_in_flight", "kind" => kind.to_string()).increment(delta);
+}
+
+/// Record_step_duration records `elapsed` against the `reconcile_step_seconds` histogram, labeled
+/// by `kind` and `step` (the `stringify!($fn)` name `check_all!` already logs via `tracing`).
+pub fn record_step_duration(kind: &str, step: &'static str, elapsed: std::time::Duration) {
+    metrics::histogram!("reconcile_step_seconds", "kind" => kind.to_string(), "step" => step)
+        .record(elapsed.as_secs_f64());
+}
+
+/// Record_publish records one `publish` status-update pass: `attempts` is the number of
+/// `replace_status` tries it took (`ct` in `publish`), and `failed` is whether all
+/// `Backoff::max_attempts` tries were exhausted without success.
+pub fn record_publish(kind: &str, attempts: u32, failed: bool) {
+    metrics::histogram!("publish_attempts", "kind" => kind.to_string()).record(attempts as f64);
+    let result = if failed { "conflict" } else { "ok" };
+    metrics::counter!("publish_results_total", "kind" => kind.to_string(), "result" => result)
+        .increment(1);
+}
+
+/// Record_handle_error increments `reconcile_errors_total`, labeled by `kind` and the
+/// `kube::runtime::controller::Error` variant name matched in `controller`'s result stream.
+pub fn record_handle_error(kind: &str, variant: &'static str) {
+    metrics::counter!("reconcile_errors_total", "kind" => kind.to_string(), "variant" => variant)
+        .increment(1);
+}
+
+/// Record_hook_result increments `hook_results_total`, labeled by `kind`, `hook` (the
+/// [`crate::subresource::Hook`] variant name), and whether the user-supplied hook returned
+/// `HookResult::Continue` or `HookResult::Return`.
+pub fn record_hook_result(kind: &str, hook: &'static str, outcome: &'static str) {
+    metrics::counter!("hook_results_total", "kind" => kind.to_string(), "hook" => hook, "outcome" => outcome)
+        .increment(1);
+}
+
+/// Record_commit_outcome increments `commit_results_total`, labeled by `kind`, `step`, and
+/// `outcome`, one of "created" (commit succeeded on the first attempt), "validated" (commit
+/// succeeded after one or more `CommitError::Validate` retries), "retried"
+/// (`CommitError::Validate`, the loop will try again), or "save-error" (`CommitError::Save`, the
+/// loop gives up).
+pub fn record_commit_outcome(kind: &str, step: &'static str, outcome: &'static str) {
+    metrics::counter!("commit_results_total", "kind" => kind.to_string(), "step" => step, "outcome" => outcome)
+        .increment(1);
+}
+
+/// Record_retry_attempt sets the `reconcile_retry_attempt` gauge to the current attempt number
+/// within a `check_*` commit loop, labeled by `kind` and `step`.
+pub fn record_retry_attempt(kind: &str, step: &'static str, attempt: u32) {
+    metrics::gauge!("reconcile_retry_attempt", "kind" => kind.to_string(), "step" => step)
+        .set(attempt as f64);
+}
+
+/// Record_reconcile_duration records `elapsed` against the `reconcile_duration_seconds`
+/// histogram, labeled by `kind` and `result` (`"ok"`, or the failing [`Error`] variant's name).
+/// Call this once per top-level `reconcile` invocation covering the whole body, including early
+/// returns (e.g. out of `check_spec`), so the histogram reflects the full cost of a reconcile and
+/// not just the path that reaches the end.
+pub fn record_reconcile_duration(kind: &str, result: &str, elapsed: std::time::Duration) {
+    metrics::histogram!(
+        "reconcile_duration_seconds", "kind" => kind.to_string(), "result" => result.to_string()
+    )
+    .record(elapsed.as_secs_f64());
+}
+
+/// ReconcileTimer is an RAII guard that times one top-level `reconcile` invocation, so
+/// [`crate::indexers`], [`crate::matchers`], [`crate::updaters`], and [`crate::clairs`] can all
+/// instrument their reconcile loops the same way instead of each hand-rolling the
+/// `Instant::now()`/`elapsed()` bookkeeping [`record_reconcile_duration`] used to require.
+///
+/// [`Self::start`] records the reconcile start and bumps `reconciles_in_flight`; dropping the
+/// guard records its duration and result. Since [`Drop::drop`] only ever sees `&mut self`, never
+/// the `Result` a reconciler is about to return, call [`Self::finish`] with that `Result` first ---
+/// forgetting to is harmless, it just means the drop records `"ok"`.
+pub struct ReconcileTimer {
+    kind: String,
+    start: std::time::Instant,
+    result: &'static str,
+}
+
+impl ReconcileTimer {
+    /// Start begins timing a reconcile for `kind`: increments `reconciles_started_total` and the
+    /// `reconciles_in_flight` gauge, same as the old `record_reconcile_start` call this replaces.
+    pub fn start(kind: &str) -> Self {
+        record_reconcile_start(kind);
+        record_reconcile_inflight(kind, 1.0);
         Self {
-            runs: meter
-                .u64_counter("reconciles")
-                .with_unit("{request}")
-                .build(),
-            failures: meter.u64_counter("failures").with_unit("{request}").build(),
+            kind: kind.to_string(),
+            start: std::time::Instant::now(),
+            result: "ok",
         }
     }
+
+    /// Finish records `ret`'s outcome as the `result` label this guard reports on drop: `"ok"`,
+    /// or the failing [`Error`] variant's name.
+    pub fn finish<T>(&mut self, ret: &Result<T>) {
+        self.result = match ret {
+            Ok(_) => "ok",
+            Err(error) => error.variant_name(),
+        };
+    }
+}
+
+impl Drop for ReconcileTimer {
+    fn drop(&mut self) {
+        record_reconcile_inflight(&self.kind, -1.0);
+        record_reconcile_duration(&self.kind, self.result, self.start.elapsed());
+        record_reconcile_result(&self.kind, self.result == "ok");
+    }
+}
+
+/// Record_owned_patch increments `owned_object_patches_total`, labeled by `kind` and the owned
+/// `resource` kind (e.g. `"Deployment"`, `"Service"`, `"HPA"`) that was just patched.
+pub fn record_owned_patch(kind: &str, resource: &'static str) {
+    metrics::counter!("owned_object_patches_total", "kind" => kind.to_string(), "resource" => resource)
+        .increment(1);
+}
+
+/// Record_condition sets the `clair_condition` gauge for `condition_type`, labeled by `kind`, to
+/// `1.0` if `status` is `"True"` and `0.0` otherwise, mirroring how a [`Condition`]'s `status`
+/// field itself only distinguishes `"True"` from everything else.
+///
+/// [`Condition`]: k8s_openapi::apimachinery::pkg::apis::meta::v1::Condition
+pub fn record_condition(kind: &str, condition_type: &str, status: &str) {
+    metrics::gauge!(
+        "clair_condition", "kind" => kind.to_string(), "type" => condition_type.to_string()
+    )
+    .set(if status == "True" { 1.0 } else { 0.0 });
+}
+
+/// Serve installs a Prometheus exporter as the `metrics` crate's global recorder and binds its
+/// `/metrics` HTTP endpoint at `addr`.
+///
+/// Only needed when a subresource `controller()` future is spawned on its own; a `main` that
+/// already installs a recorder (see `main`'s `PrometheusBuilder::with_http_listener`) must not
+/// call this too, since the `metrics` crate allows installing a global recorder only once.
+pub fn serve(addr: SocketAddr) -> Result<()> {
+    PrometheusBuilder::new()
+        .with_http_listener(addr)
+        .install()
+        .map_err(|err| Error::Other(err.into()))
 }
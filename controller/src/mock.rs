@@ -0,0 +1,114 @@
+//! Mock is test-only scaffolding for driving reconcile logic against canned HTTP responses
+//! instead of a live cluster, following the approach in Akri's `shared_test_utils`: a
+//! [`tower::Service`] is handed a queue of expected `(verb, path) -> response` pairs and panics
+//! loudly the moment a request doesn't match what was expected next.
+#![allow(missing_docs)]
+
+use std::{collections::VecDeque, sync::Arc};
+
+use http::{Method, Request, Response, StatusCode};
+use kube::client::{Body, Client};
+use tower_test::mock;
+
+use crate::Context;
+
+type Handle = mock::Handle<Request<Body>, Response<Body>>;
+
+/// A single expected request, and the response to hand back for it.
+struct Exchange {
+    method: Method,
+    path: String,
+    response: Response<Body>,
+}
+
+/// ContextBuilder enqueues expected `(verb, path) -> response` pairs for a mock-backed
+/// [`Context`], so reconcile logic can be exercised deterministically without a cluster.
+#[derive(Default)]
+pub(crate) struct ContextBuilder {
+    exchanges: VecDeque<Exchange>,
+}
+
+impl ContextBuilder {
+    /// Expect_get enqueues a GET, answered with `body`.
+    pub(crate) fn expect_get(self, path: impl Into<String>, body: &serde_json::Value) -> Self {
+        self.expect(Method::GET, path, StatusCode::OK, body)
+    }
+
+    /// Expect_list enqueues a GET against a collection endpoint, answered with `items` (an
+    /// already-assembled `List` document).
+    pub(crate) fn expect_list(self, path: impl Into<String>, items: &serde_json::Value) -> Self {
+        self.expect(Method::GET, path, StatusCode::OK, items)
+    }
+
+    /// Expect_patch enqueues a PATCH (as issued by `Api::patch`/`patch_status`), answered with
+    /// `body`, which should be the object as the apiserver would return it post-patch.
+    pub(crate) fn expect_patch(self, path: impl Into<String>, body: &serde_json::Value) -> Self {
+        self.expect(Method::PATCH, path, StatusCode::OK, body)
+    }
+
+    /// Expect_create enqueues a POST, answered with `body`.
+    pub(crate) fn expect_create(self, path: impl Into<String>, body: &serde_json::Value) -> Self {
+        self.expect(Method::POST, path, StatusCode::CREATED, body)
+    }
+
+    /// Expect_not_found enqueues a GET answered with a 404, e.g. for a `get_opt` call that
+    /// should see "no such object".
+    pub(crate) fn expect_not_found(self, path: impl Into<String>) -> Self {
+        self.expect(
+            Method::GET,
+            path,
+            StatusCode::NOT_FOUND,
+            &serde_json::json!({
+                "apiVersion": "v1",
+                "kind": "Status",
+                "status": "Failure",
+                "reason": "NotFound",
+                "code": 404,
+            }),
+        )
+    }
+
+    fn expect(
+        mut self,
+        method: Method,
+        path: impl Into<String>,
+        status: StatusCode,
+        body: &serde_json::Value,
+    ) -> Self {
+        let response = Response::builder()
+            .status(status)
+            .body(Body::from(serde_json::to_vec(body).expect("value is serializable")))
+            .expect("response is well-formed");
+        self.exchanges.push_back(Exchange {
+            method,
+            path: path.into(),
+            response,
+        });
+        self
+    }
+
+    /// Build spawns a task driving the queued exchanges and returns a mock-backed [`Context`],
+    /// plus the join handle, so callers can assert the whole queue drained.
+    pub(crate) fn build(self, image: &str) -> (Arc<Context>, tokio::task::JoinHandle<()>) {
+        let (service, handle) = mock::pair::<Request<Body>, Response<Body>>();
+        let client = Client::new(service, "default");
+        let task = tokio::spawn(drive(handle, self.exchanges));
+        (Arc::new(Context::new(client, image)), task)
+    }
+}
+
+async fn drive(mut handle: Handle, mut exchanges: VecDeque<Exchange>) {
+    while let Some((req, send)) = handle.next_request().await {
+        let exp = exchanges
+            .pop_front()
+            .unwrap_or_else(|| panic!("unexpected request: {} {}", req.method(), req.uri()));
+        assert_eq!(req.method(), exp.method, "method mismatch for {}", req.uri());
+        assert_eq!(req.uri().path(), exp.path, "path mismatch");
+        send.send_response(exp.response);
+    }
+    assert!(
+        exchanges.is_empty(),
+        "{} expected request(s) never arrived",
+        exchanges.len()
+    );
+}
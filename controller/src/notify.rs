@@ -0,0 +1,141 @@
+//! Notify delivers outbound webhook notifications when a `status.conditions` entry transitions,
+//! so external systems (CI dashboards, chatops bots, ...) can react to `Clair` lifecycle events
+//! the same way they'd react to a CI run-state change, instead of polling `GET /status/...`
+//! (see [`crate::admin`]).
+//!
+//! Each configured [`Endpoint`] gets its own shared secret; the JSON body is signed HMAC-SHA256
+//! via `openssl` (already a dependency for [`crate::Error::Hmac`]) and carried in the
+//! [`SIGNATURE_HEADER`] header, hex-encoded, so a receiver can verify the notification actually
+//! came from this controller.
+
+use k8s_openapi::{api::core, apimachinery::pkg::apis::meta};
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+use serde::Serialize;
+#[allow(unused_imports)]
+use tracing::{debug, instrument, trace, warn};
+
+use crate::Result;
+
+/// Signature_header carries the request body's hex-encoded HMAC-SHA256 signature.
+pub const SIGNATURE_HEADER: &str = "X-Clair-Signature-256";
+
+/// Endpoint is one configured outbound webhook target.
+#[derive(Clone, Debug)]
+pub struct Endpoint {
+    /// Url is where the notification is POSTed.
+    pub url: String,
+    /// Secret signs the JSON body via HMAC-SHA256.
+    pub secret: String,
+    /// Events filters which condition types this endpoint wants notified about; an empty list
+    /// means "all of them".
+    pub events: Vec<String>,
+}
+
+impl Endpoint {
+    /// New creates an Endpoint subscribed to every condition type.
+    pub fn new<U, S>(url: U, secret: S) -> Self
+    where
+        U: ToString,
+        S: ToString,
+    {
+        Self {
+            url: url.to_string(),
+            secret: secret.to_string(),
+            events: Vec::new(),
+        }
+    }
+
+    /// With_events restricts this Endpoint to the named condition types.
+    pub fn with_events<I, S>(mut self, events: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: ToString,
+    {
+        self.events = events.into_iter().map(|e| e.to_string()).collect();
+        self
+    }
+
+    fn wants(&self, type_: &str) -> bool {
+        self.events.is_empty() || self.events.iter().any(|e| e == type_)
+    }
+}
+
+/// Transition is the JSON body POSTed to each subscribed [`Endpoint`]: an object ref, the
+/// condition that transitioned, its old/new status, and a timestamp.
+#[derive(Clone, Debug, Serialize)]
+pub struct Transition {
+    /// Object is the `Clair` (or other subresource) the condition belongs to.
+    pub object: core::v1::ObjectReference,
+    /// Type is the condition's `type_`, e.g. `clair_condition("Initialized")`.
+    #[serde(rename = "type")]
+    pub type_: String,
+    /// Old_status is the condition's `status` before this transition, `None` if this is the
+    /// first time the condition has been recorded.
+    pub old_status: Option<String>,
+    /// New_status is the condition's `status` after this transition.
+    pub new_status: String,
+    /// Reason is the condition's `reason`.
+    pub reason: String,
+    /// Message is the condition's `message`.
+    pub message: String,
+    /// Timestamp is when the transition was recorded.
+    pub timestamp: meta::v1::Time,
+}
+
+/// Notifier posts signed [`Transition`] notifications to a configured set of [`Endpoint`]s.
+///
+/// Delivery failures are logged and otherwise swallowed: a webhook receiver being down shouldn't
+/// fail the reconcile that triggered the notification (compare [`crate::Context::record_transition`],
+/// which this is called from).
+#[derive(Clone, Debug, Default)]
+pub struct Notifier {
+    client: reqwest::Client,
+    endpoints: Vec<Endpoint>,
+}
+
+impl Notifier {
+    /// New creates a Notifier posting to `endpoints`.
+    pub fn new(endpoints: Vec<Endpoint>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoints,
+        }
+    }
+
+    /// Notify signs and POSTs `transition` to every [`Endpoint`] subscribed to its condition
+    /// type. Individual endpoint failures are logged via `warn!` and do not short-circuit
+    /// delivery to the rest.
+    #[instrument(skip(self, transition), fields(type_ = %transition.type_))]
+    pub async fn notify(&self, transition: &Transition) -> Result<()> {
+        if self.endpoints.is_empty() {
+            return Ok(());
+        }
+        let body = serde_json::to_vec(transition)?;
+        for endpoint in self.endpoints.iter().filter(|e| e.wants(&transition.type_)) {
+            let signature = sign(&endpoint.secret, &body)?;
+            let res = self
+                .client
+                .post(&endpoint.url)
+                .header(SIGNATURE_HEADER, signature)
+                .header("Content-Type", "application/json")
+                .body(body.clone())
+                .send()
+                .await;
+            match res {
+                Ok(resp) if resp.status().is_success() => trace!(url = endpoint.url, "delivered"),
+                Ok(resp) => warn!(url = endpoint.url, status = %resp.status(), "webhook endpoint rejected notification"),
+                Err(err) => warn!(url = endpoint.url, %err, "webhook delivery failed"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sign returns the hex-encoded HMAC-SHA256 of `body` keyed by `secret`.
+fn sign(secret: &str, body: &[u8]) -> Result<String> {
+    let key = PKey::hmac(secret.as_bytes())?;
+    let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+    signer.update(body)?;
+    let mac = signer.sign_to_vec()?;
+    Ok(mac.iter().map(|b| format!("{b:02x}")).collect())
+}
@@ -0,0 +1,288 @@
+//! Registry resolves a mutable image tag to the immutable content digest it currently points to,
+//! so a `Recreate`-strategy Deployment can be pinned to `repo@sha256:...` instead of a tag that
+//! can move out from under it.
+
+use std::{collections::BTreeMap, fmt, sync::LazyLock};
+
+use futures::future::BoxFuture;
+use tracing::instrument;
+
+use crate::{Error, Result};
+
+/// TAG_ANNOTATION is the annotation key recording the tag an image reference was resolved from.
+pub static TAG_ANNOTATION: LazyLock<String> = LazyLock::new(|| crate::clair_label("image-tag"));
+
+/// DIGEST_ANNOTATION is the annotation key recording the content digest `TAG_ANNOTATION` was
+/// resolved to.
+pub static DIGEST_ANNOTATION: LazyLock<String> =
+    LazyLock::new(|| crate::clair_label("image-digest"));
+
+/// Resolved is what a [`DigestResolver`] reports back for an image reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Resolved {
+    /// Digest is the resolved `sha256:...` content digest.
+    pub digest: String,
+    /// Reference is `image` rewritten to `repo@digest`, with any tag or digest suffix dropped.
+    pub reference: String,
+}
+
+/// DigestResolver looks up the content digest a tag currently points to.
+///
+/// This is a trait, rather than a concrete registry client, so reconcile logic can be exercised
+/// against a canned resolver instead of making real registry calls.
+pub trait DigestResolver: fmt::Debug + Send + Sync {
+    /// Resolve returns the digest `image` currently points to.
+    ///
+    /// If `image` is already pinned to a digest, it's returned unchanged.
+    fn resolve<'a>(&'a self, image: &'a str) -> BoxFuture<'a, Result<Resolved>>;
+}
+
+/// Resolve_image asks `ctx`'s [`DigestResolver`] for the digest `image` currently points to,
+/// returning the rewritten `repo@sha256:...` reference along with annotations recording the tag
+/// it was resolved from and the digest it resolved to, ready to hand to
+/// [`DeploymentBuilder::image`](clair_templates::DeploymentBuilder::image) and
+/// [`DeploymentBuilder::annotations`](clair_templates::DeploymentBuilder::annotations).
+pub async fn resolve_image(
+    ctx: &crate::Context,
+    image: &str,
+) -> Result<(String, BTreeMap<String, String>)> {
+    let resolved = ctx.resolver.resolve(image).await?;
+    let annotations = BTreeMap::from([
+        (TAG_ANNOTATION.clone(), image.to_string()),
+        (DIGEST_ANNOTATION.clone(), resolved.digest.clone()),
+    ]);
+    Ok((resolved.reference, annotations))
+}
+
+/// RegistryResolver is the real [`DigestResolver`]: it does the same manifest HEAD that
+/// `skopeo inspect`/`docker manifest inspect` do, against the registry's `/v2/` API, and reads
+/// back the `Docker-Content-Digest` response header.
+#[derive(Debug, Default)]
+pub struct RegistryResolver {
+    client: reqwest::Client,
+}
+
+impl DigestResolver for RegistryResolver {
+    #[instrument(skip(self))]
+    fn resolve<'a>(&'a self, image: &'a str) -> BoxFuture<'a, Result<Resolved>> {
+        Box::pin(async move {
+            let (repo, suffix) = split_suffix(image);
+            if let Some(digest) = suffix.strip_prefix('@') {
+                return Ok(Resolved {
+                    digest: digest.to_string(),
+                    reference: image.to_string(),
+                });
+            }
+            let tag = suffix.strip_prefix(':').unwrap_or("latest");
+            let (registry, path) = split_registry(repo);
+            let url = format!("https://{registry}/v2/{path}/manifests/{tag}");
+
+            const ACCEPT: &str = "application/vnd.oci.image.manifest.v1+json, \
+                 application/vnd.oci.image.index.v1+json, \
+                 application/vnd.docker.distribution.manifest.v2+json, \
+                 application/vnd.docker.distribution.manifest.list.v2+json";
+
+            let resp = self
+                .client
+                .head(&url)
+                .header("Accept", ACCEPT)
+                .send()
+                .await?;
+            let resp = if resp.status() == reqwest::StatusCode::UNAUTHORIZED {
+                let challenge = resp
+                    .headers()
+                    .get(reqwest::header::WWW_AUTHENTICATE)
+                    .and_then(|v| v.to_str().ok())
+                    .ok_or_else(|| {
+                        Error::Registry(format!(
+                            "registry returned 401 with no WWW-Authenticate challenge for {image:?}"
+                        ))
+                    })?;
+                let token = self.bearer_token(challenge).await?;
+                self.client
+                    .head(&url)
+                    .header("Accept", ACCEPT)
+                    .bearer_auth(token)
+                    .send()
+                    .await?
+            } else {
+                resp
+            };
+            let resp = resp.error_for_status().map_err(|err| {
+                Error::Registry(format!("registry request failed for {image:?}: {err}"))
+            })?;
+            let digest = resp
+                .headers()
+                .get("Docker-Content-Digest")
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| Error::Registry(format!("no digest reported for {image:?}")))?
+                .to_string();
+
+            Ok(Resolved {
+                reference: format!("{repo}@{digest}"),
+                digest,
+            })
+        })
+    }
+}
+
+impl RegistryResolver {
+    /// Bearer_token completes the standard [distribution auth handshake]: it parses `challenge`
+    /// (a `WWW-Authenticate: Bearer realm="...",service="...",scope="..."` header value) and
+    /// fetches a token from `realm`, the same way `docker`/`skopeo` do for an anonymous pull of a
+    /// public image. Virtually every registry, including quay.io, requires this even when no
+    /// credentials are configured.
+    ///
+    /// [distribution auth handshake]: https://distribution.github.io/distribution/spec/auth/token/
+    async fn bearer_token(&self, challenge: &str) -> Result<String> {
+        let params = parse_bearer_challenge(challenge).ok_or_else(|| {
+            Error::Registry(format!("unsupported WWW-Authenticate challenge: {challenge:?}"))
+        })?;
+        let realm = params.get("realm").ok_or_else(|| {
+            Error::Registry(format!("WWW-Authenticate challenge has no realm: {challenge:?}"))
+        })?;
+
+        let mut req = self.client.get(realm);
+        for key in ["service", "scope"] {
+            if let Some(v) = params.get(key) {
+                req = req.query(&[(key, v)]);
+            }
+        }
+        let resp = req.send().await?.error_for_status()?;
+        let buf = resp.bytes().await?;
+        let token: TokenResponse = serde_json::from_slice(&buf)?;
+        token
+            .token
+            .or(token.access_token)
+            .ok_or_else(|| Error::Registry(format!("token response from {realm} has no token")))
+    }
+}
+
+/// TokenResponse is the relevant subset of a [distribution auth token response]: registries
+/// disagree on which of `token`/`access_token` they populate, so both are accepted.
+///
+/// [distribution auth token response]: https://distribution.github.io/distribution/spec/auth/token/#token-response-fields
+#[derive(serde::Deserialize)]
+struct TokenResponse {
+    token: Option<String>,
+    access_token: Option<String>,
+}
+
+/// Parse_bearer_challenge extracts the `key="value"` pairs out of a `Bearer ...`
+/// `WWW-Authenticate` challenge, returning `None` if the header isn't a `Bearer` challenge at
+/// all.
+fn parse_bearer_challenge(header: &str) -> Option<BTreeMap<String, String>> {
+    let rest = header.strip_prefix("Bearer ")?;
+    Some(
+        rest.split(',')
+            .filter_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                Some((key.trim().to_string(), value.trim().trim_matches('"').to_string()))
+            })
+            .collect(),
+    )
+}
+
+/// Split_suffix splits `image` into its repo and its trailing `:tag` or `@digest`, if any.
+fn split_suffix(image: &str) -> (&str, &str) {
+    if let Some(at) = image.rfind('@') {
+        return (&image[..at], &image[at..]);
+    }
+    if let Some(colon) = image.rfind(':') {
+        if image.rfind('/').is_none_or(|slash| colon > slash) {
+            return (&image[..colon], &image[colon..]);
+        }
+    }
+    (image, "")
+}
+
+/// Split_registry splits a (tag/digest-less) repo reference into its registry host and path,
+/// defaulting to Docker Hub the way `docker pull` does for unqualified references.
+fn split_registry(repo: &str) -> (String, String) {
+    const DOCKERHUB: &str = "registry-1.docker.io";
+    match repo.split_once('/') {
+        Some((host, path)) if host.contains('.') || host.contains(':') || host == "localhost" => {
+            (host.to_string(), path.to_string())
+        }
+        Some(_) => (DOCKERHUB.to_string(), repo.to_string()),
+        None => (DOCKERHUB.to_string(), format!("library/{repo}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_suffix_tag() {
+        assert_eq!(
+            split_suffix("quay.io/projectquay/clair:nightly"),
+            ("quay.io/projectquay/clair", ":nightly")
+        );
+    }
+
+    #[test]
+    fn split_suffix_digest() {
+        assert_eq!(
+            split_suffix("quay.io/projectquay/clair@sha256:abc"),
+            ("quay.io/projectquay/clair", "@sha256:abc")
+        );
+    }
+
+    #[test]
+    fn split_suffix_port_no_tag() {
+        assert_eq!(
+            split_suffix("localhost:5000/clair"),
+            ("localhost:5000/clair", "")
+        );
+    }
+
+    #[test]
+    fn split_registry_qualified_host() {
+        assert_eq!(
+            split_registry("quay.io/projectquay/clair"),
+            ("quay.io".to_string(), "projectquay/clair".to_string())
+        );
+    }
+
+    #[test]
+    fn split_registry_unqualified_defaults_to_dockerhub() {
+        assert_eq!(
+            split_registry("clair"),
+            (
+                "registry-1.docker.io".to_string(),
+                "library/clair".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn split_registry_namespaced_defaults_to_dockerhub() {
+        assert_eq!(
+            split_registry("projectquay/clair"),
+            (
+                "registry-1.docker.io".to_string(),
+                "projectquay/clair".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn parse_bearer_challenge_quay() {
+        let params = parse_bearer_challenge(
+            r#"Bearer realm="https://quay.io/v2/auth",service="quay.io",scope="repository:projectquay/clair:pull""#,
+        )
+        .expect("Bearer challenge");
+        assert_eq!(params.get("realm").map(String::as_str), Some("https://quay.io/v2/auth"));
+        assert_eq!(params.get("service").map(String::as_str), Some("quay.io"));
+        assert_eq!(
+            params.get("scope").map(String::as_str),
+            Some("repository:projectquay/clair:pull")
+        );
+    }
+
+    #[test]
+    fn parse_bearer_challenge_rejects_basic() {
+        assert!(parse_bearer_challenge(r#"Basic realm="registry""#).is_none());
+    }
+}
@@ -1,19 +1,25 @@
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::net::SocketAddr;
 use std::pin::Pin;
-use std::sync::OnceLock;
+use std::sync::{LazyLock, Mutex, OnceLock};
 
-use k8s_openapi::DeepMerge;
+use k8s_openapi::{apimachinery::pkg::api::resource::Quantity, DeepMerge};
 use kube::{
     core::object::{HasSpec, HasStatus},
+    core::GroupVersionKind,
     core::Object,
-    runtime::controller::Error as CtrlErr,
+    runtime::{
+        controller::Error as CtrlErr,
+        finalizer::{finalizer, Event as Finalizer},
+    },
     CustomResourceExt, Resource, ResourceExt,
 };
 use serde::{de::DeserializeOwned, Serialize};
 use tokio::{
     signal::unix::{signal, SignalKind},
-    time::Duration,
+    time::{sleep, Duration, Instant},
 };
 use tokio_stream::wrappers::SignalStream;
 
@@ -29,6 +35,11 @@ pub enum HookResult {
     Continue,
     /// Return indicates that the function should return a result immediately.
     Return(bool),
+    /// Requeue indicates the step isn't done or failed, just not ready yet --- e.g. a `Creation`
+    /// hook waiting on a dependency it doesn't own --- and the controller should come back to
+    /// this object after the given [`Duration`] instead of reporting success or failure. See
+    /// [`Request::request_requeue`].
+    Requeue(Duration),
 }
 
 /// HookFunc is the type for a hook function.
@@ -56,20 +67,105 @@ pub enum Hook {
     HPA,
     /// Hook the `check_creation` step.
     Creation,
+    /// Hook the finalizer `Cleanup` teardown step.
+    Cleanup,
 }
 
 /// HookMap holds HookFuncs.
 type HookMap<Obj, Status> = HashMap<Hook, HookFunc<Obj, Status>>;
 
+/// CUSTOM_METRICS_GVK identifies the aggregated custom-metrics API, used to probe (via
+/// [`Context::gvk_exists`]) whether a cluster can serve request-per-second metrics for
+/// [`Controller::check_hpa`] to scale on.
+static CUSTOM_METRICS_GVK: LazyLock<GroupVersionKind> = LazyLock::new(|| GroupVersionKind {
+    group: "custom.metrics.k8s.io".into(),
+    version: "v1beta2".into(),
+    kind: "MetricValueList".into(),
+});
+
+/// Backoff is the retry/requeue policy carried on [`HookContext`]: commit-retry loops sleep
+/// between attempts instead of hot-looping against a persistently rejecting API server, and
+/// [`Controller::publish`]/[`Controller::handle_error`] scale their requeue delay by how many
+/// times in a row the object has failed.
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    /// Base is the delay before the first retry.
+    pub base: Duration,
+    /// Max caps the computed delay, however many attempts have accumulated.
+    pub max: Duration,
+    /// Multiplier is applied once per attempt after the first: `base * multiplier^(attempt - 1)`,
+    /// capped at `max`.
+    pub multiplier: f64,
+    /// Jitter adds up to this fraction of the computed delay, spread deterministically by the
+    /// caller-supplied key so that objects failing in lockstep don't all retry in lockstep too.
+    pub jitter: f64,
+    /// Max_attempts bounds the commit-retry loops in `check_dropin`/`check_deployment`/
+    /// `check_service`/`check_hpa`/`cleanup` before they give up on this reconcile pass.
+    pub max_attempts: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_millis(250),
+            max: Duration::from_secs(5 * 60),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: 3,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay returns how long to wait after `attempt` consecutive failed attempts (`base` after
+    /// the first), jittered by up to `jitter` of the computed delay and seeded by `key` so
+    /// repeated calls for the same key+attempt return a stable offset rather than a different one
+    /// on every call.
+    fn delay(&self, attempt: u32, key: &str) -> Duration {
+        let scale = self
+            .multiplier
+            .powi(attempt.saturating_sub(1).min(16) as i32)
+            .max(1.0);
+        let delay = self.base.mul_f64(scale).min(self.max);
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (key, attempt).hash(&mut hasher);
+        let frac = (hasher.finish() % 1000) as f64 / 1000.0;
+        delay.mul_f64(1.0 + self.jitter * frac).min(self.max)
+    }
+}
+
 struct HookContext<Obj, Status> {
     hooks: HookMap<Obj, Status>,
     context: Context,
+    backoff: Backoff,
+    /// Failures tracks, per object (keyed by `namespace/name`), how many consecutive times in a
+    /// row that object's reconcile has failed, so [`Self::note_failure`]'s caller can scale its
+    /// requeue delay instead of using a flat duration. Reset by [`Self::note_success`] once a
+    /// reconcile actually succeeds.
+    failures: Mutex<HashMap<String, u32>>,
 }
 
 impl<Obj, Status> HookContext<Obj, Status> {
     fn client(&self) -> kube::Client {
         self.context.client.clone()
     }
+
+    /// Note_failure bumps and returns the consecutive-failure count recorded for `key`.
+    fn note_failure(&self, key: &str) -> u32 {
+        let mut failures = self.failures.lock().unwrap();
+        let ct = failures.entry(key.to_string()).or_insert(0);
+        *ct += 1;
+        *ct
+    }
+
+    /// Note_success clears the consecutive-failure count recorded for `key`, so the next failure
+    /// (if any) backs off from `backoff.base` again instead of continuing to escalate.
+    fn note_success(&self, key: &str) {
+        self.failures.lock().unwrap().remove(key);
+    }
 }
 
 /// Controller configures and starts a controller for a Clair subresource.
@@ -78,6 +174,8 @@ pub fn controller<Obj, Status, Spec>(
     cancel: CancellationToken,
     ctx: Context,
     hooks: HookMap<Obj, Status>,
+    metrics_address: Option<SocketAddr>,
+    backoff: Backoff,
 ) -> Result<ControllerFuture>
 where
     Obj: Clone
@@ -88,12 +186,17 @@ where
         + HasSpec<Spec = Spec>
         + HasStatus<Status = Status>
         + Resource<Scope = kube::core::NamespaceResourceScope>
+        + Serialize
         + Send
         + Sync
         + 'static,
     Status: Clone + Default + Serialize + StatusCommon + SubStatusCommon + Send + 'static,
     Spec: Clone + Serialize + SpecCommon + SubSpecCommon + Send + 'static,
 {
+    if let Some(addr) = metrics_address {
+        crate::metrics::serve(addr)?;
+    }
+
     let client = ctx.client.clone();
     let ctlcfg = watcher::Config::default();
     let sig = SignalStream::new(signal(SignalKind::user_defined1())?);
@@ -120,6 +223,8 @@ where
     let ctx = Arc::new(HookContext {
         hooks,
         context: ctx,
+        backoff,
+        failures: Mutex::new(HashMap::new()),
     });
 
     Ok(async move {
@@ -128,14 +233,23 @@ where
             .for_each(|ret| {
                 match ret {
                     Ok(_) => (),
-                    Err(err) => match err {
-                        CtrlErr::ObjectNotFound(objref) => error!(%objref, "object not found"),
-                        CtrlErr::ReconcilerFailed(error, objref) => {
-                            error!(%objref, %error, "reconcile error")
+                    Err(err) => {
+                        let variant = match &err {
+                            CtrlErr::ObjectNotFound(_) => "object_not_found",
+                            CtrlErr::ReconcilerFailed(_, _) => "reconciler_failed",
+                            CtrlErr::QueueError(_) => "queue_error",
+                            CtrlErr::RunnerError(_) => "runner_error",
+                        };
+                        crate::metrics::record_handle_error(&Obj::kind(&()), variant);
+                        match err {
+                            CtrlErr::ObjectNotFound(objref) => error!(%objref, "object not found"),
+                            CtrlErr::ReconcilerFailed(error, objref) => {
+                                error!(%objref, %error, "reconcile error")
+                            }
+                            CtrlErr::QueueError(error) => error!(%error, "queue error"),
+                            CtrlErr::RunnerError(error) => error!(%error, "runner error"),
                         }
-                        CtrlErr::QueueError(error) => error!(%error, "queue error"),
-                        CtrlErr::RunnerError(error) => error!(%error, "runner error"),
-                    },
+                    }
                 };
                 futures::future::ready(())
             })
@@ -157,12 +271,18 @@ where
 
 impl<Obj, Status, Spec> Controller<Obj, Status, Spec>
 where
-    Obj: CrdCommon
+    Obj: Clone
+        + CrdCommon
         + CustomResourceExt
+        + std::fmt::Debug
         + DeserializeOwned
         + HasSpec<Spec = Spec>
         + HasStatus<Status = Status>
-        + Resource<Scope = kube::core::NamespaceResourceScope>,
+        + Resource<Scope = kube::core::NamespaceResourceScope>
+        + Serialize
+        + Send
+        + Sync
+        + 'static,
     Status: Clone + Default + Serialize + StatusCommon + SubStatusCommon,
     Spec: Clone + Serialize + SpecCommon + SubSpecCommon,
 {
@@ -171,6 +291,14 @@ where
         NAME.get_or_init(|| Obj::kind(&()).to_ascii_lowercase())
     }
 
+    /// Finalizer_name is the finalizer placed on `Obj` while it holds a dropin reference on its
+    /// owning [`v1alpha1::Clair`], so the reference is removed deterministically instead of
+    /// racing the apiserver's garbage collector.
+    fn finalizer_name() -> &'static str {
+        static NAME: OnceLock<String> = OnceLock::new();
+        NAME.get_or_init(|| crate::clair_label(format!("{}-dropin", Self::name())))
+    }
+
     fn lookup_name<T>(obj: &Obj) -> String
     where
         T: kube::Resource<DynamicType = ()>,
@@ -181,8 +309,47 @@ where
             .unwrap_or_else(|| format!("{}-{}", obj.name_any(), Self::name()))
     }
 
+    /// Obj_key identifies `obj` for [`HookContext::note_failure`]/[`HookContext::note_success`]
+    /// bookkeeping.
+    fn obj_key(obj: &Obj) -> String {
+        format!("{}/{}", obj.namespace().unwrap_or_default(), obj.name_any())
+    }
+
+    /// Note_retries_exhausted records a "RetriesExhausted" condition on `next`, so a `check_*`
+    /// function giving up after [`Backoff::max_attempts`] commit attempts shows up in status
+    /// instead of just silently stopping the check chain. [`Self::apply`] looks for this
+    /// condition after running the chain and requeues on [`Backoff`]'s schedule instead of
+    /// falling back to the default requeue interval.
+    fn note_retries_exhausted(req: &Request, obj: &Obj, next: &mut Status, detail: &str) {
+        next.add_condition(Condition {
+            last_transition_time: req.now(),
+            observed_generation: obj.meta().generation,
+            message: detail.into(),
+            reason: "RetriesExhausted".into(),
+            status: "True".into(),
+            type_: clair_condition("RetriesExhausted"),
+        });
+    }
+
     #[instrument(skip_all)]
     async fn reconcile(obj: Arc<Obj>, ctx: Arc<HookContext<Obj, Status>>) -> Result<Action> {
+        let mut timer = crate::metrics::ReconcileTimer::start(Self::name());
+        let api: Api<Obj> = Api::default_namespaced(ctx.client());
+        let inner = ctx.clone();
+        let ret = finalizer(&api, Self::finalizer_name(), obj, |event| async move {
+            match event {
+                Finalizer::Apply(obj) => Self::apply(obj, inner).await.map_err(Box::new),
+                Finalizer::Cleanup(obj) => Self::cleanup(obj, inner).await.map_err(Box::new),
+            }
+        })
+        .await
+        .map_err(|e| Error::Other(e.into()));
+        timer.finish(&ret);
+        ret
+    }
+
+    #[instrument(skip_all)]
+    async fn apply(obj: Arc<Obj>, ctx: Arc<HookContext<Obj, Status>>) -> Result<Action> {
         trace!("start");
         let req = Request::new(&ctx.client());
         assert!(obj.meta().name.is_some());
@@ -238,7 +405,9 @@ where
                 'checks: {
 $(
                     debug!(step = stringify!($fn), "running check");
+                    let step_start = Instant::now();
                     let cont = $fn(&obj, &ctx, &req, &mut next).await?;
+                    crate::metrics::record_step_duration(Self::name(), stringify!($fn), step_start.elapsed());
                     debug!(step = stringify!($fn), "continue" = cont, "ran check");
                     if !cont {
                         break 'checks
@@ -258,12 +427,107 @@ $(
         );
 
         trace!("done");
-        Self::publish(obj, ctx, req, next).await
+        let key = Self::obj_key(&obj);
+        let exhausted = next.get_conditions().iter().any(|c| {
+            c.type_ == clair_condition("RetriesExhausted") && c.status == "True"
+        });
+        let requested_requeue = req.requested_requeue();
+        let action = Self::publish(obj, ctx.clone(), req, next).await?;
+        if let Some(after) = requested_requeue {
+            // A hook asked to be checked back on at a specific time (see `HookResult::Requeue`)
+            // rather than reporting the step succeeded or failed; honor that ahead of whatever
+            // `publish` or the exhausted-retries fallback below would otherwise pick.
+            return Ok(Action::requeue(after));
+        }
+        if exhausted {
+            // A check gave up after exhausting its retries: requeue on the backoff schedule
+            // instead of whatever interval `publish` picked from the resource-version diff.
+            let failures = ctx.note_failure(&key);
+            return Ok(Action::requeue(ctx.backoff.delay(failures, &key)));
+        }
+        Ok(action)
     }
 
+    /// Cleanup runs on the finalizer's `Cleanup` event, removing the dropin reference
+    /// [`check_dropin`] placed on the owning `Clair`'s `spec.dropins`. The finalizer is only
+    /// dropped once `kube::runtime::finalizer::finalizer` sees this return `Ok`, so the owner
+    /// edit is guaranteed to have committed before the finalizer itself is removed.
     #[instrument(skip_all)]
-    fn handle_error(_obj: Arc<Obj>, _err: &Error, _ctx: Arc<HookContext<Obj, Status>>) -> Action {
-        Action::await_change()
+    async fn cleanup(obj: Arc<Obj>, ctx: Arc<HookContext<Obj, Status>>) -> Result<Action> {
+        if let Some(hook) = ctx.hooks.get(&Hook::Cleanup) {
+            trace!("hook exists, using it");
+            let req = Request::new(&ctx.client());
+            let mut next = Status::default();
+            let threshold = ctx.context.slow_step_threshold;
+            match with_poll_timer("CleanupHook", threshold, hook(&obj, &ctx.context, &req, &mut next)).await? {
+                HookResult::Continue => crate::metrics::record_hook_result(Self::name(), "Cleanup", "continue"),
+                HookResult::Return(_) => {
+                    crate::metrics::record_hook_result(Self::name(), "Cleanup", "return");
+                    return Ok(Action::await_change());
+                }
+                HookResult::Requeue(after) => {
+                    crate::metrics::record_hook_result(Self::name(), "Cleanup", "requeue");
+                    return Ok(Action::requeue(after));
+                }
+            }
+        }
+
+        let owner = match obj
+            .owner_references()
+            .iter()
+            .find(|&r| r.controller.unwrap_or(false))
+        {
+            None => {
+                trace!("not owned, nothing to clean up");
+                return Ok(Action::await_change());
+            }
+            Some(o) => o,
+        };
+        let name = Self::lookup_name::<ConfigMap>(&obj);
+        trace!(owner = owner.name, name, "removing dropin reference");
+
+        let api: Api<v1alpha1::Clair> = Api::default_namespaced(ctx.client());
+        let mut ct = 0;
+        while ct < ctx.backoff.max_attempts {
+            if ct > 0 {
+                sleep(ctx.backoff.delay(ct, &name)).await;
+            }
+            ct += 1;
+            let entry = match api.entry(&owner.name).await? {
+                Entry::Vacant(_) => {
+                    trace!("owning Clair already gone, nothing to clean up");
+                    return Ok(Action::await_change());
+                }
+                Entry::Occupied(e) => e,
+            };
+            let entry = entry.and_modify(|c| {
+                c.spec
+                    .dropins
+                    .retain(|d| !d.config_map_key_ref.as_ref().is_some_and(|c| c.name == name));
+            });
+            match with_poll_timer("CleanupCommit", ctx.context.slow_step_threshold, entry.commit(&CREATE_PARAMS)).await {
+                Ok(()) => {
+                    debug!("removed dropin reference from owning Clair");
+                    return Ok(Action::await_change());
+                }
+                Err(err) => match err {
+                    CommitError::Validate(reason) => {
+                        debug!(reason = reason.to_string(), "commit failed, retrying")
+                    }
+                    CommitError::Save(_) => return Err(Error::Commit(err)),
+                },
+            };
+        }
+        Err(Error::BadName(format!(
+            "failed to remove dropin reference for {name} after {ct} attempts"
+        )))
+    }
+
+    #[instrument(skip_all)]
+    fn handle_error(obj: Arc<Obj>, _err: &Error, ctx: Arc<HookContext<Obj, Status>>) -> Action {
+        let key = Self::obj_key(&obj);
+        let failures = ctx.note_failure(&key);
+        Action::requeue(ctx.backoff.delay(failures, &key))
     }
 
     #[instrument(skip_all)]
@@ -275,13 +539,17 @@ $(
     ) -> Result<Action> {
         let api: Api<Obj> = Api::default_namespaced(ctx.client());
         let name = obj.name_any();
+        let key = Self::obj_key(&obj);
 
         let prev = obj.meta().resource_version.clone().unwrap();
         let mut cur = None;
         let mut c = Object::new(&name, &Obj::api_resource(), None::<Spec>);
         c.status = Some(next);
         let mut ct = 0;
-        while ct < 3 {
+        while ct < ctx.backoff.max_attempts {
+            if ct > 0 {
+                sleep(ctx.backoff.delay(ct, &key)).await;
+            }
             c.metadata = obj.meta().clone();
             ct += 1;
             let buf = serde_json::to_vec(&c)?;
@@ -295,15 +563,20 @@ $(
         }
 
         if cur.is_none() {
-            // Unable to update, so requeue soon.
-            return Ok(Action::requeue(Duration::from_secs(5)));
+            // Unable to update, so requeue with a delay scaled by this object's consecutive
+            // failure count instead of a flat duration.
+            let failures = ctx.note_failure(&key);
+            crate::metrics::record_publish(Self::name(), ct, true);
+            return Ok(Action::requeue(ctx.backoff.delay(failures, &key)));
         }
         let cur = cur.unwrap();
+        ctx.note_success(&key);
+        crate::metrics::record_publish(Self::name(), ct, false);
 
         debug!(attempt = ct, prev, cur, "published status");
         if cur == prev {
-            // If there was no change, queue out in the future.
-            Ok(Action::requeue(Duration::from_secs(3600)))
+            // If there was no change, queue out at the backoff's max delay.
+            Ok(Action::requeue(ctx.backoff.max))
         } else {
             // Handled, so discard the event.
             Ok(Action::await_change())
@@ -311,6 +584,41 @@ $(
     }
 }
 
+/// Default_dropin synthesizes the initial per-component dropin ConfigMap for `obj`, templated the
+/// same way [`check_deployment`](Controller::check_deployment) and
+/// [`check_service`](Controller::check_service) template their own owned objects, then tags it
+/// with [`crate::DROPIN_LABEL`] so the rest of [`check_dropin`](Controller::check_dropin) can read
+/// back which key holds the managed dropin.
+async fn default_dropin<Obj>(
+    obj: &Obj,
+    flavor: v1alpha1::ConfigDialect,
+    ctx: &Context,
+) -> Result<(String, ConfigMap)>
+where
+    Obj: v1alpha1::CrdCommon,
+{
+    let key = format!("{flavor}.clair-conf");
+    let mut cm: ConfigMap = new_templated(obj, ctx).await?;
+    cm.annotations_mut()
+        .insert(crate::DROPIN_LABEL.to_string(), key.clone());
+    Ok((key, cm))
+}
+
+/// Resolve_values runs `f`, catching a panic instead of letting it unwind through the reconcile
+/// loop. `f` is where a malformed `ConfigSource` or image string (see [`SubSpecCommon::set_values`]
+/// and [`SpecCommon::image_default`]) would panic --- e.g. [`make_volumes`]'s asserts on a dropin
+/// with neither `config_map_key_ref` nor `secret_key_ref` set --- so one bad component spec
+/// degrades just that component instead of taking the whole controller down.
+fn resolve_values<R>(f: impl FnOnce() -> R) -> std::result::Result<R, String> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+        payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic while resolving subresource values".into())
+    })
+}
+
 impl<Obj, Status, Spec> Controller<Obj, Status, Spec>
 where
     Obj: Resource<Scope = kube::core::NamespaceResourceScope>
@@ -322,6 +630,24 @@ where
     Status: StatusCommon + SubStatusCommon + Default + Clone + Serialize,
     Spec: SpecCommon + SubSpecCommon + Clone + Serialize,
 {
+    /// Bad_values records `reason` as a `False` condition of type `type_` on `next`, discarding
+    /// any conditions a previous, successful reconcile left behind --- the caught panic means this
+    /// component's resolved values can't be trusted, so stale `True` conditions shouldn't survive
+    /// alongside it.
+    fn bad_values(next: &mut Status, obj: &Obj, req: &Request, type_: String, reason: String) {
+        StatusCommon::set_conditions(
+            next,
+            vec![Condition {
+                last_transition_time: req.now(),
+                observed_generation: obj.meta().generation,
+                message: reason,
+                reason: "InternalError".into(),
+                status: "False".into(),
+                type_,
+            }],
+        );
+    }
+
     #[instrument(skip_all)]
     pub async fn check_dropin(
         obj: &Obj,
@@ -331,9 +657,18 @@ where
     ) -> Result<bool> {
         if let Some(hook) = ctx.hooks.get(&Hook::Dropin) {
             trace!("hook exists, using it");
-            match hook(obj, &ctx.context, req, next).await? {
-                HookResult::Continue => (),
-                HookResult::Return(res) => return Ok(res),
+            let threshold = ctx.context.slow_step_threshold;
+            match with_poll_timer("DropinHook", threshold, hook(obj, &ctx.context, req, next)).await? {
+                HookResult::Continue => crate::metrics::record_hook_result(Self::name(), "Dropin", "continue"),
+                HookResult::Return(res) => {
+                    crate::metrics::record_hook_result(Self::name(), "Dropin", "return");
+                    return Ok(res);
+                }
+                HookResult::Requeue(after) => {
+                    crate::metrics::record_hook_result(Self::name(), "Dropin", "requeue");
+                    req.request_requeue(after);
+                    return Ok(false);
+                }
             }
         }
 
@@ -361,8 +696,12 @@ where
 
         let api: Api<ConfigMap> = Api::default_namespaced(ctx.client());
         let mut ct = 0;
-        while ct < 3 {
+        while ct < ctx.backoff.max_attempts {
+            if ct > 0 {
+                sleep(ctx.backoff.delay(ct, &name)).await;
+            }
             ct += 1;
+            crate::metrics::record_retry_attempt(Self::name(), "Dropin", ct);
             let entry = api.entry(&name).await?;
             let mut entry = match entry {
                 Entry::Occupied(e) => e,
@@ -380,6 +719,22 @@ where
                 }
             };
             let cm = entry.get_mut();
+            if let Some(selector) = obj.spec().template("ConfigMap") {
+                trace!(name = selector.name, key = selector.key, "user template found");
+                let tplcx = templates::UserTemplateContext {
+                    name: name.clone(),
+                    namespace: obj.namespace().unwrap_or_default(),
+                    image: obj.spec().image_default(&crate::DEFAULT_IMAGE),
+                    clair_conf: String::new(),
+                    clair_mode: Self::name().into(),
+                    config_dialect: Some(flavor.to_string()),
+                    volumes: Vec::new(),
+                    volume_mounts: Vec::new(),
+                };
+                let rendered: ConfigMap =
+                    templates::render_user_template(selector, &tplcx, &ctx.context).await?;
+                cm.merge_from(rendered);
+            }
             if let Some(k) = cm.annotations().get(crate::DROPIN_LABEL.as_str()) {
                 if let Some(data) = cm.data.as_ref() {
                     if !data.contains_key(k) {
@@ -405,14 +760,21 @@ where
                 return Ok(true);
             };
             next.add_ref(cm);
-            match entry.commit(&CREATE_PARAMS).await {
-                Ok(()) => (),
+            match with_poll_timer("DropinCommit", ctx.context.slow_step_threshold, entry.commit(&CREATE_PARAMS)).await {
+                Ok(()) => {
+                    let outcome = if ct == 1 { "created" } else { "validated" };
+                    crate::metrics::record_commit_outcome(Self::name(), "Dropin", outcome);
+                }
                 Err(err) => match err {
                     CommitError::Validate(reason) => {
+                        crate::metrics::record_commit_outcome(Self::name(), "Dropin", "retried");
                         debug!(reason = reason.to_string(), "commit failed, retrying");
                         continue;
                     }
-                    CommitError::Save(_) => return Err(Error::Commit(err)),
+                    CommitError::Save(_) => {
+                        crate::metrics::record_commit_outcome(Self::name(), "Dropin", "save-error");
+                        return Err(Error::Commit(err));
+                    }
                 },
             };
 
@@ -451,21 +813,28 @@ where
                         debug!("no update needed");
                         return Ok(true);
                     }
-                    match entry.commit(&CREATE_PARAMS).await {
+                    match with_poll_timer("DropinCommit", ctx.context.slow_step_threshold, entry.commit(&CREATE_PARAMS)).await {
                         Ok(()) => {
+                            let outcome = if ct == 1 { "created" } else { "validated" };
+                            crate::metrics::record_commit_outcome(Self::name(), "Dropin", outcome);
                             debug!("updated owning Clair");
                             return Ok(true);
                         }
                         Err(err) => match err {
                             CommitError::Validate(reason) => {
+                                crate::metrics::record_commit_outcome(Self::name(), "Dropin", "retried");
                                 debug!(reason = reason.to_string(), "commit failed, retrying")
                             }
-                            CommitError::Save(_) => return Err(Error::Commit(err)),
+                            CommitError::Save(_) => {
+                                crate::metrics::record_commit_outcome(Self::name(), "Dropin", "save-error");
+                                return Err(Error::Commit(err));
+                            }
                         },
                     };
                 }
             };
         }
+        Self::note_retries_exhausted(req, obj, next, "exhausted retries reconciling dropin");
         Ok(false)
     }
 
@@ -478,9 +847,18 @@ where
     ) -> Result<bool> {
         if let Some(hook) = ctx.hooks.get(&Hook::Config) {
             trace!("hook exists, using it");
-            match hook(obj, &ctx.context, req, next).await? {
-                HookResult::Continue => (),
-                HookResult::Return(res) => return Ok(res),
+            let threshold = ctx.context.slow_step_threshold;
+            match with_poll_timer("ConfigHook", threshold, hook(obj, &ctx.context, req, next)).await? {
+                HookResult::Continue => crate::metrics::record_hook_result(Self::name(), "Config", "continue"),
+                HookResult::Return(res) => {
+                    crate::metrics::record_hook_result(Self::name(), "Config", "return");
+                    return Ok(res);
+                }
+                HookResult::Requeue(after) => {
+                    crate::metrics::record_hook_result(Self::name(), "Config", "requeue");
+                    req.request_requeue(after);
+                    return Ok(false);
+                }
             }
         }
 
@@ -500,11 +878,21 @@ where
     ) -> Result<bool> {
         if let Some(hook) = ctx.hooks.get(&Hook::Deployment) {
             trace!("hook exists, using it");
-            match hook(obj, &ctx.context, req, next).await? {
-                HookResult::Continue => (),
-                HookResult::Return(res) => return Ok(res),
+            let threshold = ctx.context.slow_step_threshold;
+            match with_poll_timer("DeploymentHook", threshold, hook(obj, &ctx.context, req, next)).await? {
+                HookResult::Continue => crate::metrics::record_hook_result(Self::name(), "Deployment", "continue"),
+                HookResult::Return(res) => {
+                    crate::metrics::record_hook_result(Self::name(), "Deployment", "return");
+                    return Ok(res);
+                }
+                HookResult::Requeue(after) => {
+                    crate::metrics::record_hook_result(Self::name(), "Deployment", "requeue");
+                    req.request_requeue(after);
+                    return Ok(false);
+                }
             }
         }
+        let backoff = ctx.backoff.clone();
         let ctx = &ctx.context;
         use self::apps::v1::Deployment;
         use self::core::v1::EnvVar;
@@ -516,12 +904,28 @@ where
             .ok_or(Error::BadName("missing needed spec field: config".into()))?;
         trace!("have configsource");
         let api = Api::<Deployment>::default_namespaced(ctx.client.clone());
-        let want_image = spec.image_default(&crate::DEFAULT_IMAGE);
+        let (want_image, root_vols, root_mounts, config) =
+            match resolve_values(|| {
+                let want_image = spec.image_default(&crate::DEFAULT_IMAGE);
+                let (vols, mounts, config) = make_volumes(cfgsrc);
+                (want_image, vols, mounts, config)
+            }) {
+                Ok(v) => v,
+                Err(reason) => {
+                    Self::bad_values(next, obj, req, clair_condition("DeploymentOK"), reason);
+                    return Ok(false);
+                }
+            };
 
         let mut ct = 0;
-        while ct < 3 {
+        let mut ok = false;
+        while ct < backoff.max_attempts {
+            if ct > 0 {
+                sleep(backoff.delay(ct, &name)).await;
+            }
             ct += 1;
             trace!(ct, "reconcile attempt");
+            crate::metrics::record_retry_attempt(Self::name(), "Deployment", ct);
             let entry = api.entry(&name).await?;
             let mut entry = match entry {
                 Entry::Occupied(e) => e,
@@ -535,7 +939,17 @@ where
             trace!("checking deployment");
             d.labels_mut()
                 .insert(COMPONENT_LABEL.to_string(), Self::name().into());
-            let (mut vols, mut mounts, config) = make_volumes(cfgsrc);
+            let (mut vols, mut mounts, config) = (root_vols.clone(), root_mounts.clone(), config.clone());
+            let tplcx = templates::UserTemplateContext {
+                name: name.clone(),
+                namespace: obj.namespace().unwrap_or_default(),
+                image: want_image.clone(),
+                clair_conf: config.clone(),
+                clair_mode: Self::name().into(),
+                config_dialect: None,
+                volumes: vols.clone(),
+                volume_mounts: mounts.clone(),
+            };
             if let Some(ref mut spec) = d.spec {
                 if spec.selector.match_labels.is_none() {
                     spec.selector.match_labels = Some(Default::default());
@@ -593,22 +1007,40 @@ where
                 }
                 trace!(?spec, "deployment spec");
             };
+            if let Some(selector) = spec.template("Deployment") {
+                trace!(name = selector.name, key = selector.key, "user template found");
+                let rendered: Deployment =
+                    templates::render_user_template(selector, &tplcx, ctx).await?;
+                d.merge_from(rendered);
+            }
             next.add_ref(d);
-            match entry.commit(&CREATE_PARAMS).await {
-                Ok(()) => break,
+            match with_poll_timer("DeploymentCommit", ctx.slow_step_threshold, entry.commit(&CREATE_PARAMS)).await {
+                Ok(()) => {
+                    let outcome = if ct == 1 { "created" } else { "validated" };
+                    crate::metrics::record_commit_outcome(Self::name(), "Deployment", outcome);
+                    ok = true;
+                    break;
+                }
                 Err(err) => {
                     trace!(error = ?err, "commit error");
                     match err {
                         CommitError::Validate(reason) => {
+                            crate::metrics::record_commit_outcome(Self::name(), "Deployment", "retried");
                             debug!(reason = reason.to_string(), "commit failed, retrying")
                         }
-                        CommitError::Save(_) => return Err(Error::Commit(err)),
+                        CommitError::Save(_) => {
+                            crate::metrics::record_commit_outcome(Self::name(), "Deployment", "save-error");
+                            return Err(Error::Commit(err));
+                        }
                     };
                 }
             };
         }
         trace!(ct, "reconciled");
-        Ok(ct != 3)
+        if !ok {
+            Self::note_retries_exhausted(req, obj, next, "exhausted retries reconciling Deployment");
+        }
+        Ok(ok)
     }
 
     #[instrument(skip_all)]
@@ -620,20 +1052,48 @@ where
     ) -> Result<bool> {
         if let Some(hook) = ctx.hooks.get(&Hook::Service) {
             trace!("hook exists, using it");
-            match hook(obj, &ctx.context, req, next).await? {
-                HookResult::Continue => (),
-                HookResult::Return(res) => return Ok(res),
+            let threshold = ctx.context.slow_step_threshold;
+            match with_poll_timer("ServiceHook", threshold, hook(obj, &ctx.context, req, next)).await? {
+                HookResult::Continue => crate::metrics::record_hook_result(Self::name(), "Service", "continue"),
+                HookResult::Return(res) => {
+                    crate::metrics::record_hook_result(Self::name(), "Service", "return");
+                    return Ok(res);
+                }
+                HookResult::Requeue(after) => {
+                    crate::metrics::record_hook_result(Self::name(), "Service", "requeue");
+                    req.request_requeue(after);
+                    return Ok(false);
+                }
             }
         }
+        let backoff = ctx.backoff.clone();
         let ctx = &ctx.context;
         use self::core::v1::Service;
 
         let name = Self::lookup_name::<Service>(obj);
         let api = Api::<Service>::default_namespaced(ctx.client.clone());
+        let spec = obj.spec();
+        let (want_image, vols, mounts, config) = match resolve_values(|| {
+            let want_image = spec.image_default(&crate::DEFAULT_IMAGE);
+            let (vols, mounts, config) = SubSpecCommon::get_config(spec)
+                .map(make_volumes)
+                .unwrap_or_default();
+            (want_image, vols, mounts, config)
+        }) {
+            Ok(v) => v,
+            Err(reason) => {
+                Self::bad_values(next, obj, req, clair_condition("ServiceOK"), reason);
+                return Ok(false);
+            }
+        };
 
         let mut ok = false;
-        for ct in 0..3 {
+        for ct in 0..backoff.max_attempts {
+            if ct > 0 {
+                sleep(backoff.delay(ct, &name)).await;
+            }
             trace!(ct, "reconcile attempt");
+            crate::metrics::record_retry_attempt(Self::name(), "Service", ct);
             let mut entry = match api.entry(&name).await? {
                 Entry::Occupied(e) => e,
                 Entry::Vacant(e) => {
@@ -644,9 +1104,27 @@ where
                 }
             };
 
+            if let Some(selector) = spec.template("Service") {
+                trace!(name = selector.name, key = selector.key, "user template found");
+                let tplcx = templates::UserTemplateContext {
+                    name: name.clone(),
+                    namespace: obj.namespace().unwrap_or_default(),
+                    image: want_image.clone(),
+                    clair_conf: config.clone(),
+                    clair_mode: Self::name().into(),
+                    config_dialect: None,
+                    volumes: vols.clone(),
+                    volume_mounts: mounts.clone(),
+                };
+                let rendered: Service =
+                    templates::render_user_template(selector, &tplcx, ctx).await?;
+                entry.get_mut().merge_from(rendered);
+            }
             next.add_ref(entry.get());
-            match entry.commit(&CREATE_PARAMS).await {
+            match with_poll_timer("ServiceCommit", ctx.slow_step_threshold, entry.commit(&CREATE_PARAMS)).await {
                 Ok(()) => {
+                    let outcome = if ct == 0 { "created" } else { "validated" };
+                    crate::metrics::record_commit_outcome(Self::name(), "Service", outcome);
                     ok = true;
                     break;
                 }
@@ -654,14 +1132,21 @@ where
                     trace!(error = ?err, "commit error");
                     match err {
                         CommitError::Validate(reason) => {
+                            crate::metrics::record_commit_outcome(Self::name(), "Service", "retried");
                             debug!(reason = reason.to_string(), "commit failed, retrying")
                         }
-                        CommitError::Save(_) => return Err(Error::Commit(err)),
+                        CommitError::Save(_) => {
+                            crate::metrics::record_commit_outcome(Self::name(), "Service", "save-error");
+                            return Err(Error::Commit(err));
+                        }
                     };
                 }
             };
         }
         trace!("reconciled");
+        if !ok {
+            Self::note_retries_exhausted(req, obj, next, "exhausted retries reconciling Service");
+        }
         Ok(ok)
     }
 
@@ -674,22 +1159,82 @@ where
     ) -> Result<bool> {
         if let Some(hook) = ctx.hooks.get(&Hook::HPA) {
             trace!("hook exists, using it");
-            match hook(obj, &ctx.context, req, next).await? {
-                HookResult::Continue => (),
-                HookResult::Return(res) => return Ok(res),
+            let threshold = ctx.context.slow_step_threshold;
+            match with_poll_timer("HPAHook", threshold, hook(obj, &ctx.context, req, next)).await? {
+                HookResult::Continue => crate::metrics::record_hook_result(Self::name(), "HPA", "continue"),
+                HookResult::Return(res) => {
+                    crate::metrics::record_hook_result(Self::name(), "HPA", "return");
+                    return Ok(res);
+                }
+                HookResult::Requeue(after) => {
+                    crate::metrics::record_hook_result(Self::name(), "HPA", "requeue");
+                    req.request_requeue(after);
+                    return Ok(false);
+                }
             }
         }
+        let backoff = ctx.backoff.clone();
         let ctx = &ctx.context;
         use self::apps::v1::Deployment;
-        use self::autoscaling::v2::HorizontalPodAutoscaler;
+        use self::autoscaling::v2::{
+            HorizontalPodAutoscaler, MetricIdentifier, MetricSpec, MetricTarget, PodsMetricSource,
+        };
 
         let name = Self::lookup_name::<HorizontalPodAutoscaler>(obj);
         let dname = Self::lookup_name::<Deployment>(obj);
         let api = Api::<HorizontalPodAutoscaler>::default_namespaced(ctx.client.clone());
 
+        // Decide once, up front, which metric source to scale on: an operator-supplied
+        // `spec.metrics` wins outright; otherwise probe for the custom metrics API (the frontend's
+        // request-per-second counter rides on `Pods` metrics, same shape as any other labelled
+        // counter family exposed through that aggregated API) and fall back to the HPA's default
+        // CPU-utilization `Resource` metric if it isn't being served.
+        let autoscaling = obj.spec().autoscaling();
+        let user_defined = autoscaling.map(|a| !a.metrics.is_empty()).unwrap_or(false);
+        let (metric, mode) = if user_defined {
+            (None, "OperatorDefined")
+        } else if ctx.gvk_exists(&CUSTOM_METRICS_GVK).await {
+            let metric_name = autoscaling
+                .and_then(|a| a.request_rate_metric_name.clone())
+                .unwrap_or_else(|| "http_requests_per_second".into());
+            let target = autoscaling
+                .and_then(|a| a.request_rate_target.clone())
+                .unwrap_or_else(|| Quantity("100".into()));
+            let metric = MetricSpec {
+                type_: "Pods".into(),
+                pods: Some(PodsMetricSource {
+                    metric: MetricIdentifier {
+                        name: metric_name,
+                        selector: None,
+                    },
+                    target: MetricTarget {
+                        type_: "AverageValue".into(),
+                        average_value: Some(target),
+                        ..Default::default()
+                    },
+                }),
+                ..Default::default()
+            };
+            (Some(metric), "CustomMetrics")
+        } else {
+            (None, "CPUUtilization")
+        };
+        next.add_condition(Condition {
+            last_transition_time: req.now(),
+            observed_generation: obj.meta().generation,
+            message: format!("scaling on {mode} metrics"),
+            reason: mode.into(),
+            status: "True".into(),
+            type_: clair_condition("HPAMetrics"),
+        });
+
         let mut ok = false;
-        for n in 0..3 {
+        for n in 0..backoff.max_attempts {
+            if n > 0 {
+                sleep(backoff.delay(n, &name)).await;
+            }
             trace!(n, "reconcile attempt");
+            crate::metrics::record_retry_attempt(Self::name(), "HPA", n);
             let mut entry = api
                 .entry(&name)
                 .await?
@@ -701,14 +1246,17 @@ where
                         .insert(COMPONENT_LABEL.to_string(), Self::name().into());
                     if let Some(ref mut spec) = h.spec {
                         spec.scale_target_ref.name = dname.clone();
+                        if let Some(ref metric) = metric {
+                            spec.metrics = Some(vec![metric.clone()]);
+                        }
                     };
-                    // TODO(hank) Check if the metrics API is enabled and if the frontend supports
-                    // request-per-second metrics.
                 });
 
             next.add_ref(entry.get());
-            match entry.commit(&CREATE_PARAMS).await {
+            match with_poll_timer("HPACommit", ctx.slow_step_threshold, entry.commit(&CREATE_PARAMS)).await {
                 Ok(()) => {
+                    let outcome = if n == 0 { "created" } else { "validated" };
+                    crate::metrics::record_commit_outcome(Self::name(), "HPA", outcome);
                     ok = true;
                     break;
                 }
@@ -716,14 +1264,21 @@ where
                     trace!(error = ?err, "commit error");
                     match err {
                         CommitError::Validate(reason) => {
+                            crate::metrics::record_commit_outcome(Self::name(), "HPA", "retried");
                             debug!(reason = reason.to_string(), "commit failed, retrying")
                         }
-                        CommitError::Save(_) => return Err(Error::Commit(err)),
+                        CommitError::Save(_) => {
+                            crate::metrics::record_commit_outcome(Self::name(), "HPA", "save-error");
+                            return Err(Error::Commit(err));
+                        }
                     };
                 }
             };
         }
         trace!("reconciled");
+        if !ok {
+            Self::note_retries_exhausted(req, obj, next, "exhausted retries reconciling HPA");
+        }
         Ok(ok)
     }
 
@@ -736,9 +1291,18 @@ where
     ) -> Result<bool> {
         if let Some(hook) = ctx.hooks.get(&Hook::Creation) {
             trace!("hook exists, using it");
-            match hook(obj, &ctx.context, req, next).await? {
-                HookResult::Continue => (),
-                HookResult::Return(res) => return Ok(res),
+            let threshold = ctx.context.slow_step_threshold;
+            match with_poll_timer("CreationHook", threshold, hook(obj, &ctx.context, req, next)).await? {
+                HookResult::Continue => crate::metrics::record_hook_result(Self::name(), "Creation", "continue"),
+                HookResult::Return(res) => {
+                    crate::metrics::record_hook_result(Self::name(), "Creation", "return");
+                    return Ok(res);
+                }
+                HookResult::Requeue(after) => {
+                    crate::metrics::record_hook_result(Self::name(), "Creation", "requeue");
+                    req.request_requeue(after);
+                    return Ok(false);
+                }
             }
         }
         use self::apps::v1::Deployment;
@@ -0,0 +1,82 @@
+//! Supervisor restarts a controller's reconcile loop, with exponential backoff, if it panics or
+//! its stream ends before it's told to shut down --- instead of silently losing a controller ---
+//! and rolls each restart into [`Context`] so it shows up on `/diagnostics` and as a Kubernetes
+//! `Event`.
+
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::{Context, ControllerFuture, Result};
+
+/// Base_backoff is how long the supervisor waits before the first restart attempt.
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+/// Max_backoff caps the exponential backoff between restarts.
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// Supervise drives `make`'s controller future to completion, restarting it with exponential
+/// backoff if it panics or returns before `cancel` is triggered. `make` is called again for every
+/// restart, since a [`ControllerFuture`] can only be polled to completion once; it should be a
+/// cheap closure over clones of whatever the controller constructor needs (usually a
+/// `CancellationToken` and an `Arc<Context>`).
+///
+/// `name` tags this worker in [`Diagnostics::workers`](crate::Diagnostics::workers) and in the
+/// `Warning` events/`ControllerHealthy` condition recorded on restart.
+pub fn supervise<F>(
+    name: String,
+    cancel: CancellationToken,
+    ctx: Arc<Context>,
+    make: F,
+) -> ControllerFuture
+where
+    F: Fn() -> Result<ControllerFuture> + Send + Sync + 'static,
+{
+    Box::pin(async move {
+        let mut restarts = 0u32;
+        loop {
+            let fut = match make() {
+                Ok(fut) => fut,
+                Err(err) => {
+                    error!(worker = name, %err, "supervised worker failed to start, giving up");
+                    if let Err(err) = ctx.record_worker_failed(&name, &err.to_string()).await {
+                        error!(worker = name, %err, "failed to record worker failure");
+                    }
+                    return Err(err);
+                }
+            };
+            ctx.record_worker_running(&name).await;
+            let outcome = tokio::spawn(fut).await;
+
+            if cancel.is_cancelled() {
+                info!(worker = name, "supervised worker shut down");
+                return Ok(());
+            }
+
+            let error = match outcome {
+                Ok(Ok(())) => "reconcile stream ended unexpectedly".to_string(),
+                Ok(Err(err)) => err.to_string(),
+                Err(join_err) if join_err.is_panic() => format!("panicked: {join_err}"),
+                Err(join_err) => format!("terminated: {join_err}"),
+            };
+
+            restarts += 1;
+            let backoff = BASE_BACKOFF
+                .saturating_mul(1 << (restarts - 1).min(16))
+                .min(MAX_BACKOFF);
+            warn!(worker = name, restarts, ?backoff, %error, "supervised worker stopped, restarting");
+            if let Err(err) = ctx.record_worker_restart(&name, restarts, &error).await {
+                error!(worker = name, %err, "failed to record worker restart");
+            }
+
+            tokio::select! {
+                _ = sleep(backoff) => {}
+                _ = cancel.cancelled() => {
+                    info!(worker = name, "cancelled while backing off, not restarting");
+                    return Ok(());
+                }
+            }
+        }
+    })
+}
@@ -1,10 +1,18 @@
-use std::{borrow::Cow, collections::HashMap};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+};
 
+use handlebars::Handlebars;
 use k8s_openapi::serde;
 use lazy_static::lazy_static;
 use tracing::trace;
 
-// TODO(hank) Set up compile-time compression for these assets.
+// In debug builds, `Asset::get_bytes` reads the raw file straight off disk on every call. In
+// release builds it's backed by `build.rs`'s `compress_assets`, which mirrors `etc/` into
+// `$OUT_DIR/compressed-etc/` with every file zstd-compressed, so the release binary embeds the
+// compressed form instead of the raw one; see `decode_bytes` below for the matching unpack step.
+#[cfg(debug_assertions)]
 #[iftree::include_file_tree(
     "
 paths = '''
@@ -21,6 +29,38 @@ pub struct Asset {
     pub get_bytes: fn() -> Cow<'static, [u8]>,
 }
 
+#[cfg(not(debug_assertions))]
+#[iftree::include_file_tree(
+    "
+paths = '''
+**
+!tests
+!README.md
+'''
+base_folder = '${OUT_DIR}/compressed-etc/'
+#template.identifiers = false
+"
+)]
+pub struct Asset {
+    relative_path: &'static str,
+    pub get_bytes: fn() -> Cow<'static, [u8]>,
+}
+
+/// Unpacks an asset as produced by [`Asset::get_bytes`]. In release builds the embedded bytes are
+/// zstd-compressed, so this decompresses them; in debug builds `get_bytes` already returns the raw
+/// file, so it's a pass-through. Called once per asset while populating `TEMPLATES`/`DROPINS`
+/// below, so the decompression itself is amortized by those `lazy_static`s rather than redone on
+/// every lookup.
+#[cfg(not(debug_assertions))]
+fn decode_bytes(raw: Cow<'static, [u8]>) -> Cow<'static, [u8]> {
+    Cow::Owned(zstd::stream::decode_all(raw.as_ref()).expect("embedded asset is valid zstd"))
+}
+
+#[cfg(debug_assertions)]
+fn decode_bytes(raw: Cow<'static, [u8]>) -> Cow<'static, [u8]> {
+    raw
+}
+
 lazy_static! {
     static ref TEMPLATES: HashMap<String, Cow<'static, [u8]>> = {
         ASSETS
@@ -28,7 +68,7 @@ lazy_static! {
             .filter_map(|a| {
                 a.relative_path
                     .strip_prefix("templates/")
-                    .map(|p| (p.to_string(), (a.get_bytes)()))
+                    .map(|p| (p.to_string(), decode_bytes((a.get_bytes)())))
             })
             .collect()
     };
@@ -37,7 +77,7 @@ lazy_static! {
             .iter()
             .filter_map(|a| {
                 if a.relative_path.ends_with("_dropin.json-patch") {
-                    Some((a.relative_path.to_string(), (a.get_bytes)()))
+                    Some((a.relative_path.to_string(), decode_bytes((a.get_bytes)())))
                 } else {
                     None
                 }
@@ -54,7 +94,31 @@ const FROM_DISK: bool = true;
 #[cfg(not(debug_assertions))]
 const FROM_DISK: bool = false;
 
+/// RenderContext carries the values substituted into a template before it's parsed, so one
+/// checked-in `{kind}.yaml` can serve every namespace/image/component permutation instead of
+/// needing a `{kind}-{variant}.yaml-patch` for each one.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct RenderContext {
+    pub namespace: String,
+    pub component: String,
+    pub image: String,
+    pub version: String,
+    pub labels: BTreeMap<String, String>,
+    pub annotations: BTreeMap<String, String>,
+}
+
 pub async fn resource_for<S, K>(kind: S) -> Result<K, DynError>
+where
+    S: AsRef<str>,
+    K: kube::Resource<DynamicType = ()> + serde::de::DeserializeOwned,
+{
+    resource_for_ctx(kind, &RenderContext::default()).await
+}
+
+/// Resource_for_ctx is [`resource_for`], but first renders the base template through handlebars
+/// with `ctx` as the variable context (`{{ namespace }}`, `{{ image }}`, etc.), before applying
+/// the `{kind}-{variant}.yaml-patch` override as before.
+pub async fn resource_for_ctx<S, K>(kind: S, ctx: &RenderContext) -> Result<K, DynError>
 where
     S: AsRef<str>,
     K: kube::Resource<DynamicType = ()> + serde::de::DeserializeOwned,
@@ -71,10 +135,11 @@ where
         "looking for resources"
     );
 
-    let mut doc: Value = TEMPLATES
+    let template = TEMPLATES
         .get(&base_file)
-        .ok_or_else(|| -> DynError { format!("missing template: {base_file}").into() })
-        .map(|b| serde_yaml::from_slice(b))??;
+        .ok_or_else(|| -> DynError { format!("missing template: {base_file}").into() })?;
+    let rendered = Handlebars::new().render_template(&String::from_utf8_lossy(template), ctx)?;
+    let mut doc: Value = serde_yaml::from_str(&rendered)?;
     let patch: Option<Patch> = TEMPLATES
         .get(&patch_file)
         .and_then(|b| serde_yaml::from_slice(b).ok());
@@ -87,6 +152,10 @@ where
 }
 
 /// Returns as json.
+///
+/// A dropin may be a plain JSON-patch array, or an object declaring `"$include"`: a list of other
+/// dropins (resolved from `DROPINS`) whose operations are spliced in ahead of this dropin's own
+/// `"patch"` array, in declaration order. Includes compose recursively.
 pub async fn dropin_for<S>(kind: S) -> Result<Cow<'static, [u8]>, DynError>
 where
     S: AsRef<str>,
@@ -95,8 +164,52 @@ where
     let base_file = format!("{kind}_dropin.json-patch");
     trace!(base_file, embed = !FROM_DISK, "looking for resource");
 
-    DROPINS
-        .get(&base_file)
-        .map(Clone::clone)
-        .ok_or_else(|| -> DynError { format!("missing dropin: {base_file}").into() })
+    let mut stack = Vec::new();
+    let ops = compose_dropin(&base_file, &mut stack)?;
+    Ok(Cow::Owned(serde_json::to_vec(&ops)?))
+}
+
+/// Compose_dropin resolves `name` from `DROPINS`, splicing in any `"$include"`d dropins ahead of
+/// its own `"patch"` operations. `stack` is the chain of dropins currently being resolved, used to
+/// detect include cycles.
+fn compose_dropin(name: &str, stack: &mut Vec<String>) -> Result<Vec<serde_json::Value>, DynError> {
+    use serde_json::Value;
+
+    if stack.iter().any(|s| s == name) {
+        stack.push(name.to_string());
+        return Err(format!("dropin include cycle detected: {}", stack.join(" -> ")).into());
+    }
+    stack.push(name.to_string());
+
+    let bytes = DROPINS
+        .get(name)
+        .ok_or_else(|| -> DynError { format!("missing dropin: {name:?}").into() })?;
+    let doc: Value = serde_json::from_slice(bytes)?;
+
+    let ops = match doc {
+        Value::Array(ops) => ops,
+        Value::Object(mut obj) => {
+            let includes: Vec<String> = match obj.remove("$include") {
+                Some(v) => serde_json::from_value(v)?,
+                None => Vec::new(),
+            };
+            let mut ops = Vec::new();
+            for include in includes {
+                ops.extend(compose_dropin(&include, stack)?);
+            }
+            if let Some(Value::Array(own)) = obj.remove("patch") {
+                ops.extend(own);
+            }
+            ops
+        }
+        other => {
+            return Err(format!(
+                "malformed dropin {name:?}: expected a JSON-patch array or an object, got {other}"
+            )
+            .into());
+        }
+    };
+
+    stack.pop();
+    Ok(ops)
 }
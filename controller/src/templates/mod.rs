@@ -2,10 +2,17 @@
 
 use std::collections::HashMap;
 
-use k8s_openapi::api::core::v1::Service;
+use k8s_openapi::api::core::v1::{ConfigMap, Service, Volume, VolumeMount};
+use kube::Api;
 use serde;
 use serde_json::json;
 
+use api::v1alpha1::{ConfigMapKeySelector, CrdCommon, SpecCommon};
+
+use crate::{capabilities, Context, Error, Result};
+
+mod overlay;
+
 /// DEFAULT_CONFIG ...
 pub static DEFAULT_CONFIG: &str = include_str!("default_config.json");
 
@@ -35,10 +42,91 @@ where
     serde_json::to_string(&v).ok()
 }
 
+/// UserTemplateContext carries the values substituted into a user-supplied Handlebars template
+/// (see [`render_user_template`]), mirroring the fields `check_deployment`/`check_service`/
+/// `check_dropin` would otherwise hard-code, so a template only has to set the fields it wants to
+/// override.
+#[derive(Clone, Debug, Default, serde::Serialize)]
+pub struct UserTemplateContext {
+    /// Name is the generated object's name.
+    pub name: String,
+    /// Namespace is the generated object's namespace.
+    pub namespace: String,
+    /// Image is the resolved container image (see [`SpecCommon::image_default`]).
+    pub image: String,
+    /// ClairConf is the `CLAIR_CONF` environment value computed by
+    /// [`crate::make_volumes`](crate::make_volumes) for the owner's `ConfigSource`.
+    pub clair_conf: String,
+    /// ClairMode is the `CLAIR_MODE` environment value, i.e. the component name.
+    pub clair_mode: String,
+    /// ConfigDialect is the owning Clair's configured dialect, if any.
+    pub config_dialect: Option<String>,
+    /// Volumes is the volume set computed by [`crate::make_volumes`](crate::make_volumes) for the
+    /// owner's `ConfigSource`.
+    pub volumes: Vec<Volume>,
+    /// VolumeMounts is the mount set computed alongside `volumes`.
+    pub volume_mounts: Vec<VolumeMount>,
+}
+
+/// Render_user_template fetches the ConfigMap key `selector` points at, renders it as a
+/// Handlebars template using `tplcx` as the variable context (`{{ name }}`, `{{ image }}`, etc.),
+/// and parses the result as YAML into `K`.
+///
+/// Callers `DeepMerge`/`merge_from` the result over their hardcoded default, so a template only
+/// needs to set the fields it wants to override --- pod security context, resource requests, node
+/// selectors, extra volumes, and the like.
+pub async fn render_user_template<K>(
+    selector: &ConfigMapKeySelector,
+    tplcx: &UserTemplateContext,
+    ctx: &Context,
+) -> Result<K>
+where
+    K: serde::de::DeserializeOwned,
+{
+    let api: Api<ConfigMap> = Api::default_namespaced(ctx.client.clone());
+    let cm = api.get(&selector.name).await?;
+    let tpl = cm
+        .data
+        .as_ref()
+        .and_then(|d| d.get(&selector.key))
+        .ok_or_else(|| {
+            Error::BadName(format!(
+                "ConfigMap {} missing template key {}",
+                selector.name, selector.key
+            ))
+        })?;
+    let rendered = handlebars::Handlebars::new()
+        .render_template(tpl, tplcx)
+        .map_err(|err| Error::Assets(err.to_string()))?;
+    let v: serde_json::Value = serde_yaml::from_str(&rendered)?;
+    Ok(serde_json::from_value(v)?)
+}
+
+/// Named_port builds the JSON representation of an `IntOrString` referencing a named container
+/// port. Every supported k8s-openapi version accepts `IntOrString` as either the bare port number
+/// or this string form, but routing all the probe/`targetPort` fields below through here keeps
+/// the templates from drifting onto a raw port number --- which wouldn't survive a container
+/// changing its port numbering --- as new cases get copy-pasted in.
+fn named_port(name: &str) -> serde_json::Value {
+    json!(name)
+}
+
 /// Render ...
-pub fn render<O, K>(owner: &O) -> K
+///
+/// If `owner`'s spec carries an [`Overlay`](api::v1alpha1::Overlay) for `K`'s kind, it's applied
+/// to the generated resource (as a raw `serde_json::Value`) before deserializing into `K`, so
+/// callers can customize fields this function doesn't hard-code --- node selectors, tolerations,
+/// extra volumes, image pull secrets, resource limits, etc. --- without forking the operator. The
+/// managed labels and owner reference set below are applied after the overlay, so a patch can't
+/// clobber them.
+///
+/// `apiVersion`s for Kinds whose preferred group-version isn't served on every supported cluster
+/// (`HorizontalPodAutoscaler`, `CronJob`, `Deployment`) are resolved against `ctx` via
+/// [`capabilities::api_version`] rather than hard-coded, so this keeps working against clusters
+/// that only serve an older group-version.
+pub async fn render<O, K>(owner: &O, ctx: &Context) -> K
 where
-    O: kube::Resource<DynamicType = ()>,
+    O: kube::Resource<DynamicType = ()> + CrdCommon,
     K: kube::Resource<DynamicType = ()> + serde::de::DeserializeOwned,
 {
     use kube::ResourceExt;
@@ -50,7 +138,7 @@ where
         ("app.kubernetes.io/managed-by", "clair-operator"),
         ("app.kubernetes.io/component", &kind),
     ]);
-    let v = match K::kind(&()).as_ref() {
+    let mut v = match K::kind(&()).as_ref() {
         "CronJob" => {
             labels.remove("app.kubernetes.io/component");
             let metadata = json!( {
@@ -84,24 +172,25 @@ where
               },
               "startupProbe": {
                 "tcpSocket": {
-                  "port": "http"
+                  "port": named_port("http")
                 },
                 "initialDelaySeconds": 5,
                 "periodSeconds": 1
               },
               "livenessProbe": {
-                "httpGet": { "path": "/healthz", "port": "introspection" },
+                "httpGet": { "path": "/healthz", "port": named_port("introspection") },
                 "initialDelaySeconds": 15,
                 "periodSeconds": 20
               },
               "readinessProbe": {
-                "httpGet": { "path": "/readyz", "port": "introspection" },
+                "httpGet": { "path": "/readyz", "port": named_port("introspection") },
                 "initialDelaySeconds": 5,
                 "periodSeconds": 10
               }
             });
+            let api_version = capabilities::api_version(ctx, "CronJob").await;
             json!({
-              "apiVersion": "batch/v1",
+              "apiVersion": api_version,
               "kind": "CronJob",
               "metadata": metadata,
               "spec": {
@@ -162,14 +251,14 @@ where
                     "requests": { "cpu": "1" }
                 },
                 "startupProbe": {
-                    "tcpSocket": { "port": "api" },
+                    "tcpSocket": { "port": named_port("api") },
                     "initialDelaySeconds": 5,
                     "periodSeconds": 1
                 },
                 "livenessProbe": {
                     "httpGet": {
                         "path": "/healthz",
-                        "port": "introspection"
+                        "port": named_port("introspection")
                     },
                     "initialDelaySeconds": 15,
                     "periodSeconds": 20
@@ -177,15 +266,16 @@ where
                 "readinessProbe": {
                     "httpGet": {
                         "path": "/readyz",
-                        "port": "introspection"
+                        "port": named_port("introspection")
                     },
                     "initialDelaySeconds": 5,
                     "periodSeconds": 10
                 }
             });
 
+            let api_version = capabilities::api_version(ctx, "Deployment").await;
             json!({
-                "apiVersion": "apps/v1",
+                "apiVersion": api_version,
                 "kind": "Deployment",
                 "metadata": {
                     "name": name,
@@ -222,7 +312,12 @@ where
                   {
                     "name": "api",
                     "port": 80,
-                    "targetPort": "api"
+                    "targetPort": named_port("api")
+                  },
+                  {
+                    "name": "introspection",
+                    "port": 8089,
+                    "targetPort": named_port("introspection")
                   }
                 ],
                 "selector": labels,
@@ -230,9 +325,56 @@ where
             })
         }
 
+        // ServiceMonitor and PodMonitor scrape the "introspection" port added to the Service and
+        // Deployment above. The default path/interval below can be overridden per-object with an
+        // Overlay, same as any other rendered field.
+        "ServiceMonitor" => {
+            json!({
+              "apiVersion": "monitoring.coreos.com/v1",
+              "kind": "ServiceMonitor",
+              "metadata": {
+                "name": name,
+                "labels": labels,
+              },
+              "spec": {
+                "selector": { "matchLabels": labels },
+                "endpoints": [
+                  {
+                    "port": "introspection",
+                    "path": "/metrics",
+                    "interval": "30s"
+                  }
+                ]
+              }
+            })
+        }
+
+        "PodMonitor" => {
+            json!({
+              "apiVersion": "monitoring.coreos.com/v1",
+              "kind": "PodMonitor",
+              "metadata": {
+                "name": name,
+                "labels": labels,
+              },
+              "spec": {
+                "selector": { "matchLabels": labels },
+                "podMetricsEndpoints": [
+                  {
+                    "port": "introspection",
+                    "path": "/metrics",
+                    "interval": "30s"
+                  }
+                ]
+              }
+            })
+        }
+
         "HorizontalPodAutoscaler" => {
+            let api_version = capabilities::api_version(ctx, "HorizontalPodAutoscaler").await;
+            let deployment_api_version = capabilities::api_version(ctx, "Deployment").await;
             json!({
-              "apiVersion": "autoscaling/v2",
+              "apiVersion": api_version,
               "kind": "HorizontalPodAutoscaler",
               "metadata": {
                 "name": name,
@@ -242,7 +384,7 @@ where
                 "minReplicas": 1,
                 "maxReplicas": 10,
                 "scaleTargetRef": {
-                  "apiVersion": "apps/v1",
+                  "apiVersion": deployment_api_version,
                   "kind": "Deployment",
                   "name": name,
                 },
@@ -262,14 +404,135 @@ where
             })
         }
 
+        // Ingress and its Gateway API equivalents (HTTPRoute, routed through a Gateway this
+        // function also renders) both front the `name` Service rendered above on its `api` port,
+        // using whatever `Endpoint` the owner's spec carries --- see
+        // [`SpecCommon::endpoint`](api::v1alpha1::SpecCommon::endpoint). Only `O`s with an actual
+        // `Endpoint` field (currently just [`api::v1alpha1::ClairSpec`]) produce a non-default
+        // one; other owners render a bare, host-less rule.
         "Ingress" => {
-            json!({})
+            let endpoint = owner.get_spec().endpoint();
+            let path = endpoint
+                .and_then(|e| e.path.clone())
+                .unwrap_or_else(|| "/".to_string());
+
+            let mut rule = json!({
+                "http": {
+                    "paths": [
+                        {
+                            "path": path,
+                            "pathType": "Prefix",
+                            "backend": {
+                                "service": {
+                                    "name": name,
+                                    "port": { "name": "api" }
+                                }
+                            }
+                        }
+                    ]
+                }
+            });
+            let hostname = endpoint.and_then(|e| e.hostname.clone());
+            if let Some(hostname) = &hostname {
+                rule["host"] = json!(hostname);
+            }
+
+            let mut spec = json!({ "rules": [rule] });
+            if let Some(class) = endpoint.and_then(|e| e.ingress_class_name.clone()) {
+                spec["ingressClassName"] = json!(class);
+            }
+            if let Some(tls) = endpoint.and_then(|e| e.tls.as_ref()) {
+                spec["tls"] = json!([{
+                    "hosts": hostname.into_iter().collect::<Vec<_>>(),
+                    "secretName": tls.name,
+                }]);
+            }
+
+            json!({
+                "apiVersion": "networking.k8s.io/v1",
+                "kind": "Ingress",
+                "metadata": { "name": name, "labels": labels },
+                "spec": spec
+            })
+        }
+
+        "HTTPRoute" => {
+            let endpoint = owner.get_spec().endpoint();
+            let path = endpoint
+                .and_then(|e| e.path.clone())
+                .unwrap_or_else(|| "/".to_string());
+
+            let rule = json!({
+                "matches": [ { "path": { "type": "PathPrefix", "value": path } } ],
+                "backendRefs": [ { "name": name, "port": 80 } ]
+            });
+            let mut spec = json!({
+                "parentRefs": [ { "name": format!("{name}-gateway") } ],
+                "rules": [rule]
+            });
+            if let Some(hostname) = endpoint.and_then(|e| e.hostname.clone()) {
+                spec["hostnames"] = json!([hostname]);
+            }
+
+            json!({
+                "apiVersion": "gateway.networking.k8s.io/v1",
+                "kind": "HTTPRoute",
+                "metadata": { "name": name, "labels": labels },
+                "spec": spec
+            })
+        }
+
+        // Gateway is only needed for clusters that don't already run a shared Gateway the
+        // "HTTPRoute" arm above could instead target by name; rendering our own keeps the
+        // Ingress and Gateway API paths equally self-contained.
+        "Gateway" => {
+            let endpoint = owner.get_spec().endpoint();
+            let mut listener = json!({
+                "name": "http",
+                "port": 80,
+                "protocol": "HTTP",
+                "allowedRoutes": { "namespaces": { "from": "Same" } }
+            });
+            if let Some(tls) = endpoint.and_then(|e| e.tls.as_ref()) {
+                listener = json!({
+                    "name": "https",
+                    "port": 443,
+                    "protocol": "HTTPS",
+                    "tls": {
+                        "mode": "Terminate",
+                        "certificateRefs": [ { "name": tls.name } ]
+                    },
+                    "allowedRoutes": { "namespaces": { "from": "Same" } }
+                });
+            }
+            if let Some(hostname) = endpoint.and_then(|e| e.hostname.clone()) {
+                listener["hostname"] = json!(hostname);
+            }
+
+            json!({
+                "apiVersion": "gateway.networking.k8s.io/v1",
+                "kind": "Gateway",
+                "metadata": { "name": format!("{name}-gateway"), "labels": labels },
+                "spec": {
+                    "gatewayClassName": endpoint
+                        .and_then(|e| e.ingress_class_name.clone())
+                        .unwrap_or_default(),
+                    "listeners": [listener]
+                }
+            })
         }
 
         _ => panic!("programmer error: unexpected type: {}", K::kind(&())),
     };
+    if let Some(patch) = owner.get_spec().overlay(K::kind(&()).as_ref()) {
+        overlay::apply(&mut v, patch).expect("programmer error: malformed overlay");
+    }
     let mut k: K =
         serde_json::from_value(v).expect("programmer error: unable to deserialize template");
+    let managed = k.meta_mut().labels.get_or_insert_with(Default::default);
+    for (key, value) in &labels {
+        managed.insert(key.to_string(), value.to_string());
+    }
     k.meta_mut().owner_references = owner.controller_owner_ref(&()).map(|r| vec![r]);
     k
 }
@@ -281,42 +544,130 @@ mod tests {
     use assert_json_diff::assert_json_eq;
     use serde_json::{from_str, to_value, Value};
 
+    use crate::mock::ContextBuilder;
+
+    /// Api_resource_list builds the discovery document `ctx.gvk_exists` expects back from a GET
+    /// against `/apis/{group_version}` (or `/api/{version}` for the core group), listing a single
+    /// resource of `kind`.
+    fn api_resource_list(group_version: &str, kind: &str) -> Value {
+        json!({
+            "kind": "APIResourceList",
+            "apiVersion": "v1",
+            "groupVersion": group_version,
+            "resources": [
+                {
+                    "name": format!("{}s", kind.to_ascii_lowercase()),
+                    "singularName": kind.to_ascii_lowercase(),
+                    "namespaced": true,
+                    "kind": kind,
+                    "verbs": ["get", "list", "watch", "create", "update", "patch", "delete"],
+                }
+            ]
+        })
+    }
+
     #[cfg(test)]
     mod indexer {
         use super::*;
 
         use api::v1alpha1::Indexer;
 
-        #[test]
-        fn deployment() {
+        #[tokio::test]
+        async fn deployment() {
             use k8s_openapi::api::apps::v1::Deployment;
 
             let indexer = Indexer::new("test", Default::default());
-            let got: Deployment = render(&indexer);
+            let (ctx, verifier) = ContextBuilder::default()
+                .expect_get("/apis/apps/v1", &api_resource_list("apps/v1", "Deployment"))
+                .build(crate::DEFAULT_IMAGE.as_str());
+            let got: Deployment = render(&indexer, &ctx).await;
             let got = to_value(got).unwrap();
             let want: Value = from_str(include_str!("_fixture/indexer/deployment.json")).unwrap();
 
             assert_json_eq!(got, want);
+            drop(ctx);
+            verifier.await.expect("mock apiserver task panicked");
         }
 
-        #[test]
-        fn service() {
+        #[tokio::test]
+        async fn service() {
             use k8s_openapi::api::core::v1::Service;
 
             let indexer = Indexer::new("test", Default::default());
-            let got: Service = render(&indexer);
+            let (ctx, _verifier) = ContextBuilder::default().build(crate::DEFAULT_IMAGE.as_str());
+            let got: Service = render(&indexer, &ctx).await;
             let got = to_value(got).unwrap();
             let want: Value = from_str(include_str!("_fixture/indexer/service.json")).unwrap();
 
             assert_json_eq!(got, want);
         }
 
-        #[test]
-        fn horizontal_pod_autoscaler() {
+        #[tokio::test]
+        async fn service_monitor() {
+            use monitoring_coreos_com::v1::servicemonitors::ServiceMonitor;
+
+            let indexer = Indexer::new("test", Default::default());
+            let (ctx, _verifier) = ContextBuilder::default().build(crate::DEFAULT_IMAGE.as_str());
+            let got: ServiceMonitor = render(&indexer, &ctx).await;
+            let got = to_value(got).unwrap();
+            let want: Value =
+                from_str(include_str!("_fixture/indexer/servicemonitor.json")).unwrap();
+
+            assert_json_eq!(got, want);
+        }
+
+        #[tokio::test]
+        async fn pod_monitor() {
+            use monitoring_coreos_com::v1::podmonitors::PodMonitor;
+
+            let indexer = Indexer::new("test", Default::default());
+            let (ctx, _verifier) = ContextBuilder::default().build(crate::DEFAULT_IMAGE.as_str());
+            let got: PodMonitor = render(&indexer, &ctx).await;
+            let got = to_value(got).unwrap();
+            let want: Value = from_str(include_str!("_fixture/indexer/podmonitor.json")).unwrap();
+
+            assert_json_eq!(got, want);
+        }
+
+        #[tokio::test]
+        async fn ingress() {
+            use k8s_openapi::api::networking::v1::Ingress;
+
+            let indexer = Indexer::new("test", Default::default());
+            let (ctx, _verifier) = ContextBuilder::default().build(crate::DEFAULT_IMAGE.as_str());
+            let got: Ingress = render(&indexer, &ctx).await;
+            let got = to_value(got).unwrap();
+            let want: Value = from_str(include_str!("_fixture/indexer/ingress.json")).unwrap();
+
+            assert_json_eq!(got, want);
+        }
+
+        #[tokio::test]
+        async fn http_route() {
+            use gateway_networking_k8s_io::v1::httproutes::HTTPRoute;
+
+            let indexer = Indexer::new("test", Default::default());
+            let (ctx, _verifier) = ContextBuilder::default().build(crate::DEFAULT_IMAGE.as_str());
+            let got: HTTPRoute = render(&indexer, &ctx).await;
+            let got = to_value(got).unwrap();
+            let want: Value = from_str(include_str!("_fixture/indexer/httproute.json")).unwrap();
+
+            assert_json_eq!(got, want);
+        }
+
+        #[tokio::test]
+        async fn horizontal_pod_autoscaler() {
             use k8s_openapi::api::autoscaling::v2::HorizontalPodAutoscaler;
 
             let indexer = Indexer::new("test", Default::default());
-            let got: HorizontalPodAutoscaler = render(&indexer);
+            let (ctx, verifier) = ContextBuilder::default()
+                .expect_get(
+                    "/apis/autoscaling/v2",
+                    &api_resource_list("autoscaling/v2", "HorizontalPodAutoscaler"),
+                )
+                .expect_get("/apis/apps/v1", &api_resource_list("apps/v1", "Deployment"))
+                .build(crate::DEFAULT_IMAGE.as_str());
+            let got: HorizontalPodAutoscaler = render(&indexer, &ctx).await;
             let got = to_value(got).unwrap();
             let want: Value = from_str(include_str!(
                 "_fixture/indexer/horizontalpodautoscaler.json"
@@ -324,18 +675,25 @@ mod tests {
             .unwrap();
 
             assert_json_eq!(got, want);
+            drop(ctx);
+            verifier.await.expect("mock apiserver task panicked");
         }
 
-        #[test]
-        fn cron_job() {
+        #[tokio::test]
+        async fn cron_job() {
             use k8s_openapi::api::batch::v1::CronJob;
 
             let indexer = Indexer::new("test", Default::default());
-            let got: CronJob = render(&indexer);
+            let (ctx, verifier) = ContextBuilder::default()
+                .expect_get("/apis/batch/v1", &api_resource_list("batch/v1", "CronJob"))
+                .build(crate::DEFAULT_IMAGE.as_str());
+            let got: CronJob = render(&indexer, &ctx).await;
             let got = to_value(got).unwrap();
             let want: Value = from_str(include_str!("_fixture/indexer/cronjob.json")).unwrap();
 
             assert_json_eq!(got, want);
+            drop(ctx);
+            verifier.await.expect("mock apiserver task panicked");
         }
 
         #[test]
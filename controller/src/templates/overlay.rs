@@ -0,0 +1,88 @@
+//! Overlay applies a user-supplied [`Overlay`] onto a rendered base `serde_json::Value`, before
+//! [`render`](super::render) deserializes it into the real typed resource, so operators can add
+//! fields the hard-coded templates don't know about without forking the operator.
+
+use api::v1alpha1::Overlay;
+use serde_json::Value;
+
+use crate::{Error, Result};
+
+/// Patch_merge_keys maps the well-known strategic-merge array fields this operator's templates
+/// emit to the key their elements are merged on, mirroring the `patchMergeKey` metadata
+/// Kubernetes' own OpenAPI schema carries for these fields. A field not listed here is replaced
+/// wholesale, same as [`Overlay::Merge`] would do.
+const PATCH_MERGE_KEYS: &[(&str, &str)] = &[
+    ("containers", "name"),
+    ("initContainers", "name"),
+    ("volumes", "name"),
+    ("volumeMounts", "name"),
+    ("env", "name"),
+    ("ports", "containerPort"),
+    ("imagePullSecrets", "name"),
+];
+
+/// Apply applies `overlay` to `doc` in place.
+pub fn apply(doc: &mut Value, overlay: &Overlay) -> Result<()> {
+    match overlay {
+        Overlay::Json(ops) => {
+            let ops: json_patch::Patch = serde_json::from_value(ops.clone())?;
+            json_patch::patch(doc, &ops).map_err(Error::from)?;
+        }
+        Overlay::Merge(patch) => json_patch::merge(doc, patch),
+        Overlay::Strategic(patch) => strategic_merge(doc, patch),
+    }
+    Ok(())
+}
+
+/// Strategic_merge implements just enough of Kubernetes' strategic-merge-patch to be useful for
+/// the workloads `render` emits: objects merge key-by-key (a `null` value deletes the key, as in
+/// an RFC7386 merge patch), and arrays listed in [`PATCH_MERGE_KEYS`] merge element-by-element on
+/// their key instead of being replaced wholesale.
+fn strategic_merge(doc: &mut Value, patch: &Value) {
+    match (doc, patch) {
+        (Value::Object(doc), Value::Object(patch)) => {
+            for (key, value) in patch {
+                if value.is_null() {
+                    doc.remove(key);
+                    continue;
+                }
+                match doc.get_mut(key) {
+                    Some(existing @ Value::Array(_)) if value.is_array() => {
+                        if let Some((_, merge_key)) =
+                            PATCH_MERGE_KEYS.iter().find(|(field, _)| field == key)
+                        {
+                            merge_list_by_key(existing, value, merge_key);
+                        } else {
+                            *existing = value.clone();
+                        }
+                    }
+                    Some(existing) => strategic_merge(existing, value),
+                    None => {
+                        doc.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (doc, patch) => *doc = patch.clone(),
+    }
+}
+
+/// Merge_list_by_key merges `patch`'s entries into `existing` (both assumed to be JSON arrays of
+/// objects), matching on `merge_key` and falling back to appending when an entry has no match.
+fn merge_list_by_key(existing: &mut Value, patch: &Value, merge_key: &str) {
+    let (Value::Array(existing), Value::Array(patch)) = (existing, patch) else {
+        return;
+    };
+    for entry in patch {
+        let entry_key = entry.get(merge_key);
+        let slot = entry_key.and_then(|k| {
+            existing
+                .iter_mut()
+                .find(|e| e.get(merge_key) == Some(k))
+        });
+        match slot {
+            Some(slot) => strategic_merge(slot, entry),
+            None => existing.push(entry.clone()),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Acme is the `acme` feature's certificate acquisition path: instead of reading a `cert-dir`
+//! pair off disk, it drives a full ACME order (RFC 8555) against a configurable CA directory
+//! (Let's Encrypt, step-ca, or anything else speaking the protocol), persists the issued
+//! chain/key to a `Secret` via the same [`kube::Client`] the reconcilers use, and feeds the
+//! result into the [`super::rustls_tls::CertReloader`] that backend already hot-swaps on file
+//! change --- an ACME-issued certificate is renewed the same way a rotated on-disk one is
+//! reloaded, just with a different source for the replacement [`CertifiedKey`]. Requires the
+//! `rustls-tls` feature, since [`CertifiedKey`] is that backend's type.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use instant_acme::{
+    Account, AuthorizationStatus, ChallengeType, Identifier, NewAccount, NewOrder, OrderStatus,
+};
+use k8s_openapi::{api::core::v1::Secret, ByteString};
+use kube::api::{Api, Patch, PatchParams};
+use rcgen::{CertificateParams, DistinguishedName, KeyPair, PKCS_ECDSA_P256_SHA256};
+use ring::signature::{EcdsaKeyPair, ECDSA_P384_SHA384_FIXED_SIGNING};
+use tokio::time::{sleep, Duration};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use super::rustls_tls::{load_certified_key_pem, Acceptor, CertReloader};
+use super::ChallengeState;
+use crate::{Error, Result};
+
+/// FIELD_MANAGER identifies this module's server-side-apply ownership of the persisted cert
+/// `Secret`, the same way `webhook::bootstrap::FIELD_MANAGER` does for the self-signed path.
+const FIELD_MANAGER: &str = "clair-operator-webhook-acme";
+
+/// NOT_AFTER_ANNOTATION records the issued leaf certificate's expiry on the `Secret` so [`run`]
+/// can decide whether a freshly loaded cert still needs renewing without re-parsing the PEM.
+const NOT_AFTER_ANNOTATION: &str = "clair-operator.io/acme-not-after";
+
+/// Config names everything an ACME order needs: where to order from, what names to request for,
+/// and when to start renewing ahead of expiry.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub directory_url: String,
+    pub dns_names: Vec<String>,
+    pub renewal_window: Duration,
+    pub secret_namespace: String,
+    pub secret_name: String,
+}
+
+/// Run loads a still-valid certificate from `cfg.secret_name` if one exists, otherwise drives a
+/// fresh ACME order, then returns an [`Acceptor`] backed by a [`CertReloader`] and spawns a
+/// background task that reorders and re-persists the certificate once it's within
+/// `cfg.renewal_window` of expiry.
+pub async fn run(
+    cfg: Config,
+    client: kube::Client,
+    challenges: Arc<ChallengeState>,
+    cancel: CancellationToken,
+) -> Result<Acceptor> {
+    let secrets: Api<Secret> = Api::namespaced(client, &cfg.secret_namespace);
+    let key = match load_from_secret(&secrets, &cfg.secret_name, cfg.renewal_window).await? {
+        Some(key) => key,
+        None => {
+            let (key, not_after) = issue(&cfg, &challenges).await?;
+            persist_to_secret(&secrets, &cfg.secret_name, &key, not_after).await?;
+            load_certified_key_pem(&key.0, &key.1)?
+        }
+    };
+    let reloader = Arc::new(CertReloader::new(key));
+    tokio::spawn(renew(cfg, secrets, reloader.clone(), challenges, cancel));
+    Ok(Acceptor::from_reloader(reloader))
+}
+
+/// Renew sleeps until the currently-loaded certificate is within `cfg.renewal_window` of expiry
+/// (re-checking every hour in case the window itself is longer than that), then orders a
+/// replacement and swaps it into `reloader`.
+async fn renew(
+    cfg: Config,
+    secrets: Api<Secret>,
+    reloader: Arc<CertReloader>,
+    challenges: Arc<ChallengeState>,
+    cancel: CancellationToken,
+) {
+    loop {
+        tokio::select! {
+            () = cancel.cancelled() => return,
+            () = sleep(Duration::from_secs(3600)) => {},
+        }
+        match load_from_secret(&secrets, &cfg.secret_name, cfg.renewal_window).await {
+            Ok(Some(_)) => continue,
+            Ok(None) => {}
+            Err(error) => {
+                warn!(%error, "failed to check ACME certificate expiry, will retry");
+                continue;
+            }
+        }
+        info!("ACME certificate nearing expiry, renewing");
+        match issue(&cfg, &challenges).await {
+            Ok((pem, not_after)) => {
+                if let Err(error) = persist_to_secret(&secrets, &cfg.secret_name, &pem, not_after).await {
+                    warn!(%error, "issued renewed ACME certificate but failed to persist it");
+                }
+                match load_certified_key_pem(&pem.0, &pem.1) {
+                    Ok(key) => reloader.current.store(Arc::new(key)),
+                    Err(error) => warn!(%error, "issued renewed ACME certificate but failed to load it"),
+                }
+            }
+            Err(error) => warn!(%error, "failed to renew ACME certificate, keeping current one"),
+        }
+    }
+}
+
+/// Generate_account_key produces a fresh P-384 ACME account key, PKCS#8-encoded.
+///
+/// A fresh key every order keeps this stateless across restarts, at the cost of re-registering
+/// an account with the CA each time -- acceptable for a controller that orders infrequently (once
+/// per `cfg.renewal_window`), and it avoids persisting account credentials alongside the serving
+/// certificate.
+fn generate_account_key() -> Result<Vec<u8>> {
+    let rng = ring::rand::SystemRandom::new();
+    let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P384_SHA384_FIXED_SIGNING, &rng)
+        .map_err(|_| Error::Other(anyhow::anyhow!("failed to generate ACME account key")))?;
+    Ok(pkcs8.as_ref().to_vec())
+}
+
+/// Issue drives a single ACME order for `cfg.dns_names`, satisfying each authorization's http-01
+/// challenge via `challenges`, and returns the issued chain/key PEM plus the leaf's expiry.
+async fn issue(cfg: &Config, challenges: &ChallengeState) -> Result<((Vec<u8>, Vec<u8>), DateTime<Utc>)> {
+    let account_key = generate_account_key()?;
+    let account = Account::create_with_key(
+        &NewAccount {
+            contact: &[],
+            terms_of_service_agreed: true,
+            only_return_existing: false,
+        },
+        &cfg.directory_url,
+        account_key,
+        None,
+    )
+    .await
+    .map_err(|e| Error::Other(e.into()))?;
+
+    let identifiers = cfg
+        .dns_names
+        .iter()
+        .map(|n| Identifier::Dns(n.clone()))
+        .collect::<Vec<_>>();
+    let mut order = account
+        .new_order(&NewOrder {
+            identifiers: &identifiers,
+        })
+        .await
+        .map_err(|e| Error::Other(e.into()))?;
+
+    let authorizations = order.authorizations().await.map_err(|e| Error::Other(e.into()))?;
+    let mut pending_tokens = Vec::new();
+    for authz in &authorizations {
+        if authz.status == AuthorizationStatus::Valid {
+            continue;
+        }
+        let challenge = authz
+            .challenges
+            .iter()
+            .find(|c| c.r#type == ChallengeType::Http01)
+            .ok_or_else(|| Error::BadName("no http-01 challenge offered for ACME authorization".into()))?;
+        let key_authorization = order.key_authorization(challenge).as_str().to_string();
+        challenges.set(challenge.token.clone(), key_authorization);
+        pending_tokens.push(challenge.token.clone());
+        order
+            .set_challenge_ready(&challenge.url)
+            .await
+            .map_err(|e| Error::Other(e.into()))?;
+    }
+
+    // Poll with a capped exponential backoff rather than a fixed interval: most CAs validate a
+    // challenge within a couple of seconds, but there's no guarantee, and hammering the directory
+    // every poll would be rude.
+    let mut delay = Duration::from_millis(250);
+    let status = loop {
+        sleep(delay).await;
+        let state = order.refresh().await.map_err(|e| Error::Other(e.into()))?;
+        if matches!(state.status, OrderStatus::Ready | OrderStatus::Invalid) {
+            break state.status;
+        }
+        delay = (delay * 2).min(Duration::from_secs(10));
+    };
+    for token in &pending_tokens {
+        challenges.clear(token);
+    }
+    if status != OrderStatus::Ready {
+        return Err(Error::Other(anyhow::anyhow!(
+            "ACME order for {:?} did not become ready: {status:?}",
+            cfg.dns_names
+        )));
+    }
+
+    let mut params = CertificateParams::new(cfg.dns_names.clone());
+    params.distinguished_name = DistinguishedName::new();
+    let cert_key = KeyPair::generate(&PKCS_ECDSA_P256_SHA256).map_err(|e| Error::Other(e.into()))?;
+    params.key_pair = Some(cert_key);
+    let cert = rcgen::Certificate::from_params(params).map_err(|e| Error::Other(e.into()))?;
+    let csr_der = cert.serialize_request_der().map_err(|e| Error::Other(e.into()))?;
+    order.finalize(&csr_der).await.map_err(|e| Error::Other(e.into()))?;
+
+    let cert_chain_pem = loop {
+        match order.certificate().await.map_err(|e| Error::Other(e.into()))? {
+            Some(pem) => break pem,
+            None => sleep(Duration::from_secs(1)).await,
+        }
+    };
+    let private_key_pem = cert.serialize_private_key_pem();
+
+    let not_after = leaf_not_after(&cert_chain_pem)?;
+    Ok((
+        (cert_chain_pem.into_bytes(), private_key_pem.into_bytes()),
+        not_after,
+    ))
+}
+
+/// Leaf_not_after parses the first certificate in `chain_pem` and returns its expiry.
+fn leaf_not_after(chain_pem: &str) -> Result<DateTime<Utc>> {
+    let (_, pem) =
+        x509_parser::pem::parse_x509_pem(chain_pem.as_bytes()).map_err(|e| Error::Other(anyhow::anyhow!("{e}")))?;
+    let cert = pem.parse_x509().map_err(|e| Error::Other(anyhow::anyhow!("{e}")))?;
+    DateTime::from_timestamp(cert.validity().not_after.timestamp(), 0)
+        .ok_or_else(|| Error::BadName("ACME-issued certificate had an unparseable expiry".into()))
+}
+
+/// Load_from_secret returns the `Secret`'s PEM cert/key parsed into a [`CertifiedKey`], or `None`
+/// if the `Secret` doesn't exist yet or its [`NOT_AFTER_ANNOTATION`] says it's within
+/// `renewal_window` of expiry (or missing/unparseable, treated the same as "needs renewing").
+async fn load_from_secret(
+    secrets: &Api<Secret>,
+    name: &str,
+    renewal_window: Duration,
+) -> Result<Option<CertifiedKey>> {
+    let Some(secret) = secrets.get_opt(name).await? else {
+        return Ok(None);
+    };
+    let not_after = secret
+        .metadata
+        .annotations
+        .as_ref()
+        .and_then(|a| a.get(NOT_AFTER_ANNOTATION))
+        .and_then(|v| DateTime::parse_from_rfc3339(v).ok())
+        .map(|t| t.with_timezone(&Utc));
+    let Some(not_after) = not_after else {
+        return Ok(None);
+    };
+    if not_after - Utc::now() < chrono::Duration::from_std(renewal_window).unwrap_or_default() {
+        return Ok(None);
+    }
+    let data = secret.data.unwrap_or_default();
+    let (Some(cert), Some(key)) = (data.get("tls.crt"), data.get("tls.key")) else {
+        return Ok(None);
+    };
+    Ok(Some(load_certified_key_pem(&cert.0, &key.0)?))
+}
+
+/// Persist_to_secret server-side-applies `pem`'s cert/key into `name`, annotated with `not_after`
+/// so the next [`load_from_secret`] knows when to renew without re-parsing the certificate.
+async fn persist_to_secret(
+    secrets: &Api<Secret>,
+    name: &str,
+    pem: &(Vec<u8>, Vec<u8>),
+    not_after: DateTime<Utc>,
+) -> Result<()> {
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+
+    let secret = Secret {
+        metadata: ObjectMeta {
+            name: Some(name.to_string()),
+            annotations: Some([(NOT_AFTER_ANNOTATION.to_string(), not_after.to_rfc3339())].into()),
+            ..Default::default()
+        },
+        data: Some(
+            [
+                ("tls.crt".to_string(), ByteString(pem.0.clone())),
+                ("tls.key".to_string(), ByteString(pem.1.clone())),
+            ]
+            .into(),
+        ),
+        type_: Some("kubernetes.io/tls".to_string()),
+        ..Default::default()
+    };
+    secrets
+        .patch(name, &PatchParams::apply(FIELD_MANAGER), &Patch::Apply(&secret))
+        .await?;
+    Ok(())
+}
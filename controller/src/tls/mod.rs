@@ -0,0 +1,66 @@
+//! Tls gives `webhooks()` a single [`Acceptor`] whose underlying implementation is picked at
+//! build time by the mutually exclusive `rustls-tls`/`openssl-tls` Cargo features, so a
+//! downstream packager can choose the crypto stack linked into the binary (a FIPS-validated
+//! OpenSSL module, or dropping OpenSSL entirely for a smaller distroless image) without
+//! `webhooks()`, `startup()`, or `run()` knowing or caring which one is compiled in.
+
+#[cfg(all(feature = "rustls-tls", feature = "openssl-tls"))]
+compile_error!("`rustls-tls` and `openssl-tls` are mutually exclusive, enable only one");
+
+#[cfg(all(feature = "acme", not(feature = "rustls-tls")))]
+compile_error!("`acme` requires the `rustls-tls` feature, since it issues a CertifiedKey");
+
+#[cfg(feature = "rustls-tls")]
+mod rustls_tls;
+#[cfg(feature = "rustls-tls")]
+pub use rustls_tls::Acceptor;
+
+#[cfg(feature = "openssl-tls")]
+mod openssl_tls;
+#[cfg(feature = "openssl-tls")]
+pub use openssl_tls::Acceptor;
+
+#[cfg(feature = "acme")]
+pub mod acme;
+
+/// HostCert is one additional `--cert-host` entry: a hostname and the cert/key pair to serve for
+/// connections whose SNI names it, so [`Acceptor::new`] can resolve several identities off a
+/// single listener instead of always serving the default `cert-dir` pair.
+#[derive(Clone, Debug)]
+pub struct HostCert {
+    pub host: String,
+    pub certfile: std::path::PathBuf,
+    pub keyfile: std::path::PathBuf,
+}
+
+/// ChallengeState is the shared token -> key-authorization map an ACME http-01 challenge
+/// responder reads from; kept outside the `acme` feature gate so callers (namely `main`'s admin
+/// server, which is always compiled) can hold and route to one unconditionally, whether or not
+/// this binary was built with ACME support.
+#[derive(Default)]
+pub struct ChallengeState(std::sync::Mutex<std::collections::HashMap<String, String>>);
+
+impl ChallengeState {
+    pub fn new() -> std::sync::Arc<Self> {
+        std::sync::Arc::default()
+    }
+
+    /// Set records the key authorization an in-progress ACME order expects to be served back for
+    /// `token`. No-op unless the `acme` feature is calling it.
+    #[allow(dead_code)]
+    pub(crate) fn set(&self, token: String, key_authorization: String) {
+        self.0.lock().unwrap().insert(token, key_authorization);
+    }
+
+    /// Clear removes `token` once its challenge has been validated (or abandoned).
+    #[allow(dead_code)]
+    pub(crate) fn clear(&self, token: &str) {
+        self.0.lock().unwrap().remove(token);
+    }
+
+    /// Get returns the key authorization to serve for `token`, if this process is currently
+    /// answering a challenge for it.
+    pub fn get(&self, token: &str) -> Option<String> {
+        self.0.lock().unwrap().get(token).cloned()
+    }
+}
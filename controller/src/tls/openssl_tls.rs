@@ -0,0 +1,123 @@
+//! Openssl_tls is the `openssl-tls` feature's [`Acceptor`] implementation, for packagers who need
+//! the platform OpenSSL (e.g. a FIPS-validated module) rather than rustls's pure-Rust crypto.
+
+use std::{
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
+use openssl::ssl::{Ssl, SslAcceptor, SslFiletype, SslMethod};
+use tokio::net::TcpStream;
+use tokio_openssl::SslStream;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use controller::{Error, Result};
+
+/// Load_acceptor builds an [`SslAcceptor`] from the PEM-encoded `certfile`/`keyfile` pair.
+///
+/// Unlike the `rustls-tls` backend's [`ResolvesServerCert`](tokio_rustls::rustls::server::ResolvesServerCert),
+/// an `SslAcceptor` bakes the loaded certificate into its `SslContext` at construction time, so
+/// [`watch_cert_files`] swaps the whole acceptor on reload rather than a resolver underneath it.
+fn load_acceptor(certfile: &Path, keyfile: &Path) -> Result<SslAcceptor> {
+    let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())
+        .map_err(|e| Error::Other(e.into()))?;
+    builder
+        .set_private_key_file(keyfile, SslFiletype::PEM)
+        .map_err(|e| Error::Other(e.into()))?;
+    builder
+        .set_certificate_chain_file(certfile)
+        .map_err(|e| Error::Other(e.into()))?;
+    builder.check_private_key().map_err(|e| Error::Other(e.into()))?;
+    Ok(builder.build())
+}
+
+/// Watch_cert_files polls `certfile`/`keyfile`'s mtimes and rebuilds `current`'s acceptor
+/// whenever either changes, so a rotated Secret volume mount is picked up within one poll
+/// interval instead of waiting for the pod to restart.
+async fn watch_cert_files(
+    certfile: PathBuf,
+    keyfile: PathBuf,
+    current: Arc<ArcSwap<SslAcceptor>>,
+    cancel: CancellationToken,
+) {
+    use tokio::time::{interval, Duration};
+
+    let mtime = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    let mut last = (mtime(&certfile), mtime(&keyfile));
+    let mut ticker = interval(Duration::from_secs(30));
+
+    loop {
+        tokio::select! {
+            () = cancel.cancelled() => return,
+            _ = ticker.tick() => {},
+        }
+        let now = (mtime(&certfile), mtime(&keyfile));
+        if now == last {
+            continue;
+        }
+        last = now;
+        match load_acceptor(&certfile, &keyfile) {
+            Ok(acceptor) => {
+                info!("reloaded webhook serving certificate");
+                current.store(Arc::new(acceptor));
+            }
+            Err(error) => {
+                warn!(%error, "failed to reload webhook serving certificate, keeping current one")
+            }
+        }
+    }
+}
+
+/// Acceptor is the `openssl-tls` backend's TLS listener.
+#[derive(Clone)]
+pub struct Acceptor {
+    current: Arc<ArcSwap<SslAcceptor>>,
+}
+
+impl Acceptor {
+    /// New loads `certfile`/`keyfile` and spawns a background task that reloads them whenever
+    /// their mtimes change, for as long as `cancel` is live.
+    ///
+    /// `hosts` is accepted only for API parity with the `rustls-tls` backend's
+    /// [`Acceptor::new`](super::rustls_tls::Acceptor::new), which resolves per-host certificates by
+    /// SNI: `SslAcceptor` bakes its certificate into the `SslContext` at construction, so it can't
+    /// resolve per-connection the way `rustls`'s `ResolvesServerCert` does. A non-empty `hosts` is
+    /// logged and otherwise ignored rather than silently dropped.
+    pub fn new(
+        certfile: &Path,
+        keyfile: &Path,
+        hosts: &[super::HostCert],
+        cancel: CancellationToken,
+    ) -> Result<Self> {
+        if !hosts.is_empty() {
+            warn!(
+                count = hosts.len(),
+                "openssl-tls backend does not support per-host SNI certificates, ignoring --cert-host"
+            );
+        }
+        let current = Arc::new(ArcSwap::new(Arc::new(load_acceptor(certfile, keyfile)?)));
+        tokio::spawn(watch_cert_files(
+            certfile.to_path_buf(),
+            keyfile.to_path_buf(),
+            current.clone(),
+            cancel,
+        ));
+        Ok(Self { current })
+    }
+
+    /// Accept completes a TLS handshake on `stream`, against whatever acceptor is current at the
+    /// moment the handshake starts.
+    pub async fn accept(&self, stream: TcpStream) -> Result<SslStream<TcpStream>> {
+        let acceptor = self.current.load_full();
+        let ssl = Ssl::new(acceptor.context()).map_err(|e| Error::Other(e.into()))?;
+        let mut stream = SslStream::new(ssl, stream).map_err(|e| Error::Other(e.into()))?;
+        Pin::new(&mut stream)
+            .accept()
+            .await
+            .map_err(|e| Error::Other(e.into()))?;
+        Ok(stream)
+    }
+}
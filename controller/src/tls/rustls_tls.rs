@@ -0,0 +1,204 @@
+//! Rustls_tls is the `rustls-tls` feature's [`Acceptor`] implementation.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use arc_swap::ArcSwap;
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    rustls::{
+        self,
+        server::{ClientHello, ResolvesServerCert},
+        sign::CertifiedKey,
+    },
+    TlsAcceptor,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use controller::{Error, Result};
+
+/// CertReloader is a [`ResolvesServerCert`] backed by an [`ArcSwap`], so every TLS handshake
+/// resolves against whatever [`CertifiedKey`] [`watch_cert_files`] most recently loaded instead
+/// of a value fixed at listener-setup time. This is how a cert-manager-rotated serving
+/// certificate takes effect without restarting the process.
+///
+/// `pub(super)` since [`super::acme`] reuses it verbatim: an ACME-issued certificate is renewed in
+/// the background the same way a file-based one is reloaded from disk, just with a different
+/// source for the replacement [`CertifiedKey`].
+pub(super) struct CertReloader {
+    pub(super) current: ArcSwap<CertifiedKey>,
+}
+
+impl CertReloader {
+    /// New wraps an already-issued `key`, for callers (like [`super::acme`]) that obtain a
+    /// [`CertifiedKey`] some way other than [`load_certified_key`].
+    pub(super) fn new(key: CertifiedKey) -> Self {
+        Self {
+            current: ArcSwap::new(Arc::new(key)),
+        }
+    }
+}
+
+impl std::fmt::Debug for CertReloader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CertReloader").finish_non_exhaustive()
+    }
+}
+
+impl ResolvesServerCert for CertReloader {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.load_full())
+    }
+}
+
+/// SniResolver picks a [`CertReloader`] by the TLS handshake's SNI server name, for a listener
+/// fronting several hostnames (e.g. admission/conversion webhooks for multiple Clair CRDs or
+/// tenants) off a single port. Falls back to `default` when the client sends no SNI, or one this
+/// resolver doesn't recognize, rather than failing the handshake.
+struct SniResolver {
+    by_host: HashMap<String, Arc<CertReloader>>,
+    default: Arc<CertReloader>,
+}
+
+impl std::fmt::Debug for SniResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SniResolver").finish_non_exhaustive()
+    }
+}
+
+impl ResolvesServerCert for SniResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let reloader = client_hello
+            .server_name()
+            .and_then(|name| self.by_host.get(name))
+            .unwrap_or(&self.default);
+        Some(reloader.current.load_full())
+    }
+}
+
+/// Load_certified_key parses the PEM-encoded `certfile`/`keyfile` pair into a [`CertifiedKey`]
+/// rustls can serve.
+fn load_certified_key(certfile: &Path, keyfile: &Path) -> Result<CertifiedKey> {
+    load_certified_key_pem(
+        &std::fs::read(certfile)?,
+        &std::fs::read(keyfile)?,
+    )
+}
+
+/// Load_certified_key_pem is [`load_certified_key`]'s byte-slice counterpart, for
+/// [`super::acme`], which has its cert/key PEM in memory (fresh from an ACME order, or read back
+/// out of a `Secret`) rather than on disk.
+pub(super) fn load_certified_key_pem(cert_pem: &[u8], key_pem: &[u8]) -> Result<CertifiedKey> {
+    let certs = rustls_pemfile::certs(&mut std::io::Cursor::new(cert_pem)).collect::<std::io::Result<Vec<_>>>()?;
+    let key = rustls_pemfile::private_key(&mut std::io::Cursor::new(key_pem))?
+        .ok_or_else(|| Error::BadName("no private key found in keyfile".into()))?;
+    let key = rustls::crypto::ring::sign::any_supported_type(&key)?;
+    Ok(CertifiedKey::new(certs, key))
+}
+
+/// Watch_cert_files polls `certfile`/`keyfile`'s mtimes and reloads `reloader`'s current
+/// [`CertifiedKey`] whenever either changes, so a rotated Secret volume mount is picked up within
+/// one poll interval instead of waiting for the pod to restart.
+async fn watch_cert_files(
+    certfile: PathBuf,
+    keyfile: PathBuf,
+    reloader: Arc<CertReloader>,
+    cancel: CancellationToken,
+) {
+    use tokio::time::{interval, Duration};
+
+    let mtime = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    let mut last = (mtime(&certfile), mtime(&keyfile));
+    let mut ticker = interval(Duration::from_secs(30));
+
+    loop {
+        tokio::select! {
+            () = cancel.cancelled() => return,
+            _ = ticker.tick() => {},
+        }
+        let now = (mtime(&certfile), mtime(&keyfile));
+        if now == last {
+            continue;
+        }
+        last = now;
+        match load_certified_key(&certfile, &keyfile) {
+            Ok(key) => {
+                info!("reloaded webhook serving certificate");
+                reloader.current.store(Arc::new(key));
+            }
+            Err(error) => {
+                warn!(%error, "failed to reload webhook serving certificate, keeping current one")
+            }
+        }
+    }
+}
+
+/// Acceptor is the `rustls-tls` backend's TLS listener.
+#[derive(Clone)]
+pub struct Acceptor(TlsAcceptor);
+
+impl Acceptor {
+    /// New loads `certfile`/`keyfile` as the default identity, plus one additional [`CertReloader`]
+    /// per entry in `hosts`, and spawns a background task reloading each of them whenever its
+    /// files' mtimes change, for as long as `cancel` is live. When `hosts` is empty the default
+    /// pair resolves every handshake, same as before `--cert-host` existed; otherwise handshakes
+    /// are resolved by SNI via [`SniResolver`], falling back to the default pair.
+    pub fn new(
+        certfile: &Path,
+        keyfile: &Path,
+        hosts: &[super::HostCert],
+        cancel: CancellationToken,
+    ) -> Result<Self> {
+        let default = Arc::new(CertReloader::new(load_certified_key(certfile, keyfile)?));
+        tokio::spawn(watch_cert_files(
+            certfile.to_path_buf(),
+            keyfile.to_path_buf(),
+            default.clone(),
+            cancel.clone(),
+        ));
+        if hosts.is_empty() {
+            return Ok(Self::from_reloader(default));
+        }
+
+        let mut by_host = HashMap::with_capacity(hosts.len());
+        for host in hosts {
+            let reloader = Arc::new(CertReloader::new(load_certified_key(
+                &host.certfile,
+                &host.keyfile,
+            )?));
+            tokio::spawn(watch_cert_files(
+                host.certfile.clone(),
+                host.keyfile.clone(),
+                reloader.clone(),
+                cancel.clone(),
+            ));
+            by_host.insert(host.host.clone(), reloader);
+        }
+        let tls_config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(SniResolver { by_host, default }));
+        Ok(Self(TlsAcceptor::from(Arc::new(tls_config))))
+    }
+
+    /// From_reloader wraps an already-populated [`CertReloader`], for [`super::acme`] which
+    /// populates and refreshes one itself rather than watching files.
+    pub(super) fn from_reloader(reloader: Arc<CertReloader>) -> Self {
+        let tls_config = rustls::ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(reloader);
+        Self(TlsAcceptor::from(Arc::new(tls_config)))
+    }
+
+    /// Accept completes a TLS handshake on `stream`, against whatever certificate is current at
+    /// the moment the handshake starts.
+    pub async fn accept(
+        &self,
+        stream: TcpStream,
+    ) -> Result<impl tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + Unpin + 'static> {
+        self.0.accept(stream).await.map_err(Error::from)
+    }
+}
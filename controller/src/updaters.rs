@@ -1,38 +1,307 @@
-use std::sync::Arc;
+//! Updaters holds the controller for the "Updater" CRD.
+use std::sync::{Arc, LazyLock};
 
-use tokio::{task, time::Duration};
+use k8s_openapi::merge_strategies;
+use kube::{
+    api::{Api, Patch},
+    client::Client,
+    core::GroupVersionKind,
+    runtime::controller::Error as CtrlErr,
+    ResourceExt,
+};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    time::Duration,
+};
+use tokio_stream::wrappers::SignalStream;
 
-use crate::prelude::*;
-use crate::*;
+use crate::{clair_condition, cmp_condition, merge_condition, prelude::*};
+use clair_templates::{Build, CronJobBuilder};
+use v1alpha1::Updater;
 
-fn error_policy(_obj: Arc<v1alpha1::Updater>, _e: &Error, _ctx: Arc<Context>) -> Action {
-    debug!("error!");
-    Action::await_change()
-}
+/// Kind labels every metric this controller records; see the identical constant in
+/// `indexers.rs`.
+const KIND: &str = "Updater";
 
-async fn reconcile(_obj: Arc<v1alpha1::Updater>, _ctx: Arc<Context>) -> Result<Action> {
-    debug!("reconcile!");
-    Ok(Action::requeue(Duration::from_secs(300)))
-}
+static SELF_GVK: LazyLock<GroupVersionKind> = LazyLock::new(|| GroupVersionKind {
+    group: Updater::group(&()).to_string(),
+    version: Updater::version(&()).to_string(),
+    kind: Updater::kind(&()).to_string(),
+});
 
-pub fn controller(set: &mut task::JoinSet<Result<()>>, ctx: Arc<Context>) {
-    let cfg = watcher::Config::default();
+/// Controller is the Updater controller.
+///
+/// An error is returned if any setup fails.
+#[instrument(skip_all)]
+pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<ControllerFuture> {
     let client = ctx.client.clone();
-    let updaters: Api<v1alpha1::Updater> = Api::default_namespaced(client.clone());
-    let configmaps: Api<core::v1::ConfigMap> = Api::default_namespaced(client.clone());
-    let secrets: Api<core::v1::ConfigMap> = Api::default_namespaced(client.clone());
-    let srvs: Api<core::v1::Service> = Api::default_namespaced(client.clone());
-    let deploys: Api<apps::v1::Deployment> = Api::default_namespaced(client);
-    let ctl = Controller::new(updaters, cfg.clone())
-        .owns(configmaps, cfg.clone())
-        .owns(secrets, cfg.clone())
-        .owns(srvs, cfg.clone())
-        .owns(deploys, cfg);
-    info!("spawning updater controller");
-    set.spawn(async move {
-        ctl.run(reconcile, error_policy, ctx)
-            .for_each(|_| futures::future::ready(()))
+    let ctlcfg = watcher::Config::default();
+    let sig = SignalStream::new(signal(SignalKind::user_defined1())?);
+
+    Ok(async move {
+        info!("spawning updater controller");
+
+        let ctl = Controller::new(Api::<Updater>::all(client.clone()), ctlcfg.clone())
+            .owns(Api::<batch::v1::CronJob>::all(client), ctlcfg)
+            .reconcile_all_on(sig)
+            .graceful_shutdown_on(cancel.cancelled_owned());
+
+        if !ctx.gvk_exists(&SELF_GVK).await {
+            error!("CRD is not queryable ({SELF_GVK:?}); is the CRD installed?");
+            return Err(Error::BadName("no CRD".into()));
+        }
+
+        ctl.run(reconcile, handle_error, ctx)
+            .for_each(|ret| {
+                match ret {
+                    Ok(_) => (),
+                    Err(err) => {
+                        let variant = match &err {
+                            CtrlErr::ObjectNotFound(_) => "object_not_found",
+                            CtrlErr::ReconcilerFailed(_, _) => "reconciler_failed",
+                            CtrlErr::QueueError(_) => "queue_error",
+                            CtrlErr::RunnerError(_) => "runner_error",
+                        };
+                        crate::metrics::record_handle_error(KIND, variant);
+                        match err {
+                            CtrlErr::ObjectNotFound(objref) => error!(%objref, "object not found"),
+                            CtrlErr::ReconcilerFailed(error, objref) => {
+                                error!(%objref, %error, "reconcile error")
+                            }
+                            CtrlErr::QueueError(error) => error!(%error, "queue error"),
+                            CtrlErr::RunnerError(error) => error!(%error, "runner error"),
+                        }
+                    }
+                };
+                futures::future::ready(())
+            })
             .await;
+        debug!("updater controller finished");
+
+        Ok(())
+    }
+    .boxed())
+}
+
+#[derive(Debug)]
+struct Reconciler {
+    updater: Arc<Updater>,
+    ctx: Arc<Context>,
+    namespace: String,
+    api: Api<Updater>,
+}
+
+impl From<(Arc<Updater>, Arc<Context>)> for Reconciler {
+    fn from(value: (Arc<Updater>, Arc<Context>)) -> Self {
+        let (updater, ctx) = value;
+        let namespace = updater.namespace().unwrap(); // Updater is namespace scoped
+        let api: Api<Updater> = Api::namespaced(ctx.client.clone(), &namespace);
+        Self {
+            updater,
+            ctx,
+            namespace,
+            api,
+        }
+    }
+}
+
+impl Reconciler {
+    fn client(&self) -> Client {
+        self.ctx.client.clone()
+    }
+    fn ns(&self) -> &str {
+        self.namespace.as_str()
+    }
+    fn name(&self) -> String {
+        self.updater.name_unchecked()
+    }
+
+    #[instrument(skip(self), ret)]
+    async fn set_condition(&self, cnd: Condition) -> Result<()> {
+        let mut next = self
+            .api
+            .get_status(&self.name())
+            .instrument(debug_span!("get_status"))
+            .await?;
+        next.meta_mut().managed_fields = None;
+        let status = next.status.get_or_insert_default();
+        let cnds = status.conditions.get_or_insert_default();
+        merge_strategies::list::map(cnds, vec![cnd], &[cmp_condition], merge_condition);
+        debug!(payload = ?next, "patching status");
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&next))
+            .instrument(debug_span!("patch_status"))
+            .await?;
+        Ok(())
+    }
+
+    /// Config promotes `spec.config` to `status.config`, since [`CronJobBuilder`] builds from the
+    /// last-validated config rather than the live spec (the same split `clairs.rs`'s own
+    /// `configuration` keeps between what a user wrote and what's actually been acted on).
+    #[instrument(skip(self), ret)]
+    async fn config(&self) -> Result<()> {
+        let config = self
+            .updater
+            .spec
+            .config
+            .clone()
+            .expect("check_spec already gated on spec.config being present");
+
+        if self
+            .updater
+            .status
+            .as_ref()
+            .and_then(|s| s.config.as_ref())
+            == Some(&config)
+        {
+            debug!("no need to update status");
+            return Ok(());
+        }
+        debug!("updating status");
+
+        let mut next = self
+            .api
+            .get_status(&self.name())
+            .instrument(debug_span!("get_status"))
+            .await?;
+        next.meta_mut().managed_fields = None;
+        let status = next.status.get_or_insert_default();
+        status.config = Some(config);
+
+        let cnd = Condition {
+            message: "promoted spec.config".into(),
+            observed_generation: self.updater.metadata.generation,
+            last_transition_time: meta::v1::Time(Utc::now()),
+            reason: "ConfigReady".into(),
+            status: "True".into(),
+            type_: clair_condition("ConfigReady"),
+        };
+        let cnds = status.conditions.get_or_insert_default();
+        merge_strategies::list::map(cnds, vec![cnd], &[cmp_condition], merge_condition);
+
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&next))
+            .instrument(debug_span!("patch_status"))
+            .await?;
         Ok(())
-    });
+    }
+
+    #[instrument(skip(self), ret)]
+    async fn cron_job(&self) -> Result<()> {
+        use batch::v1::CronJob;
+
+        let api = Api::<CronJob>::namespaced(self.client(), self.ns());
+        let had_cron_job = self
+            .updater
+            .status
+            .as_ref()
+            .and_then(|s| s.cron_job.as_ref())
+            .is_some();
+
+        let c = CronJobBuilder::try_from(self.updater.as_ref())?.build();
+        let name = c.name_any();
+        api.patch(&name, &PATCH_PARAMS, &Patch::Apply(&c))
+            .instrument(debug_span!("patch", kind = "CronJob"))
+            .await
+            .inspect_err(|error| error!(%error, "failed to patch CronJob"))?;
+
+        let cron_job_status = v1alpha1::UpdaterStatus {
+            cron_job: Some(core::v1::TypedLocalObjectReference {
+                kind: CronJob::kind(&()).to_string(),
+                api_group: CronJob::api_version(&()).to_string().into(),
+                name,
+            }),
+            ..Default::default()
+        };
+        self.api
+            .patch_status(&self.name(), &PATCH_PARAMS, &Patch::Apply(&cron_job_status))
+            .instrument(debug_span!("patch_status", field = "cronJob"))
+            .await
+            .inspect_err(|error| error!(%error, "unable to patch cronJob status"))?;
+
+        if had_cron_job {
+            debug!("no need to update status");
+            return Ok(());
+        }
+        debug!("updating status");
+
+        let cnd = Condition {
+            message: "created CronJob".into(),
+            observed_generation: self.updater.metadata.generation,
+            last_transition_time: meta::v1::Time(Utc::now()),
+            reason: "CronJobCreated".into(),
+            status: "True".into(),
+            type_: clair_condition("CronJobCreated"),
+        };
+        self.set_condition(cnd).await?;
+
+        Ok(())
+    }
+
+    #[instrument(skip(self), ret)]
+    async fn check_spec(&self) -> Result<Option<Action>> {
+        let mut cnd = Condition {
+            last_transition_time: meta::v1::Time(Utc::now()),
+            observed_generation: self.updater.metadata.generation,
+            type_: clair_condition("SpecOK"),
+            message: "".into(),
+            reason: "SpecIncomplete".into(),
+            status: "False".into(),
+        };
+
+        if self.updater.spec.config.is_none() {
+            error!("spec missing ConfigSource");
+            self.set_condition(cnd).await?;
+            return Ok(Action::requeue(Duration::from_secs(3600)).into());
+        }
+        if self.updater.spec.image.is_none() {
+            error!("spec missing image");
+            self.set_condition(cnd).await?;
+            return Ok(Action::requeue(Duration::from_secs(3600)).into());
+        }
+
+        cnd.status = "True".into();
+        cnd.reason = "SpecComplete".into();
+        self.set_condition(cnd).await?;
+        Ok(None)
+    }
+}
+
+/// Reconcile is the main entrypoint for the reconcile loop.
+#[instrument(skip(ctx, updater), fields(name = updater.name_any(), namespace = updater.namespace().unwrap()))]
+async fn reconcile(updater: Arc<Updater>, ctx: Arc<Context>) -> Result<Action> {
+    assert!(updater.meta().name.is_some());
+    info!("reconciling Updater");
+    let mut timer = crate::metrics::ReconcileTimer::start(KIND);
+
+    let ret = reconcile_updater(updater.clone(), ctx.clone()).await;
+    if ret.is_ok() {
+        let key = format!("{}/{}", updater.namespace().unwrap(), updater.name_any());
+        ctx.backoff_reset(&SELF_GVK, &key);
+    }
+
+    timer.finish(&ret);
+    ret
+}
+
+/// Reconcile_updater is [`reconcile`]'s body, split out so the duration/result metrics wrap every
+/// return path --- including the early return out of [`Reconciler::check_spec`] --- instead of
+/// only the happy path that falls through to the end.
+async fn reconcile_updater(updater: Arc<Updater>, ctx: Arc<Context>) -> Result<Action> {
+    let r = Reconciler::from((updater.clone(), ctx.clone()));
+
+    if let Some(a) = r.check_spec().await? {
+        return Ok(a);
+    };
+    r.config().await?;
+    r.cron_job().await?;
+
+    Ok(DEFAULT_REQUEUE.clone())
+}
+
+#[instrument(skip_all)]
+fn handle_error(obj: Arc<Updater>, err: &Error, ctx: Arc<Context>) -> Action {
+    error!(%err, "reconcile error");
+    let key = format!("{}/{}", obj.namespace().unwrap(), obj.name_any());
+    ctx.backoff_action(&SELF_GVK, &key)
 }
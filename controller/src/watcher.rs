@@ -0,0 +1,519 @@
+//! Watcher implements a node/pod/deployment watching subsystem that observes Clair component
+//! health out-of-band from the CRD-driven controllers.
+//!
+//! The CRD controllers (see [`crate::clairs`], [`crate::indexers`]) only resync on a timer or
+//! when an owned object changes, so losing a node can leave a Clair component unscheduled for a
+//! while. This module is modeled on Akri's `node_watcher`/`pod_watcher`/`instance_action` split:
+//! [`watch_nodes`] tracks node readiness, [`watch_pods`] tracks Clair-owned Pods and places a
+//! finalizer on them so deletions are observed deterministically instead of racing the
+//! apiserver's garbage collector, [`watch_deployments`] reflects Deployment health onto whichever
+//! CRD owns it, and [`instance_action`] nudges the owning CRD to reconcile whenever a watcher
+//! decides a workload needs to be re-placed.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    sync::{Arc, LazyLock},
+};
+
+use k8s_openapi::NamespaceResourceScope;
+use kube::{
+    api::Patch,
+    core::object::HasStatus,
+    runtime::{
+        finalizer::{finalizer, Event as Finalizer},
+        reflector::{self, ObjectRef, Store},
+        watcher::Event,
+        WatchStreamExt,
+    },
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::json;
+use tokio::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use api::v1alpha1::{CrdCommon, StatusCommon};
+
+use crate::prelude::*;
+
+/// FINALIZER is placed on Clair-owned Pods so deletions are observed deterministically.
+static FINALIZER: LazyLock<String> = LazyLock::new(|| crate::clair_label("watcher"));
+
+/// FLAP_DEBOUNCE is the minimum time between acting on repeated NotReady transitions for the
+/// same node, to avoid thrashing on a node that's merely flapping.
+static FLAP_DEBOUNCE: Duration = Duration::from_secs(30);
+
+/// RELOCATED_ANNOTATION is touched on the owning CRD to force its controller to reconcile.
+static RELOCATED_ANNOTATION: LazyLock<String> = LazyLock::new(|| crate::clair_label("relocated-at"));
+
+/// NodeEntry is what's tracked per-node in the [`NodeMap`].
+#[derive(Default)]
+struct NodeEntry {
+    ready: bool,
+    last_transition: Option<Instant>,
+    pods: HashSet<(String, String)>,
+}
+
+/// NodeMap is the shared node-to-instance mapping.
+///
+/// It's keyed by node name and records which namespaced Pods are currently scheduled there, so
+/// that [`watch_nodes`] can find the Pods to evict without an extra API call, while
+/// [`watch_pods`] keeps the membership up to date as Pods come and go.
+#[derive(Default)]
+pub struct NodeMap(HashMap<String, NodeEntry>);
+
+/// SharedNodeMap is a [`NodeMap`] guarded for concurrent use by the node and pod watchers.
+pub type SharedNodeMap = Arc<RwLock<NodeMap>>;
+
+/// Controller starts the node/pod watcher subsystem.
+///
+/// Unlike the CRD controllers, this isn't keyed off of a single kind, so it's always run
+/// alongside whichever CRD controllers are selected.
+#[instrument(skip_all)]
+pub fn controller(cancel: CancellationToken, ctx: Arc<Context>) -> Result<ControllerFuture> {
+    let nodes = SharedNodeMap::default();
+
+    Ok(async move {
+        info!("spawning node/pod/deployment watcher");
+        tokio::try_join!(
+            watch_nodes(ctx.clone(), nodes.clone(), cancel.clone()),
+            watch_pods(ctx.clone(), nodes.clone(), cancel.clone()),
+            watch_deployments(ctx.clone(), cancel.clone()),
+        )?;
+        Ok(())
+    }
+    .boxed())
+}
+
+/// Watch_nodes watches cluster [`Node`](core::v1::Node) objects and reschedules any tracked Pods
+/// on a node that goes `NotReady` or disappears.
+#[instrument(skip_all)]
+async fn watch_nodes(ctx: Arc<Context>, nodes: SharedNodeMap, cancel: CancellationToken) -> Result<()> {
+    let api: Api<core::v1::Node> = Api::all(ctx.client.clone());
+    let stream = watcher::watcher(api, watcher::Config::default())
+        .default_backoff()
+        .take_until(cancel.cancelled_owned());
+    tokio::pin!(stream);
+
+    while let Some(ev) = stream.try_next().await? {
+        match ev {
+            Event::Apply(node) | Event::InitApply(node) => {
+                let name = node.name_any();
+                let ready = node
+                    .status
+                    .as_ref()
+                    .and_then(|s| s.conditions.as_ref())
+                    .and_then(|cs| cs.iter().find(|c| c.type_ == "Ready"))
+                    .is_some_and(|c| c.status == "True");
+                handle_node_transition(&ctx, &nodes, &name, ready).await?;
+            }
+            Event::Delete(node) => {
+                handle_node_transition(&ctx, &nodes, &node.name_any(), false).await?;
+            }
+            Event::Init | Event::InitDone => {}
+        }
+    }
+    Ok(())
+}
+
+/// Handle_node_transition debounces flapping nodes and, once a node is confirmed `NotReady`,
+/// triggers [`instance_action`] for every Pod tracked against it.
+async fn handle_node_transition(
+    ctx: &Arc<Context>,
+    nodes: &SharedNodeMap,
+    node: &str,
+    ready: bool,
+) -> Result<()> {
+    let pods = {
+        let mut map = nodes.write().await;
+        let entry = map.0.entry(node.to_string()).or_default();
+        if entry.ready == ready {
+            return Ok(());
+        }
+        if let Some(last) = entry.last_transition {
+            if !ready && last.elapsed() < FLAP_DEBOUNCE {
+                trace!(node, "debouncing node flap");
+                entry.ready = ready;
+                return Ok(());
+            }
+        }
+        entry.ready = ready;
+        entry.last_transition = Some(Instant::now());
+        if ready {
+            return Ok(());
+        }
+        entry.pods.clone()
+    };
+
+    if pods.is_empty() {
+        return Ok(());
+    }
+    warn!(node, count = pods.len(), "node unready, rescheduling tracked pods");
+    for (namespace, name) in pods {
+        let api: Api<core::v1::Pod> = Api::namespaced(ctx.client.clone(), &namespace);
+        if let Ok(pod) = api.get(&name).await {
+            reflect_node_not_ready(ctx, &pod).await?;
+            instance_action(ctx, &pod).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Watch_pods watches Clair-owned [`Pod`](core::v1::Pod) objects, keeping [`NodeMap`] current
+/// and placing [`FINALIZER`] on them so this subsystem observes deletions (including evictions)
+/// before the apiserver finishes garbage collection.
+#[instrument(skip_all)]
+async fn watch_pods(ctx: Arc<Context>, nodes: SharedNodeMap, cancel: CancellationToken) -> Result<()> {
+    let api: Api<core::v1::Pod> = Api::all(ctx.client.clone());
+    let cfg = watcher::Config::default().labels(&format!("{}=clair", *crate::APP_NAME_LABEL));
+    let stream = watcher::watcher(api.clone(), cfg)
+        .default_backoff()
+        .take_until(cancel.cancelled_owned());
+    tokio::pin!(stream);
+
+    while let Some(ev) = stream.try_next().await? {
+        match ev {
+            Event::Apply(pod) | Event::InitApply(pod) => {
+                track_pod(&nodes, &pod).await;
+                let api = api.clone();
+                let ctx = ctx.clone();
+                finalizer(&api, FINALIZER.as_str(), pod, |event| async move {
+                    match event {
+                        Finalizer::Apply(_) => Ok(Action::await_change()),
+                        Finalizer::Cleanup(pod) => {
+                            instance_action(&ctx, &pod).await.map_err(Box::new)?;
+                            Ok(Action::await_change())
+                        }
+                    }
+                })
+                .await
+                .map_err(|e| Error::Other(e.into()))?;
+            }
+            Event::Delete(pod) => {
+                untrack_pod(&nodes, &pod).await;
+            }
+            Event::Init | Event::InitDone => {}
+        }
+    }
+    Ok(())
+}
+
+async fn track_pod(nodes: &SharedNodeMap, pod: &core::v1::Pod) {
+    let Some(node) = pod.spec.as_ref().and_then(|s| s.node_name.clone()) else {
+        return;
+    };
+    let key = (pod.namespace().unwrap_or_default(), pod.name_any());
+    let mut map = nodes.write().await;
+    map.0.entry(node).or_default().pods.insert(key);
+}
+
+async fn untrack_pod(nodes: &SharedNodeMap, pod: &core::v1::Pod) {
+    let Some(node) = pod.spec.as_ref().and_then(|s| s.node_name.clone()) else {
+        return;
+    };
+    let key = (pod.namespace().unwrap_or_default(), pod.name_any());
+    let mut map = nodes.write().await;
+    if let Some(entry) = map.0.get_mut(&node) {
+        entry.pods.remove(&key);
+    }
+}
+
+/// Reflect_node_not_ready walks `pod`'s owner chain the same way [`instance_action`] does and
+/// patches an `Available=False` (`NodeNotReady`) condition straight onto whichever worker CRD
+/// owns it, so a stranded Pod is visible on the CRD status immediately instead of waiting for
+/// [`watch_deployments`]'s own `Available`/`Progressing` reflection to eventually catch up once
+/// the Deployment's own status drifts.
+#[instrument(skip(ctx), fields(pod = pod.name_any()))]
+async fn reflect_node_not_ready(ctx: &Arc<Context>, pod: &core::v1::Pod) -> Result<()> {
+    let Some(ns) = pod.namespace() else {
+        return Ok(());
+    };
+    let Some(rs_ref) = controller_of(pod) else {
+        return Ok(());
+    };
+    let replicasets: Api<apps::v1::ReplicaSet> = Api::namespaced(ctx.client.clone(), &ns);
+    let Ok(rs) = replicasets.get(&rs_ref.name).await else {
+        return Ok(());
+    };
+    let Some(dep_ref) = controller_of(&rs) else {
+        return Ok(());
+    };
+    let deployments: Api<apps::v1::Deployment> = Api::namespaced(ctx.client.clone(), &ns);
+    let Ok(dep) = deployments.get(&dep_ref.name).await else {
+        return Ok(());
+    };
+    let Some(owner) = controller_of(&dep) else {
+        return Ok(());
+    };
+
+    use v1alpha1::{Indexer, Matcher, Notifier};
+    let cnd = Condition {
+        message: format!("pod {} is stranded on a NotReady node", pod.name_any()),
+        observed_generation: None,
+        last_transition_time: meta::v1::Time(Utc::now()),
+        reason: "NodeNotReady".into(),
+        status: "False".into(),
+        type_: crate::clair_condition("Available"),
+    };
+    macro_rules! try_reflect {
+        ($kind:ty) => {
+            if owner.kind == <$kind>::kind(&()) {
+                let api: Api<$kind> = Api::namespaced(ctx.client.clone(), &ns);
+                let mut status = api
+                    .get_status(&owner.name)
+                    .await?
+                    .status()
+                    .cloned()
+                    .unwrap_or_default();
+                status.add_condition(cnd);
+                let patch = Patch::Apply(json!({
+                    "apiVersion": <$kind>::api_version(&()),
+                    "kind": <$kind>::kind(&()),
+                    "status": status,
+                }));
+                api.patch_status(&owner.name, &PATCH_PARAMS, &patch).await?;
+                return Ok(());
+            }
+        };
+    }
+    try_reflect!(Indexer);
+    try_reflect!(Matcher);
+    try_reflect!(Notifier);
+    Ok(())
+}
+
+/// Instance_action walks a Pod's owner chain (Pod -> ReplicaSet -> Deployment -> CRD) and
+/// touches [`RELOCATED_ANNOTATION`] on the owning CRD, which bumps its `resourceVersion` and
+/// causes the owning component's own controller to reconcile and re-place the workload.
+#[instrument(skip(ctx), fields(pod = pod.name_any()))]
+async fn instance_action(ctx: &Arc<Context>, pod: &core::v1::Pod) -> Result<()> {
+    let Some(ns) = pod.namespace() else {
+        return Ok(());
+    };
+    let Some(rs_ref) = controller_of(pod) else {
+        return Ok(());
+    };
+    let replicasets: Api<apps::v1::ReplicaSet> = Api::namespaced(ctx.client.clone(), &ns);
+    let Ok(rs) = replicasets.get(&rs_ref.name).await else {
+        return Ok(());
+    };
+    let Some(dep_ref) = controller_of(&rs) else {
+        return Ok(());
+    };
+    let deployments: Api<apps::v1::Deployment> = Api::namespaced(ctx.client.clone(), &ns);
+    let Ok(dep) = deployments.get(&dep_ref.name).await else {
+        return Ok(());
+    };
+    let Some(owner) = controller_of(&dep) else {
+        return Ok(());
+    };
+
+    info!(
+        kind = owner.kind,
+        name = owner.name,
+        "rescheduling via owning resource"
+    );
+    if let Err(err) = patch_owner(ctx, &ns, &owner).await {
+        warn!(%err, "failed to nudge owning resource, will retry on next event");
+    }
+    Ok(())
+}
+
+/// Controller_of returns the owner reference with `controller: true`, if any.
+fn controller_of<K: Resource<DynamicType = ()>>(
+    obj: &K,
+) -> Option<meta::v1::OwnerReference> {
+    obj.owner_references()
+        .iter()
+        .find(|r| r.controller == Some(true))
+        .cloned()
+}
+
+/// Patch_owner touches [`RELOCATED_ANNOTATION`] on whichever CRD kind `owner` names.
+async fn patch_owner(
+    ctx: &Arc<Context>,
+    ns: &str,
+    owner: &meta::v1::OwnerReference,
+) -> Result<()> {
+    use v1alpha1::{Clair, Indexer, Matcher, Notifier};
+
+    let now = chrono::Utc::now().to_rfc3339();
+    let patch = Patch::Merge(json!({
+        "metadata": { "annotations": { RELOCATED_ANNOTATION.as_str(): now } },
+    }));
+    macro_rules! try_patch {
+        ($kind:ty) => {
+            if owner.kind == <$kind>::kind(&()) {
+                let api: Api<$kind> = Api::namespaced(ctx.client.clone(), ns);
+                api.patch(&owner.name, &PATCH_PARAMS, &patch).await?;
+                return Ok(());
+            }
+        };
+    }
+    try_patch!(Clair);
+    try_patch!(Indexer);
+    try_patch!(Matcher);
+    try_patch!(Notifier);
+    Ok(())
+}
+
+/// Watch_deployments watches Clair-owned [`Deployment`](apps::v1::Deployment) objects and
+/// reflects their health onto whichever CRD owns them, so that a Deployment going unhealthy or
+/// disappearing out-of-band is observed instead of waiting on the owner's next timed resync.
+#[instrument(skip_all)]
+async fn watch_deployments(ctx: Arc<Context>, cancel: CancellationToken) -> Result<()> {
+    let api: Api<apps::v1::Deployment> = Api::all(ctx.client.clone());
+    let cfg = watcher::Config::default().labels(&format!("{}=clair", *crate::APP_NAME_LABEL));
+    let (store, writer) = reflector::store();
+    let stream = reflector::reflector(writer, watcher::watcher(api, cfg))
+        .default_backoff()
+        .take_until(cancel.cancelled_owned());
+    tokio::pin!(stream);
+
+    while let Some(ev) = stream.try_next().await? {
+        match ev {
+            Event::Apply(dep) | Event::InitApply(dep) => {
+                reflect_deployment_health(&ctx, &store, &dep).await?;
+            }
+            Event::Delete(dep) => {
+                reflect_deployment_deleted(&ctx, &dep).await?;
+            }
+            Event::Init | Event::InitDone => {}
+        }
+    }
+    Ok(())
+}
+
+/// Reflect_deployment_health reads `dep` back out of `store` (so the patch reflects the
+/// reflector's latest cached copy rather than the event's; every event still issues its own
+/// status patch) and patches its controlling owner's `status.conditions` with
+/// `Available`/`Progressing`.
+async fn reflect_deployment_health(
+    ctx: &Arc<Context>,
+    store: &Store<apps::v1::Deployment>,
+    dep: &apps::v1::Deployment,
+) -> Result<()> {
+    let Some(ns) = dep.namespace() else {
+        return Ok(());
+    };
+    let Some(owner) = controller_of(dep) else {
+        return Ok(());
+    };
+    let Some(dep) = store.get(&ObjectRef::from_obj(dep)) else {
+        return Ok(());
+    };
+
+    use v1alpha1::{Indexer, Matcher, Notifier};
+    macro_rules! try_reflect {
+        ($kind:ty) => {
+            if owner.kind == <$kind>::kind(&()) {
+                return reflect_conditions::<$kind>(ctx, &ns, &owner.name, &dep).await;
+            }
+        };
+    }
+    try_reflect!(Indexer);
+    try_reflect!(Matcher);
+    try_reflect!(Notifier);
+    Ok(())
+}
+
+/// Reflect_deployment_deleted nudges the owning CRD to reconcile when its managed Deployment
+/// disappears, so `create_if_needed` recreates it on the owner's next pass.
+async fn reflect_deployment_deleted(ctx: &Arc<Context>, dep: &apps::v1::Deployment) -> Result<()> {
+    let Some(ns) = dep.namespace() else {
+        return Ok(());
+    };
+    let Some(owner) = controller_of(dep) else {
+        return Ok(());
+    };
+    warn!(
+        kind = owner.kind,
+        name = owner.name,
+        "managed deployment deleted, nudging owner to reconcile"
+    );
+    patch_owner(ctx, &ns, &owner).await
+}
+
+/// Reflect_conditions patches `dep`'s derived conditions onto `K`'s status, ignoring the event if
+/// `dep` isn't the Deployment recorded in `status.has_ref`, e.g. a stale event for a Deployment
+/// the owner has since replaced.
+async fn reflect_conditions<K>(
+    ctx: &Arc<Context>,
+    ns: &str,
+    name: &str,
+    dep: &apps::v1::Deployment,
+) -> Result<()>
+where
+    K: Resource<Scope = NamespaceResourceScope, DynamicType = ()>
+        + CrdCommon
+        + HasStatus<Status = <K as CrdCommon>::Status>
+        + Clone
+        + DeserializeOwned
+        + Serialize
+        + Debug,
+    K::Status: Clone + Default + Serialize,
+{
+    let api: Api<K> = Api::namespaced(ctx.client.clone(), ns);
+    let owner = api.get_status(name).await?;
+
+    let recorded = owner
+        .status()
+        .and_then(|s| s.has_ref::<apps::v1::Deployment>())
+        .map(|r| r.name);
+    if recorded.as_deref() != Some(dep.name_any().as_str()) {
+        trace!(name, "deployment isn't the one recorded in status, ignoring");
+        return Ok(());
+    }
+
+    let mut status = owner.status().cloned().unwrap_or_default();
+    for cnd in deployment_conditions(dep, owner.meta().generation) {
+        status.add_condition(cnd);
+    }
+    let patch = Patch::Apply(json!({
+        "apiVersion": K::api_version(&()),
+        "kind": K::kind(&()),
+        "status": status,
+    }));
+    api.patch_status(name, &PATCH_PARAMS, &patch).await?;
+    Ok(())
+}
+
+/// Deployment_conditions derives `Available`/`Progressing` conditions from `dep`'s status, for
+/// reflection onto whichever CRD owns it. `observed_generation` should be the owner's, not the
+/// Deployment's, generation.
+fn deployment_conditions(dep: &apps::v1::Deployment, observed_generation: Option<i64>) -> Vec<Condition> {
+    let status = dep.status.clone().unwrap_or_default();
+    let desired = dep.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+    let available = status.available_replicas.unwrap_or(0);
+    let ready = status.ready_replicas.unwrap_or(0);
+    let now = meta::v1::Time(Utc::now());
+
+    vec![
+        Condition {
+            message: format!("{available}/{desired} replicas available"),
+            observed_generation,
+            last_transition_time: now.clone(),
+            reason: if available > 0 {
+                "MinimumReplicasAvailable".into()
+            } else {
+                "MinimumReplicasUnavailable".into()
+            },
+            status: if available > 0 { "True".into() } else { "False".into() },
+            type_: crate::clair_condition("Available"),
+        },
+        Condition {
+            message: format!("{ready}/{desired} replicas ready"),
+            observed_generation,
+            last_transition_time: now,
+            reason: if ready >= desired {
+                "NewReplicaSetAvailable".into()
+            } else {
+                "ReplicaSetUpdating".into()
+            },
+            status: if ready >= desired { "True".into() } else { "False".into() },
+            type_: crate::clair_condition("Progressing"),
+        },
+    ]
+}
@@ -2,20 +2,26 @@
 
 use std::sync::Arc;
 
-use axum::{Json, Router, extract, routing::post};
+use axum::{
+    Json, Router, extract,
+    routing::{get, post},
+};
 use tower_http::trace::TraceLayer;
 #[allow(unused_imports)]
 use tracing::{debug, error, info, instrument, trace};
 
+use crate::Context;
+
 /// State is the webhook application server state.
 pub struct State {
     client: kube::Client,
+    ctx: Arc<Context>,
 }
 
 impl State {
     /// New creates a new State.
-    pub fn new(client: kube::Client) -> State {
-        State { client }
+    pub fn new(client: kube::Client, ctx: Arc<Context>) -> State {
+        State { client, ctx }
     }
 }
 
@@ -27,6 +33,7 @@ pub fn app(srv: State) -> Router {
         .route("/convert", post(convert::handler))
         .route("/v1alpha1/mutate", post(v1alpha1::mutate::handler))
         .route("/v1alpha1/validate", post(v1alpha1::validate::handler))
+        .route("/diagnostics", get(diagnostics::handler))
         .layer(TraceLayer::new_for_http())
         .with_state(state);
     trace!("router constructed");
@@ -47,16 +54,238 @@ mod prelude {
     };
     pub use serde::Deserialize;
     pub use tracing::{debug, error, info, instrument, trace};
+    pub use validator::Validate;
 
     pub use super::State;
 }
 
+mod diagnostics {
+    use super::prelude::*;
+
+    /// Handler serves `/diagnostics`: a read-only JSON snapshot of the most recent
+    /// status-condition transition and a rolling per-condition-type count, so operators can
+    /// check controller liveness without digging through `kubectl describe`.
+    #[instrument(skip_all)]
+    pub async fn handler(
+        extract::State(srv): extract::State<Arc<State>>,
+    ) -> Json<crate::Diagnostics> {
+        Json(srv.ctx.diagnostics().await)
+    }
+}
+
 mod convert {
+    use std::collections::HashMap;
+    use std::sync::LazyLock;
+
+    use kube::core::{
+        DynamicObject, TypeMeta,
+        conversion::{ConversionResponse, ConversionReview},
+    };
+    use serde_json::Value;
+
+    use api::{self, v1alpha1};
+
     use super::*;
 
+    /// HUB_VERSION is the version every converter composes through.
+    ///
+    /// Keying converters by `(from, to, kind)` directly would need O(n²) converters as versions
+    /// are added; going through a hub means each new version only needs a converter to and from
+    /// the hub, i.e. O(n).
+    static HUB_VERSION: &str = "v1alpha1";
+
+    /// Convert is implemented by every on-the-wire version of a kind, and knows how to move
+    /// to/from that kind's [`HUB_VERSION`] representation.
+    trait Convert: Sized + serde::Serialize + serde::de::DeserializeOwned {
+        fn to_hub(self) -> Result<Value, serde_json::Error> {
+            serde_json::to_value(self)
+        }
+        fn from_hub(v: Value) -> Result<Self, serde_json::Error> {
+            serde_json::from_value(v)
+        }
+    }
+
+    macro_rules! hub_impls {
+        ($($kind:ty),+ $(,)?) => {
+            $(impl Convert for $kind {})+
+        };
+    }
+    hub_impls!(
+        v1alpha1::Clair,
+        v1alpha1::Indexer,
+        v1alpha1::Matcher,
+        v1alpha1::Notifier,
+        v1alpha1::Updater,
+        api::v1beta1::Clair,
+    );
+
+    /// ToHub is a per-kind, per-version function converting a [`Value`] into the hub
+    /// representation.
+    type ToHub = fn(Value) -> Result<Value, serde_json::Error>;
+    /// FromHub is a per-kind, per-version function converting the hub representation into a
+    /// [`Value`] for that version.
+    type FromHub = fn(Value) -> Result<Value, serde_json::Error>;
+
+    /// CONVERTERS is the `(kind, version) -> (to_hub, from_hub)` table.
+    ///
+    /// Adding a new on-the-wire version for a kind is just adding an entry here; nothing else in
+    /// this module needs to change.
+    static CONVERTERS: LazyLock<HashMap<(&'static str, &'static str), (ToHub, FromHub)>> =
+        LazyLock::new(|| {
+            fn pair<T: Convert>() -> (ToHub, FromHub) {
+                (
+                    |v| Ok(T::to_hub(serde_json::from_value::<T>(v)?)?),
+                    |v| serde_json::to_value(T::from_hub(v)?),
+                )
+            }
+            HashMap::from([
+                (("Clair", HUB_VERSION), pair::<v1alpha1::Clair>()),
+                (("Indexer", HUB_VERSION), pair::<v1alpha1::Indexer>()),
+                (("Matcher", HUB_VERSION), pair::<v1alpha1::Matcher>()),
+                (("Notifier", HUB_VERSION), pair::<v1alpha1::Notifier>()),
+                (("Updater", HUB_VERSION), pair::<v1alpha1::Updater>()),
+                (("Clair", "v1beta1"), pair::<api::v1beta1::Clair>()),
+            ])
+        });
+
+    /// Convert_object converts a single object to `to_version`, composing through the hub.
+    pub(super) fn convert_object(
+        mut obj: DynamicObject,
+        to_version: &str,
+    ) -> Result<DynamicObject, String> {
+        let types = obj
+            .types
+            .clone()
+            .ok_or_else(|| "object is missing \"apiVersion\"/\"kind\"".to_string())?;
+        let kind = types.kind.as_str();
+        let from_version = types
+            .api_version
+            .rsplit('/')
+            .next()
+            .unwrap_or(types.api_version.as_str());
+
+        if from_version == to_version {
+            return Ok(obj);
+        }
+
+        let (to_hub, _) = CONVERTERS
+            .get(&(kind, from_version))
+            .ok_or_else(|| format!("no converter for {kind} {from_version} -> {HUB_VERSION}"))?;
+        let (_, from_hub) = CONVERTERS
+            .get(&(kind, to_version))
+            .ok_or_else(|| format!("no converter for {kind} {HUB_VERSION} -> {to_version}"))?;
+
+        let hub = to_hub(Value::Object(obj.data.as_object().cloned().unwrap_or_default()))
+            .map_err(|err| err.to_string())?;
+        let data = from_hub(hub).map_err(|err| err.to_string())?;
+        obj.data = data;
+        obj.types = Some(TypeMeta {
+            api_version: format!("{}/{to_version}", api::GROUP),
+            kind: kind.to_string(),
+        });
+        Ok(obj)
+    }
+
+    /// Handler serves the `/convert` endpoint: it accepts a [`ConversionReview`], converts each
+    /// object in the request to `desiredAPIVersion`, and returns the results in a single
+    /// response, matching the kube-apiserver CRD conversion webhook contract.
     #[instrument(skip_all)]
-    pub async fn handler(extract::Json(_req): Json<()>) -> Json<()> {
-        todo!()
+    pub async fn handler(extract::Json(rev): Json<ConversionReview>) -> Json<ConversionReview> {
+        let Some(mut req) = rev.request else {
+            error!("missing \"request\" in ConversionReview");
+            return Json(ConversionReview {
+                types: rev.types,
+                request: None,
+                response: None,
+            });
+        };
+        let to_version = req.desired_api_version.clone();
+        let incoming = std::mem::take(&mut req.objects);
+        let mut res = ConversionResponse::for_request(req);
+
+        let mut objects = Vec::with_capacity(incoming.len());
+        for obj in incoming {
+            match convert_object(obj, &to_version) {
+                Ok(obj) => objects.push(obj),
+                Err(err) => {
+                    error!(error = %err, "conversion failed");
+                    return Json(res.failure(&err).into_review());
+                }
+            }
+        }
+
+        res.converted_objects = objects;
+        Json(res.success().into_review())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use kube::CustomResourceExt;
+
+        /// Round_trip exercises a kind's registered (to_hub, from_hub) pair back-to-back, the
+        /// same composition `convert_object` performs whenever `from` and `to` differ, and
+        /// checks nothing is lost.
+        #[test]
+        fn hub_round_trip_is_lossless() {
+            let clair = v1alpha1::Clair::new("test", Default::default());
+            let want = serde_json::to_value(&clair).expect("serializable");
+
+            let (to_hub, from_hub) = CONVERTERS
+                .get(&("Clair", HUB_VERSION))
+                .expect("Clair is a registered kind");
+            let hub = to_hub(want.clone()).expect("convert up");
+            let got = from_hub(hub).expect("convert down");
+
+            assert_eq!(want, got);
+        }
+
+        /// Clair_v1alpha1_v1beta1_round_trip feeds a batch of `v1alpha1` `Clair`s with varying
+        /// specs through `convert_object` up to `v1beta1` and back down, the same path the
+        /// `/convert` endpoint composes for a `ConversionReview` listing objects at one version
+        /// while `desiredAPIVersion` names the other, and checks each one survives unchanged.
+        #[test]
+        fn clair_v1alpha1_v1beta1_round_trip_is_lossless() {
+            let specs = [
+                v1alpha1::ClairSpec::default(),
+                v1alpha1::ClairSpec {
+                    image: Some("localhost/test:1".parse().unwrap()),
+                    notifier: Some(true),
+                    ..Default::default()
+                },
+            ];
+
+            for spec in specs {
+                let clair = v1alpha1::Clair::new("test", spec);
+                let mut obj = DynamicObject::new(
+                    clair.metadata.name.as_deref().unwrap(),
+                    &v1alpha1::Clair::api_resource(),
+                );
+                obj.types = Some(TypeMeta {
+                    api_version: format!("{}/{HUB_VERSION}", api::GROUP),
+                    kind: "Clair".into(),
+                });
+                obj.data = serde_json::to_value(&clair).expect("serializable");
+
+                let up = convert_object(obj.clone(), "v1beta1").expect("convert up");
+                assert_eq!(up.types.as_ref().unwrap().api_version, format!("{}/v1beta1", api::GROUP));
+                let down = convert_object(up, HUB_VERSION).expect("convert down");
+
+                assert_eq!(down.data, obj.data);
+            }
+        }
+
+        #[test]
+        fn convert_object_rejects_unknown_kind() {
+            let mut obj = DynamicObject::new("test", &v1alpha1::Clair::api_resource());
+            obj.types = Some(TypeMeta {
+                api_version: format!("{}/{HUB_VERSION}", api::GROUP),
+                kind: "NoSuchKind".into(),
+            });
+
+            let err = convert_object(obj, "v1beta1").unwrap_err();
+            assert!(err.contains("no converter"));
+        }
     }
 }
 
@@ -79,12 +308,39 @@ mod v1alpha1 {
     pub(super) mod mutate {
         use super::*;
 
+        use std::sync::LazyLock;
+
         use json_patch::jsonptr::PointerBuf;
         use json_patch::{AddOperation as Add, Patch, PatchOperation as Op};
         use serde_json::Value;
 
         use crate::DEFAULT_IMAGE;
 
+        /// OPERATOR_VERSION_ANNOTATION is the annotation key recording the version of the
+        /// operator that last defaulted a CR, so `kubectl get -o yaml` shows which build a
+        /// resource was last mutated by.
+        static OPERATOR_VERSION_ANNOTATION: LazyLock<String> =
+            LazyLock::new(|| crate::clair_label("operator-version"));
+
+        /// Custom `Either` type for our config handling.
+        ///
+        /// Mirrors the type of the same name in [`super::validate`] — kept separate since each
+        /// webhook handler owns its own config-loading path.
+        enum Either {
+            ConfigMap(core::v1::ConfigMap),
+            Secret(core::v1::Secret),
+        }
+        impl From<core::v1::ConfigMap> for Either {
+            fn from(value: core::v1::ConfigMap) -> Self {
+                Self::ConfigMap(value)
+            }
+        }
+        impl From<core::v1::Secret> for Either {
+            fn from(value: core::v1::Secret) -> Self {
+                Self::Secret(value)
+            }
+        }
+
         #[instrument(skip_all)]
         pub async fn handler(
             extract::State(srv): extract::State<Arc<State>>,
@@ -101,7 +357,7 @@ mod v1alpha1 {
 
         #[instrument(skip_all)]
         async fn clair(
-            _srv: Arc<State>,
+            srv: Arc<State>,
             rev: AdmissionReview<Clair>,
         ) -> Result<Json<AdmissionReview<DynamicObject>>, StatusCode> {
             let req: AdmissionRequest<Clair> = rev.try_into().map_err(|err| {
@@ -111,18 +367,117 @@ mod v1alpha1 {
             let mut res = AdmissionResponse::from(&req);
 
             let cur = req.object.as_ref().unwrap();
+            let mut ops = Vec::new();
+
             if cur.spec.image.is_none() {
-                res = res
-                    .with_patch(Patch(vec![Op::Add(Add {
-                        path: PointerBuf::from_tokens(["spec", "image"]),
-                        value: Value::String(DEFAULT_IMAGE.clone()),
-                    })]))
-                    .expect("programmer error: unable to serialize known data");
+                ops.push(Op::Add(Add {
+                    path: PointerBuf::from_tokens(["spec", "image"]),
+                    value: Value::String(DEFAULT_IMAGE.clone()),
+                }));
+            }
+
+            // If notifier is enabled but has no database of its own, default it to share the
+            // matcher's -- notifier and matcher are both just plain stores of records keyed by
+            // vulnerability/manifest, so reusing the connection is a reasonable default rather
+            // than forcing every CR to spell out a third database up front.
+            if cur.spec.notifier == Some(true) {
+                if let Some(databases) = cur.spec.databases.as_ref() {
+                    if databases.notifier.is_none() {
+                        ops.push(Op::Add(Add {
+                            path: PointerBuf::from_tokens(["spec", "databases", "notifier"]),
+                            value: serde_json::to_value(&databases.matcher)
+                                .expect("programmer error: unable to serialize known data"),
+                        }));
+                    }
+                }
+            }
+
+            let mut annotations = cur.metadata.annotations.clone().unwrap_or_default();
+            annotations.insert(
+                OPERATOR_VERSION_ANNOTATION.clone(),
+                env!("CARGO_PKG_VERSION").to_string(),
+            );
+            ops.push(Op::Add(Add {
+                path: PointerBuf::from_tokens(["metadata", "annotations"]),
+                value: serde_json::to_value(annotations)
+                    .expect("programmer error: unable to serialize known data"),
+            }));
+
+            res = res
+                .with_patch(Patch(ops))
+                .expect("programmer error: unable to serialize known data");
+
+            if req.operation == Operation::Create || req.operation == Operation::Update {
+                match config_default_patch(&srv, cur).await {
+                    Ok(Some(patch)) => {
+                        res = res
+                            .with_patch(patch)
+                            .expect("programmer error: unable to serialize known data");
+                    }
+                    Ok(None) => (),
+                    Err(err) => {
+                        // Best-effort: a config the mutate path can't load or default is left
+                        // exactly as submitted. The validate webhook is what denies a bad config.
+                        tracing::warn!(error = %err, "unable to compute config defaulting patch; leaving config as submitted");
+                    }
+                }
             }
 
             Ok(Json(res.into_review()))
         }
 
+        /// Config_default_patch loads the `Clair`'s config the same way [`super::validate::clair`]
+        /// does, then diffs the submitted rendered config against the Go-side-defaulted version,
+        /// returning the RFC 6902 patch needed to fill in defaults — or `None` if the two already
+        /// match.
+        async fn config_default_patch(
+            srv: &Arc<State>,
+            cur: &Clair,
+        ) -> anyhow::Result<Option<Patch>> {
+            let cm_api: Api<core::v1::ConfigMap> = Api::default_namespaced(srv.client.clone());
+            let sec_api: Api<core::v1::Secret> = Api::default_namespaced(srv.client.clone());
+
+            let cfgsrc = cur.spec.with_root(format!("{}-config", cur.name_any()))?;
+            let root = cm_api
+                .get_opt(&cfgsrc.root.name)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no such config: {}", cfgsrc.root.name))?;
+
+            let mut b = clair_config::Builder::from_root(&root, cfgsrc.root.key.clone())?;
+            let mut ds = Vec::new();
+            for d in cfgsrc.dropins.iter() {
+                if let Some(r) = &d.config_map_key_ref {
+                    let m = cm_api
+                        .get_opt(&r.name)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("no such config: {}", r.name))?;
+                    ds.push((Either::from(m), &r.key));
+                } else if let Some(r) = &d.secret_key_ref {
+                    let m = sec_api
+                        .get_opt(&r.name)
+                        .await?
+                        .ok_or_else(|| anyhow::anyhow!("no such config: {}", r.name))?;
+                    ds.push((Either::from(m), &r.key));
+                } else {
+                    unreachable!()
+                }
+            }
+            for (d, key) in ds {
+                b = match d {
+                    Either::ConfigMap(v) => b.add(v, key),
+                    Either::Secret(v) => b.add(v, key),
+                }?;
+            }
+
+            let p: clair_config::Parts = b.into();
+            let submitted = p.render()?;
+            let defaulted = p.defaults().await?;
+            if submitted == defaulted {
+                return Ok(None);
+            }
+            Ok(Some(json_patch::diff(&submitted, &defaulted)))
+        }
+
         #[instrument(skip_all)]
         async fn indexer(
             _srv: Arc<State>,
@@ -230,15 +585,10 @@ mod v1alpha1 {
 
             if req.operation == Operation::Create || req.operation == Operation::Update {
                 let spec = &cur.spec;
-                if spec.image.is_none() {
-                    trace!(op = ?req.operation, "image misconfigured");
-                    return Ok(Json(
-                        res.deny("field \"/spec/image\" must be provided")
-                            .into_review(),
-                    ));
-                }
-                trace!(op = ?req.operation, "image OK");
-
+                // `spec.image` no longer needs a hard check here: the mutating webhook always
+                // fills it in from `DEFAULT_IMAGE` before this handler ever sees the object.
+                // There's no sensible default for the database connections below, so those
+                // still deny outright rather than guessing at credentials.
                 if spec.databases.is_none() {
                     trace!(op = ?req.operation, "databases misconfigured");
                     return Ok(Json(
@@ -259,22 +609,20 @@ mod v1alpha1 {
                 }
                 trace!(op = ?req.operation, "notifier OK");
 
-                for (i, d) in spec.dropins.iter().enumerate() {
-                    if d.config_map_key_ref.is_none() && d.secret_key_ref.is_none() {
-                        trace!(op = ?req.operation, index = i, "dropins misconfigured");
-                        return Ok(Json(
-                            res.deny(format!("invalid dropin at index {i}: no ref specified"))
-                                .into_review(),
-                        ));
-                    }
+                if let Err(err) = spec.validate() {
+                    trace!(op = ?req.operation, error = %err, "spec failed validation");
+                    return Ok(Json(res.deny(err.to_string()).into_review()));
                 }
-                trace!(op = ?req.operation, "dropins OK");
+                trace!(op = ?req.operation, "spec OK");
             }
 
             let cm_api: Api<core::v1::ConfigMap> = Api::default_namespaced(srv.client.clone());
             let sec_api: Api<core::v1::Secret> = Api::default_namespaced(srv.client.clone());
 
-            let cfgsrc = cur.spec.with_root(format!("{}-config", cur.name_any()));
+            let cfgsrc = match cur.spec.with_root(format!("{}-config", cur.name_any())) {
+                Ok(cfgsrc) => cfgsrc,
+                Err(err) => return Ok(Json(res.deny(err.to_string()).into_review())),
+            };
             let root = match cm_api.get_opt(&cfgsrc.root.name).await {
                 Ok(root) => root,
                 Err(err) => return Ok(Json(AdmissionResponse::invalid(err).into_review())),
@@ -333,29 +681,35 @@ mod v1alpha1 {
                     return Err(StatusCode::INTERNAL_SERVER_ERROR);
                 }
             };
-            let to_check = [&v.indexer, &v.matcher, &v.notifier, &v.updater];
-            let mut errd = 0usize;
+            // Fatal per-mode errors deny below; non-fatal `Warnings` lines are surfaced via
+            // `AdmissionResponse.warnings` further down so `kubectl apply` still shows them even
+            // though the object was admitted.
+            let to_check = [
+                ("indexer", &v.indexer),
+                ("matcher", &v.matcher),
+                ("notifier", &v.notifier),
+                ("updater", &v.updater),
+            ];
+            for (mode, r) in to_check {
+                if let Err(err) = r {
+                    trace!(mode, error = %err, "validation failed");
+                    return Ok(Json(res.deny(err.to_string()).into_review()));
+                }
+            }
+
             let warn = to_check
                 .iter()
-                .filter_map(|r| {
-                    if let Err(err) = r {
-                        errd = errd.saturating_add(1);
-                        Some(err.to_string())
-                    } else {
-                        None
-                    }
+                .flat_map(|(mode, r)| {
+                    r.as_ref()
+                        .expect("checked above")
+                        .lines()
+                        .map(move |w| format!("{mode}: {w}"))
                 })
                 .collect::<Vec<_>>();
             if !warn.is_empty() {
                 res.warnings = Some(warn);
             }
 
-            if errd == to_check.len() && req.operation == Operation::Update {
-                return Ok(Json(
-                    res.deny("configuration change is extremely invalid")
-                        .into_review(),
-                ));
-            }
             info!("OK");
             Ok(Json(res.into_review()))
         }
@@ -389,6 +743,11 @@ mod v1alpha1 {
                 ));
             }
 
+            if let Err(err) = cur.spec.validate() {
+                info!(error = %err, "spec failed validation");
+                return Ok(Json(res.deny(err.to_string()).into_review()));
+            }
+
             Ok(Json(res.into_review()))
         }
 
@@ -405,7 +764,27 @@ mod v1alpha1 {
                 }
             };
             let res = AdmissionResponse::from(&req);
-            info!("TODO");
+            let cur = req.object.as_ref().unwrap();
+            debug!(op = ?req.operation, "doing validation");
+
+            match req.operation {
+                Operation::Create | Operation::Update => (),
+                Operation::Delete | Operation::Connect => return Ok(Json(res.into_review())),
+            };
+
+            if cur.spec.config.is_none() {
+                info!("missing config source");
+                return Ok(Json(
+                    res.deny("missing configuration source \"/spec/config\"")
+                        .into_review(),
+                ));
+            }
+
+            if let Err(err) = cur.spec.validate() {
+                info!(error = %err, "spec failed validation");
+                return Ok(Json(res.deny(err.to_string()).into_review()));
+            }
+
             Ok(Json(res.into_review()))
         }
 
@@ -422,7 +801,27 @@ mod v1alpha1 {
                 }
             };
             let res = AdmissionResponse::from(&req);
-            info!("TODO");
+            let cur = req.object.as_ref().unwrap();
+            debug!(op = ?req.operation, "doing validation");
+
+            match req.operation {
+                Operation::Create | Operation::Update => (),
+                Operation::Delete | Operation::Connect => return Ok(Json(res.into_review())),
+            };
+
+            if cur.spec.config.is_none() {
+                info!("missing config source");
+                return Ok(Json(
+                    res.deny("missing configuration source \"/spec/config\"")
+                        .into_review(),
+                ));
+            }
+
+            if let Err(err) = cur.spec.validate() {
+                info!(error = %err, "spec failed validation");
+                return Ok(Json(res.deny(err.to_string()).into_review()));
+            }
+
             Ok(Json(res.into_review()))
         }
 
@@ -439,7 +838,19 @@ mod v1alpha1 {
                 }
             };
             let res = AdmissionResponse::from(&req);
-            info!("TODO");
+            let cur = req.object.as_ref().unwrap();
+            debug!(op = ?req.operation, "doing validation");
+
+            match req.operation {
+                Operation::Create | Operation::Update => (),
+                Operation::Delete | Operation::Connect => return Ok(Json(res.into_review())),
+            };
+
+            if let Err(err) = cur.spec.validate() {
+                info!(error = %err, "spec failed validation");
+                return Ok(Json(res.deny(err.to_string()).into_review()));
+            }
+
             Ok(Json(res.into_review()))
         }
     }
@@ -5,6 +5,9 @@ use tracing::trace;
 
 use controller::*;
 
+/// Test_context builds a [`Context`] wired to whatever cluster the ambient kubeconfig points
+/// at, for the `in_ci()`-gated integration tests. Reconcile logic that doesn't need a real
+/// cluster should prefer the mock-backed `ContextBuilder` in `controller::mock` instead.
 pub async fn test_context() -> Arc<Context> {
     let config = kube::Config::infer()
         .await
@@ -52,6 +55,13 @@ pub async fn load_crds(client: &kube::Client) -> Result<()> {
     Ok(())
 }
 
+/// In_ci reports whether this process is running under the real-cluster integration suite
+/// (set by `xtask`'s `kind local ci` subcommand), as opposed to a plain `cargo test` run that
+/// only has the mock-backed tests available.
+pub fn in_ci() -> bool {
+    std::env::var_os("CI").is_some()
+}
+
 fn workspace() -> std::path::PathBuf {
     std::path::Path::new(&env!("CARGO_MANIFEST_DIR"))
         .ancestors()
@@ -108,7 +108,7 @@ mod mutate {
                 ..Default::default()
             }
             .into();
-            object.spec.image = "localhost/test:1".to_string().into();
+            object.spec.image = Some("localhost/test:1".parse::<ImageReference>().unwrap());
 
             let adm: Vec<u8> = to_vec(&json!({
                 "apiVersion": "admission.k8s.io/v1",
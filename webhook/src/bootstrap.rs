@@ -0,0 +1,386 @@
+//! Self-bootstrapping TLS and registration for the webhook server.
+//!
+//! Operators running this crate's [`crate::app`] used to need a serving certificate, a `Service`,
+//! and `Validating`/`MutatingWebhookConfiguration` objects hand-written and kept in sync out of
+//! band. [`reconcile`] does all of that at startup instead: it mints a self-signed CA and a leaf
+//! serving certificate (storing both in a `Secret`), then server-side-applies the `Service`, the
+//! webhook configurations, and each CRD's `spec.conversion` so the `caBundle` always matches
+//! what's actually serving. Running it again (e.g. on every restart) is a no-op beyond re-patching
+//! the `caBundle`, which is what makes cert rotation safe: rotate the `Secret` out of band and the
+//! next reconcile picks up the new CA.
+
+use std::env;
+
+use k8s_openapi::{
+    api::{
+        admissionregistration::v1::{
+            MutatingWebhook, MutatingWebhookConfiguration, RuleWithOperations, ServiceReference,
+            ValidatingWebhook, ValidatingWebhookConfiguration, WebhookClientConfig,
+        },
+        apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition,
+        core::v1::{Pod, Secret, Service, ServicePort},
+    },
+    apimachinery::{
+        pkg::apis::meta::v1::{ObjectMeta, OwnerReference},
+        pkg::util::intstr::IntOrString,
+    },
+    ByteString,
+};
+use kube::{
+    api::{Api, Patch, PatchParams},
+    core::ResourceExt,
+    CustomResourceExt,
+};
+use openssl::{
+    asn1::Asn1Time,
+    bn::{BigNum, MsbOption},
+    hash::MessageDigest,
+    pkey::PKey,
+    rsa::Rsa,
+    x509::{
+        extension::{BasicConstraints, KeyUsage, SubjectAlternativeName},
+        X509NameBuilder, X509,
+    },
+};
+use serde_json::json;
+
+use api::v1alpha1::{Clair, Indexer, Matcher, Notifier, Updater};
+
+/// FIELD_MANAGER identifies this bootstrap's server-side-apply ownership, the same way
+/// `controller::PATCH_PARAMS` does for reconciled subresources.
+const FIELD_MANAGER: &str = "clair-operator-webhook";
+
+/// LABEL_NAME is the `app.kubernetes.io/name` value the operator's Helm chart puts on its
+/// Deployment and Pods; used to select the webhook `Service`'s backend.
+const LABEL_NAME: &str = "clair-operator";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("tls error: {0}")]
+    Tls(#[from] openssl::error::ErrorStack),
+    #[error("kube error: {0}")]
+    Kube(#[from] kube::Error),
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Config names every object this module manages, so a caller can point it at a non-default
+/// installation (e.g. in tests) without touching the reconcile logic.
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub namespace: String,
+    pub service_name: String,
+    pub secret_name: String,
+    pub webhook_port: i32,
+}
+
+impl Config {
+    /// Dns_name is the in-cluster DNS name the serving certificate must be valid for.
+    fn dns_name(&self) -> String {
+        format!("{}.{}.svc", self.service_name, self.namespace)
+    }
+}
+
+/// Materials is the PEM-encoded output of [`self_signed_cert`]: a CA plus a leaf certificate and
+/// key for [`Config::dns_name`].
+struct Materials {
+    ca_cert: Vec<u8>,
+    leaf_cert: Vec<u8>,
+    leaf_key: Vec<u8>,
+}
+
+/// Self_signed_cert mints a fresh CA and a leaf serving certificate for `dns_name`, both valid
+/// immediately, all PEM-encoded.
+fn self_signed_cert(dns_name: &str) -> Result<Materials, openssl::error::ErrorStack> {
+    let ca_key = PKey::from_rsa(Rsa::generate(2048)?)?;
+    let ca_name = {
+        let mut b = X509NameBuilder::new()?;
+        b.append_entry_by_text("CN", "clair-operator webhook CA")?;
+        b.build()
+    };
+    let mut ca_builder = X509::builder()?;
+    ca_builder.set_version(2)?;
+    ca_builder.set_serial_number(&random_serial()?)?;
+    ca_builder.set_subject_name(&ca_name)?;
+    ca_builder.set_issuer_name(&ca_name)?;
+    ca_builder.set_pubkey(&ca_key)?;
+    ca_builder.set_not_before(&Asn1Time::days_from_now(0)?)?;
+    ca_builder.set_not_after(&Asn1Time::days_from_now(365 * 10)?)?;
+    ca_builder.append_extension(BasicConstraints::new().critical().ca().build()?)?;
+    ca_builder.append_extension(
+        KeyUsage::new()
+            .critical()
+            .key_cert_sign()
+            .crl_sign()
+            .build()?,
+    )?;
+    ca_builder.sign(&ca_key, MessageDigest::sha256())?;
+    let ca_cert = ca_builder.build();
+
+    let leaf_key = PKey::from_rsa(Rsa::generate(2048)?)?;
+    let leaf_name = {
+        let mut b = X509NameBuilder::new()?;
+        b.append_entry_by_text("CN", dns_name)?;
+        b.build()
+    };
+    let mut leaf_builder = X509::builder()?;
+    leaf_builder.set_version(2)?;
+    leaf_builder.set_serial_number(&random_serial()?)?;
+    leaf_builder.set_subject_name(&leaf_name)?;
+    leaf_builder.set_issuer_name(ca_cert.subject_name())?;
+    leaf_builder.set_pubkey(&leaf_key)?;
+    leaf_builder.set_not_before(&Asn1Time::days_from_now(0)?)?;
+    leaf_builder.set_not_after(&Asn1Time::days_from_now(825)?)?;
+    leaf_builder.append_extension(BasicConstraints::new().build()?)?;
+    let san = SubjectAlternativeName::new()
+        .dns(dns_name)
+        .build(&leaf_builder.x509v3_context(Some(&ca_cert), None))?;
+    leaf_builder.append_extension(san)?;
+    leaf_builder.sign(&ca_key, MessageDigest::sha256())?;
+    let leaf_cert = leaf_builder.build();
+
+    Ok(Materials {
+        ca_cert: ca_cert.to_pem()?,
+        leaf_cert: leaf_cert.to_pem()?,
+        leaf_key: leaf_key.private_key_to_pem_pkcs8()?,
+    })
+}
+
+fn random_serial() -> Result<openssl::asn1::Asn1Integer, openssl::error::ErrorStack> {
+    let mut bn = BigNum::new()?;
+    bn.rand(159, MsbOption::MAYBE_ZERO, false)?;
+    bn.to_asn1_integer()
+}
+
+/// Operator_owner_ref finds the `OwnerReference` pointing at the Deployment running this process,
+/// by reading the `CONTROLLER_POD_NAME` env var (set via the downward API, same as
+/// `controller::REPORTER`) and walking Pod -> ReplicaSet's own owner. Returns `None` rather than
+/// failing bootstrap if any of that can't be resolved, e.g. when running outside a Pod in
+/// development.
+async fn operator_owner_ref(client: &kube::Client, namespace: &str) -> Option<OwnerReference> {
+    let pod_name = env::var("CONTROLLER_POD_NAME").ok()?;
+    let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+    let pod = pods.get_opt(&pod_name).await.ok()??;
+    let rs_ref = pod
+        .owner_references()
+        .iter()
+        .find(|r| r.kind == "ReplicaSet")?
+        .clone();
+    let rs_api: Api<k8s_openapi::api::apps::v1::ReplicaSet> =
+        Api::namespaced(client.clone(), namespace);
+    let rs = rs_api.get_opt(&rs_ref.name).await.ok()??;
+    rs.owner_references()
+        .iter()
+        .find(|r| r.kind == "Deployment")
+        .cloned()
+}
+
+/// Reconcile ensures the `Secret` holding the webhook's serving certificate, the `Service`
+/// fronting it, the `Validating`/`MutatingWebhookConfiguration`s, and each managed CRD's
+/// `spec.conversion` all exist and carry the current `caBundle`. It then writes the leaf
+/// certificate and key to `cert_path`/`key_path` so the caller's TLS listener (see
+/// `controller::main::webhooks`) can pick them up. Safe to call on every startup.
+pub async fn reconcile(
+    client: &kube::Client,
+    cfg: &Config,
+    cert_path: impl AsRef<std::path::Path>,
+    key_path: impl AsRef<std::path::Path>,
+) -> Result<(), Error> {
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), &cfg.namespace);
+    let owner = operator_owner_ref(client, &cfg.namespace).await;
+
+    let (ca_cert, leaf_cert, leaf_key) = match secrets.get_opt(&cfg.secret_name).await? {
+        Some(existing) => {
+            let data = existing.data.unwrap_or_default();
+            let get = |k: &str| data.get(k).map(|v| v.0.clone()).unwrap_or_default();
+            (get("ca.crt"), get("tls.crt"), get("tls.key"))
+        }
+        None => {
+            let m = self_signed_cert(&cfg.dns_name())?;
+            let secret = Secret {
+                metadata: ObjectMeta {
+                    name: Some(cfg.secret_name.clone()),
+                    namespace: Some(cfg.namespace.clone()),
+                    owner_references: owner.clone().into_iter().collect::<Vec<_>>().into(),
+                    ..Default::default()
+                },
+                data: Some(
+                    [
+                        ("ca.crt".to_string(), ByteString(m.ca_cert.clone())),
+                        ("tls.crt".to_string(), ByteString(m.leaf_cert.clone())),
+                        ("tls.key".to_string(), ByteString(m.leaf_key.clone())),
+                    ]
+                    .into(),
+                ),
+                type_: Some("kubernetes.io/tls".to_string()),
+                ..Default::default()
+            };
+            secrets
+                .patch(
+                    &cfg.secret_name,
+                    &PatchParams::apply(FIELD_MANAGER),
+                    &Patch::Apply(&secret),
+                )
+                .await?;
+            (m.ca_cert, m.leaf_cert, m.leaf_key)
+        }
+    };
+
+    tokio::fs::write(cert_path, &leaf_cert).await?;
+    tokio::fs::write(key_path, &leaf_key).await?;
+
+    let services: Api<Service> = Api::namespaced(client.clone(), &cfg.namespace);
+    let service = Service {
+        metadata: ObjectMeta {
+            name: Some(cfg.service_name.clone()),
+            namespace: Some(cfg.namespace.clone()),
+            owner_references: owner.clone().into_iter().collect::<Vec<_>>().into(),
+            ..Default::default()
+        },
+        spec: Some(k8s_openapi::api::core::v1::ServiceSpec {
+            selector: Some([("app.kubernetes.io/name".to_string(), LABEL_NAME.to_string())].into()),
+            ports: Some(vec![ServicePort {
+                port: 443,
+                target_port: Some(IntOrString::Int(cfg.webhook_port)),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    services
+        .patch(
+            &cfg.service_name,
+            &PatchParams::apply(FIELD_MANAGER),
+            &Patch::Apply(&service),
+        )
+        .await?;
+
+    let client_config = |path: &str| WebhookClientConfig {
+        ca_bundle: Some(ByteString(ca_cert.clone())),
+        service: Some(ServiceReference {
+            name: cfg.service_name.clone(),
+            namespace: cfg.namespace.clone(),
+            path: Some(path.to_string()),
+            port: Some(443),
+        }),
+        ..Default::default()
+    };
+
+    let rules = || {
+        vec![RuleWithOperations {
+            api_groups: Some(vec![api::GROUP.to_string()]),
+            // "clairs" also serves v1beta1 (see `api::v1beta1::Clair`); the other four kinds are
+            // still v1alpha1-only, but admission rules match on the cross product of versions and
+            // resources, so listing both versions here is harmless for them -- no v1beta1 request
+            // for e.g. "indexers" can ever be made since that CRD doesn't serve it.
+            api_versions: Some(vec!["v1alpha1".to_string(), "v1beta1".to_string()]),
+            operations: Some(vec!["CREATE".to_string(), "UPDATE".to_string()]),
+            resources: Some(vec![
+                "clairs".to_string(),
+                "indexers".to_string(),
+                "matchers".to_string(),
+                "notifiers".to_string(),
+                "updaters".to_string(),
+            ]),
+            scope: Some("Namespaced".to_string()),
+        }]
+    };
+
+    let validating: Api<ValidatingWebhookConfiguration> = Api::all(client.clone());
+    let validating_name = format!("{}-validating", cfg.service_name);
+    validating
+        .patch(
+            &validating_name,
+            &PatchParams::apply(FIELD_MANAGER),
+            &Patch::Apply(&ValidatingWebhookConfiguration {
+                metadata: ObjectMeta {
+                    name: Some(validating_name.clone()),
+                    owner_references: owner.clone().into_iter().collect::<Vec<_>>().into(),
+                    ..Default::default()
+                },
+                webhooks: Some(vec![ValidatingWebhook {
+                    name: format!("{}.{}", validating_name, api::GROUP),
+                    client_config: client_config("/v1alpha1/validate"),
+                    rules: Some(rules()),
+                    side_effects: "None".to_string(),
+                    admission_review_versions: vec!["v1".to_string()],
+                    failure_policy: Some("Fail".to_string()),
+                    ..Default::default()
+                }]),
+            }),
+        )
+        .await?;
+
+    let mutating: Api<MutatingWebhookConfiguration> = Api::all(client.clone());
+    let mutating_name = format!("{}-mutating", cfg.service_name);
+    mutating
+        .patch(
+            &mutating_name,
+            &PatchParams::apply(FIELD_MANAGER),
+            &Patch::Apply(&MutatingWebhookConfiguration {
+                metadata: ObjectMeta {
+                    name: Some(mutating_name.clone()),
+                    owner_references: owner.clone().into_iter().collect::<Vec<_>>().into(),
+                    ..Default::default()
+                },
+                webhooks: Some(vec![MutatingWebhook {
+                    name: format!("{}.{}", mutating_name, api::GROUP),
+                    client_config: client_config("/v1alpha1/mutate"),
+                    rules: Some(rules()),
+                    side_effects: "None".to_string(),
+                    admission_review_versions: vec!["v1".to_string()],
+                    failure_policy: Some("Ignore".to_string()),
+                    ..Default::default()
+                }]),
+            }),
+        )
+        .await?;
+
+    // CRD conversion wiring is patched as a bare JSON merge (like `controller::util`'s status
+    // patches) rather than a full typed `CustomResourceDefinition`, since a complete spec would
+    // require re-stating fields (group, names, versions, schemas) this module has no business
+    // owning; server-side apply only needs the fields it's actually responsible for.
+    let crds: Api<CustomResourceDefinition> = Api::all(client.clone());
+    let ca_bundle_b64 = serde_json::to_value(ByteString(ca_cert.clone()))?;
+    for crd_name in [
+        Clair::crd_name(),
+        Indexer::crd_name(),
+        Matcher::crd_name(),
+        Notifier::crd_name(),
+        Updater::crd_name(),
+    ] {
+        let patch = json!({
+            "apiVersion": "apiextensions.k8s.io/v1",
+            "kind": "CustomResourceDefinition",
+            "metadata": { "name": crd_name },
+            "spec": {
+                "conversion": {
+                    "strategy": "Webhook",
+                    "webhook": {
+                        "conversionReviewVersions": ["v1"],
+                        "clientConfig": {
+                            "caBundle": ca_bundle_b64.clone(),
+                            "service": {
+                                "name": cfg.service_name.clone(),
+                                "namespace": cfg.namespace.clone(),
+                                "path": "/convert",
+                                "port": 443,
+                            },
+                        },
+                    },
+                },
+            },
+        });
+        crds.patch(
+            crd_name,
+            &PatchParams::apply(FIELD_MANAGER),
+            &Patch::Apply(&patch),
+        )
+        .await?;
+    }
+
+    Ok(())
+}
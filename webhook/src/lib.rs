@@ -1,9 +1,16 @@
 //! Webhooks for the clair-operator.
 
-use std::sync::Arc;
+use std::convert::Infallible;
+use std::sync::{Arc, LazyLock};
 
-use axum::{extract, http::StatusCode, routing::post, Json, Router};
-use k8s_openapi::api::core;
+use axum::{
+    extract,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    routing::{get, post},
+    Json, Router,
+};
+use k8s_openapi::{api::core, apimachinery::pkg::apis::meta::v1::StatusCause};
 use kube::{
     api::Api,
     core::{
@@ -12,11 +19,41 @@ use kube::{
     },
 };
 use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
 use tower_http::trace::TraceLayer;
 use tracing::{debug, error, info, instrument, trace};
 
 use api::v1alpha1;
 
+// Tricks to create the DEFAULT_IMAGE value; mirrors `controller::DEFAULT_IMAGE`. This crate can't
+// depend on `controller` (the `main` binary depends on this crate, not the other way around), so
+// the same repository/tag trick is duplicated here rather than shared.
+#[cfg(debug_assertions)]
+const DEFAULT_CONTAINER_TAG: &str = "nightly";
+#[cfg(not(debug_assertions))]
+const DEFAULT_CONTAINER_TAG: &str = "4.8.0";
+const DEFAULT_CONTAINER_REPOSITORY: &str = "quay.io/projectquay/clair";
+
+/// DEFAULT_IMAGE is the image the mutating webhook defaults `spec.image` to when a CR omits it.
+static DEFAULT_IMAGE: LazyLock<v1alpha1::ImageReference> = LazyLock::new(|| {
+    format!(
+        "{}:{}",
+        option_env!("CONTAINER_REPOSITORY").unwrap_or(DEFAULT_CONTAINER_REPOSITORY),
+        option_env!("CONTAINER_TAG").unwrap_or(DEFAULT_CONTAINER_TAG),
+    )
+    .parse()
+    .expect("programmer error: invalid default image reference")
+});
+
+/// MANAGED_BY_LABEL/MANAGED_BY_VALUE are set on every CR the mutating webhook defaults, mirroring
+/// the "app.kubernetes.io/managed-by" convention `clair_templates`/`controller::templates` already
+/// use for the subresources generated from these CRs.
+const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+const MANAGED_BY_VALUE: &str = "clair-operator";
+
+pub mod bootstrap;
+
 pub struct State {
     client: kube::Client,
 }
@@ -27,22 +64,289 @@ impl State {
     }
 }
 
+/// Record_admission increments `admission_requests_total`, labeled by `hook` ("mutate" or
+/// "validate"), `kind` (the CR kind the request was for), `operation` (e.g. "create"), and
+/// `outcome` ("allowed", "denied", or "invalid" for a request that couldn't even be decoded).
+///
+/// This goes through the same global `metrics` recorder `controller::metrics`'s free functions
+/// do; this crate can't depend on `controller` (see the `DEFAULT_IMAGE` comment above), but `main`
+/// installs the recorder before either crate's code runs, so calling the `metrics` macros
+/// directly here is enough for the counters to show up on `main`'s existing introspection
+/// endpoint.
+fn record_admission(hook: &'static str, kind: &'static str, operation: Operation, outcome: &'static str) {
+    let operation = format!("{operation:?}").to_ascii_lowercase();
+    metrics::counter!(
+        "admission_requests_total",
+        "hook" => hook, "kind" => kind, "operation" => operation, "outcome" => outcome
+    )
+    .increment(1);
+}
+
+impl Review {
+    /// Kind returns the CR kind this request was for, for labeling metrics.
+    fn kind(&self) -> &'static str {
+        match self {
+            Review::Clair(_) => "Clair",
+            Review::Indexer(_) => "Indexer",
+            Review::Matcher(_) => "Matcher",
+            Review::Notifier(_) => "Notifier",
+            Review::Updater(_) => "Updater",
+        }
+    }
+
+    /// Operation returns the inbound request's [`Operation`], or `None` if this review carries a
+    /// response instead of a request (shouldn't happen for anything routed through [`app`]'s
+    /// handlers, but the field really is optional).
+    fn operation(&self) -> Option<Operation> {
+        match self {
+            Review::Clair(rev) => rev.request.as_ref().map(|r| r.operation.clone()),
+            Review::Indexer(rev) => rev.request.as_ref().map(|r| r.operation.clone()),
+            Review::Matcher(rev) => rev.request.as_ref().map(|r| r.operation.clone()),
+            Review::Notifier(rev) => rev.request.as_ref().map(|r| r.operation.clone()),
+            Review::Updater(rev) => rev.request.as_ref().map(|r| r.operation.clone()),
+        }
+    }
+}
+
 pub fn app(srv: State) -> Router {
     let state = Arc::new(srv);
     trace!("state constructed");
     let app = Router::new()
-        .route("/convert", post(convert))
+        .route("/convert", post(convert::handler))
         .route("/v1alpha1/mutate", post(mutate_v1alpha1))
         .route("/v1alpha1/validate", post(validate_v1alpha1))
+        .route("/v1alpha1/validate/stream", get(validate_v1alpha1_stream))
         .layer(TraceLayer::new_for_http())
         .with_state(state);
     trace!("router constructed");
     app
 }
 
-#[instrument(skip_all)]
-async fn convert(extract::Json(_req): Json<()>) -> Json<()> {
-    todo!()
+mod convert {
+    use std::collections::HashMap;
+    use std::sync::LazyLock;
+
+    use axum::{extract, Json};
+    use kube::core::{
+        conversion::{ConversionRequest, ConversionResponse, ConversionReview},
+        DynamicObject, TypeMeta,
+    };
+    use serde_json::Value;
+    use tracing::{error, instrument};
+
+    use api::{self, v1alpha1};
+
+    /// HUB_VERSION is the version every converter composes through.
+    ///
+    /// Keying converters by `(from, to, kind)` directly would need O(n²) converters as versions
+    /// are added; going through a hub means each new version only needs a converter to and from
+    /// the hub, i.e. O(n).
+    static HUB_VERSION: &str = "v1alpha1";
+
+    /// Convert is implemented by every on-the-wire version of a kind, and knows how to move
+    /// to/from that kind's [`HUB_VERSION`] representation.
+    trait Convert: Sized + serde::Serialize + serde::de::DeserializeOwned {
+        fn to_hub(self) -> Result<Value, serde_json::Error> {
+            serde_json::to_value(self)
+        }
+        fn from_hub(v: Value) -> Result<Self, serde_json::Error> {
+            serde_json::from_value(v)
+        }
+    }
+
+    macro_rules! hub_impls {
+        ($($kind:ty),+ $(,)?) => {
+            $(impl Convert for $kind {})+
+        };
+    }
+    hub_impls!(
+        v1alpha1::Clair,
+        v1alpha1::Indexer,
+        v1alpha1::Matcher,
+        v1alpha1::Notifier,
+        v1alpha1::Updater,
+        api::v1beta1::Clair,
+    );
+
+    /// ToHub is a per-kind, per-version function converting a [`Value`] into the hub
+    /// representation.
+    type ToHub = fn(Value) -> Result<Value, serde_json::Error>;
+    /// FromHub is a per-kind, per-version function converting the hub representation into a
+    /// [`Value`] for that version.
+    type FromHub = fn(Value) -> Result<Value, serde_json::Error>;
+
+    /// CONVERTERS is the `(kind, version) -> (to_hub, from_hub)` table.
+    ///
+    /// Adding a new on-the-wire version for a kind is just adding an entry here; nothing else in
+    /// this module needs to change.
+    static CONVERTERS: LazyLock<HashMap<(&'static str, &'static str), (ToHub, FromHub)>> =
+        LazyLock::new(|| {
+            fn pair<T: Convert>() -> (ToHub, FromHub) {
+                (
+                    |v| Ok(T::to_hub(serde_json::from_value::<T>(v)?)?),
+                    |v| serde_json::to_value(T::from_hub(v)?),
+                )
+            }
+            HashMap::from([
+                (("Clair", HUB_VERSION), pair::<v1alpha1::Clair>()),
+                (("Indexer", HUB_VERSION), pair::<v1alpha1::Indexer>()),
+                (("Matcher", HUB_VERSION), pair::<v1alpha1::Matcher>()),
+                (("Notifier", HUB_VERSION), pair::<v1alpha1::Notifier>()),
+                (("Updater", HUB_VERSION), pair::<v1alpha1::Updater>()),
+                (("Clair", "v1beta1"), pair::<api::v1beta1::Clair>()),
+            ])
+        });
+
+    /// Convert_object converts a single object to `to_version`, composing through the hub.
+    fn convert_object(mut obj: DynamicObject, to_version: &str) -> Result<DynamicObject, String> {
+        let types = obj
+            .types
+            .clone()
+            .ok_or_else(|| "object is missing \"apiVersion\"/\"kind\"".to_string())?;
+        let kind = types.kind.as_str();
+        let from_version = types
+            .api_version
+            .rsplit('/')
+            .next()
+            .unwrap_or(types.api_version.as_str());
+
+        if from_version == to_version {
+            return Ok(obj);
+        }
+
+        let (to_hub, _) = CONVERTERS
+            .get(&(kind, from_version))
+            .ok_or_else(|| format!("no converter for {kind} {from_version} -> {HUB_VERSION}"))?;
+        let (_, from_hub) = CONVERTERS
+            .get(&(kind, to_version))
+            .ok_or_else(|| format!("no converter for {kind} {HUB_VERSION} -> {to_version}"))?;
+
+        let hub = to_hub(Value::Object(obj.data.as_object().cloned().unwrap_or_default()))
+            .map_err(|err| err.to_string())?;
+        let data = from_hub(hub).map_err(|err| err.to_string())?;
+        obj.data = data;
+        obj.types = Some(TypeMeta {
+            api_version: format!("{}/{to_version}", api::GROUP),
+            kind: kind.to_string(),
+        });
+        Ok(obj)
+    }
+
+    /// Handler serves the `/convert` endpoint: it accepts a [`ConversionReview`], converts each
+    /// object in the request to `desiredAPIVersion`, and returns the results in a single
+    /// response, matching the kube-apiserver CRD conversion webhook contract.
+    #[instrument(skip_all)]
+    pub async fn handler(extract::Json(rev): Json<ConversionReview>) -> Json<ConversionReview> {
+        let Some(mut req) = rev.request else {
+            error!("missing \"request\" in ConversionReview");
+            return Json(ConversionReview {
+                types: rev.types,
+                request: None,
+                response: None,
+            });
+        };
+        // `desiredAPIVersion` is the fully-qualified "group/version" per the conversion webhook
+        // contract (e.g. "clair.projectquay.io/v1beta1"), but `CONVERTERS` and the fast-path
+        // comparison in `convert_object` both work in bare versions, the same as `from_version`.
+        let to_version = req
+            .desired_api_version
+            .rsplit('/')
+            .next()
+            .unwrap_or(req.desired_api_version.as_str())
+            .to_string();
+        let incoming = std::mem::take(&mut req.objects);
+        let mut res = ConversionResponse::for_request(req);
+
+        let mut objects = Vec::with_capacity(incoming.len());
+        for obj in incoming {
+            match convert_object(obj, &to_version) {
+                Ok(obj) => objects.push(obj),
+                Err(err) => {
+                    error!(error = %err, "conversion failed");
+                    return Json(res.failure(&err).into_review());
+                }
+            }
+        }
+
+        res.converted_objects = objects;
+        Json(res.success().into_review())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use kube::CustomResourceExt;
+
+        /// Round_trip exercises a kind's registered (to_hub, from_hub) pair back-to-back, the
+        /// same composition `convert_object` performs whenever `from` and `to` differ, and
+        /// checks nothing is lost. Today `v1alpha1` is the only registered version on both ends
+        /// of the hub, so this also doubles as a check that the table is wired up correctly; it
+        /// should gain real up/down cases as soon as a second version exists.
+        #[test]
+        fn hub_round_trip_is_lossless() {
+            let clair = v1alpha1::Clair::new("test", Default::default());
+            let want = serde_json::to_value(&clair).expect("serializable");
+
+            let (to_hub, from_hub) = CONVERTERS
+                .get(&("Clair", HUB_VERSION))
+                .expect("Clair is a registered kind");
+            let hub = to_hub(want.clone()).expect("convert up");
+            let got = from_hub(hub).expect("convert down");
+
+            assert_eq!(want, got);
+        }
+
+        #[test]
+        fn convert_object_rejects_unknown_kind() {
+            let mut obj = DynamicObject::new("test", &v1alpha1::Clair::api_resource());
+            obj.types = Some(TypeMeta {
+                api_version: format!("{}/{HUB_VERSION}", api::GROUP),
+                kind: "NoSuchKind".into(),
+            });
+
+            let err = convert_object(obj, "v1beta1").unwrap_err();
+            assert!(err.contains("no converter"));
+        }
+
+        /// Handler_accepts_fully_qualified_desired_version exercises [`handler`] itself (not just
+        /// [`convert_object`]) with a `desiredAPIVersion` shaped the way the apiserver actually
+        /// sends it: `"{group}/{version}"`, not a bare version. Before `to_version` was stripped
+        /// the same way `from_version` already was, this request's fast-path comparison never
+        /// matched (a bare `from_version` against a fully-qualified `to_version`), so even a
+        /// no-op conversion like this one fell through to `CONVERTERS.get(&(kind, to_version))`
+        /// and failed with "no converter for Clair v1alpha1 -> projectclair.io/v1alpha1".
+        #[tokio::test]
+        async fn handler_accepts_fully_qualified_desired_version() {
+            let api_version = format!("{}/v1alpha1", api::GROUP);
+            let mut obj = DynamicObject::new("test", &v1alpha1::Clair::api_resource());
+            obj.types = Some(TypeMeta {
+                api_version: api_version.clone(),
+                kind: "Clair".into(),
+            });
+
+            let rev = ConversionReview {
+                types: TypeMeta {
+                    api_version: "apiextensions.k8s.io/v1".to_string(),
+                    kind: "ConversionReview".to_string(),
+                },
+                request: Some(ConversionRequest {
+                    uid: "00".to_string(),
+                    desired_api_version: api_version.clone(),
+                    objects: vec![obj],
+                }),
+                response: None,
+            };
+
+            let Json(rev) = handler(extract::Json(rev)).await;
+            let response = rev.response.expect("handler always answers a request");
+            assert!(
+                response.result.status.as_deref() != Some("Failure"),
+                "conversion failed: {:?}",
+                response.result.message,
+            );
+            assert_eq!(response.converted_objects[0].types.as_ref().unwrap().api_version, api_version);
+        }
+    }
 }
 
 /// Review is an enum containing any of the possible types that can be sent to the webhooks.
@@ -56,6 +360,96 @@ enum Review {
     Updater(AdmissionReview<v1alpha1::Updater>),
 }
 
+/// Apply_defaults runs `default` over a clone of `obj`, diffs the original against the result,
+/// and attaches whatever changed to `res` as a JSONPatch. Every `mutate_v1alpha1_*` handler calls
+/// this with its own defaulting function so the diff/patch plumbing only lives in one place.
+fn apply_defaults<T, F>(
+    res: AdmissionResponse,
+    obj: &T,
+    default: F,
+) -> Result<AdmissionResponse, serde_json::Error>
+where
+    T: Clone + serde::Serialize,
+    F: FnOnce(&mut T),
+{
+    let original = serde_json::to_value(obj)?;
+    let mut defaulted = obj.clone();
+    default(&mut defaulted);
+    let defaulted = serde_json::to_value(&defaulted)?;
+
+    let patch = json_patch::diff(&original, &defaulted);
+    if patch.0.is_empty() {
+        return Ok(res);
+    }
+    res.with_patch(patch)
+}
+
+/// Default_managed_by sets [`MANAGED_BY_LABEL`] if it isn't already present.
+fn default_managed_by<T: ResourceExt>(obj: &mut T) {
+    obj.labels_mut()
+        .entry(MANAGED_BY_LABEL.to_string())
+        .or_insert_with(|| MANAGED_BY_VALUE.to_string());
+}
+
+/// Default_config_root fills in a minimal [`v1alpha1::ConfigSource`] naming the conventional
+/// `{name}-config` ConfigMap when a spec doesn't reference any config at all.
+fn default_config_root(name: &str, config: &mut Option<v1alpha1::ConfigSource>) {
+    if config.is_some() {
+        return;
+    }
+    *config = Some(v1alpha1::ConfigSource {
+        root: v1alpha1::ConfigMapKeySelector {
+            name: format!("{name}-config"),
+            key: "config.json".to_string(),
+        },
+        dropins: Vec::new(),
+        persistent: None,
+    });
+}
+
+fn default_clair(obj: &mut v1alpha1::Clair) {
+    default_managed_by(obj);
+    if obj.spec.image.is_none() {
+        obj.spec.image = Some(DEFAULT_IMAGE.clone());
+    }
+}
+
+fn default_indexer(obj: &mut v1alpha1::Indexer) {
+    default_managed_by(obj);
+    if obj.spec.image.is_none() {
+        obj.spec.image = Some(DEFAULT_IMAGE.clone());
+    }
+    let name = obj.name_any();
+    default_config_root(&name, &mut obj.spec.config);
+}
+
+fn default_matcher(obj: &mut v1alpha1::Matcher) {
+    default_managed_by(obj);
+    if obj.spec.image.is_none() {
+        obj.spec.image = Some(DEFAULT_IMAGE.clone());
+    }
+    let name = obj.name_any();
+    default_config_root(&name, &mut obj.spec.config);
+}
+
+fn default_notifier(obj: &mut v1alpha1::Notifier) {
+    default_managed_by(obj);
+    if obj.spec.image.is_none() {
+        obj.spec.image = Some(DEFAULT_IMAGE.clone());
+    }
+    let name = obj.name_any();
+    default_config_root(&name, &mut obj.spec.config);
+}
+
+fn default_updater(obj: &mut v1alpha1::Updater) {
+    default_managed_by(obj);
+    if obj.spec.image.is_none() {
+        obj.spec.image = Some(DEFAULT_IMAGE.clone());
+    }
+    let name = obj.name_any();
+    default_config_root(&name, &mut obj.spec.config);
+}
+
 // Validate functions:
 
 #[instrument(skip_all)]
@@ -63,13 +457,26 @@ async fn mutate_v1alpha1(
     extract::State(srv): extract::State<Arc<State>>,
     extract::Json(rev): Json<Review>,
 ) -> Result<Json<AdmissionReview<DynamicObject>>, StatusCode> {
-    match rev.into() {
+    let kind = rev.kind();
+    let operation = rev.operation();
+    let ret = match rev.into() {
         Review::Clair(rev) => mutate_v1alpha1_clair(srv, rev).await,
         Review::Indexer(rev) => mutate_v1alpha1_indexer(srv, rev).await,
         Review::Matcher(rev) => mutate_v1alpha1_matcher(srv, rev).await,
         Review::Notifier(rev) => mutate_v1alpha1_notifier(srv, rev).await,
         Review::Updater(rev) => mutate_v1alpha1_updater(srv, rev).await,
+    };
+    if let Some(operation) = operation {
+        let outcome = match &ret {
+            Ok(rev) => match rev.0.response.as_ref().map(|r| r.allowed) {
+                Some(true) => "allowed",
+                _ => "denied",
+            },
+            Err(_) => "invalid",
+        };
+        record_admission("mutate", kind, operation, outcome);
     }
+    ret
 }
 
 #[instrument(skip_all)]
@@ -82,6 +489,13 @@ async fn mutate_v1alpha1_clair(
         StatusCode::BAD_REQUEST
     })?;
     let res = AdmissionResponse::from(&req);
+    let res = match req.object.as_ref() {
+        Some(cur) => apply_defaults(res, cur, default_clair).map_err(|err| {
+            error!(error = %err, "unable to compute defaulting patch");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        None => res,
+    };
     Ok(Json(res.into_review()))
 }
 #[instrument(skip_all)]
@@ -94,6 +508,13 @@ async fn mutate_v1alpha1_indexer(
         StatusCode::BAD_REQUEST
     })?;
     let res = AdmissionResponse::from(&req);
+    let res = match req.object.as_ref() {
+        Some(cur) => apply_defaults(res, cur, default_indexer).map_err(|err| {
+            error!(error = %err, "unable to compute defaulting patch");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        None => res,
+    };
     Ok(Json(res.into_review()))
 }
 #[instrument(skip_all)]
@@ -106,6 +527,13 @@ async fn mutate_v1alpha1_matcher(
         StatusCode::BAD_REQUEST
     })?;
     let res = AdmissionResponse::from(&req);
+    let res = match req.object.as_ref() {
+        Some(cur) => apply_defaults(res, cur, default_matcher).map_err(|err| {
+            error!(error = %err, "unable to compute defaulting patch");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        None => res,
+    };
     Ok(Json(res.into_review()))
 }
 #[instrument(skip_all)]
@@ -118,6 +546,13 @@ async fn mutate_v1alpha1_notifier(
         StatusCode::BAD_REQUEST
     })?;
     let res = AdmissionResponse::from(&req);
+    let res = match req.object.as_ref() {
+        Some(cur) => apply_defaults(res, cur, default_notifier).map_err(|err| {
+            error!(error = %err, "unable to compute defaulting patch");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        None => res,
+    };
     Ok(Json(res.into_review()))
 }
 #[instrument(skip_all)]
@@ -130,6 +565,13 @@ async fn mutate_v1alpha1_updater(
         StatusCode::BAD_REQUEST
     })?;
     let res = AdmissionResponse::from(&req);
+    let res = match req.object.as_ref() {
+        Some(cur) => apply_defaults(res, cur, default_updater).map_err(|err| {
+            error!(error = %err, "unable to compute defaulting patch");
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?,
+        None => res,
+    };
     Ok(Json(res.into_review()))
 }
 
@@ -140,13 +582,261 @@ async fn validate_v1alpha1(
     extract::State(srv): extract::State<Arc<State>>,
     extract::Json(rev): Json<Review>,
 ) -> Result<Json<AdmissionReview<DynamicObject>>, StatusCode> {
-    match rev.into() {
+    let kind = rev.kind();
+    let operation = rev.operation();
+    let ret = match rev.into() {
         Review::Clair(rev) => validate_v1alpha1_clair(srv, rev).await,
         Review::Indexer(rev) => validate_v1alpha1_indexer(srv, rev).await,
         Review::Matcher(rev) => validate_v1alpha1_matcher(srv, rev).await,
         Review::Notifier(rev) => validate_v1alpha1_notifier(srv, rev).await,
         Review::Updater(rev) => validate_v1alpha1_updater(srv, rev).await,
+    };
+    if let Some(operation) = operation {
+        let outcome = match &ret {
+            Ok(rev) => match rev.0.response.as_ref().map(|r| r.allowed) {
+                Some(true) => "allowed",
+                _ => "denied",
+            },
+            Err(_) => "invalid",
+        };
+        record_admission("validate", kind, operation, outcome);
+    }
+    ret
+}
+
+/// Severity decides what a [`ValidationError`] does to the request it was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    /// Fatal errors deny the request.
+    Fatal,
+    /// Warning errors still admit the object, but surface as an `AdmissionResponse` warning so
+    /// `kubectl` can print an advisory.
+    Warning,
+}
+
+/// ValidationError is one problem found while validating a spec, carrying enough structure that
+/// `kubectl` can point at the offending field and distinguish "this is fatal" from "this is
+/// merely suspicious", instead of a flat string.
+#[derive(Debug, Clone)]
+struct ValidationError {
+    /// Field is a JSONPath-style pointer to the offending part of the spec, e.g.
+    /// `/spec/databases/notifier`.
+    field: String,
+    /// Message is the human-readable description.
+    message: String,
+    /// Reason is a machine-readable code, in the same vocabulary as `StatusCause::reason`/
+    /// `metav1.StatusReason` (e.g. "FieldValueRequired", "FieldValueInvalid",
+    /// "FieldValueForbidden").
+    reason: &'static str,
+    severity: Severity,
+}
+
+impl ValidationError {
+    fn fatal(field: impl ToString, reason: &'static str, message: impl ToString) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+            reason,
+            severity: Severity::Fatal,
+        }
+    }
+
+    fn warning(field: impl ToString, reason: &'static str, message: impl ToString) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+            reason,
+            severity: Severity::Warning,
+        }
+    }
+}
+
+/// Apply_validation partitions `errors` by [`Severity`]: if any are fatal, `res` is denied with
+/// every fatal error's field/message/reason folded into `status.details.causes` (see
+/// [`deny_with_cause`]); otherwise the warning-severity errors' messages are appended to
+/// `res.warnings` so the object is still admitted but `kubectl` surfaces the advisories.
+fn apply_validation(mut res: AdmissionResponse, errors: Vec<ValidationError>) -> AdmissionResponse {
+    let (fatal, warning): (Vec<_>, Vec<_>) =
+        errors.into_iter().partition(|e| e.severity == Severity::Fatal);
+    if !fatal.is_empty() {
+        let message = fatal
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        res = res.deny(message);
+        let mut details = res.result.details.take().unwrap_or_default();
+        details
+            .causes
+            .extend(fatal.into_iter().map(|e| StatusCause {
+                field: Some(e.field),
+                message: Some(e.message),
+                reason: Some(e.reason.to_string()),
+            }));
+        res.result.details = Some(details);
+        return res;
+    }
+    if !warning.is_empty() {
+        res.warnings
+            .get_or_insert_with(Vec::new)
+            .extend(warning.into_iter().map(|e| e.message));
+    }
+    res
+}
+
+/// Check_clair_required validates the fields a `Clair` spec must carry for a create or update,
+/// collecting every problem instead of denying on the first so `kubectl` can report them all at
+/// once.
+fn check_clair_required(cur: &v1alpha1::Clair) -> Vec<ValidationError> {
+    let spec = &cur.spec;
+    let mut errors = Vec::new();
+
+    if spec.databases.is_none() {
+        errors.push(ValidationError::fatal(
+            "/spec/databases",
+            "FieldValueRequired",
+            "field \"/spec/databases\" must be provided",
+        ));
+    }
+    if spec.notifier == Some(true)
+        && spec
+            .databases
+            .as_ref()
+            .is_some_and(|d| d.notifier.is_none())
+    {
+        errors.push(ValidationError::warning(
+            "/spec/databases/notifier",
+            "FieldValueRequired",
+            "notifier enabled without dedicated database connection",
+        ));
+    }
+    for (i, d) in spec.dropins.iter().enumerate() {
+        if d.config_map_key_ref.is_none() && d.secret_key_ref.is_none() {
+            errors.push(ValidationError::fatal(
+                format!("/spec/dropins/{i}"),
+                "FieldValueInvalid",
+                format!("invalid dropin at index {i}: no ref specified"),
+            ));
+        }
     }
+    errors
+}
+
+/// Check_clair_immutable validates the fields that can't change across an update.
+fn check_clair_immutable(prev: &v1alpha1::Clair, cur: &v1alpha1::Clair) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    if prev.spec.config_dialect != cur.spec.config_dialect {
+        errors.push(ValidationError::fatal(
+            "/spec/configDialect",
+            "FieldValueForbidden",
+            "cannot change field \"/spec/configDialect\"",
+        ));
+    }
+    errors
+}
+
+/// Deny_with_cause rejects the request like [`AdmissionResponse::deny`], but additionally
+/// populates `status.details.causes` with a [`StatusCause`] pointing at `field`, so callers
+/// (e.g. `kubectl apply`) get a machine-readable JSON path for the offending part of the spec
+/// rather than just a free-text message.
+fn deny_with_cause(
+    res: AdmissionResponse,
+    message: impl ToString,
+    field: impl ToString,
+) -> AdmissionResponse {
+    let message = message.to_string();
+    let mut res = res.deny(message.clone());
+    let mut details = res.result.details.take().unwrap_or_default();
+    details.causes.push(StatusCause {
+        field: Some(field.to_string()),
+        message: Some(message),
+        reason: Some("FieldValueInvalid".to_string()),
+    });
+    res.result.details = Some(details);
+    res
+}
+
+/// Resolve_namespace picks the namespace to scope config/secret lookups to: the
+/// `AdmissionRequest`'s own `namespace` (set by the apiserver for every review of a namespaced
+/// resource) when present, falling back to the reviewed object's own metadata namespace. Using
+/// `Api::default_namespaced` here instead would silently look up ConfigMaps/Secrets in whatever
+/// namespace the operator itself runs in, rather than the one the CR was actually created in.
+fn resolve_namespace<T: ResourceExt>(req: &AdmissionRequest<T>, obj: &T) -> String {
+    req.namespace
+        .clone()
+        .or_else(|| obj.namespace())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+/// Load_config_builder resolves `cfgsrc`'s root and dropins, scoped to `namespace`, into a
+/// [`clair_config::Builder`]. Anything that should become the webhook's response (a missing
+/// reference, or a kube/config error) is folded into `res` and returned as `Err`, so callers can
+/// just `return Ok(Json(...))` on failure.
+async fn load_config_builder(
+    client: &kube::Client,
+    namespace: &str,
+    res: AdmissionResponse,
+    cfgsrc: &v1alpha1::ConfigSource,
+) -> Result<(AdmissionResponse, clair_config::Builder), AdmissionReview<DynamicObject>> {
+    let cm_api: Api<core::v1::ConfigMap> = Api::namespaced(client.clone(), namespace);
+    let sec_api: Api<core::v1::Secret> = Api::namespaced(client.clone(), namespace);
+
+    let root = match cm_api.get_opt(&cfgsrc.root.name).await {
+        Ok(Some(root)) => root,
+        Ok(None) => {
+            let name = &cfgsrc.root.name;
+            return Err(
+                deny_with_cause(res, format!("no such config: {name}"), "/spec/config/root")
+                    .into_review(),
+            );
+        }
+        Err(err) => return Err(AdmissionResponse::invalid(err).into_review()),
+    };
+
+    let mut b = match clair_config::Builder::from_root(&root, cfgsrc.root.key.clone()) {
+        Ok(b) => b,
+        Err(err) => return Err(AdmissionResponse::invalid(err).into_review()),
+    };
+
+    for (i, d) in cfgsrc.dropins.iter().enumerate() {
+        let field = format!("/spec/config/dropins/{i}");
+        let (src, key) = if let Some(r) = &d.config_map_key_ref {
+            match cm_api.get_opt(&r.name).await {
+                Ok(Some(m)) => (Either::from(m), r.key.clone()),
+                Ok(None) => {
+                    let name = &r.name;
+                    return Err(
+                        deny_with_cause(res, format!("no such config: {name}"), field)
+                            .into_review(),
+                    );
+                }
+                Err(err) => return Err(AdmissionResponse::invalid(err).into_review()),
+            }
+        } else if let Some(r) = &d.secret_key_ref {
+            match sec_api.get_opt(&r.name).await {
+                Ok(Some(m)) => (Either::from(m), r.key.clone()),
+                Ok(None) => {
+                    let name = &r.name;
+                    return Err(
+                        deny_with_cause(res, format!("no such config: {name}"), field)
+                            .into_review(),
+                    );
+                }
+                Err(err) => return Err(AdmissionResponse::invalid(err).into_review()),
+            }
+        } else {
+            unreachable!()
+        };
+        b = match match src {
+            Either::ConfigMap(v) => b.add(v, key),
+            Either::Secret(v) => b.add(v, key),
+        } {
+            Ok(b) => b,
+            Err(err) => return Err(AdmissionResponse::invalid(err).into_review()),
+        };
+    }
+
+    Ok((res, b))
 }
 
 enum Either {
@@ -182,57 +872,39 @@ async fn validate_v1alpha1_clair(
     let cur = req.object.as_ref().unwrap();
     debug!(op = ?req.operation, "doing validation");
 
+    let mut errors = Vec::new();
     if req.operation == Operation::Create || req.operation == Operation::Update {
-        let spec = &cur.spec;
-        if spec.databases.is_none() {
-            trace!(op = ?req.operation, "databases misconfigured");
-            return Ok(Json(
-                res.deny("field \"/spec/databases\" must be provided")
-                    .into_review(),
-            ));
-        }
-        trace!(op = ?req.operation, "databases OK");
-        if spec.notifier == Some(true) && spec.databases.as_ref().unwrap().notifier.is_none() {
-            trace!(op = ?req.operation, "notifier misconfigured");
-            return Ok(Json(
-                res.deny("field \"/spec/notifier\" is set but \"/spec/databases/notifier\" is not")
-                    .into_review(),
-            ));
-        }
-        trace!(op = ?req.operation, "notifier OK");
-        for (i, d) in spec.dropins.iter().enumerate() {
-            if d.config_map_key_ref.is_none() && d.secret_key_ref.is_none() {
-                trace!(op = ?req.operation, index = i, "dropins misconfigured");
-                return Ok(Json(
-                    res.deny(format!("invalid dropin at index {i}: no ref specified"))
-                        .into_review(),
-                ));
-            }
-        }
-        trace!(op = ?req.operation, "dropins OK");
+        errors.extend(check_clair_required(cur));
     }
-
     if req.operation == Operation::Update {
-        let prev = prev.unwrap();
-        if prev.spec.config_dialect != cur.spec.config_dialect {
-            trace!(op = ?req.operation, "unable to change configDialect");
-            return Ok(Json(
-                res.deny("cannot change field \"/spec/configDialect\"")
-                    .into_review(),
-            ));
-        }
+        errors.extend(check_clair_immutable(prev.unwrap(), cur));
+    }
+    trace!(op = ?req.operation, errors = errors.len(), "spec checks done");
+    if errors.iter().any(|e| e.severity == Severity::Fatal) {
+        return Ok(Json(apply_validation(res, errors).into_review()));
     }
+    res = apply_validation(res, errors);
 
-    let cm_api: Api<core::v1::ConfigMap> = Api::default_namespaced(srv.client.clone());
-    let sec_api: Api<core::v1::Secret> = Api::default_namespaced(srv.client.clone());
+    let namespace = resolve_namespace(&req, cur);
+    let cm_api: Api<core::v1::ConfigMap> = Api::namespaced(srv.client.clone(), &namespace);
+    let sec_api: Api<core::v1::Secret> = Api::namespaced(srv.client.clone(), &namespace);
 
-    let cfgsrc = cur.spec.with_root(format!("{}-config", cur.name_any()));
+    let cfgsrc = match cur.spec.with_root(format!("{}-config", cur.name_any())) {
+        Ok(cfgsrc) => cfgsrc,
+        Err(err) => {
+            let target = err.target.clone().unwrap_or_else(|| "/spec".into());
+            return Ok(Json(deny_with_cause(res, err.to_string(), target).into_review()));
+        }
+    };
     let root = match cm_api.get_opt(&cfgsrc.root.name).await {
         Ok(root) => root,
         Err(err) => return Ok(Json(AdmissionResponse::invalid(err).into_review())),
     };
     let root = if root.is_none() {
-        return Ok(Json(res.deny("no such config: {name}").into_review()));
+        let name = &cfgsrc.root.name;
+        return Ok(Json(
+            deny_with_cause(res, format!("no such config: {name}"), "/spec").into_review(),
+        ));
     } else {
         root.unwrap()
     };
@@ -242,7 +914,7 @@ async fn validate_v1alpha1_clair(
         Err(err) => return Ok(Json(AdmissionResponse::invalid(err).into_review())),
     };
     let mut ds = Vec::new();
-    for d in cfgsrc.dropins.iter() {
+    for (i, d) in cfgsrc.dropins.iter().enumerate() {
         if let Some(r) = &d.config_map_key_ref {
             let name = &r.name;
             let m = match cm_api.get_opt(name).await {
@@ -250,7 +922,14 @@ async fn validate_v1alpha1_clair(
                 Err(err) => return Ok(Json(AdmissionResponse::invalid(err).into_review())),
             };
             if m.is_none() {
-                return Ok(Json(res.deny("no such config: {name}").into_review()));
+                return Ok(Json(
+                    deny_with_cause(
+                        res,
+                        format!("no such config: {name}"),
+                        format!("/spec/dropins/{i}"),
+                    )
+                    .into_review(),
+                ));
             };
             ds.push((Either::from(m.unwrap()), &r.key));
         } else if let Some(r) = &d.secret_key_ref {
@@ -260,7 +939,14 @@ async fn validate_v1alpha1_clair(
                 Err(err) => return Ok(Json(AdmissionResponse::invalid(err).into_review())),
             };
             if m.is_none() {
-                return Ok(Json(res.deny("no such config: {name}").into_review()));
+                return Ok(Json(
+                    deny_with_cause(
+                        res,
+                        format!("no such config: {name}"),
+                        format!("/spec/dropins/{i}"),
+                    )
+                    .into_review(),
+                ));
             };
             ds.push((Either::from(m.unwrap()), &r.key));
         } else {
@@ -299,12 +985,12 @@ async fn validate_v1alpha1_clair(
         })
         .collect::<Vec<_>>();
     if !warn.is_empty() {
-        res.warnings = Some(warn);
+        res.warnings.get_or_insert_with(Vec::new).extend(warn);
     }
 
     if errd == to_check.len() && req.operation == Operation::Update {
         return Ok(Json(
-            res.deny("configuration change is extremely invalid")
+            deny_with_cause(res, "configuration change is extremely invalid", "/spec")
                 .into_review(),
         ));
     }
@@ -313,7 +999,7 @@ async fn validate_v1alpha1_clair(
 }
 #[instrument(skip_all)]
 async fn validate_v1alpha1_indexer(
-    _srv: Arc<State>,
+    srv: Arc<State>,
     rev: AdmissionReview<v1alpha1::Indexer>,
 ) -> Result<Json<AdmissionReview<DynamicObject>>, StatusCode> {
     let req: AdmissionRequest<v1alpha1::Indexer> = match rev.try_into() {
@@ -324,12 +1010,68 @@ async fn validate_v1alpha1_indexer(
         }
     };
     let res = AdmissionResponse::from(&req);
-    info!("TODO");
+
+    if req.operation == Operation::Connect {
+        return Ok(Json(res.deny("verb CONNECT makes no sense").into_review()));
+    }
+    if req.operation != Operation::Create && req.operation != Operation::Update {
+        return Ok(Json(res.into_review()));
+    }
+
+    let cur = req.object.as_ref().unwrap();
+    let cfgsrc = match &cur.spec.config {
+        Some(cfgsrc) => cfgsrc.clone(),
+        None => {
+            return Ok(Json(
+                deny_with_cause(res, "field \"/spec/config\" must be provided", "/spec/config")
+                    .into_review(),
+            ));
+        }
+    };
+    if cfgsrc.dropins.is_empty() {
+        return Ok(Json(
+            deny_with_cause(
+                res,
+                "indexer requires at least one config drop-in providing its database connection",
+                "/spec/config/dropins",
+            )
+            .into_review(),
+        ));
+    }
+
+    let namespace = resolve_namespace(&req, cur);
+    let (mut res, b) = match load_config_builder(&srv.client, &namespace, res, &cfgsrc).await {
+        Ok(ok) => ok,
+        Err(review) => return Ok(Json(review)),
+    };
+
+    let p: clair_config::Parts = b.into();
+    let v = match p.validate().await {
+        Ok(v) => v,
+        Err(_err) => {
+            // TODO(hank) log
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    if let Err(err) = &v.indexer {
+        return Ok(Json(
+            deny_with_cause(res, err.to_string(), "/spec/config").into_review(),
+        ));
+    }
+    let warn = [&v.matcher, &v.notifier, &v.updater]
+        .into_iter()
+        .filter_map(|r| r.as_ref().err().map(|err| err.to_string()))
+        .collect::<Vec<_>>();
+    if !warn.is_empty() {
+        res.warnings = Some(warn);
+    }
+
+    info!("OK");
     Ok(Json(res.into_review()))
 }
 #[instrument(skip_all)]
 async fn validate_v1alpha1_matcher(
-    _srv: Arc<State>,
+    srv: Arc<State>,
     rev: AdmissionReview<v1alpha1::Matcher>,
 ) -> Result<Json<AdmissionReview<DynamicObject>>, StatusCode> {
     let req: AdmissionRequest<v1alpha1::Matcher> = match rev.try_into() {
@@ -340,12 +1082,68 @@ async fn validate_v1alpha1_matcher(
         }
     };
     let res = AdmissionResponse::from(&req);
-    info!("TODO");
+
+    if req.operation == Operation::Connect {
+        return Ok(Json(res.deny("verb CONNECT makes no sense").into_review()));
+    }
+    if req.operation != Operation::Create && req.operation != Operation::Update {
+        return Ok(Json(res.into_review()));
+    }
+
+    let cur = req.object.as_ref().unwrap();
+    let cfgsrc = match &cur.spec.config {
+        Some(cfgsrc) => cfgsrc.clone(),
+        None => {
+            return Ok(Json(
+                deny_with_cause(res, "field \"/spec/config\" must be provided", "/spec/config")
+                    .into_review(),
+            ));
+        }
+    };
+    if cfgsrc.dropins.is_empty() {
+        return Ok(Json(
+            deny_with_cause(
+                res,
+                "matcher requires at least one config drop-in referencing a matcher database",
+                "/spec/config/dropins",
+            )
+            .into_review(),
+        ));
+    }
+
+    let namespace = resolve_namespace(&req, cur);
+    let (mut res, b) = match load_config_builder(&srv.client, &namespace, res, &cfgsrc).await {
+        Ok(ok) => ok,
+        Err(review) => return Ok(Json(review)),
+    };
+
+    let p: clair_config::Parts = b.into();
+    let v = match p.validate().await {
+        Ok(v) => v,
+        Err(_err) => {
+            // TODO(hank) log
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    if let Err(err) = &v.matcher {
+        return Ok(Json(
+            deny_with_cause(res, err.to_string(), "/spec/config").into_review(),
+        ));
+    }
+    let warn = [&v.indexer, &v.notifier, &v.updater]
+        .into_iter()
+        .filter_map(|r| r.as_ref().err().map(|err| err.to_string()))
+        .collect::<Vec<_>>();
+    if !warn.is_empty() {
+        res.warnings = Some(warn);
+    }
+
+    info!("OK");
     Ok(Json(res.into_review()))
 }
 #[instrument(skip_all)]
 async fn validate_v1alpha1_notifier(
-    _srv: Arc<State>,
+    srv: Arc<State>,
     rev: AdmissionReview<v1alpha1::Notifier>,
 ) -> Result<Json<AdmissionReview<DynamicObject>>, StatusCode> {
     let req: AdmissionRequest<v1alpha1::Notifier> = match rev.try_into() {
@@ -356,12 +1154,68 @@ async fn validate_v1alpha1_notifier(
         }
     };
     let res = AdmissionResponse::from(&req);
-    info!("TODO");
+
+    if req.operation == Operation::Connect {
+        return Ok(Json(res.deny("verb CONNECT makes no sense").into_review()));
+    }
+    if req.operation != Operation::Create && req.operation != Operation::Update {
+        return Ok(Json(res.into_review()));
+    }
+
+    let cur = req.object.as_ref().unwrap();
+    let cfgsrc = match &cur.spec.config {
+        Some(cfgsrc) => cfgsrc.clone(),
+        None => {
+            return Ok(Json(
+                deny_with_cause(res, "field \"/spec/config\" must be provided", "/spec/config")
+                    .into_review(),
+            ));
+        }
+    };
+    if cfgsrc.dropins.is_empty() {
+        return Ok(Json(
+            deny_with_cause(
+                res,
+                "notifier requires at least one config drop-in configuring its notifier database",
+                "/spec/config/dropins",
+            )
+            .into_review(),
+        ));
+    }
+
+    let namespace = resolve_namespace(&req, cur);
+    let (mut res, b) = match load_config_builder(&srv.client, &namespace, res, &cfgsrc).await {
+        Ok(ok) => ok,
+        Err(review) => return Ok(Json(review)),
+    };
+
+    let p: clair_config::Parts = b.into();
+    let v = match p.validate().await {
+        Ok(v) => v,
+        Err(_err) => {
+            // TODO(hank) log
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    if let Err(err) = &v.notifier {
+        return Ok(Json(
+            deny_with_cause(res, err.to_string(), "/spec/config").into_review(),
+        ));
+    }
+    let warn = [&v.indexer, &v.matcher, &v.updater]
+        .into_iter()
+        .filter_map(|r| r.as_ref().err().map(|err| err.to_string()))
+        .collect::<Vec<_>>();
+    if !warn.is_empty() {
+        res.warnings = Some(warn);
+    }
+
+    info!("OK");
     Ok(Json(res.into_review()))
 }
 #[instrument(skip_all)]
 async fn validate_v1alpha1_updater(
-    _srv: Arc<State>,
+    srv: Arc<State>,
     rev: AdmissionReview<v1alpha1::Updater>,
 ) -> Result<Json<AdmissionReview<DynamicObject>>, StatusCode> {
     let req: AdmissionRequest<v1alpha1::Updater> = match rev.try_into() {
@@ -372,10 +1226,302 @@ async fn validate_v1alpha1_updater(
         }
     };
     let res = AdmissionResponse::from(&req);
-    info!("TODO");
+
+    if req.operation == Operation::Connect {
+        return Ok(Json(res.deny("verb CONNECT makes no sense").into_review()));
+    }
+    if req.operation != Operation::Create && req.operation != Operation::Update {
+        return Ok(Json(res.into_review()));
+    }
+
+    let cur = req.object.as_ref().unwrap();
+    let cfgsrc = match &cur.spec.config {
+        Some(cfgsrc) => cfgsrc.clone(),
+        None => {
+            return Ok(Json(
+                deny_with_cause(res, "field \"/spec/config\" must be provided", "/spec/config")
+                    .into_review(),
+            ));
+        }
+    };
+    if cfgsrc.dropins.is_empty() {
+        return Ok(Json(
+            deny_with_cause(
+                res,
+                "updater requires at least one config drop-in referencing a matcher database",
+                "/spec/config/dropins",
+            )
+            .into_review(),
+        ));
+    }
+
+    let namespace = resolve_namespace(&req, cur);
+    let (mut res, b) = match load_config_builder(&srv.client, &namespace, res, &cfgsrc).await {
+        Ok(ok) => ok,
+        Err(review) => return Ok(Json(review)),
+    };
+
+    let p: clair_config::Parts = b.into();
+    let v = match p.validate().await {
+        Ok(v) => v,
+        Err(_err) => {
+            // TODO(hank) log
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    // The upstream config validator doesn't implement "updater" mode validation yet (see
+    // clair_config::Validate's doc comment), so unlike the other three handlers' own-mode check,
+    // there's no implemented mode to deny on here; every mode's result is only ever surfaced as a
+    // warning.
+    let warn = [&v.indexer, &v.matcher, &v.notifier, &v.updater]
+        .into_iter()
+        .filter_map(|r| r.as_ref().err().map(|err| err.to_string()))
+        .collect::<Vec<_>>();
+    if !warn.is_empty() {
+        res.warnings = Some(warn);
+    }
+
+    info!("OK");
     Ok(Json(res.into_review()))
 }
 
+/// ValidateStreamQuery identifies the object a `GET /v1alpha1/validate/stream` request wants
+/// progress for. SSE requests can't carry an `AdmissionReview` body the way the synchronous
+/// `/v1alpha1/validate` route does, so the object is named by reference instead.
+#[derive(Deserialize)]
+struct ValidateStreamQuery {
+    kind: String,
+    namespace: String,
+    name: String,
+}
+
+/// GET /v1alpha1/validate/stream streams the same config resolution the
+/// `validate_v1alpha1_*` handlers do, but as Server-Sent Events instead of a single response, so
+/// a client watching a large, many-dropin config can see progress as it happens rather than
+/// waiting for the whole thing to resolve. It reads an already-stored object by reference rather
+/// than reviewing an incoming admission request, so it has no bearing on the synchronous
+/// admission path above.
+#[instrument(skip_all, fields(kind = %q.kind, namespace = %q.namespace, name = %q.name))]
+async fn validate_v1alpha1_stream(
+    extract::State(srv): extract::State<Arc<State>>,
+    extract::Query(q): extract::Query<ValidateStreamQuery>,
+) -> Sse<ReceiverStream<Result<Event, Infallible>>> {
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(run_validate_stream(srv.client.clone(), q, tx));
+    Sse::new(ReceiverStream::new(rx)).keep_alive(KeepAlive::default())
+}
+
+/// Sse_event builds the payload for one step of [`run_validate_stream`]; `kind` doubles as the
+/// SSE event name (`"dropin-loaded"`, `"merged"`, `"component-result"`, `"summary"`, `"error"`)
+/// so a client can `EventSource.addEventListener` on just the phases it cares about.
+fn sse_event(kind: &'static str, data: serde_json::Value) -> Result<Event, Infallible> {
+    Ok(Event::default()
+        .event(kind)
+        .json_data(data)
+        .unwrap_or_else(|_| Event::default().event("error").data("failed to encode event")))
+}
+
+async fn send_event(
+    tx: &mpsc::Sender<Result<Event, Infallible>>,
+    kind: &'static str,
+    data: serde_json::Value,
+) -> bool {
+    tx.send(sse_event(kind, data)).await.is_ok()
+}
+
+/// Cfg_source_for fetches `name` as `kind` and resolves its `spec.config`, the same field
+/// `load_config_builder`'s callers pull from for the synchronous validate path (Clair composes
+/// one via [`v1alpha1::ClairSpec::with_root`] instead of storing it directly). Returns `Ok(None)`
+/// for an unrecognized `kind` rather than an error, since that's a client mistake, not a cluster
+/// one.
+async fn cfg_source_for(
+    client: &kube::Client,
+    q: &ValidateStreamQuery,
+) -> Result<Option<v1alpha1::ConfigSource>, kube::Error> {
+    let ns = q.namespace.as_str();
+    Ok(match q.kind.as_str() {
+        "Clair" => {
+            let api: Api<v1alpha1::Clair> = Api::namespaced(client.clone(), ns);
+            let obj = api.get(&q.name).await?;
+            match obj.spec.with_root(format!("{}-config", q.name)) {
+                Ok(cfgsrc) => Some(cfgsrc),
+                Err(err) => {
+                    debug!(error = %err, "clair spec failed pre-flight check");
+                    None
+                }
+            }
+        }
+        "Indexer" => {
+            let api: Api<v1alpha1::Indexer> = Api::namespaced(client.clone(), ns);
+            api.get(&q.name).await?.spec.config
+        }
+        "Matcher" => {
+            let api: Api<v1alpha1::Matcher> = Api::namespaced(client.clone(), ns);
+            api.get(&q.name).await?.spec.config
+        }
+        "Notifier" => {
+            let api: Api<v1alpha1::Notifier> = Api::namespaced(client.clone(), ns);
+            api.get(&q.name).await?.spec.config
+        }
+        "Updater" => {
+            let api: Api<v1alpha1::Updater> = Api::namespaced(client.clone(), ns);
+            api.get(&q.name).await?.spec.config
+        }
+        _ => None,
+    })
+}
+
+/// Run_validate_stream drives one `/v1alpha1/validate/stream` request to completion, sending
+/// events over `tx` as each phase finishes. It stops early (silently) if the receiver end is
+/// dropped, which happens whenever the client disconnects mid-stream.
+///
+/// This duplicates `load_config_builder`'s root/dropin resolution loop rather than calling it,
+/// since that helper only returns once everything is resolved and this handler's whole point is
+/// to report each dropin as it loads.
+async fn run_validate_stream(
+    client: kube::Client,
+    q: ValidateStreamQuery,
+    tx: mpsc::Sender<Result<Event, Infallible>>,
+) {
+    macro_rules! ok_or_emit_error {
+        ($result:expr) => {
+            match $result {
+                Ok(v) => v,
+                Err(err) => {
+                    send_event(&tx, "error", serde_json::json!({ "message": err.to_string() }))
+                        .await;
+                    return;
+                }
+            }
+        };
+    }
+
+    let cfgsrc = ok_or_emit_error!(cfg_source_for(&client, &q).await);
+    let Some(cfgsrc) = cfgsrc else {
+        send_event(
+            &tx,
+            "error",
+            serde_json::json!({ "message": format!("unknown kind: {}", q.kind) }),
+        )
+        .await;
+        return;
+    };
+
+    let cm_api: Api<core::v1::ConfigMap> = Api::namespaced(client.clone(), &q.namespace);
+    let sec_api: Api<core::v1::Secret> = Api::namespaced(client.clone(), &q.namespace);
+
+    let root = ok_or_emit_error!(cm_api.get_opt(&cfgsrc.root.name).await);
+    let Some(root) = root else {
+        let name = &cfgsrc.root.name;
+        send_event(
+            &tx,
+            "error",
+            serde_json::json!({ "message": format!("no such config: {name}") }),
+        )
+        .await;
+        return;
+    };
+    if !send_event(
+        &tx,
+        "dropin-loaded",
+        serde_json::json!({ "ref": cfgsrc.root.name, "role": "root" }),
+    )
+    .await
+    {
+        return;
+    }
+
+    let mut b = ok_or_emit_error!(clair_config::Builder::from_root(
+        &root,
+        cfgsrc.root.key.clone()
+    ));
+
+    for d in &cfgsrc.dropins {
+        let (src, key, name) = if let Some(r) = &d.config_map_key_ref {
+            let m = ok_or_emit_error!(cm_api.get_opt(&r.name).await);
+            let Some(m) = m else {
+                let name = &r.name;
+                send_event(
+                    &tx,
+                    "error",
+                    serde_json::json!({ "message": format!("no such config: {name}") }),
+                )
+                .await;
+                return;
+            };
+            (Either::from(m), r.key.clone(), r.name.clone())
+        } else if let Some(r) = &d.secret_key_ref {
+            let m = ok_or_emit_error!(sec_api.get_opt(&r.name).await);
+            let Some(m) = m else {
+                let name = &r.name;
+                send_event(
+                    &tx,
+                    "error",
+                    serde_json::json!({ "message": format!("no such config: {name}") }),
+                )
+                .await;
+                return;
+            };
+            (Either::from(m), r.key.clone(), r.name.clone())
+        } else {
+            continue;
+        };
+        b = ok_or_emit_error!(match src {
+            Either::ConfigMap(v) => b.add(v, key),
+            Either::Secret(v) => b.add(v, key),
+        });
+        if !send_event(
+            &tx,
+            "dropin-loaded",
+            serde_json::json!({ "ref": name, "role": "dropin" }),
+        )
+        .await
+        {
+            return;
+        }
+    }
+
+    if !send_event(&tx, "merged", serde_json::json!({})).await {
+        return;
+    }
+
+    let p: clair_config::Parts = b.into();
+    let v = ok_or_emit_error!(p.validate().await);
+    // `Parts::validate` resolves all four components in a single async call instead of
+    // reporting each as it finishes, so these land together rather than trickling in; the event
+    // schema still lets a client distinguish them the moment they're available.
+    let mut ok = 0;
+    for (component, result) in [
+        ("indexer", &v.indexer),
+        ("matcher", &v.matcher),
+        ("notifier", &v.notifier),
+        ("updater", &v.updater),
+    ] {
+        let status = match result {
+            Ok(_) => {
+                ok += 1;
+                "ok"
+            }
+            Err(_) => "error",
+        };
+        if !send_event(
+            &tx,
+            "component-result",
+            serde_json::json!({
+                "component": component,
+                "status": status,
+                "detail": result.as_ref().err().map(|err| err.to_string()),
+            }),
+        )
+        .await
+        {
+            return;
+        }
+    }
+
+    send_event(&tx, "summary", serde_json::json!({ "ok": ok, "total": 4 })).await;
+}
+
 #[cfg(test)]
 mod tests {
     //use super::*;
@@ -0,0 +1,83 @@
+//! Alias resolves user-defined task aliases -- entries in `etc/xtask.toml`'s `[alias]` table (or
+//! `[workspace.metadata.xtask.alias]` in the workspace `Cargo.toml`, if `etc/xtask.toml` doesn't
+//! exist) that expand a name into an argument vector, the same way cargo's own `[alias]`
+//! mechanism lets `cargo b` stand in for `cargo build`.
+
+use std::collections::HashMap;
+use std::fs;
+
+use serde::Deserialize;
+
+use crate::{Result, WORKSPACE};
+
+/// Value is one alias's definition: either a single command line, split on whitespace, or an
+/// already-tokenized argument vector (for entries whose tokens contain spaces of their own).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Value {
+    Line(String),
+    Tokens(Vec<String>),
+}
+
+impl Value {
+    fn into_tokens(self) -> Vec<String> {
+        match self {
+            Value::Line(s) => s.split_whitespace().map(str::to_string).collect(),
+            Value::Tokens(t) => t,
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct AliasTable {
+    #[serde(default)]
+    alias: HashMap<String, Value>,
+}
+
+#[derive(Deserialize, Default)]
+struct WorkspaceMetadata {
+    #[serde(default)]
+    xtask: AliasTable,
+}
+
+#[derive(Deserialize, Default)]
+struct Workspace {
+    #[serde(default)]
+    metadata: WorkspaceMetadata,
+}
+
+#[derive(Deserialize, Default)]
+struct CargoToml {
+    #[serde(default)]
+    workspace: Workspace,
+}
+
+/// Load reads the alias table from `etc/xtask.toml`, falling back to
+/// `[workspace.metadata.xtask.alias]` in the workspace `Cargo.toml` if that file doesn't exist.
+/// Both are optional; a missing file or alias-less table yields an empty map rather than an
+/// error.
+fn load() -> Result<HashMap<String, Value>> {
+    let dedicated = WORKSPACE.join("etc/xtask.toml");
+    if dedicated.is_file() {
+        let raw = fs::read_to_string(&dedicated)?;
+        let table: AliasTable =
+            toml::from_str(&raw).map_err(|err| format!("parsing {}: {err}", dedicated.display()))?;
+        return Ok(table.alias);
+    }
+
+    let manifest = WORKSPACE.join("Cargo.toml");
+    if manifest.is_file() {
+        let raw = fs::read_to_string(&manifest)?;
+        let cargo: CargoToml =
+            toml::from_str(&raw).map_err(|err| format!("parsing {}: {err}", manifest.display()))?;
+        return Ok(cargo.workspace.metadata.xtask.alias);
+    }
+
+    Ok(HashMap::new())
+}
+
+/// Resolve expands `name` into its recorded argument vector, or returns `None` if `name` isn't a
+/// defined alias.
+pub fn resolve(name: &str) -> Result<Option<Vec<String>>> {
+    Ok(load()?.remove(name).map(Value::into_tokens))
+}
@@ -1,12 +1,85 @@
 use std::{
     env::{self, consts::*},
+    path::{Path, PathBuf},
     sync::LazyLock,
 };
 
+use regex::Regex;
 use xshell::{Shell, cmd};
 
 use crate::*;
 
+/// Offline gates the installers to resolve tools from [`TOOL_CACHE`] instead of the network, for
+/// CI or disconnected environments that can't reach `kind.sigs.k8s.io`, `dl.k8s.io`, or GitHub
+/// releases. Populate the cache first with `cargo xtask vendor` while online.
+static OFFLINE: LazyLock<bool> =
+    LazyLock::new(|| env::var("CLAIR_XTASK_OFFLINE").as_deref() == Ok("1"));
+
+/// Tool_cache is where `vendor` writes verified downloads, and where [`fetch_verified`] reads them
+/// back from in [`OFFLINE`] mode.
+static TOOL_CACHE: LazyLock<PathBuf> = LazyLock::new(|| {
+    env::var_os("CLAIR_XTASK_TOOL_CACHE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| WORKSPACE.join(".tool-cache"))
+});
+
+/// Cache_key names a vendored artifact, unique per tool/version/os/arch.
+fn cache_key(name: &str, version: &str, os: &str, arch: &str) -> String {
+    format!("{name}-{version}-{os}-{arch}")
+}
+
+/// Fetch_verified resolves `url` into `dest`, checking its SHA-256 digest against `expected` (see
+/// [`crate::lock`]) before the caller does anything with it — so a truncated download, a
+/// compromised mirror, or a stale cache entry fails loudly here instead of silently yielding a
+/// broken or malicious tool.
+///
+/// In [`OFFLINE`] mode this reads `key` out of [`TOOL_CACHE`] instead of touching the network,
+/// erroring clearly if the artifact hasn't been vendored there yet.
+///
+/// Also used by [`crate::generate`] to verify fetched CRD YAML before it's handed to `kopium`.
+pub(crate) fn fetch_verified(sh: &Shell, key: &str, url: &str, dest: &Path, expected: &str) -> Result<()> {
+    let _tmp = sh.create_temp_dir()?;
+    let tmp = _tmp.path().join("download");
+    if *OFFLINE {
+        let cached = TOOL_CACHE.join(key);
+        if !cached.is_file() {
+            return Err(format!(
+                "CLAIR_XTASK_OFFLINE is set but {key} isn't vendored in {} -- run `cargo xtask vendor` while online first",
+                TOOL_CACHE.display(),
+            )
+            .into());
+        }
+        sh.copy_file(&cached, &tmp)?;
+    } else {
+        download(sh, url, &tmp)?;
+    }
+
+    let buf = sh.read_binary_file(&tmp)?;
+    let got = sha256_hex(&buf);
+    if got != expected {
+        return Err(format!(
+            "checksum mismatch for {key}:\n\texpected: {expected}\n\tcomputed: {got}"
+        )
+        .into());
+    }
+
+    if let Some(dir) = dest.parent() {
+        sh.create_dir(dir)?;
+    }
+    sh.copy_file(&tmp, dest)?;
+    Ok(())
+}
+
+/// Sha256_hex hashes `buf` and hex-encodes the digest.
+fn sha256_hex(buf: &[u8]) -> String {
+    use openssl::hash::{MessageDigest, hash};
+    hash(MessageDigest::sha256(), buf)
+        .expect("SHA-256 is always available")
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
 static ARCH: LazyLock<&'static str> = LazyLock::new(|| {
     let arch = self::env::consts::ARCH;
     match arch {
@@ -18,142 +91,416 @@ static ARCH: LazyLock<&'static str> = LazyLock::new(|| {
     }
 });
 
-pub fn kind(sh: &Shell) -> Result<()> {
-    let version: &str = &KIND_VERSION;
-    let arch: &str = &ARCH;
-    if cmd!(sh, "which kind")
+/// Matches the first bare `MAJOR.MINOR.PATCH` (with optional leading `v` and pre-release/build
+/// metadata) in a tool's version output, which is the common denominator across every format
+/// these installers' tools report in.
+static VERSION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"v?(\d+\.\d+\.\d+(?:-[0-9A-Za-z.-]+)?)").expect("programmer error: bad static regexp")
+});
+
+/// Extract_version pulls the first semver-looking substring out of a tool's version output.
+fn extract_version(s: &str) -> Option<String> {
+    VERSION_RE.captures(s).map(|c| c[1].to_string())
+}
+
+/// Resolve_bin returns the pinned copy in [`BIN_DIR`] if one exists, otherwise whatever `which`
+/// finds on `PATH` -- so a previously-installed pinned copy is always preferred over a
+/// system-provided one of unknown vintage.
+fn resolve_bin(sh: &Shell, name: &str) -> Option<PathBuf> {
+    let pinned = BIN_DIR.join(format!("{name}{EXE_SUFFIX}"));
+    if pinned.is_file() {
+        return Some(pinned);
+    }
+    cmd!(sh, "which {name}")
         .quiet()
-        .ignore_stdout()
         .ignore_stderr()
-        .run()
-        .is_err()
-    {
-        let exe = format!("{}/kind{EXE_SUFFIX}", BIN_DIR.display());
-        sh.create_dir(BIN_DIR.as_path())?;
-        cmd!(
-            sh,
-            "curl -fsSLo {exe} https://kind.sigs.k8s.io/dl/v{version}/kind-{OS}-{arch}"
-        )
-        .run()?;
-        cmd!(sh, "chmod +x {exe}").run()?;
+        .read()
+        .ok()
+        .map(PathBuf::from)
+}
+
+/// Check_version runs `bin`'s version subcommand and reports whether the reported version matches
+/// `want` exactly. Each tool has its own subcommand and output format, so this dispatches on the
+/// binary's file name rather than trying to normalize them up front.
+fn check_version(sh: &Shell, bin: &Path, want: &str) -> Result<bool> {
+    let name = bin
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let got = match name {
+        "kind" => extract_version(&cmd!(sh, "{bin} version").read()?),
+        "kubectl" => {
+            let out = cmd!(sh, "{bin} version --client -o json").read()?;
+            let v: serde_json::Value = serde_json::from_str(&out)?;
+            v["clientVersion"]["gitVersion"]
+                .as_str()
+                .and_then(extract_version)
+        }
+        "kustomize" => extract_version(&cmd!(sh, "{bin} version").read()?),
+        "operator-sdk" => extract_version(&cmd!(sh, "{bin} version").read()?),
+        "opm" => extract_version(&cmd!(sh, "{bin} version").read()?),
+        "istioctl" => extract_version(&cmd!(sh, "{bin} version --remote=false").read()?),
+        _ => return Err(format!("no version check known for {name}").into()),
+    };
+    Ok(got.as_deref() == Some(want))
+}
+
+/// Kind describes how a [`Tool`]'s download is packaged, since that's the one axis the six
+/// installers actually differ on beyond URL and version.
+enum Kind {
+    /// A single executable, written directly to `BIN_DIR/{name}{EXE_SUFFIX}`.
+    Binary,
+    /// An archive extracted into `BIN_DIR` (`.tar.gz` on Unix, `.zip` on Windows -- see
+    /// `windows_url_template`); `member` selects a single archive entry (e.g. `"*/bin/istioctl"`,
+    /// matched by file name on Windows since zip layouts don't always mirror the tarball's) or,
+    /// if empty, extracts everything. `strip_components` is only meaningful for the Unix `tar`
+    /// path.
+    Archive {
+        strip_components: u8,
+        member: &'static str,
+    },
+}
+
+/// Tool is the declarative description of one installable dependency: following rustbuild's
+/// "extensible to other components" design, adding a tool here (and a matching entry in
+/// `tools.lock`) is the whole job -- no new function needed.
+struct Tool {
+    name: &'static str,
+    version: &'static str,
+    /// `{version}`/`{os}`/`{arch}`/`{exe_suffix}` are substituted in by [`expand_url`]. Used on
+    /// Unix always, and on Windows when `windows_url_template` is `None`.
+    url_template: &'static str,
+    /// Overrides `url_template` on Windows, for tools whose Windows release uses a different
+    /// archive format (e.g. `.zip` instead of `.tar.gz`) rather than just a different `{os}`.
+    windows_url_template: Option<&'static str>,
+    kind: Kind,
+}
+
+static TOOLS: LazyLock<Vec<Tool>> = LazyLock::new(|| {
+    vec![
+        Tool {
+            name: "kind",
+            version: KIND_VERSION.as_str(),
+            url_template: "https://kind.sigs.k8s.io/dl/v{version}/kind-{os}-{arch}{exe_suffix}",
+            windows_url_template: None,
+            kind: Kind::Binary,
+        },
+        Tool {
+            name: "kubectl",
+            version: KUBE_VERSION.as_str(),
+            url_template: "https://dl.k8s.io/release/v{version}/bin/{os}/{arch}/kubectl{exe_suffix}",
+            windows_url_template: None,
+            kind: Kind::Binary,
+        },
+        Tool {
+            name: "kustomize",
+            version: KUSTOMIZE_VERSION.as_str(),
+            url_template: "https://github.com/kubernetes-sigs/kustomize/releases/download/kustomize%2Fv{version}/kustomize_v{version}_{os}_{arch}.tar.gz",
+            // kustomize's Windows release is a `.zip`, not a `.tar.gz`, of the same layout.
+            windows_url_template: Some(
+                "https://github.com/kubernetes-sigs/kustomize/releases/download/kustomize%2Fv{version}/kustomize_v{version}_{os}_{arch}.zip",
+            ),
+            kind: Kind::Archive {
+                strip_components: 0,
+                member: "",
+            },
+        },
+        Tool {
+            name: "operator-sdk",
+            version: OPERATOR_SDK_VERSION.as_str(),
+            url_template: "https://github.com/operator-framework/operator-sdk/releases/download/v{version}/operator-sdk_{os}_{arch}{exe_suffix}",
+            windows_url_template: None,
+            kind: Kind::Binary,
+        },
+        Tool {
+            name: "opm",
+            version: OPM_VERSION.as_str(),
+            url_template: "https://github.com/operator-framework/operator-registry/releases/download/v{version}/{os}-{arch}-opm{exe_suffix}",
+            windows_url_template: None,
+            kind: Kind::Binary,
+        },
+        Tool {
+            name: "istioctl",
+            version: ISTIO_VERSION.as_str(),
+            url_template: "https://github.com/istio/istio/releases/download/{version}/istio-{version}-{os}-{arch}.tar.gz",
+            // Istio only publishes a single Windows archive per release (no per-arch split).
+            windows_url_template: Some("https://github.com/istio/istio/releases/download/{version}/istio-{version}-win.zip"),
+            kind: Kind::Archive {
+                strip_components: 2,
+                member: "*/bin/istioctl",
+            },
+        },
+    ]
+});
+
+/// Expand_url substitutes `{version}`/`{os}`/`{arch}`/`{exe_suffix}` into a [`Tool`]'s
+/// `url_template`.
+fn expand_url(template: &str, version: &str, arch: &str) -> String {
+    template
+        .replace("{version}", version)
+        .replace("{os}", OS)
+        .replace("{arch}", arch)
+        .replace("{exe_suffix}", EXE_SUFFIX)
+}
+
+/// Tool_url_and_key resolves `tool`'s download URL for the current platform (see
+/// `Tool::windows_url_template`) and its [`TOOL_CACHE`] key.
+fn tool_url_and_key(tool: &Tool) -> (String, String) {
+    let arch: &str = &ARCH;
+    let template = if cfg!(windows) {
+        tool.windows_url_template.unwrap_or(tool.url_template)
+    } else {
+        tool.url_template
+    };
+    let url = expand_url(template, tool.version, arch);
+    let key = cache_key(tool.name, tool.version, OS, arch);
+    (url, key)
+}
+
+/// Install downloads and verifies `tool`'s pinned release into [`BIN_DIR`], per its [`Kind`].
+///
+/// The fetch, extract, and mark-executable steps each have a Unix and a Windows implementation
+/// (see [`download`], [`extract_archive`], [`make_executable`]) so this doesn't assume `curl`,
+/// GNU `tar`, or `chmod` are present.
+fn install(sh: &Shell, tool: &Tool) -> Result<()> {
+    let arch: &str = &ARCH;
+    let (url, key) = tool_url_and_key(tool);
+    let expected = lock::expect(tool.name, tool.version, OS, arch)?;
+    sh.create_dir(BIN_DIR.as_path())?;
+    match tool.kind {
+        Kind::Binary => {
+            let exe = BIN_DIR.join(format!("{}{EXE_SUFFIX}", tool.name));
+            fetch_verified(sh, &key, &url, &exe, expected)?;
+            make_executable(sh, &exe)?;
+        }
+        Kind::Archive {
+            strip_components,
+            member,
+        } => {
+            let dir = BIN_DIR.as_path();
+            let _tmp = sh.create_temp_dir()?;
+            let archive = _tmp.path().join("archive");
+            fetch_verified(sh, &key, &url, &archive, expected)?;
+            extract_archive(sh, &archive, dir, strip_components, member)?;
+        }
     }
     Ok(())
 }
 
-pub fn kubectl(sh: &Shell) -> Result<()> {
-    let version: &str = &KUBE_VERSION;
-    let arch: &str = &ARCH;
-    if cmd!(sh, "which kubectl")
-        .quiet()
-        .ignore_stdout()
-        .ignore_stderr()
-        .run()
-        .is_err()
-    {
-        let exe = format!("{}/kubectl{EXE_SUFFIX}", BIN_DIR.display());
-        sh.create_dir(BIN_DIR.as_path())?;
-        cmd!(
-            sh,
-            "curl -fsSLo {exe} https://dl.k8s.io/release/v{version}/bin/{OS}/{arch}/kubectl{EXE_SUFFIX}"
-        )
-        .run()?;
-        cmd!(sh, "chmod +x {exe}").run()?;
+/// Vendor downloads and verifies every registered [`Tool`]'s pinned release into [`TOOL_CACHE`],
+/// always touching the network regardless of [`OFFLINE`] -- run this once while connected, then
+/// commit or archive the cache directory so `CLAIR_XTASK_OFFLINE=1` has something to resolve from.
+pub fn vendor(sh: &Shell) -> Result<()> {
+    sh.create_dir(TOOL_CACHE.as_path())?;
+    for tool in TOOLS.iter() {
+        let (url, key) = tool_url_and_key(tool);
+        let expected = lock::expect(tool.name, tool.version, OS, &ARCH)?;
+        let dest = TOOL_CACHE.join(&key);
+        download(sh, &url, &dest)?;
+
+        let buf = sh.read_binary_file(&dest)?;
+        let got = sha256_hex(&buf);
+        if got != expected {
+            return Err(format!(
+                "checksum mismatch for {key}:\n\texpected: {expected}\n\tcomputed: {got}"
+            )
+            .into());
+        }
+        eprintln!("# vendored {key}");
     }
     Ok(())
 }
 
-pub fn kustomize(sh: &Shell) -> Result<()> {
-    let version: &str = &KUSTOMIZE_VERSION;
-    let arch: &str = &ARCH;
-    if cmd!(sh, "which kustomize")
-        .quiet()
-        .ignore_stdout()
-        .ignore_stderr()
-        .run()
-        .is_err()
-    {
-        // The kustomize install is excessively dumb.
-        let dir = BIN_DIR.as_path();
-        sh.create_dir(dir)?;
-        let _tmp = sh.create_temp_dir()?;
-        let tmp = _tmp.path();
-        cmd!(
-            sh,
-            "curl -fsSLo {tmp}/tgz https://github.com/kubernetes-sigs/kustomize/releases/download/kustomize%2Fv{version}/kustomize_v{version}_{OS}_{arch}.tar.gz"
-        )
-        .run()?;
-        cmd!(sh, "tar -xz -C {dir} -f {tmp}/tgz").run()?;
+/// Download fetches `url` to `dest`. On Unix this shells out to `curl`; on Windows, where `curl`
+/// isn't guaranteed to be on `PATH`, this uses a Rust-native HTTP client instead.
+#[cfg(unix)]
+fn download(sh: &Shell, url: &str, dest: &Path) -> Result<()> {
+    cmd!(sh, "curl -fsSLo {dest} {url}").run()?;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn download(_sh: &Shell, url: &str, dest: &Path) -> Result<()> {
+    let bytes = reqwest::blocking::get(url)?.error_for_status()?.bytes()?;
+    std::fs::write(dest, &bytes)?;
+    Ok(())
+}
+
+/// Make_executable sets the executable bit on Unix; a no-op on Windows, where a `.exe` suffix is
+/// what makes a file runnable, not a permission bit.
+#[cfg(unix)]
+fn make_executable(sh: &Shell, exe: &Path) -> Result<()> {
+    cmd!(sh, "chmod +x {exe}").run()?;
+    Ok(())
+}
+
+#[cfg(windows)]
+fn make_executable(_sh: &Shell, _exe: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Extract_archive unpacks `archive` into `dir`. On Unix this shells out to GNU `tar`; on Windows
+/// (where the archive is a `.zip`, not a `.tar.gz` -- see `Tool::windows_url_template`) this uses
+/// the `zip` crate instead of assuming `tar` is present.
+#[cfg(unix)]
+fn extract_archive(
+    sh: &Shell,
+    archive: &Path,
+    dir: &Path,
+    strip_components: u8,
+    member: &str,
+) -> Result<()> {
+    let strip = format!("--strip-components={strip_components}");
+    if member.is_empty() {
+        cmd!(sh, "tar -xz -C {dir} -f {archive} {strip}").run()?;
+    } else {
+        cmd!(sh, "tar -xz -C {dir} -f {archive} {strip} {member}").run()?;
     }
     Ok(())
 }
 
-pub fn operator_sdk(sh: &Shell) -> Result<()> {
-    let version: &str = &OPERATOR_SDK_VERSION;
-    let arch: &str = &ARCH;
-    if cmd!(sh, "which operator-sdk")
-        .quiet()
-        .ignore_stdout()
-        .ignore_stderr()
-        .run()
-        .is_err()
-    {
-        let exe = format!("{}/operator-sdk{EXE_SUFFIX}", BIN_DIR.display());
-        sh.create_dir(BIN_DIR.as_path())?;
-        cmd!(
-            sh,
-            "curl -fsSLo {exe} https://github.com/operator-framework/operator-sdk/releases/download/v{version}/operator-sdk_{OS}_{arch}"
-        )
-        .run()?;
-        cmd!(sh, "chmod +x {exe}").run()?;
+#[cfg(windows)]
+fn extract_archive(
+    _sh: &Shell,
+    archive: &Path,
+    dir: &Path,
+    _strip_components: u8,
+    member: &str,
+) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(std::fs::File::open(archive)?)?;
+    if member.is_empty() {
+        zip.extract(dir)?;
+        return Ok(());
+    }
+    // `member` is a Unix-style path (e.g. "*/bin/istioctl"); the zip's layout isn't guaranteed to
+    // match, so just pull out whichever entry has the same file name (with or without the
+    // platform's executable suffix).
+    let name = Path::new(member)
+        .file_name()
+        .expect("programmer error: member must have a file name")
+        .to_string_lossy()
+        .into_owned();
+    let candidate = format!("{name}{EXE_SUFFIX}");
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        let matches = entry.is_file()
+            && (entry.name().ends_with(&name) || entry.name().ends_with(&candidate));
+        if matches {
+            let mut out = std::fs::File::create(dir.join(&candidate))?;
+            std::io::copy(&mut entry, &mut out)?;
+            return Ok(());
+        }
+    }
+    Err(format!("no entry named {name} found in {}", archive.display()).into())
+}
+
+/// Ensure installs `name`'s pinned version if the binary [`resolve_bin`] finds is missing or
+/// reports a different version, preferring the [`BIN_DIR`] copy over a stale `PATH` one.
+fn ensure(sh: &Shell, name: &str) -> Result<()> {
+    let tool = TOOLS
+        .iter()
+        .find(|t| t.name == name)
+        .unwrap_or_else(|| panic!("programmer error: {name} not registered in TOOLS"));
+    let need_install = match resolve_bin(sh, name) {
+        Some(bin) => !check_version(sh, &bin, tool.version).unwrap_or(false),
+        None => true,
+    };
+    if need_install {
+        install(sh, tool)?;
     }
     Ok(())
 }
 
-pub fn opm(sh: &Shell) -> Result<()> {
-    let version: &str = &OPM_VERSION;
-    let arch: &str = &ARCH;
-    if cmd!(sh, "which opm")
-        .quiet()
-        .ignore_stdout()
-        .ignore_stderr()
-        .run()
-        .is_err()
-    {
-        let exe = format!("{}/opm{EXE_SUFFIX}", BIN_DIR.display());
-        sh.create_dir(BIN_DIR.as_path())?;
-        cmd!(
-            sh,
-            "curl -fsSLo {exe} https://github.com/operator-framework/operator-registry/releases/download/v{version}/{OS}-{arch}-opm"
-        ).run()?;
-        cmd!(sh, "chmod +x {exe}").run()?;
+/// Install_all ensures every registered [`Tool`] is present at its pinned version.
+pub fn install_all(sh: &Shell) -> Result<()> {
+    for tool in TOOLS.iter() {
+        ensure(sh, tool.name)?;
     }
     Ok(())
 }
 
+pub fn kind(sh: &Shell) -> Result<()> {
+    ensure(sh, "kind")
+}
+
+pub fn kubectl(sh: &Shell) -> Result<()> {
+    ensure(sh, "kubectl")
+}
+
+pub fn kustomize(sh: &Shell) -> Result<()> {
+    ensure(sh, "kustomize")
+}
+
+pub fn operator_sdk(sh: &Shell) -> Result<()> {
+    ensure(sh, "operator-sdk")
+}
+
+pub fn opm(sh: &Shell) -> Result<()> {
+    ensure(sh, "opm")
+}
+
 pub fn istioctl(sh: &Shell) -> Result<()> {
-    let version: &str = &ISTIO_VERSION;
-    let arch: &str = &ARCH;
-    if cmd!(sh, "which istioctl")
-        .quiet()
-        .ignore_stdout()
-        .ignore_stderr()
-        .run()
-        .is_err()
-    {
-        let dir = BIN_DIR.as_path();
-        sh.create_dir(dir)?;
-        let _tmp = sh.create_temp_dir()?;
-        let tmp = _tmp.path();
-        cmd!(
-            sh,
-            "curl -fsSLo {tmp}/tgz https://github.com/istio/istio/releases/download/{version}/istio-{version}-{OS}-{arch}.tar.gz"
-        )
-        .run()?;
-        cmd!(
-            sh,
-            "tar -xz -C {dir} -f {tmp}/tgz --strip-components=2 */bin/istioctl"
+    ensure(sh, "istioctl")
+}
+
+/// KubeContext is the subset of a kubeconfig's `current-context` entry [`kube_context`] cares
+/// about.
+#[derive(Debug, serde::Deserialize)]
+pub struct KubeContext {
+    pub cluster: String,
+    pub user: String,
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct NamedContext {
+    name: String,
+    context: KubeContext,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct Kubeconfig {
+    #[serde(rename = "current-context")]
+    current_context: String,
+    contexts: Vec<NamedContext>,
+}
+
+/// Kube_context reads the kubeconfig at `$KUBECONFIG` (the path `demo`/`ci` set with
+/// `sh.set_var("KUBECONFIG", ...)`), resolves `current-context`, and returns the matching
+/// `contexts[].context` entry.
+pub fn kube_context(sh: &Shell) -> Result<KubeContext> {
+    let path = sh
+        .var("KUBECONFIG")
+        .map_err(|_| "KUBECONFIG is not set".to_string())?;
+    let raw = sh.read_file(&path)?;
+    let cfg: Kubeconfig = serde_yaml::from_str(&raw)?;
+    cfg.contexts
+        .into_iter()
+        .find(|c| c.name == cfg.current_context)
+        .map(|c| c.context)
+        .ok_or_else(|| {
+            format!(
+                "current-context {:?} not found in {path}'s contexts",
+                cfg.current_context
+            )
+            .into()
+        })
+}
+
+/// Require_kind_cluster errors unless the active kubeconfig context's cluster is the local KinD
+/// cluster named `kind-{name}` (what [`KinDBuilder::build`] actually creates), so a destructive
+/// command like `ci`'s namespace label or CRD apply can't land on whatever cluster the caller's
+/// `KUBECONFIG` happened to point at.
+pub fn require_kind_cluster(sh: &Shell, name: &str) -> Result<()> {
+    let want = format!("kind-{name}");
+    let ctx = kube_context(sh)?;
+    if ctx.cluster != want {
+        let got = ctx.cluster;
+        return Err(format!(
+            "refusing to proceed: active kubeconfig context points at cluster {got:?}, not the local {want:?} cluster"
         )
-        .run()?;
+        .into());
     }
     Ok(())
 }
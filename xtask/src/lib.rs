@@ -2,19 +2,62 @@ use std::{
     borrow::Cow,
     env,
     path::{Path, PathBuf},
-    sync::LazyLock,
+    sync::{LazyLock, Mutex},
 };
 
+use clap::ValueEnum;
+use serde::Serialize;
 use xshell::{Shell, cmd};
 
+pub mod alias;
 pub mod check;
 pub mod find;
+mod lock;
 pub mod manifests;
 pub mod olm;
 
 pub type DynError = Box<dyn std::error::Error>;
 pub type Result<T> = std::result::Result<T, DynError>;
 
+/// MessageFormat selects how `--dry-run` reports the [`PLAN`] it recorded: one `+ ...` line per
+/// step, or a single JSON document, for callers that want to consume the plan programmatically.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+/// Verbosity controls how much progress [`Context::status`] prints and which level
+/// [`Context::rust_log_level`] hands to subcommands that shell out to this repo's own binaries,
+/// driven by the global `-v`/`-q` flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    Quiet,
+    #[default]
+    Normal,
+    Verbose,
+}
+
+/// Color selects whether [`Context::status`] ANSI-colorizes its `# ...` progress lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Color {
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl Color {
+    fn enabled(self) -> bool {
+        match self {
+            Color::Always => true,
+            Color::Never => false,
+            Color::Auto => std::io::IsTerminal::is_terminal(&std::io::stderr()),
+        }
+    }
+}
+
 pub static CARGO: LazyLock<PathBuf> = LazyLock::new(|| env::var_os("CARGO").unwrap().into());
 
 // Paths:
@@ -45,6 +88,11 @@ pub static ISTIO_VERSION: LazyLock<String> =
     LazyLock::new(|| env::var("ISTIO_VERSION").unwrap_or(String::from("1.25.2")));
 pub static GATEWAY_API_VERSION: LazyLock<String> =
     LazyLock::new(|| env::var("GATEWAY_API_VERSION").unwrap_or(String::from("1.2.1")));
+pub static OPERATOR_API_VERSION: LazyLock<String> =
+    LazyLock::new(|| env::var("OPERATOR_API_VERSION").unwrap_or(String::from("0.32.0")));
+pub static PROMETHEUS_OPERATOR_VERSION: LazyLock<String> = LazyLock::new(|| {
+    env::var("PROMETHEUS_OPERATOR_VERSION").unwrap_or(String::from("0.79.2"))
+});
 
 // URLs:
 pub static INGRESS_NGINX_MANIFEST_URL: LazyLock<String> = LazyLock::new(|| {
@@ -61,7 +109,10 @@ pub const BUNDLE_IMAGE: &str = "quay.io/projectclair/clair-bundle";
 pub const CATALOG_IMAGE: &str = "quay.io/projectclair/clair-catalog";
 
 /// Shell constructs a [Shell] with the environment modified in a consistent way.
-pub fn shell() -> xshell::Result<Shell> {
+///
+/// If `source` is provided, the shell's working directory is set there instead of the
+/// [WORKSPACE] this crate was built from -- this is the `--source` global flag's seam.
+pub fn shell(source: Option<&Path>) -> xshell::Result<Shell> {
     let sh = Shell::new()?;
     let p = env::var("PATH").expect("PATH environment variable missing");
     let paths = std::iter::once(BIN_DIR.to_path_buf()).chain(std::env::split_paths(&p));
@@ -69,7 +120,7 @@ pub fn shell() -> xshell::Result<Shell> {
         "PATH",
         std::env::join_paths(paths).expect("unable to reconstruct PATH"),
     );
-    sh.change_dir(WORKSPACE.as_path());
+    sh.change_dir(source.unwrap_or(WORKSPACE.as_path()));
 
     Ok(sh)
 }
@@ -81,20 +132,150 @@ pub fn rel<'a>(p: &'a Path) -> Cow<'a, str> {
         .to_string_lossy()
 }
 
+/// Context bundles the state a subcommand needs to actually do its work: the [`Shell`] to run
+/// commands in (see [`shell`]), how much progress to print and whether to colorize it, and the
+/// container builder [`find::builder`] resolves -- built once from the global flags `main` parsed
+/// instead of each subcommand calling [`shell`] and hardcoding its own `RUST_LOG`/progress output.
+pub struct Context {
+    pub sh: Shell,
+    pub dry_run: bool,
+    verbosity: Verbosity,
+    color: Color,
+    builder: std::cell::OnceCell<String>,
+}
+
+impl Context {
+    /// New resolves a [`Shell`] rooted at `source` (see [`shell`]), bundling it with the
+    /// verbosity/color/dry-run flags `main` parsed.
+    pub fn new(source: Option<&Path>, verbosity: Verbosity, color: Color, dry_run: bool) -> Result<Self> {
+        Ok(Self {
+            sh: shell(source)?,
+            dry_run,
+            verbosity,
+            color,
+            builder: std::cell::OnceCell::new(),
+        })
+    }
+
+    /// Status prints a `# ...` progress line, unless `-q` suppressed it, colorized per `--color`.
+    pub fn status(&self, msg: impl std::fmt::Display) {
+        if self.verbosity == Verbosity::Quiet {
+            return;
+        }
+        if self.color.enabled() {
+            eprintln!("\x1b[2m# {msg}\x1b[0m");
+        } else {
+            eprintln!("# {msg}");
+        }
+    }
+
+    /// Rust_log_level picks a `RUST_LOG` level from the verbosity flags, for subcommands that run
+    /// the operator's own binaries and want `-v`/`-q` to control their log level too, not just
+    /// this crate's own progress lines.
+    pub fn rust_log_level(&self) -> &'static str {
+        match self.verbosity {
+            Verbosity::Quiet => "warn",
+            Verbosity::Normal => "debug",
+            Verbosity::Verbose => "trace",
+        }
+    }
+
+    /// Builder resolves (and caches) the container builder [`find::builder`] finds, only paying
+    /// for the `which podman`/`which docker` probe the first time a subcommand actually needs it.
+    pub fn builder(&self) -> Result<&str> {
+        if let Some(b) = self.builder.get() {
+            return Ok(b);
+        }
+        let b = find::builder(&self.sh)?;
+        Ok(self.builder.get_or_init(|| b))
+    }
+
+    /// Exec runs `cmd` through [`exec`], honoring this context's `--dry-run`.
+    pub fn exec(&self, cmd: xshell::Cmd<'_>) -> Result<()> {
+        exec(&self.sh, cmd, self.dry_run)
+    }
+}
+
+/// Step is one planned external command, recorded by [`exec`] instead of run, carrying enough
+/// to reconstruct what would have happened: the resolved program and argv, the working
+/// directory, and any of [`PLANNED_ENV_VARS`] that were set at the time.
+#[derive(Debug, Serialize)]
+pub struct Step {
+    pub program: String,
+    pub args: Vec<String>,
+    pub dir: PathBuf,
+    pub env: Vec<(String, String)>,
+    /// Line is the shell-escaped command, exactly as the human-format plan prints it.
+    pub line: String,
+}
+
+/// Plan accumulates the [`Step`]s recorded by [`exec`] while `--dry-run` is active, so a single
+/// subcommand invocation can print the whole external command graph at once instead of
+/// interleaving it with the `cmd!` calls that built it up.
+pub static PLAN: LazyLock<Mutex<Vec<Step>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+
+/// Env vars `exec` records for a planned [`Step`]. Anything else a subcommand sets via
+/// `sh.set_var` isn't otherwise visible once `exec` only has the command and Shell, so the
+/// candidate list has to be named explicitly.
+const PLANNED_ENV_VARS: &[&str] = &[
+    "KUBECONFIG",
+    "RUST_LOG",
+    "CI",
+    "RUST_TEST_TIME_INTEGRATION",
+    "RUST_BACKTRACE",
+    "CARGO_INCREMENTAL",
+    "RUSTFLAGS",
+    "LLVM_PROFILE_FILE",
+];
+
+/// Exec runs `cmd`, or, if `dry_run` is set, records it as a [`Step`] on [`PLAN`] (with any
+/// workspace paths made relative, as [`rel`] would) and returns without doing anything.
+///
+/// This is the seam every cluster-mutating `cmd!` invocation should go through so that `xtask
+/// --dry-run` can preview what a subcommand would do.
+pub fn exec(sh: &Shell, cmd: xshell::Cmd<'_>, dry_run: bool) -> Result<()> {
+    if dry_run {
+        let root = WORKSPACE.to_string_lossy().into_owned();
+        let line = cmd.to_string().replace(&root, ".");
+        let mut parts = line.split_whitespace();
+        let program = parts.next().unwrap_or_default().to_string();
+        let args = parts.map(str::to_string).collect();
+        let env = PLANNED_ENV_VARS
+            .iter()
+            .filter_map(|&k| sh.var(k).ok().map(|v| (k.to_string(), v)))
+            .collect();
+        PLAN.lock().unwrap().push(Step {
+            program,
+            args,
+            dir: sh.current_dir(),
+            env,
+            line,
+        });
+    } else {
+        cmd.run()?;
+    }
+    Ok(())
+}
+
 /// KinD is a running KinD cluster.
 ///
-/// It deletes the cluster on drop.
+/// It deletes the cluster on drop, unless it was built with `--dry-run`, in which case the
+/// teardown command is only printed.
 pub struct KinD {
     name: String,
+    dry_run: bool,
 }
 
 impl Drop for KinD {
     fn drop(&mut self) {
         let name = self.name.as_str();
-        let sh = shell().unwrap();
-        cmd!(sh, "kind --quiet delete cluster --name {name}")
-            .run()
-            .unwrap();
+        let sh = shell(None).unwrap();
+        exec(
+            &sh,
+            cmd!(sh, "kind --quiet delete cluster --name {name}"),
+            self.dry_run,
+        )
+        .unwrap();
     }
 }
 
@@ -103,6 +284,7 @@ pub struct KinDBuilder {
     ingress_nginx: bool,
     gateway: bool,
     istio: bool,
+    dry_run: bool,
 }
 
 impl KinDBuilder {
@@ -127,6 +309,11 @@ impl KinDBuilder {
         }
     }
 
+    /// Dry_run makes every cluster-mutating command print instead of run, per [`exec`].
+    pub fn dry_run(self, dry_run: bool) -> Self {
+        Self { dry_run, ..self }
+    }
+
     /// If this fails, check the KinD "[known issues]."
     /// A likely culprit is the user `inotify` limits.
     ///
@@ -134,6 +321,7 @@ impl KinDBuilder {
     pub fn build(self, sh: &Shell) -> Result<KinD> {
         use scopeguard::guard;
 
+        let dry_run = self.dry_run;
         check::kubectl(sh)?;
         check::kind(sh)?;
         if self.istio {
@@ -152,22 +340,33 @@ impl KinDBuilder {
         // state.
         let mut ok = guard(false, |ok| {
             if !ok {
-                let _ = cmd!(sh, "kind --quiet delete cluster --name {name}").run();
+                let _ = exec(
+                    sh,
+                    cmd!(sh, "kind --quiet delete cluster --name {name}"),
+                    dry_run,
+                );
             }
         });
-        cmd!(sh, "kind --quiet --config {config} create cluster").run()?;
+        exec(
+            sh,
+            cmd!(sh, "kind --quiet --config {config} create cluster"),
+            dry_run,
+        )?;
         eprintln!("# waiting for pods to ready");
-        cmd!(
+        exec(
             sh,
-            "kubectl wait pods --for=condition=Ready --timeout=300s --all --all-namespaces"
-        )
-        .run()?;
+            cmd!(
+                sh,
+                "kubectl wait pods --for=condition=Ready --timeout=300s --all --all-namespaces"
+            ),
+            dry_run,
+        )?;
 
         // Load any CRDs requested:
         if self.gateway {
             eprintln!("# installing Gateway APIs");
             let manifest = GATEWAY_API_MANIFEST_URL.as_str();
-            cmd!(sh, "kubectl apply -f {manifest}").run()?;
+            exec(sh, cmd!(sh, "kubectl apply -f {manifest}"), dry_run)?;
         }
 
         // Install any services requested:
@@ -175,21 +374,31 @@ impl KinDBuilder {
             if self.ingress_nginx {
                 eprintln!("# installing ingress-nginx");
                 let ingress_manifest = INGRESS_NGINX_MANIFEST_URL.as_str();
-                cmd!(sh, "kubectl apply -f {ingress_manifest}").run()?;
+                exec(sh, cmd!(sh, "kubectl apply -f {ingress_manifest}"), dry_run)?;
             }
             if self.istio {
                 eprintln!("# installing istio");
-                cmd!(sh, "istioctl install --set profile=minimal -y").run()?;
+                exec(
+                    sh,
+                    cmd!(sh, "istioctl install --set profile=minimal -y"),
+                    dry_run,
+                )?;
             }
             eprintln!("# installed services, waiting for pods to ready");
-            cmd!(
+            exec(
                 sh,
-                "kubectl wait pods --for=condition=Ready --timeout=300s --all --all-namespaces"
-            )
-            .run()?;
+                cmd!(
+                    sh,
+                    "kubectl wait pods --for=condition=Ready --timeout=300s --all --all-namespaces"
+                ),
+                dry_run,
+            )?;
         }
 
         *ok = true;
-        Ok(KinD { name: name.into() })
+        Ok(KinD {
+            name: name.into(),
+            dry_run,
+        })
     }
 }
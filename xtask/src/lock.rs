@@ -0,0 +1,137 @@
+//! Lock loads `tools.lock` and `crds.lock`, the checksum manifests at the workspace root that
+//! [`check::fetch_verified`](crate::check) validates tool and CRD-bindings downloads against —
+//! see that file's own header comment for the key format and how to update it.
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use serde::Deserialize;
+
+use crate::{Result, WORKSPACE};
+
+#[derive(Deserialize)]
+struct Entry {
+    name: String,
+    version: String,
+    os: String,
+    arch: String,
+    sha256: String,
+    /// Placeholder marks an entry whose `sha256` isn't the real artifact's digest (e.g. it was
+    /// written in an environment without network access to download and hash the release).
+    /// [`expect`] refuses to hand a placeholder digest to [`crate::check::fetch_verified`], since
+    /// doing so would either silently "verify" nothing meaningful or, more likely, fail every
+    /// real download with a confusing checksum-mismatch instead of a clear "this was never
+    /// filled in" error.
+    #[serde(default)]
+    placeholder: bool,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    #[serde(rename = "tool")]
+    tools: Vec<Entry>,
+}
+
+/// Key identifies one verified download: (name, version, os, arch).
+type Key = (String, String, String, String);
+
+/// Digests maps a verified download's (name, version, os, arch) to its expected hex-encoded
+/// SHA-256 digest and whether that digest is a real artifact digest or just a [placeholder],
+/// loaded once from `tools.lock`.
+///
+/// [placeholder]: Entry::placeholder
+static DIGESTS: LazyLock<HashMap<Key, (String, bool)>> = LazyLock::new(|| {
+    let path = WORKSPACE.join("tools.lock");
+    let raw = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("reading {}: {err}", path.display()));
+    let manifest: Manifest =
+        toml::from_str(&raw).unwrap_or_else(|err| panic!("parsing {}: {err}", path.display()));
+    manifest
+        .tools
+        .into_iter()
+        .map(|e| ((e.name, e.version, e.os, e.arch), (e.sha256, e.placeholder)))
+        .collect()
+});
+
+/// Expect returns the expected digest for a verified download, erroring loudly if `tools.lock`
+/// has no matching entry (rather than silently skipping verification) or if the matching entry's
+/// digest is a known [placeholder](Entry::placeholder) instead of the real artifact's digest.
+///
+/// [placeholder]: Entry::placeholder
+pub fn expect(name: &str, version: &str, os: &str, arch: &str) -> Result<&'static str> {
+    let (digest, placeholder) = DIGESTS
+        .get(&(name.into(), version.into(), os.into(), arch.into()))
+        .ok_or_else(|| -> crate::DynError {
+            format!("no tools.lock entry for {name} {version} {os}/{arch}; add one before installing").into()
+        })?;
+    if *placeholder {
+        return Err(format!(
+            "tools.lock entry for {name} {version} {os}/{arch} is a placeholder digest, not the \
+             real artifact's; replace it with the real published digest before installing \
+             (see tools.lock's header comment)"
+        )
+        .into());
+    }
+    Ok(digest.as_str())
+}
+
+#[derive(Deserialize)]
+struct CrdEntry {
+    source: String,
+    #[serde(rename = "type")]
+    type_: String,
+    version: String,
+    sha256: String,
+    /// Placeholder marks an entry whose `sha256` isn't the real artifact's digest, the same
+    /// situation [`Entry::placeholder`] covers for `tools.lock`. [`expect_crd`] refuses to hand
+    /// one out for the same reason [`expect`] does.
+    #[serde(default)]
+    placeholder: bool,
+}
+
+#[derive(Deserialize)]
+struct CrdManifest {
+    #[serde(rename = "crd")]
+    crds: Vec<CrdEntry>,
+}
+
+/// CrdKey identifies one fetched CRD YAML: (source, type, version).
+type CrdKey = (String, String, String);
+
+/// Crd_digests maps a [`crate::generate::Source`]'s (name, type, version) to the expected
+/// hex-encoded SHA-256 digest of its upstream CRD YAML and whether that digest is a real digest
+/// or just a [placeholder], loaded once from `crds.lock`.
+///
+/// [placeholder]: CrdEntry::placeholder
+static CRD_DIGESTS: LazyLock<HashMap<CrdKey, (String, bool)>> = LazyLock::new(|| {
+    let path = WORKSPACE.join("crds.lock");
+    let raw = std::fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("reading {}: {err}", path.display()));
+    let manifest: CrdManifest =
+        toml::from_str(&raw).unwrap_or_else(|err| panic!("parsing {}: {err}", path.display()));
+    manifest
+        .crds
+        .into_iter()
+        .map(|e| ((e.source, e.type_, e.version), (e.sha256, e.placeholder)))
+        .collect()
+});
+
+/// Expect_crd returns the expected digest for a fetched CRD YAML, erroring loudly if `crds.lock`
+/// has no matching entry (rather than silently skipping verification) or if the matching entry's
+/// digest is a known [placeholder](CrdEntry::placeholder) instead of the real artifact's digest.
+pub fn expect_crd(source: &str, type_: &str, version: &str) -> Result<&'static str> {
+    let (digest, placeholder) = CRD_DIGESTS
+        .get(&(source.into(), type_.into(), version.into()))
+        .ok_or_else(|| -> crate::DynError {
+            format!("no crds.lock entry for {source} {type_} {version}; add one before generating").into()
+        })?;
+    if *placeholder {
+        return Err(format!(
+            "crds.lock entry for {source} {type_} {version} is a placeholder digest, not the \
+             real artifact's; replace it with the real published digest before generating \
+             (see crds.lock's header comment)"
+        )
+        .into());
+    }
+    Ok(digest.as_str())
+}
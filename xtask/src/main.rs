@@ -1,11 +1,11 @@
 #![doc = include_str!("../README.md")]
 
 use std::{
-    env,
     path::{Path, PathBuf},
     process,
 };
 
+use clap::{Parser, Subcommand, ValueEnum};
 use kube::{CustomResourceExt, Resource};
 use signal_hook::{consts::SIGINT, low_level::pipe};
 use xshell::{cmd, Shell};
@@ -16,136 +16,345 @@ mod find;
 use xtask::*;
 
 fn main() {
-    use clap::{crate_authors, crate_name, crate_version, Arg, ArgAction, Command, ValueHint};
-    let cmd = Command::new(crate_name!())
-        .author(crate_authors!())
-        .version(crate_version!())
-        .about("Build + task support for clair-operator")
-        .subcommand_required(true)
-        .subcommands(&[
-            Command::new("bundle")
-                .about("generate OLM bundle")
-                .args(&[Arg::new("out_dir")
-                    .long("out_dir")
-                    .value_name("DIR")
-                    .help("bundle output directory")
-                    .long_help("Bundle output directory.")
-                    .default_value("target/operator")
-                    .value_hint(ValueHint::DirPath)]),
-            Command::new("bundle-image")
-                .about("generate OLM bundle image")
-                .args(&[
-                    Arg::new("out_dir")
-                        .long("out_dir")
-                        .value_name("DIR")
-                        .help("bundle output directory")
-                        .long_help("Bundle output directory.")
-                        .default_value("target/operator")
-                        .value_hint(ValueHint::DirPath),
-                    Arg::new("image")
-                        .long("image")
-                        .value_name("REPO")
-                        .help("container image repository")
-                        .long_help("Container image repository to use during build.")
-                        .default_value(BUNDLE_IMAGE),
-                    Arg::new("version")
-                        .long("version")
-                        .value_name("vX.Y.Z")
-                        .help("bundle tag version")
-                        .long_help("Bundle tag version. If not provided, one will be guessed based on git tags."),
-                ]),
-            Command::new("catalog")
-                .about("generate OLM catalog")
-                .args(&[
-                    Arg::new("bundle")
-                        .long("bundle")
-                        .value_name("TAG")
-                        .help("bundle container image reference")
-                        .long_help("Bundle container image reference to use during build.")
-                        .default_value(BUNDLE_IMAGE),
-                    Arg::new("version")
-                        .long("version")
-                        .value_name("vX.Y.Z")
-                        .help("bundle tag version")
-                        .long_help("Bundle tag version. If not provided, one will be guessed based on git tags."),
-                    Arg::new("out_dir")
-                        .long("out_dir")
-                        .value_name("DIR")
-                        .help("catalog output directory")
-                        .long_help("Catalog output directory.")
-                        .default_value("target/catalog")
-                        .value_hint(ValueHint::DirPath),
-                ]),
-            Command::new("ci")
-                .about("run CI setup, then tests")
-                .args(&[Arg::new("pass").trailing_var_arg(true).num_args(..)]),
-            Command::new("manifests").about("generate CRD manifests into config/crd"),
-            Command::new("demo")
-                .about("spin up a kind instance with CRDs loaded and controller running")
-                .args(&[Arg::new("no_controller")
-                    .long("no-run")
-                    .help("don't automatically run controllers")
-                    .action(ArgAction::SetTrue)]),
-        ]);
-
-    if let Err(e) = match cmd.get_matches().subcommand() {
-        Some(("bundle", m)) => bundle(crate_version!(), m.into()),
-        Some(("bundle-image", m)) => bundle_image(m.into()),
-        Some(("catalog", m)) => catalog(m.into()),
-        Some(("ci", m)) => ci(m.into()),
-        Some(("manifests", _)) => manifests(),
-        Some(("demo", m)) => demo(m.into()),
-        _ => unreachable!(),
-    } {
+    let argv: Vec<String> = std::env::args().collect();
+    let argv = match expand_alias(&argv) {
+        Ok(argv) => argv,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+    };
+    let cli = Cli::parse_from(argv);
+    let dry_run = cli.dry_run;
+    let message_format = cli.message_format;
+    if let Err(e) = cli.run() {
         eprintln!("{e}");
         process::exit(1);
     }
+    if dry_run {
+        emit_plan(message_format);
+    }
 }
 
-fn shell() -> xshell::Result<Shell> {
-    let sh = Shell::new()?;
-    let p = env::var("PATH").expect("PATH environment variable missing");
-    let paths = std::iter::once(BIN_DIR.to_path_buf()).chain(std::env::split_paths(&p));
-    sh.set_var(
-        "PATH",
-        std::env::join_paths(paths).expect("unable to reconstruct PATH"),
-    );
-    sh.change_dir(WORKSPACE.as_path());
+/// Expand_alias looks at `argv[1]` (the first positional argument) and, if it's a user-defined
+/// [`xtask::alias`] rather than a built-in subcommand, splices its recorded argument vector in
+/// place of that one token -- modeled on cargo's own `[alias]` expansion, which runs before
+/// `clap` ever sees the arguments, so an alias can stand in for a whole composite workflow
+/// (e.g. `release = ["bundle-image", "catalog"]`) without touching this file.
+fn expand_alias(argv: &[String]) -> Result<Vec<String>> {
+    let Some(candidate) = argv.get(1) else {
+        return Ok(argv.to_vec());
+    };
+    if candidate.starts_with('-') || Commands::has_subcommand(candidate) {
+        return Ok(argv.to_vec());
+    }
+    let Some(tokens) = alias::resolve(candidate)? else {
+        return Ok(argv.to_vec());
+    };
+
+    let mut out = Vec::with_capacity(argv.len() + tokens.len());
+    out.push(argv[0].clone());
+    out.extend(tokens);
+    out.extend(argv[2..].iter().cloned());
+    Ok(out)
+}
+
+/// Emit_plan prints the external command graph [`xtask::exec`] recorded on [`xtask::PLAN`]
+/// during a `--dry-run`, in the format requested by `--message-format`.
+fn emit_plan(format: MessageFormat) {
+    let plan = xtask::PLAN.lock().unwrap();
+    match format {
+        MessageFormat::Human => {
+            for step in plan.iter() {
+                eprintln!("+ {}", step.line);
+            }
+        }
+        MessageFormat::Json => {
+            if let Err(e) = serde_json::to_writer_pretty(std::io::stdout(), &*plan) {
+                eprintln!("{e}");
+            }
+            println!();
+        }
+    }
+}
+
+/// Build + task support for clair-operator.
+#[derive(Parser)]
+#[command(author, version, about)]
+struct Cli {
+    /// Print the commands that would run instead of running them.
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Output format for the plan printed by `--dry-run`.
+    #[arg(long, global = true, value_enum, default_value_t = MessageFormat::Human)]
+    message_format: MessageFormat,
+
+    /// Cargo build profile to use for anything that shells out to `cargo`.
+    #[arg(long, global = true, value_enum, default_value_t = Profile::Debug)]
+    profile: Profile,
+
+    /// Workspace root to operate on, if not the directory containing this crate.
+    #[arg(long, global = true, value_name = "PATH", value_hint = clap::ValueHint::DirPath)]
+    source: Option<PathBuf>,
+
+    /// Print more progress output; repeat for the operator's own `RUST_LOG` to go to `trace`.
+    #[arg(short = 'v', long = "verbose", global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Suppress progress output.
+    #[arg(short = 'q', long = "quiet", global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Whether to colorize progress output.
+    #[arg(long, global = true, value_enum, default_value_t = Color::Auto)]
+    color: Color,
+
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum Profile {
+    #[default]
+    Debug,
+    Release,
+}
+
+impl Profile {
+    fn as_cargo_arg(self) -> Option<&'static str> {
+        match self {
+            Profile::Debug => None,
+            Profile::Release => Some("--release"),
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Manage a local KinD cluster for testing against.
+    Kind {
+        #[command(subcommand)]
+        command: KindCommand,
+    },
+    /// Generate the OLM bundle.
+    Bundle {
+        /// Bundle output directory.
+        #[arg(long, default_value = "target/operator", value_hint = clap::ValueHint::DirPath)]
+        out_dir: PathBuf,
+    },
+    /// Generate the OLM bundle image.
+    BundleImage {
+        /// Bundle output directory.
+        #[arg(long, default_value = "target/operator", value_hint = clap::ValueHint::DirPath)]
+        out_dir: PathBuf,
+        /// Container image repository to use during build.
+        #[arg(long, default_value = BUNDLE_IMAGE)]
+        image: String,
+        /// Bundle tag version. If not provided, one will be guessed based on git tags.
+        #[arg(long, value_name = "vX.Y.Z")]
+        version: Option<String>,
+    },
+    /// Generate the OLM catalog.
+    Catalog {
+        /// Bundle container image reference to use during build.
+        #[arg(long, default_value = BUNDLE_IMAGE)]
+        bundle: String,
+        /// Bundle tag version. If not provided, one will be guessed based on git tags.
+        #[arg(long, value_name = "vX.Y.Z")]
+        version: Option<String>,
+        /// Catalog output directory.
+        #[arg(long, default_value = "target/catalog", value_hint = clap::ValueHint::DirPath)]
+        out_dir: PathBuf,
+        /// Channels to populate in the generated catalog.
+        #[arg(long, value_delimiter = ',', default_value = "stable")]
+        channels: Vec<String>,
+        /// Collect every `v*.*.*` release tag (plus the current pre-release) into the semver
+        /// template, instead of a single bundle, so the catalog has a real upgrade graph.
+        #[arg(long)]
+        from_tags: bool,
+    },
+    /// Generate CRD manifests into config/crd.
+    Manifests,
+    /// Check that the tools needed by the other subcommands are installed, fetching any that
+    /// are missing.
+    Check,
+    /// Pre-fetch every registered tool into the local tool cache, for offline/air-gapped builds
+    /// (see `CLAIR_XTASK_OFFLINE` and `CLAIR_XTASK_TOOL_CACHE`).
+    Vendor,
+}
+
+#[derive(Subcommand)]
+enum KindCommand {
+    /// Create a cluster and leave it running.
+    Up {
+        /// Install ingress-nginx into the cluster.
+        #[arg(long)]
+        ingress_nginx: bool,
+        /// Install the Gateway API CRDs into the cluster.
+        #[arg(long)]
+        gateway: bool,
+        /// Install istio into the cluster.
+        #[arg(long)]
+        istio: bool,
+    },
+    /// Tear down the cluster created by `kind up`.
+    Down,
+    /// Run work against a scratch cluster that's torn down on exit.
+    Local {
+        #[command(subcommand)]
+        command: LocalCommand,
+    },
+}
 
-    Ok(sh)
+#[derive(Subcommand)]
+enum LocalCommand {
+    /// Spin up a KinD cluster with CRDs loaded and controllers running.
+    Demo {
+        /// Don't automatically run controllers.
+        #[arg(long = "no-run")]
+        no_controller: bool,
+        /// Proceed even if the active kubeconfig context isn't the local KinD cluster.
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Run CI setup, then tests, against a scratch cluster.
+    Ci {
+        #[arg(trailing_var_arg = true)]
+        pass: Vec<String>,
+    },
 }
 
-fn demo(opts: DemoOpts) -> Result<()> {
+impl Cli {
+    fn run(self) -> Result<()> {
+        let dry_run = self.dry_run;
+        let profile = self.profile;
+        let source = self.source.as_deref();
+        let verbosity = self.verbosity();
+        let color = self.color;
+        let ctx = || Context::new(source, verbosity, color, dry_run);
+        match self.command {
+            Commands::Kind { command } => command.run(source, verbosity, color, dry_run),
+            Commands::Bundle { out_dir } => bundle(&ctx()?, env!("CARGO_PKG_VERSION"), &out_dir),
+            Commands::BundleImage {
+                out_dir,
+                image,
+                version,
+            } => bundle_image(&ctx()?, &out_dir, &image, version, profile),
+            Commands::Catalog {
+                bundle,
+                version,
+                out_dir,
+                channels,
+                from_tags,
+            } => catalog(&ctx()?, &bundle, version, &out_dir, &channels, from_tags),
+            Commands::Manifests => manifests(&ctx()?),
+            Commands::Check => check_all(source),
+            Commands::Vendor => {
+                let sh = shell(source)?;
+                check::vendor(&sh)
+            }
+        }
+    }
+
+    /// Verbosity turns the `-v`/`-q` flags into a single [`Verbosity`] level.
+    fn verbosity(&self) -> Verbosity {
+        if self.quiet {
+            Verbosity::Quiet
+        } else if self.verbose > 0 {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+impl KindCommand {
+    fn run(self, source: Option<&Path>, verbosity: Verbosity, color: Color, dry_run: bool) -> Result<()> {
+        match self {
+            KindCommand::Up {
+                ingress_nginx,
+                gateway,
+                istio,
+            } => {
+                let sh = shell(source)?;
+                let mut kind = KinDBuilder::default().dry_run(dry_run);
+                if ingress_nginx {
+                    kind = kind.with_ingress_nginx();
+                }
+                if gateway {
+                    kind = kind.with_gateway();
+                }
+                if istio {
+                    kind = kind.with_istio();
+                }
+                // Leak the cluster handle: the point of `kind up` is to leave it running for
+                // the caller to poke at with `kubectl`, not tear it down on return.
+                std::mem::forget(kind.build(&sh)?);
+                Ok(())
+            }
+            KindCommand::Down => {
+                let sh = shell(source)?;
+                exec(&sh, cmd!(sh, "kind --quiet delete cluster --name ci"), dry_run)
+            }
+            KindCommand::Local { command } => command.run(source, verbosity, color, dry_run),
+        }
+    }
+}
+
+impl LocalCommand {
+    fn run(self, source: Option<&Path>, verbosity: Verbosity, color: Color, dry_run: bool) -> Result<()> {
+        match self {
+            LocalCommand::Demo { no_controller, yes } => {
+                let ctx = Context::new(source, verbosity, color, dry_run)?;
+                demo(&ctx, !no_controller, yes)
+            }
+            LocalCommand::Ci { pass } => {
+                let ctx = Context::new(source, verbosity, color, dry_run)?;
+                ci(&ctx, pass)
+            }
+        }
+    }
+}
+
+fn demo(ctx: &Context, run_controller: bool, confirmed: bool) -> Result<()> {
     use std::{io::Read, os::unix::net::UnixStream, process::Command};
     let (mut rd, wr) = UnixStream::pair()?;
     pipe::register(SIGINT, wr)?;
     let cfgpath = WORKSPACE.join("kubeconfig");
     let cargo: &Path = &CARGO;
-    let sh = shell()?;
+    let sh = &ctx.sh;
+    let dry_run = ctx.dry_run;
 
     sh.set_var("KUBECONFIG", &cfgpath);
-    eprintln!("# putting KUBECONFIG at {cfgpath:?}");
-    sh.set_var(
-        "RUST_LOG",
-        "controller=debug,clair_config=debug,webhook=debug",
-    );
-    check::kubectl(&sh)?;
-    check::kustomize(&sh)?;
-    let _guard = Kind::new(&sh, true);
+    ctx.status(format!("putting KUBECONFIG at {cfgpath:?}"));
+    let lvl = ctx.rust_log_level();
+    sh.set_var("RUST_LOG", format!("controller={lvl},clair_config={lvl},webhook={lvl}"));
+    let _guard = KinDBuilder::default().dry_run(dry_run).build(sh)?;
+
+    if let Err(e) = check::require_kind_cluster(sh, "ci") {
+        if !confirmed {
+            return Err(format!("{e} (pass --yes to proceed anyway)").into());
+        }
+        ctx.status(format!("warning: {e}"));
+    }
 
-    eprintln!("# regenerating CRDs");
-    cmd!(sh, "{cargo} xtask manifests")
-        .ignore_stdout()
-        .ignore_stderr()
-        .run()?;
-    eprintln!("# loading CRDs");
+    ctx.status("regenerating CRDs");
+    exec(
+        sh,
+        cmd!(sh, "{cargo} xtask manifests")
+            .ignore_stdout()
+            .ignore_stderr(),
+        dry_run,
+    )?;
+    ctx.status("loading CRDs");
     let _tmp = sh.create_temp_dir()?;
     let crds = _tmp.path().join("crds");
-    cmd!(sh, "kustomize build config/crd -o {crds}").run()?;
-    cmd!(sh, "kubectl apply -f {crds}").run()?;
+    exec(sh, cmd!(sh, "kustomize build config/crd -o {crds}"), dry_run)?;
+    exec(sh, cmd!(sh, "kubectl apply -f {crds}"), dry_run)?;
 
-    let _ctrl = if opts.run_controller {
-        eprintln!("# running controllers");
+    let _ctrl = if run_controller && !dry_run {
+        ctx.status("running controllers");
         Some(
             Command::new(cargo)
                 .current_dir(WORKSPACE.as_path())
@@ -156,50 +365,39 @@ fn demo(opts: DemoOpts) -> Result<()> {
         None
     };
 
-    eprintln!("# take it for a spin:");
-    eprintln!("#\tKUBECONFIG={cfgpath:?} kubectl get crds");
-    eprintln!("# look in \"config/samples\" for some samples");
-    eprintln!("# ^C to tear down");
+    ctx.status("take it for a spin:");
+    ctx.status(format!("\tKUBECONFIG={cfgpath:?} kubectl get crds"));
+    ctx.status("look in \"config/samples\" for some samples");
+    ctx.status("^C to tear down");
     let mut _block = [0];
     rd.read_exact(&mut _block)?;
 
     eprintln!();
-    eprintln!("# ðŸ« ");
     Ok(())
 }
 
-struct DemoOpts {
-    run_controller: bool,
-}
-
-impl From<&clap::ArgMatches> for DemoOpts {
-    fn from(m: &clap::ArgMatches) -> Self {
-        DemoOpts {
-            run_controller: !m.get_one::<bool>("no_controller").cloned().unwrap_or(false),
-        }
-    }
-}
-
-fn ci(opts: CiOpts) -> Result<()> {
+fn ci(ctx: &Context, pass: Vec<String>) -> Result<()> {
     let cargo: &Path = &CARGO;
-    let sh = shell()?;
+    let sh = &ctx.sh;
+    let dry_run = ctx.dry_run;
     sh.set_var("CI", "true");
     sh.set_var("KUBECONFIG", WORKSPACE.join("kubeconfig"));
     sh.set_var("RUST_TEST_TIME_INTEGRATION", "30000,3000000");
-    sh.set_var(
-        "RUST_LOG",
-        "controller=trace,clair_config=trace,webhook=trace",
-    );
+    let lvl = ctx.rust_log_level();
+    sh.set_var("RUST_LOG", format!("controller={lvl},clair_config={lvl},webhook={lvl}"));
     sh.set_var("RUST_BACKTRACE", "1");
-    check::kubectl(&sh)?;
-    let _kind = Kind::new(&sh, false)?;
+    let _kind = KinDBuilder::default().dry_run(dry_run).build(sh)?;
+    check::require_kind_cluster(sh, "ci")?;
 
-    eprintln!("# adding CI label");
-    cmd!(
+    ctx.status("adding CI label");
+    exec(
         sh,
-        "kubectl label namespace default projectclair.io/safe-to-run-tests=true"
-    )
-    .run()?;
+        cmd!(
+            sh,
+            "kubectl label namespace default projectclair.io/safe-to-run-tests=true"
+        ),
+        dry_run,
+    )?;
 
     let coverage = cmd!(sh, "which grcov").quiet().run().is_ok();
     if coverage {
@@ -207,9 +405,9 @@ fn ci(opts: CiOpts) -> Result<()> {
         sh.set_var("RUSTFLAGS", "-Cinstrument-coverage");
         sh.set_var("LLVM_PROFILE_FILE", "ci_test_%m_%p.profraw");
     } else {
-        eprintln!("# skipping code coverage");
+        ctx.status("skipping code coverage");
     };
-    eprintln!("# running CI tests");
+    ctx.status("running CI tests");
     let use_nextest = cmd!(sh, "{cargo} nextest help")
         .ignore_stdout()
         .ignore_stderr()
@@ -219,10 +417,10 @@ fn ci(opts: CiOpts) -> Result<()> {
     let mut test_args = vec![];
     let w = WORKSPACE.to_string_lossy().to_string();
     if use_nextest {
-        eprintln!("# using nextest");
+        ctx.status("using nextest");
         test_args.extend_from_slice(&["nextest", "run", "--profile", "ci"]);
         if ar.exists() {
-            eprintln!("# using archive \"{}\"", ar.display());
+            ctx.status(format!("using archive \"{}\"", ar.display()));
             test_args.push("--archive-file");
             test_args.push(ar.to_str().unwrap());
             test_args.push("--workspace-remap");
@@ -234,11 +432,11 @@ fn ci(opts: CiOpts) -> Result<()> {
     } else {
         test_args.extend_from_slice(&["test", "--features", "test_ci", "--"]);
     }
-    for v in &opts.pass {
+    for v in &pass {
         test_args.push(v);
     }
-    cmd!(sh, "{cargo} {test_args...}").run()?;
-    if coverage {
+    exec(sh, cmd!(sh, "{cargo} {test_args...}"), dry_run)?;
+    if coverage && !dry_run {
         let out_dir = "target/debug/coverage";
         sh.create_dir(out_dir)?;
         cmd!(
@@ -251,100 +449,21 @@ fn ci(opts: CiOpts) -> Result<()> {
     Ok(())
 }
 
-struct CiOpts {
-    pass: Vec<String>,
-}
-
-impl From<&clap::ArgMatches> for CiOpts {
-    fn from(m: &clap::ArgMatches) -> Self {
-        CiOpts {
-            pass: m
-                .get_many::<String>("pass")
-                .unwrap_or_default()
-                .map(ToString::to_string)
-                .collect(),
-        }
-    }
-}
-
-struct Kind {
-    name: std::ffi::OsString,
-}
-impl Drop for Kind {
-    fn drop(&mut self) {
-        let name = &self.name;
-        let sh = shell().unwrap();
-        cmd!(sh, "kind delete cluster --name {name}").run().unwrap();
-    }
-}
-impl Kind {
-    fn new(sh: &Shell, ingress: bool) -> Result<Self> {
-        use scopeguard::guard;
-        use std::{thread, time};
-        let ingress_manifest = INGRESS_MANIFEST.as_str();
-        let k8s_ver = KUBE_VERSION.as_str();
-        let name = "ci";
-        // TODO(hank) Move the KIND configs out of the controller crate.
-        let config = WORKSPACE
-            .join("etc/tests/")
-            .join(format!("kind-{k8s_ver}.yaml"));
-        sh.change_dir(WORKSPACE.as_path());
-        check::kind(sh)?;
-        cmd!(sh, "kind --config {config} create cluster --name {name}").run()?;
-        let mut ok = guard(true, |ok| {
-            if !ok {
-                let _ = cmd!(sh, "kind delete cluster --name {name}").run();
-            }
-        });
-        eprintln!("# waiting for pods to ready");
-        cmd!(
-            sh,
-            "kubectl wait pods --for=condition=Ready --timeout=300s --all --all-namespaces"
-        )
-        .run()
-        .map_err(|err| {
-            *ok = false;
-            err
-        })?;
-        if ingress {
-            cmd!(sh, "kubectl apply -f {ingress_manifest}")
-                .run()
-                .map_err(|err| {
-                    *ok = false;
-                    err
-                })?;
-            'wait: for n in 0..=5 {
-                let exec = cmd!(
-                    sh,
-                    "kubectl wait --namespace ingress-nginx --for=condition=Ready pod --selector=app.kubernetes.io/component=controller --timeout=90s"
-                )
-                .run();
-                match exec {
-                    Ok(_) => break 'wait,
-                    Err(err) => {
-                        if n == 5 {
-                            *ok = false;
-                            return Err(Box::new(err));
-                        }
-                    }
-                };
-                thread::sleep(time::Duration::from_secs(1));
-            }
-        }
-        Ok(Self { name: name.into() })
-    }
-}
-
-fn bundle(v: &str, opts: BundleOpts) -> Result<()> {
-    manifests()?;
-    let out_dir = WORKSPACE.join(&opts.out_dir);
-    let sh = shell()?;
-    check::operator_sdk(&sh)?;
-    check::kustomize(&sh)?;
+fn bundle(ctx: &Context, v: &str, out_dir: &Path) -> Result<()> {
+    manifests(ctx)?;
+    let sh = &ctx.sh;
+    let dry_run = ctx.dry_run;
+    let out_dir = WORKSPACE.join(out_dir);
+    check::operator_sdk(sh)?;
+    check::kustomize(sh)?;
     let _tmp = sh.create_temp_dir()?;
 
     let tmpfile = _tmp.path().join("out");
-    cmd!(sh, "kustomize build --output={tmpfile} config/manifests").run()?;
+    exec(
+        sh,
+        cmd!(sh, "kustomize build --output={tmpfile} config/manifests"),
+        dry_run,
+    )?;
     let out = sh.read_binary_file(tmpfile)?;
 
     let args = [
@@ -358,10 +477,15 @@ fn bundle(v: &str, opts: BundleOpts) -> Result<()> {
     sh.remove_path(&out_dir)?;
     sh.create_dir(&out_dir)?;
     sh.change_dir(&out_dir);
-    cmd!(sh, "operator-sdk generate bundle {args...} --version={v}")
-        .stdin(&out)
-        .run()?;
+    exec(
+        sh,
+        cmd!(sh, "operator-sdk generate bundle {args...} --version={v}").stdin(&out),
+        dry_run,
+    )?;
 
+    if dry_run {
+        return Ok(());
+    }
     let script = "/project_layout/s/unknown/clair-operator/";
     for f in ["bundle/metadata/annotations.yaml", "bundle.Dockerfile"] {
         let sed = cmd!(sh, "sed {script} {f}").output()?;
@@ -371,29 +495,20 @@ fn bundle(v: &str, opts: BundleOpts) -> Result<()> {
     Ok(())
 }
 
-struct BundleOpts {
-    out_dir: PathBuf,
-}
-impl From<&clap::ArgMatches> for BundleOpts {
-    fn from(m: &clap::ArgMatches) -> Self {
-        Self {
-            out_dir: m.get_one::<String>("out_dir").map(PathBuf::from).unwrap(),
-        }
-    }
-}
-
 macro_rules! write_crds {
-    ($out_dir:expr,  $($kind:ty),+ $(,)?) =>{
+    ($ctx:expr, $out_dir:expr,  $($kind:ty),+ $(,)?) =>{
         let out = $out_dir;
-        eprintln!("# writing to dir: {}", &out);
-        $( write_crd::<$kind, _>(out)?; )+
+        $ctx.status(format!("writing to dir: {}", &out));
+        $( write_crd::<$kind, _>($ctx, out)?; )+
     }
 }
 
-fn manifests() -> Result<()> {
+fn manifests(ctx: &Context) -> Result<()> {
     use api::v1alpha1;
+    let out_dir = ctx.sh.current_dir().join("config/crd");
     write_crds!(
-        "config/crd",
+        ctx,
+        &out_dir,
         v1alpha1::Clair,
         v1alpha1::Indexer,
         v1alpha1::Matcher,
@@ -403,20 +518,19 @@ fn manifests() -> Result<()> {
     Ok(())
 }
 
-fn write_crd<K, P>(out_dir: P) -> Result<()>
+fn write_crd<K, P>(ctx: &Context, out_dir: P) -> Result<()>
 where
     K: Resource<DynamicType = ()> + CustomResourceExt,
     P: AsRef<Path>,
 {
     use std::fs::File;
 
+    std::fs::create_dir_all(out_dir.as_ref())?;
     let doc = serde_json::to_value(K::crd())?;
-    let out = WORKSPACE
-        .join(out_dir.as_ref())
-        .join(format!("{}.yaml", K::crd_name()));
+    let out = out_dir.as_ref().join(format!("{}.yaml", K::crd_name()));
     let w = File::create(&out)?;
     serde_yaml::to_writer(&w, &doc)?;
-    eprintln!("# wrote: {}", out.file_name().unwrap().to_string_lossy());
+    ctx.status(format!("wrote: {}", out.file_name().unwrap().to_string_lossy()));
     Ok(())
 }
 
@@ -436,77 +550,101 @@ fn generate_version(sh: &Shell) -> Result<String> {
     Ok(v)
 }
 
-fn bundle_image(opts: BundleImageOpts) -> Result<()> {
+/// Release_tags lists every `v*.*.*` release tag, excluding the placeholder `v0.0.0` tag this
+/// repo seeds new clones with, for `catalog --from-tags` to turn into a historical bundle-image
+/// list.
+fn release_tags(sh: &Shell) -> Result<Vec<String>> {
+    let raw = cmd!(sh, "git tag --list v*.*.*").read()?;
+    Ok(raw
+        .lines()
+        .map(str::trim)
+        .filter(|t| !t.is_empty() && *t != "v0.0.0")
+        .map(str::to_string)
+        .collect())
+}
+
+fn bundle_image(
+    ctx: &Context,
+    out_dir: &Path,
+    image: &str,
+    version: Option<String>,
+    profile: Profile,
+) -> Result<()> {
     let cargo: &Path = &CARGO;
-    let dir_arg = &opts.out_dir;
-    let image = &opts.image;
-    let out_dir = WORKSPACE.join(&opts.out_dir);
-    let sh = shell()?;
-    let builder = find::builder(&sh)?;
-    let v = if let Some(v) = opts.version {
+    let dir_arg = out_dir;
+    let out_dir = WORKSPACE.join(out_dir);
+    let sh = &ctx.sh;
+    let dry_run = ctx.dry_run;
+    let builder = ctx.builder()?;
+    let v = if let Some(v) = version {
         v
     } else {
-        generate_version(&sh)?
+        generate_version(sh)?
     };
+    let profile_arg = profile.as_cargo_arg();
 
-    cmd!(sh, "{cargo} xtask bundle --out_dir={dir_arg}").run()?;
+    exec(
+        sh,
+        cmd!(
+            sh,
+            "{cargo} xtask bundle --out_dir={dir_arg} {profile_arg...}"
+        ),
+        dry_run,
+    )?;
     sh.change_dir(out_dir);
-    cmd!(
+    exec(
         sh,
-        "{builder} build --quiet --tag={image}:{v} --file=bundle.Dockerfile ."
-    )
-    .run()?;
+        cmd!(
+            sh,
+            "{builder} build --quiet --tag={image}:{v} --file=bundle.Dockerfile ."
+        ),
+        dry_run,
+    )?;
 
     Ok(())
 }
-struct BundleImageOpts {
-    out_dir: PathBuf,
-    image: String,
-    version: Option<String>,
-}
-impl From<&clap::ArgMatches> for BundleImageOpts {
-    fn from(m: &clap::ArgMatches) -> Self {
-        Self {
-            out_dir: m.get_one::<String>("out_dir").map(PathBuf::from).unwrap(),
-            image: m.get_one::<String>("image").unwrap().to_string(),
-            version: m.get_one::<String>("version").cloned(),
-        }
-    }
-}
 
-fn catalog(opts: CatalogOpts) -> Result<()> {
-    let _bundle = &opts.bundle;
-    let out_dir = &opts.out_dir;
-    let sh = shell()?;
-    check::opm(&sh)?;
-    let _v = if let Some(v) = opts.version {
+fn catalog(
+    ctx: &Context,
+    bundle: &str,
+    version: Option<String>,
+    out_dir: &Path,
+    channels: &[String],
+    from_tags: bool,
+) -> Result<()> {
+    let sh = &ctx.sh;
+    let dry_run = ctx.dry_run;
+    check::opm(sh)?;
+    let v = if let Some(v) = version {
         v
     } else {
-        generate_version(&sh)?
+        generate_version(sh)?
     };
-    /*
-    let bundles: Vec<String> = cmd!(sh, "git tag --list v*.*.*")
-        .read()?
-        .lines()
-        .chain(std::iter::once(v.as_str()))
-        .filter_map(|t| {
-            if t != "v0.0.0" {
-                Some(format!("{bundle}:{t}"))
-            } else {
-                None
-            }
-        })
-        .collect();
-    */
+
     sh.remove_path(out_dir)?;
     sh.create_dir(out_dir)?;
     sh.change_dir(out_dir);
 
     let catalog = "clair-catalog";
     sh.create_dir(catalog)?;
-    cmd!(sh, "opm generate dockerfile {catalog}").run()?;
+    exec(sh, cmd!(sh, "opm generate dockerfile {catalog}"), dry_run)?;
+
+    let template = if from_tags {
+        ctx.status("collecting release tags for the upgrade graph");
+        let mut images: Vec<String> = release_tags(sh)?
+            .into_iter()
+            .map(|tag| format!("{bundle}:{tag}"))
+            .collect();
+        images.push(format!("{bundle}:{v}"));
+
+        let yaml = olm::semver_template(&images, channels)?;
+        let path = sh.current_dir().join("semver-template.yaml");
+        sh.write_file(&path, yaml)?;
+        path
+    } else {
+        WORKSPACE.join("etc/operator/template.yaml")
+    };
 
-    let template = WORKSPACE.join("etc/operator/template.yaml");
     let pkg = cmd!(
         sh,
         "opm alpha render-template semver --output=json {template}"
@@ -514,23 +652,22 @@ fn catalog(opts: CatalogOpts) -> Result<()> {
     .read()?;
     sh.write_file(out_dir.join(catalog).join("operator.json"), &pkg)?;
 
-    cmd!(sh, "opm validate {catalog}").run()?;
+    exec(sh, cmd!(sh, "opm validate {catalog}"), dry_run)?;
 
     Ok(())
 }
 
-struct CatalogOpts {
-    bundle: String,
-    out_dir: PathBuf,
-    version: Option<String>,
-}
-
-impl From<&clap::ArgMatches> for CatalogOpts {
-    fn from(m: &clap::ArgMatches) -> Self {
-        Self {
-            bundle: m.get_one::<String>("bundle").unwrap().to_string(),
-            out_dir: m.get_one::<String>("out_dir").map(PathBuf::from).unwrap(),
-            version: m.get_one::<String>("version").cloned(),
-        }
-    }
+/// Check verifies that every tool the other subcommands shell out to is installed, fetching
+/// any that are missing into [`BIN_DIR`].
+fn check_all(source: Option<&Path>) -> Result<()> {
+    let sh = shell(source)?;
+    check::kubectl(&sh)?;
+    check::kind(&sh)?;
+    check::kustomize(&sh)?;
+    check::operator_sdk(&sh)?;
+    check::opm(&sh)?;
+    check::istioctl(&sh)?;
+    find::builder(&sh)?;
+    eprintln!("# all tools present");
+    Ok(())
 }
@@ -6,11 +6,11 @@ use std::{
 };
 
 use kube::{CustomResourceExt, Resource};
-use xshell::{Shell, cmd};
 
 #[allow(unused_imports)]
-use crate::{Result, WORKSPACE, check, olm::cluster_service_versions::*};
+use crate::{olm::cluster_service_versions::*, Result, WORKSPACE};
 use api::v1alpha1::*;
+use api::v1beta1;
 
 macro_rules! write_crds {
     ($out_dir:ident,  $($kind:ty),+ $(,)?) =>{
@@ -19,19 +19,27 @@ macro_rules! write_crds {
     }
 }
 
-pub fn command(sh: Shell, opts: ManifestsOpts) -> Result<()> {
-    let out = opts.out_dir.join("crd");
-    let out = out.as_path();
-    std::fs::create_dir_all(out)?;
-    write_crds!(out, Clair, Indexer, Matcher, Updater, Notifier);
-
-    /*
-    let out = out.as_path();
-    std::fs::create_dir_all(out)?;
-    write_csv(out)?;
-    */
-    let out = opts.out_dir.join("csv");
-    write_csv(&sh, out)?;
+pub fn command(opts: ManifestsOpts) -> Result<()> {
+    use Format::*;
+
+    if matches!(opts.format, Crd | All) {
+        let out = opts.out_dir.join("crd");
+        let out = out.as_path();
+        std::fs::create_dir_all(out)?;
+        write_crds!(out, Indexer, Matcher, Updater, Notifier);
+        write_versioned_crd::<Clair, v1beta1::Clair, _>(out, "v1alpha1")?;
+    }
+
+    if matches!(opts.format, Csv | All) {
+        let out = opts.out_dir.join("csv");
+        write_csv(out, &opts.published, &opts.skip)?;
+    }
+
+    if matches!(opts.format, Helm | All) {
+        let out = opts.out_dir.join("helm");
+        write_helm(&out)?;
+    }
+
     Ok(())
 }
 
@@ -48,25 +56,94 @@ where
     Ok(())
 }
 
-// TODO(hank): Maybe just keep the kustomize setup?
-#[allow(dead_code)]
-fn write_csv<P>(sh: &Shell, out_dir: P) -> Result<()>
+/// Write_versioned_crd merges the `spec.versions` generated for two versions of the same kind
+/// (`A` and `B`, assumed to share group/kind/names) into a single CRD document, marking
+/// `storage_version` as the stored version. This is for kinds that have been promoted to a new
+/// API version but keep a single storage version and a conversion webhook to translate between
+/// them, rather than each version getting its own generated file.
+fn write_versioned_crd<A, B, P>(out_dir: P, storage_version: &str) -> Result<()>
 where
+    A: Resource<DynamicType = ()> + CustomResourceExt,
+    B: Resource<DynamicType = ()> + CustomResourceExt,
     P: AsRef<Path>,
 {
-    check::kustomize(sh)?;
+    let mut crd = A::crd();
+    let mut versions = crd.spec.versions;
+    versions.extend(B::crd().spec.versions);
+    for v in &mut versions {
+        v.storage = v.name == storage_version;
+    }
+    crd.spec.versions = versions;
+
+    let doc = serde_json::to_value(&crd)?;
+    let out = out_dir.as_ref().join(format!("{}.yaml", A::crd_name()));
+    let w = File::create(&out)?;
+    serde_yaml::to_writer(&w, &doc)?;
+    eprintln!("# wrote: {}", out.file_name().unwrap().to_string_lossy());
+    Ok(())
+}
 
-    let dir = WORKSPACE.join("xtask");
-    sh.change_dir(&dir);
+/// Upgrade_graph computes the OLM upgrade edges (`spec.replaces`/`spec.skips`) for the CSV being
+/// generated for `version`, given the names of every previously published CSV
+/// (`clair.vMAJOR.MINOR.PATCH`) and a list of versions to skip over (e.g. yanked releases) even
+/// though nothing replaces them directly.
+///
+/// `replaces` is the immediately preceding published version, or `None` for the first release.
+/// `skips` is every `skip` version strictly between `replaces` and `version`.
+fn upgrade_graph(
+    version: &semver::Version,
+    published: &[String],
+    skip: &[semver::Version],
+) -> Result<(Option<String>, Vec<String>)> {
+    let mut versions = published
+        .iter()
+        .map(|name| {
+            let suffix = name
+                .strip_prefix("clair.v")
+                .ok_or_else(|| -> DynError { format!("malformed CSV name: {name:?}").into() })?;
+            suffix.parse::<semver::Version>().map_err(Into::into)
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-    let out_dir = out_dir.as_ref();
-    cmd!(
-        sh,
-        "kustomize build --output {out_dir}/clair.csv.yaml src/manifests/csv"
-    )
-    .run()?;
+    if versions.contains(version) {
+        return Err(format!("CSV for version {version} is already published").into());
+    }
+    versions.sort();
 
-    /*
+    let latest = versions.last().cloned();
+    if let Some(latest) = latest.as_ref() {
+        if latest > version {
+            return Err(format!(
+                "new version {version} does not sort above latest published version {latest}"
+            )
+            .into());
+        }
+    }
+
+    let replaces = latest.as_ref().map(|v| format!("clair.v{v}"));
+    let skips = match latest.as_ref() {
+        // No prior published version means no upgrade graph to skip over yet; without this
+        // guard, `v > latest` is vacuously true for every `v` and a first release would skip
+        // versions that were never actually published.
+        None => Vec::new(),
+        Some(latest) => skip
+            .iter()
+            .filter(|v| *v > latest)
+            .filter(|v| *v < version)
+            .map(|v| format!("clair.v{v}"))
+            .collect(),
+    };
+
+    Ok((replaces, skips))
+}
+
+/// Write_csv natively builds the [`ClusterServiceVersion`] for the CRDs in this workspace,
+/// setting the OLM upgrade graph from `published` (every previously published CSV name) and
+/// `skip` (versions to skip over, e.g. yanked releases).
+fn write_csv<P>(out_dir: P, published: &[String], skip: &[String]) -> Result<()>
+where
+    P: AsRef<Path>,
+{
     use k8s_openapi::apimachinery::pkg::apis::meta::v1::*;
     let mut defs = ClusterServiceVersionCustomresourcedefinitions {
         owned: vec![{
@@ -304,9 +381,16 @@ where
         }),
     );
 
+    let version: semver::Version = env!("CARGO_PKG_VERSION").parse()?;
+    let skip = skip
+        .iter()
+        .map(|v| v.parse::<semver::Version>().map_err(Into::into))
+        .collect::<Result<Vec<_>>>()?;
+    let (replaces, skips) = upgrade_graph(&version, published, &skip)?;
+
     let csv = ClusterServiceVersion {
         metadata: ObjectMeta {
-            name: format!("clair.v{}", env!("CARGO_PKG_VERSION")).into(),
+            name: format!("clair.v{version}").into(),
             labels: BTreeMap::from([
                 ("operatorframework.io/arch.amd64".into(), "supported".into()),
                 ("operatorframework.io/os.linux".into(), "supported".into()),
@@ -320,7 +404,9 @@ where
             ..Default::default()
         },
         spec: ClusterServiceVersionSpec {
-            version: env!("CARGO_PKG_VERSION").to_string().into(),
+            version: version.to_string().into(),
+            replaces: replaces.into(),
+            skips: (!skips.is_empty()).then_some(skips).into(),
             maturity: "alpha".to_string().into(),
             min_kube_version: "1.28.0".to_string().into(),
             display_name: "Clair Operator".into(),
@@ -363,33 +449,57 @@ where
 
             customresourcedefinitions: defs.into(),
 
-            /*
-            webhookdefinitions: vec![
-                ClusterServiceVersionWebhookdefinitions{
-                    admission_review_versions: vec!["v1".into()].into(),
-                    generate_name: "clair-webhook".into(),
-                    side_effects: "None".into(),
-                    r#type: ClusterServiceVersionWebhookdefinitionsType::ValidatingAdmissionWebhook.into(),
-                    ..Default::default()
-                },
-            ].into(),
-            */
+            // TODO(hank): webhookdefinitions, once the webhook crate has an admission path.
             ..Default::default()
         },
         ..Default::default()
     };
 
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir)?;
     let doc = serde_json::to_value(csv)?;
-    let out = out_dir.as_ref().join("clair.csv.yaml");
+    let out = out_dir.join("clair.csv.yaml");
     let w = File::create(&out)?;
     serde_yaml::to_writer(&w, &doc)?;
     eprintln!("# wrote: {}", out.file_name().unwrap().to_string_lossy());
-    */
     Ok(())
 }
 
+/// Format selects which manifest output(s) [`command`] renders.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Crd renders only `crd/`.
+    Crd,
+    /// Csv renders only the kustomize-built OLM `csv/`.
+    Csv,
+    /// Helm renders only the installable `helm/` chart.
+    Helm,
+    /// All renders every format. The default.
+    All,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "crd" => Ok(Format::Crd),
+            "csv" => Ok(Format::Csv),
+            "helm" => Ok(Format::Helm),
+            "all" => Ok(Format::All),
+            other => Err(format!("unknown manifest format: {other:?}")),
+        }
+    }
+}
+
 pub struct ManifestsOpts {
     out_dir: PathBuf,
+    format: Format,
+    /// Published holds the `clair.vMAJOR.MINOR.PATCH` names of every previously published CSV,
+    /// used to compute the new CSV's `spec.replaces`.
+    published: Vec<String>,
+    /// Skip holds versions to add to the new CSV's `spec.skips`, e.g. yanked releases.
+    skip: Vec<String>,
 }
 
 impl From<&clap::ArgMatches> for ManifestsOpts {
@@ -398,6 +508,212 @@ impl From<&clap::ArgMatches> for ManifestsOpts {
         if !out_dir.is_absolute() {
             out_dir = WORKSPACE.join(out_dir);
         }
-        Self { out_dir }
+        let format = m
+            .get_one::<String>("format")
+            .map(|f| f.parse().unwrap_or(Format::All))
+            .unwrap_or(Format::All);
+        let published = m
+            .get_many::<String>("published")
+            .map(|vs| vs.cloned().collect())
+            .unwrap_or_default();
+        let skip = m
+            .get_many::<String>("skip")
+            .map(|vs| vs.cloned().collect())
+            .unwrap_or_default();
+        Self {
+            out_dir,
+            format,
+            published,
+            skip,
+        }
     }
 }
+
+/// Write_helm renders a complete, installable Helm chart under `out_dir`: `Chart.yaml`,
+/// `values.yaml` exposing the same knobs the CSV spec-descriptors describe, the generated CRDs
+/// under `crds/`, and the operator Deployment/RBAC under `templates/`.
+fn write_helm<P>(out_dir: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir)?;
+    eprintln!("# writing to dir: {}", crate::rel(out_dir));
+
+    write_chart_yaml(out_dir)?;
+    write_values_yaml(out_dir)?;
+
+    let crds = out_dir.join("crds");
+    std::fs::create_dir_all(&crds)?;
+    let crds = crds.as_path();
+    write_crds!(crds, Indexer, Matcher, Updater, Notifier);
+    write_versioned_crd::<Clair, v1beta1::Clair, _>(crds, "v1alpha1")?;
+
+    let templates = out_dir.join("templates");
+    std::fs::create_dir_all(&templates)?;
+    write_helm_template(&templates, "serviceaccount.yaml", HELM_SERVICEACCOUNT_YAML)?;
+    write_helm_template(&templates, "rbac.yaml", HELM_RBAC_YAML)?;
+    write_helm_template(&templates, "deployment.yaml", HELM_DEPLOYMENT_YAML)?;
+
+    Ok(())
+}
+
+fn write_chart_yaml<P>(out_dir: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let version = env!("CARGO_PKG_VERSION");
+    let doc = format!(
+        "apiVersion: v2\n\
+         name: clair-operator\n\
+         description: An operator for running Clair, the cloud-native container image scanner.\n\
+         type: application\n\
+         version: {version}\n\
+         appVersion: {version:?}\n"
+    );
+    let out = out_dir.as_ref().join("Chart.yaml");
+    std::fs::write(&out, doc)?;
+    eprintln!("# wrote: {}", out.file_name().unwrap().to_string_lossy());
+    Ok(())
+}
+
+fn write_values_yaml<P>(out_dir: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let out = out_dir.as_ref().join("values.yaml");
+    std::fs::write(&out, HELM_VALUES_YAML)?;
+    eprintln!("# wrote: {}", out.file_name().unwrap().to_string_lossy());
+    Ok(())
+}
+
+fn write_helm_template<P>(out_dir: P, name: &str, contents: &str) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let out = out_dir.as_ref().join(name);
+    std::fs::write(&out, contents)?;
+    eprintln!("# wrote: {}", out.file_name().unwrap().to_string_lossy());
+    Ok(())
+}
+
+// The same knobs the CSV spec-descriptors describe: image, notifier enable/disable, database
+// secret refs, and dropins.
+const HELM_VALUES_YAML: &str = r#"# Default values for the clair-operator chart.
+image:
+  repository: quay.io/projectclair/clair-operator
+  tag: ""
+  pullPolicy: IfNotPresent
+
+serviceAccount:
+  create: true
+  name: clair-operator
+
+notifier:
+  enabled: false
+
+databases:
+  indexer:
+    secretName: ""
+    secretKey: ""
+  matcher:
+    secretName: ""
+    secretKey: ""
+  notifier:
+    secretName: ""
+    secretKey: ""
+
+dropins: []
+"#;
+
+const HELM_SERVICEACCOUNT_YAML: &str = r#"{{- if .Values.serviceAccount.create }}
+apiVersion: v1
+kind: ServiceAccount
+metadata:
+  name: {{ .Values.serviceAccount.name }}
+  labels:
+    app.kubernetes.io/name: clair-operator
+    app.kubernetes.io/instance: {{ .Release.Name }}
+{{- end }}
+"#;
+
+const HELM_RBAC_YAML: &str = r#"apiVersion: rbac.authorization.k8s.io/v1
+kind: ClusterRole
+metadata:
+  name: {{ .Values.serviceAccount.name }}
+rules:
+  - apiGroups: ["clairproject.org"]
+    resources: ["clairs", "indexers", "matchers", "updaters", "notifiers"]
+    verbs: ["*"]
+  - apiGroups: [""]
+    resources: ["configmaps", "secrets", "services"]
+    verbs: ["*"]
+  - apiGroups: ["apps"]
+    resources: ["deployments"]
+    verbs: ["*"]
+  - apiGroups: ["autoscaling"]
+    resources: ["horizontalpodautoscalers"]
+    verbs: ["*"]
+---
+apiVersion: rbac.authorization.k8s.io/v1
+kind: ClusterRoleBinding
+metadata:
+  name: {{ .Values.serviceAccount.name }}
+roleRef:
+  apiGroup: rbac.authorization.k8s.io
+  kind: ClusterRole
+  name: {{ .Values.serviceAccount.name }}
+subjects:
+  - kind: ServiceAccount
+    name: {{ .Values.serviceAccount.name }}
+    namespace: {{ .Release.Namespace }}
+"#;
+
+const HELM_DEPLOYMENT_YAML: &str = r#"apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: {{ .Release.Name }}
+  labels:
+    app.kubernetes.io/name: clair-operator
+    app.kubernetes.io/instance: {{ .Release.Name }}
+spec:
+  replicas: 1
+  selector:
+    matchLabels:
+      app.kubernetes.io/name: clair-operator
+      app.kubernetes.io/instance: {{ .Release.Name }}
+  template:
+    metadata:
+      labels:
+        app.kubernetes.io/name: clair-operator
+        app.kubernetes.io/instance: {{ .Release.Name }}
+    spec:
+      serviceAccountName: {{ .Values.serviceAccount.name }}
+      containers:
+        - name: operator
+          image: "{{ .Values.image.repository }}:{{ .Values.image.tag | default .Chart.AppVersion }}"
+          imagePullPolicy: {{ .Values.image.pullPolicy }}
+          env:
+            - name: CLAIR_OPERATOR_NOTIFIER_ENABLED
+              value: {{ .Values.notifier.enabled | quote }}
+{{- if .Values.databases.indexer.secretName }}
+            - name: CLAIR_OPERATOR_INDEXER_DATABASE_SECRET_NAME
+              value: {{ .Values.databases.indexer.secretName | quote }}
+            - name: CLAIR_OPERATOR_INDEXER_DATABASE_SECRET_KEY
+              value: {{ .Values.databases.indexer.secretKey | quote }}
+{{- end }}
+{{- if .Values.databases.matcher.secretName }}
+            - name: CLAIR_OPERATOR_MATCHER_DATABASE_SECRET_NAME
+              value: {{ .Values.databases.matcher.secretName | quote }}
+            - name: CLAIR_OPERATOR_MATCHER_DATABASE_SECRET_KEY
+              value: {{ .Values.databases.matcher.secretKey | quote }}
+{{- end }}
+{{- if .Values.notifier.enabled }}
+{{- if .Values.databases.notifier.secretName }}
+            - name: CLAIR_OPERATOR_NOTIFIER_DATABASE_SECRET_NAME
+              value: {{ .Values.databases.notifier.secretName | quote }}
+            - name: CLAIR_OPERATOR_NOTIFIER_DATABASE_SECRET_KEY
+              value: {{ .Values.databases.notifier.secretKey | quote }}
+{{- end }}
+{{- end }}
+"#;
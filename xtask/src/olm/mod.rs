@@ -0,0 +1,88 @@
+//! Olm builds the File-Based-Catalog inputs `catalog()` hands to `opm`: today, just the
+//! semver-template variant that lets `opm alpha render-template semver` synthesize a connected
+//! upgrade graph (replaces/skipRange included) from a flat list of bundle images, instead of
+//! hand-writing `olm.channel` entries ourselves.
+//!
+//! [`cluster_service_versions`] is the `kopium`-generated binding for the OLM
+//! ClusterServiceVersion CRD, fetched from upstream by the codegen pipeline; it isn't checked in
+//! here.
+
+use serde::Serialize;
+
+use crate::Result;
+
+pub mod cluster_service_versions;
+
+#[derive(Debug, Clone, Serialize)]
+struct Tier {
+    #[serde(rename = "Bundles")]
+    bundles: Vec<BundleRef>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct BundleRef {
+    #[serde(rename = "Image")]
+    image: String,
+}
+
+#[derive(Debug, Serialize, Default)]
+struct SemverTemplate {
+    #[serde(rename = "Schema")]
+    schema: &'static str,
+    #[serde(rename = "GenerateMajorChannels")]
+    generate_major_channels: bool,
+    #[serde(rename = "GenerateMinorChannels")]
+    generate_minor_channels: bool,
+    #[serde(rename = "Candidate", skip_serializing_if = "Option::is_none")]
+    candidate: Option<Tier>,
+    #[serde(rename = "Fast", skip_serializing_if = "Option::is_none")]
+    fast: Option<Tier>,
+    #[serde(rename = "Stable", skip_serializing_if = "Option::is_none")]
+    stable: Option<Tier>,
+}
+
+/// Tier_for_channel maps this repo's channel names (`stable`/`testing`/`next`, the same three
+/// `bundle()` passes to `operator-sdk generate bundle --channels`) onto opm's fixed
+/// semver-template tier names, since opm itself only knows Candidate/Fast/Stable.
+fn tier_for_channel(channel: &str) -> Result<&'static str> {
+    match channel {
+        "stable" => Ok("Stable"),
+        "testing" => Ok("Fast"),
+        "next" => Ok("Candidate"),
+        other => {
+            Err(format!("unknown channel {other:?}; expected stable, testing, or next").into())
+        }
+    }
+}
+
+/// Semver_template renders an opm `olm.semver` template that lists `images` under every tier in
+/// `channels`, so `opm alpha render-template semver` can derive the channel membership and
+/// `replaces`/`skipRange` upgrade edges itself from the bundles' own embedded CSV versions,
+/// instead of this crate hand-computing an upgrade graph.
+pub fn semver_template(images: &[String], channels: &[String]) -> Result<String> {
+    let bundles: Vec<BundleRef> = images
+        .iter()
+        .map(|image| BundleRef {
+            image: image.clone(),
+        })
+        .collect();
+
+    let mut tmpl = SemverTemplate {
+        schema: "olm.semver",
+        generate_minor_channels: true,
+        ..Default::default()
+    };
+    for channel in channels {
+        let tier = Tier {
+            bundles: bundles.clone(),
+        };
+        match tier_for_channel(channel)? {
+            "Stable" => tmpl.stable = Some(tier),
+            "Fast" => tmpl.fast = Some(tier),
+            "Candidate" => tmpl.candidate = Some(tier),
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(serde_yaml::to_string(&tmpl)?)
+}